@@ -1,7 +1,8 @@
 use std::time::Duration;
 
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, Criterion};
 use rawdio::{prelude::*, Adsr};
+use std::hint::black_box;
 
 fn adsr_benchmarks(c: &mut Criterion) {
     c.benchmark_group("ADSR");
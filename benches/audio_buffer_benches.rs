@@ -1,5 +1,6 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, Criterion};
 use rawdio::prelude::*;
+use std::hint::black_box;
 
 fn read_interleaved(destination: &mut dyn AudioBuffer, interleaved_buffer: &[f32]) {
     destination.fill_from_interleaved(
@@ -12,7 +12,7 @@ struct Fixture {
 }
 
 impl Fixture {
-    pub fn new(impulse_duration: Duration) -> Self {
+    pub fn new(impulse_duration: Duration, true_stereo: bool) -> Self {
         let sample_rate = 48_000;
 
         let (mut context, process) =
@@ -26,10 +26,20 @@ impl Fixture {
         let impulse_sample_count =
             (impulse_duration.as_secs_f64() * sample_rate as f64).floor() as usize;
 
-        let impulse =
-            OwnedAudioBuffer::white_noise(impulse_sample_count, channel_count, sample_rate);
+        let convolution = if true_stereo {
+            let impulse = OwnedAudioBuffer::white_noise(
+                impulse_sample_count,
+                channel_count * channel_count,
+                sample_rate,
+            );
 
-        let convolution = Convolution::new(context.as_ref(), channel_count, impulse);
+            Convolution::new_matrix(context.as_ref(), channel_count, channel_count, impulse)
+        } else {
+            let impulse =
+                OwnedAudioBuffer::white_noise(impulse_sample_count, channel_count, sample_rate);
+
+            Convolution::new(context.as_ref(), channel_count, impulse)
+        };
 
         connect_nodes!("input" => convolution => "output");
 
@@ -52,9 +62,16 @@ impl Fixture {
 fn convolution_benches(c: &mut Criterion) {
     c.benchmark_group("Convolution");
 
-    c.bench_function("process 2 second convolution", |b| {
-        let impulse_duration = Duration::from_secs(2);
-        let mut fixture = Fixture::new(impulse_duration);
+    for seconds in [2, 5] {
+        c.bench_function(&format!("process {seconds} second convolution"), |b| {
+            let impulse_duration = Duration::from_secs(seconds);
+            let mut fixture = Fixture::new(impulse_duration, false);
+            b.iter(|| fixture.process());
+        });
+    }
+
+    c.bench_function("process 5 second true stereo convolution", |b| {
+        let mut fixture = Fixture::new(Duration::from_secs(5), true);
         b.iter(|| fixture.process());
     });
 }
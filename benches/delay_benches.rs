@@ -0,0 +1,84 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use rawdio::{prelude::*, Delay, DelayInterpolation};
+use std::time::Duration;
+
+struct Fixture {
+    process: Box<dyn AudioProcess + Send>,
+    delay: Delay,
+    input_buffer: OwnedAudioBuffer,
+    output_buffer: OwnedAudioBuffer,
+}
+
+impl Fixture {
+    fn new(interpolation: DelayInterpolation) -> Self {
+        let sample_rate = 48_000;
+        let frame_count = 4_096;
+        let channel_count = 2;
+
+        let (mut context, process) =
+            create_engine_with_options(EngineOptions::default().with_sample_rate(sample_rate));
+
+        let mut delay = Delay::new(
+            context.as_ref(),
+            channel_count,
+            Duration::from_secs(1),
+            interpolation,
+        );
+
+        connect_nodes!("input" => delay => "output");
+
+        context.start();
+
+        delay.delay_time().set_value_now(0.25);
+        delay.feedback().set_value_now(0.5);
+
+        let input_buffer = OwnedAudioBuffer::white_noise(frame_count, channel_count, sample_rate);
+        let output_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+
+        Self {
+            process,
+            delay,
+            input_buffer,
+            output_buffer,
+        }
+    }
+
+    fn process(&mut self) {
+        self.process
+            .process(&self.input_buffer, &mut self.output_buffer);
+    }
+}
+
+fn delay_benchmarks(c: &mut Criterion) {
+    c.benchmark_group("Delay");
+
+    for (name, interpolation) in [
+        ("linear", DelayInterpolation::Linear),
+        ("cubic", DelayInterpolation::Cubic),
+        ("allpass", DelayInterpolation::Allpass),
+    ] {
+        c.bench_function(&format!("process fixed delay ({name})"), |b| {
+            let mut fixture = Fixture::new(interpolation);
+
+            b.iter(|| fixture.process());
+        });
+    }
+
+    c.bench_function("process modulated delay", |b| {
+        let mut fixture = Fixture::new(DelayInterpolation::Cubic);
+
+        let start_time = Timestamp::zero();
+        let end_time = Timestamp::from_samples(4_096.0, 48_000);
+
+        fixture
+            .delay
+            .delay_time()
+            .linear_ramp_to_value(0.01, start_time, end_time);
+
+        b.iter(|| fixture.process());
+    });
+}
+
+criterion_group!(benches, delay_benchmarks);
+
+criterion_main!(benches);
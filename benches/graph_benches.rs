@@ -12,13 +12,16 @@ struct Fixture {
 }
 
 impl Fixture {
-    pub fn new(layer_count: usize, nodes_per_layer: usize) -> Self {
+    pub fn new(layer_count: usize, nodes_per_layer: usize, worker_thread_count: usize) -> Self {
         assert!(layer_count > 0);
         assert!(nodes_per_layer > 0);
 
         let sample_rate = 48_000;
-        let (mut context, process) =
-            create_engine_with_options(EngineOptions::default().with_sample_rate(sample_rate));
+        let (mut context, process) = create_engine_with_options(
+            EngineOptions::default()
+                .with_sample_rate(sample_rate)
+                .with_worker_thread_count(worker_thread_count),
+        );
 
         let frame_count = 4096;
         let channel_count = 2;
@@ -110,7 +113,7 @@ fn sampler_benchmarks(c: &mut Criterion) {
     c.bench_function("single node graph", |b| {
         let layer_count = 1;
         let nodes_per_layer = 1;
-        let mut fixture = Fixture::new(layer_count, nodes_per_layer);
+        let mut fixture = Fixture::new(layer_count, nodes_per_layer, 0);
 
         b.iter(|| fixture.process());
     });
@@ -118,7 +121,7 @@ fn sampler_benchmarks(c: &mut Criterion) {
     c.bench_function("deep graph", |b| {
         let layer_count = 2;
         let nodes_per_layer = 64;
-        let mut fixture = Fixture::new(layer_count, nodes_per_layer);
+        let mut fixture = Fixture::new(layer_count, nodes_per_layer, 0);
 
         b.iter(|| fixture.process());
     });
@@ -126,7 +129,7 @@ fn sampler_benchmarks(c: &mut Criterion) {
     c.bench_function("wide graph", |b| {
         let layer_count = 64;
         let nodes_per_layer = 2;
-        let mut fixture = Fixture::new(layer_count, nodes_per_layer);
+        let mut fixture = Fixture::new(layer_count, nodes_per_layer, 0);
 
         b.iter(|| fixture.process());
     });
@@ -134,7 +137,16 @@ fn sampler_benchmarks(c: &mut Criterion) {
     c.bench_function("varied graph", |b| {
         let layer_count = 12;
         let nodes_per_layer = 12;
-        let mut fixture = Fixture::new(layer_count, nodes_per_layer);
+        let mut fixture = Fixture::new(layer_count, nodes_per_layer, 0);
+
+        b.iter(|| fixture.process());
+    });
+
+    c.bench_function("varied graph with worker threads", |b| {
+        let layer_count = 12;
+        let nodes_per_layer = 12;
+        let worker_thread_count = 3;
+        let mut fixture = Fixture::new(layer_count, nodes_per_layer, worker_thread_count);
 
         b.iter(|| fixture.process());
     });
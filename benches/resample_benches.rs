@@ -0,0 +1,30 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use rawdio::{
+    resample::{Resampler, ResamplerQuality},
+    OwnedAudioBuffer,
+};
+
+fn resample_benchmarks(c: &mut Criterion) {
+    c.benchmark_group("Resample");
+
+    let sample_rate = 96_000;
+    let frame_count = 4_096;
+    let channel_count = 2;
+
+    for (name, quality) in [
+        ("cubic", ResamplerQuality::Cubic),
+        ("sinc", ResamplerQuality::Sinc),
+    ] {
+        c.bench_function(&format!("downsample 96 kHz to 48 kHz ({name})"), |b| {
+            let input = OwnedAudioBuffer::white_noise(frame_count, channel_count, sample_rate);
+            let mut output = OwnedAudioBuffer::new(frame_count / 2, channel_count, sample_rate / 2);
+            let mut resampler = Resampler::new(quality, channel_count, 2.0);
+
+            b.iter(|| resampler.process(&input, &mut output));
+        });
+    }
+}
+
+criterion_group!(benches, resample_benchmarks);
+
+criterion_main!(benches);
@@ -0,0 +1,55 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use rawdio::{prelude::*, Reverb};
+
+struct Fixture {
+    process: Box<dyn AudioProcess + Send>,
+    _reverb: Reverb,
+    input_buffer: OwnedAudioBuffer,
+    output_buffer: OwnedAudioBuffer,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let sample_rate = 48_000;
+        let frame_count = 4_096;
+        let channel_count = 2;
+
+        let (mut context, process) =
+            create_engine_with_options(EngineOptions::default().with_sample_rate(sample_rate));
+
+        let reverb = Reverb::new(context.as_ref(), channel_count);
+
+        connect_nodes!("input" => reverb => "output");
+
+        context.start();
+
+        let input_buffer = OwnedAudioBuffer::white_noise(frame_count, channel_count, sample_rate);
+        let output_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+
+        Self {
+            process,
+            _reverb: reverb,
+            input_buffer,
+            output_buffer,
+        }
+    }
+
+    fn process(&mut self) {
+        self.process
+            .process(&self.input_buffer, &mut self.output_buffer);
+    }
+}
+
+fn reverb_benchmarks(c: &mut Criterion) {
+    c.benchmark_group("Reverb");
+
+    c.bench_function("process stereo reverb", |b| {
+        let mut fixture = Fixture::new();
+
+        b.iter(|| fixture.process());
+    });
+}
+
+criterion_group!(benches, reverb_benchmarks);
+
+criterion_main!(benches);
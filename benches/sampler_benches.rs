@@ -6,7 +6,7 @@ struct Fixture {
     context: Box<dyn Context>,
     input_buffer: OwnedAudioBuffer,
     output_buffer: OwnedAudioBuffer,
-    _sampler: Sampler,
+    sampler: Sampler,
 }
 
 impl Fixture {
@@ -33,7 +33,7 @@ impl Fixture {
             context,
             input_buffer: OwnedAudioBuffer::new(frame_count, channel_count, sample_rate),
             output_buffer: OwnedAudioBuffer::new(frame_count, channel_count, sample_rate),
-            _sampler: sampler,
+            sampler,
         }
     }
 
@@ -52,6 +52,14 @@ fn sampler_benchmarks(c: &mut Criterion) {
 
         b.iter(|| fixture.process());
     });
+
+    c.bench_function("play sample with detune", |b| {
+        let mut fixture = Fixture::new();
+
+        fixture.sampler.detune().set_value_now(-700.0);
+
+        b.iter(|| fixture.process());
+    });
 }
 
 criterion_group!(benches, sampler_benchmarks);
@@ -1,5 +1,5 @@
-use examples::render_audio_process_to_file;
-use rawdio::{prelude::*, Gain, Oscillator, Pan};
+use examples::write_buffer_into_file;
+use rawdio::{prelude::*, Gain, OfflineContext, Oscillator, Pan};
 use std::time::Duration;
 use structopt::StructOpt;
 
@@ -15,21 +15,23 @@ fn main() {
 
 fn render_file(output_file: &str) {
     let sample_rate = 44100;
+    let channel_count = 2;
 
-    let (mut context, process) =
-        create_engine_with_options(EngineOptions::default().with_sample_rate(sample_rate));
+    let mut context = OfflineContext::new(
+        EngineOptions::default().with_sample_rate(sample_rate),
+        channel_count,
+        Duration::from_secs(4),
+    );
 
-    let oscillators = create_oscillators(context.as_ref());
-    let gain = create_gain(context.as_ref());
-    let pan = create_pan(context.as_ref());
+    let oscillators = create_oscillators(&context);
+    let gain = create_gain(&context);
+    let pan = create_pan(&context);
 
     make_connections(&oscillators, &gain, &pan);
 
-    context.start();
-
-    render_audio_process_to_file(sample_rate, output_file, process, Duration::from_secs(4));
+    context.render_to_end();
 
-    context.stop();
+    write_buffer_into_file(context.into_output_buffer(), output_file);
 }
 
 fn create_oscillators(context: &dyn Context) -> [Oscillator; 4] {
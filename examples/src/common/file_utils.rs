@@ -3,54 +3,22 @@
 use std::time::Duration;
 
 use rawdio::{
-    AudioBuffer, AudioProcess, MutableBorrowedAudioBuffer, OwnedAudioBuffer, SampleLocation,
+    io::{WavReader, WavSampleFormat, WavSpec, WavWriter},
+    AudioBuffer, AudioProcess, MutableBorrowedAudioBuffer, OwnedAudioBuffer,
 };
 
 pub fn read_file_into_buffer(file_path: &str) -> OwnedAudioBuffer {
-    let mut reader = hound::WavReader::open(file_path).expect("Unable to open file for reading");
-    let file_specification = reader.spec();
-    let num_channels = file_specification.channels as usize;
-    let sample_rate = file_specification.sample_rate as usize;
-    let num_samples = reader.len() as usize;
-    let num_frames = num_samples / num_channels;
-
-    let mut output_buffer = OwnedAudioBuffer::new(num_frames, num_channels, sample_rate);
-
-    let max_value = 2_i32.pow(file_specification.bits_per_sample as u32 - 1) - 1;
-
-    for (position, sample) in reader.samples::<i32>().enumerate() {
-        if let Ok(sample) = sample {
-            let frame = position / num_channels;
-            let channel = position % num_channels;
-            let sample_location = SampleLocation::new(channel, frame);
-            let sample = (sample as f64 / max_value as f64) as f32;
-            output_buffer.set_sample(sample_location, sample);
-        }
-    }
-
-    output_buffer
+    WavReader::open(file_path)
+        .expect("Unable to open file for reading")
+        .read_to_buffer()
+        .expect("Unable to read file")
 }
 
 pub fn write_buffer_into_file(buffer: OwnedAudioBuffer, output_file: &str) {
-    let bits_per_sample = 24;
-    let max_value = 2_i32.pow(bits_per_sample - 1) - 1;
-
-    let mut writer = create_writer(
-        buffer.channel_count(),
-        buffer.sample_rate(),
-        bits_per_sample,
-        output_file,
-    );
+    let mut writer = create_writer(buffer.channel_count(), buffer.sample_rate(), output_file);
 
-    for frame in 0..buffer.frame_count() {
-        for channel in 0..buffer.channel_count() {
-            let location = SampleLocation::new(channel, frame);
-            let sample = buffer.get_sample(location);
-            let sample = sample.clamp(-1.0, 1.0);
-            let sample = (sample * max_value as f32) as i32;
-            writer.write_sample(sample).expect("Failed to write sample");
-        }
-    }
+    writer.write(&buffer).expect("Failed to write samples");
+    writer.finalize().expect("Failed to finalize file");
 }
 
 pub fn render_audio_process_to_file(
@@ -59,11 +27,9 @@ pub fn render_audio_process_to_file(
     mut audio_process: Box<dyn AudioProcess>,
     length: Duration,
 ) {
-    let bits_per_sample = 24;
-    let max_value = 2_i32.pow(bits_per_sample - 1) - 1;
     let num_channels = 2;
 
-    let mut writer = create_writer(num_channels, sample_rate, bits_per_sample, output_file);
+    let mut writer = create_writer(num_channels, sample_rate, output_file);
 
     let length_in_seconds = length.as_secs_f64();
     let total_frame_count = sample_rate * length_in_seconds as usize;
@@ -79,29 +45,28 @@ pub fn render_audio_process_to_file(
             position,
             &mut audio_buffer,
             audio_process.as_mut(),
-            max_value,
             &mut writer,
         );
 
         let progress = 100.0 * position as f64 / total_frame_count as f64;
         println!("Progress: {progress:.2}%");
     }
+
+    writer.finalize().expect("Failed to finalize file");
 }
 
 fn create_writer(
     num_channels: usize,
     sample_rate: usize,
-    bits_per_sample: u32,
     output_file: &str,
-) -> hound::WavWriter<std::io::BufWriter<std::fs::File>> {
-    let file_spec = hound::WavSpec {
-        channels: num_channels as u16,
-        sample_rate: sample_rate as u32,
-        bits_per_sample: bits_per_sample as u16,
-        sample_format: hound::SampleFormat::Int,
+) -> WavWriter<std::io::BufWriter<std::fs::File>> {
+    let file_spec = WavSpec {
+        channel_count: num_channels,
+        sample_rate,
+        sample_format: WavSampleFormat::Pcm24,
     };
 
-    hound::WavWriter::create(output_file, file_spec).expect("Unable to create file writer")
+    WavWriter::create(output_file, file_spec).expect("Unable to create file writer")
 }
 
 fn process_block<T>(
@@ -109,8 +74,7 @@ fn process_block<T>(
     frame_offset: usize,
     audio_buffer: &mut dyn AudioBuffer,
     audio_process: &mut dyn AudioProcess,
-    max_value: i32,
-    writer: &mut hound::WavWriter<T>,
+    writer: &mut WavWriter<T>,
 ) where
     T: std::io::Write + std::io::Seek,
 {
@@ -125,13 +89,7 @@ fn process_block<T>(
 
     audio_process.process(&input_buffer, &mut frame_buffer);
 
-    for frame in 0..frame_buffer.frame_count() {
-        for channel in 0..frame_buffer.channel_count() {
-            let location = SampleLocation::new(channel, frame);
-            let sample = frame_buffer.get_sample(location);
-            let sample = sample.clamp(-1.0, 1.0);
-            let sample = (sample * max_value as f32) as i32;
-            writer.write_sample(sample).expect("Failed to write sample");
-        }
-    }
+    writer
+        .write(&frame_buffer)
+        .expect("Failed to write samples");
 }
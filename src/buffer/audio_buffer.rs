@@ -302,7 +302,9 @@ pub trait AudioBuffer {
 
     /// Copy audio from a different audio buffer at a different sample rate
     ///
-    /// This will perform an interpolation-based sample rate conversion
+    /// This will perform an interpolation-based sample rate conversion. It
+    /// doesn't filter out frequencies above the new Nyquist frequency, so use
+    /// [crate::resample::resample_buffer] where quality is important.
     fn sample_rate_convert_from(
         &mut self,
         audio_buffer: &dyn AudioBuffer,
@@ -1,5 +1,7 @@
-use super::{parameter_change_request::CancelChangeRequest, Id, ParameterChangeRequest};
-use crate::graph::*;
+use super::{
+    parameter_change_request::CancelChangeRequest, Id, MidiEventRequest, ParameterChangeRequest,
+};
+use crate::graph::{Connection, Dsp};
 
 pub enum Command {
     Start,
@@ -11,8 +13,9 @@ pub enum Command {
     CancelParameterChanges(CancelChangeRequest),
     ParameterValueChange(ParameterChangeRequest),
 
+    ScheduleMidiEvent(MidiEventRequest),
+    CancelMidiEvents(Id),
+
     AddConnection(Connection),
     RemoveConnection(Connection),
-    ConnectToOutput(Endpoint),
-    ConnectToInput(Endpoint),
 }
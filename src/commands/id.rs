@@ -1,14 +1,19 @@
 use std::sync::atomic::{AtomicUsize, Ordering};
 
+/// A unique identifier for a node in the audio graph
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub struct Id(usize);
 
 impl Id {
-    pub fn with_value(value: usize) -> Self {
-        Self(value)
+    pub(crate) fn system_input() -> Self {
+        Self(usize::MAX)
     }
 
-    pub fn generate() -> Self {
+    pub(crate) fn system_output() -> Self {
+        Self(usize::MAX - 1)
+    }
+
+    pub(crate) fn generate() -> Self {
         static NEXT: AtomicUsize = AtomicUsize::new(0);
         Self(NEXT.fetch_add(1, Ordering::Relaxed))
     }
@@ -0,0 +1,7 @@
+use super::Id;
+use crate::midi::MidiEvent;
+
+pub struct MidiEventRequest {
+    pub dsp_id: Id,
+    pub event: MidiEvent,
+}
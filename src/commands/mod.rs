@@ -1,8 +1,10 @@
 mod command;
 mod id;
+mod midi_event_request;
 mod parameter_change_request;
 
 pub use command::Command;
 pub use id::Id;
+pub use midi_event_request::MidiEventRequest;
 pub use parameter_change_request::CancelChangeRequest;
 pub use parameter_change_request::ParameterChangeRequest;
@@ -3,6 +3,7 @@
 use std::{iter::zip, simd::Simd};
 
 use itertools::izip;
+use rustfft::num_complex::Complex64;
 
 pub fn mix_into(source: &[f32], destination: &mut [f32]) {
     debug_assert_eq!(source.len(), destination.len());
@@ -189,3 +190,109 @@ pub fn multiply_by_value(data: &mut [f32], gain: f32) {
         *sample *= gain;
     }
 }
+
+/// Read between samples using cubic (Hermite) interpolation
+///
+/// Samples outside of `data` are treated as silence
+pub fn read_cubic(data: &[f32], position: f64) -> f32 {
+    let index = position.floor() as usize;
+    let fraction = (position - index as f64) as f32;
+
+    let sample_at = |index: usize| data.get(index).copied().unwrap_or(0.0);
+
+    let y0 = index.checked_sub(1).map_or(0.0, sample_at);
+    let y1 = sample_at(index);
+
+    if fraction == 0.0 {
+        return y1;
+    }
+
+    let y2 = sample_at(index + 1);
+    let y3 = sample_at(index + 2);
+
+    let c0 = y1;
+    let c1 = 0.5 * (y2 - y0);
+    let c2 = y0 - 2.5 * y1 + 2.0 * y2 - 0.5 * y3;
+    let c3 = 0.5 * (y3 - y0) + 1.5 * (y1 - y2);
+
+    ((c3 * fraction + c2) * fraction + c1) * fraction + c0
+}
+
+/// The polynomial band-limited step (PolyBLEP) correction for a discontinuity
+/// at the start of each cycle
+///
+/// `phase` is the position in the cycle, from 0 to 1, and `increment` is the
+/// change in phase per sample. Subtracting this from a naive sawtooth (or
+/// adding it at each edge of a square wave) greatly reduces aliasing.
+pub fn poly_blep(phase: f64, increment: f64) -> f64 {
+    if phase < increment {
+        let t = phase / increment;
+        return t + t - t * t - 1.0;
+    }
+
+    if phase > 1.0 - increment {
+        let t = (phase - 1.0) / increment;
+        return t * t + t + t + 1.0;
+    }
+
+    0.0
+}
+
+/// The polynomial band-limited ramp (PolyBLAMP) correction for a change in
+/// slope at the start of each cycle
+///
+/// This is the integral of [poly_blep], and is used to smooth the corners of
+/// waveforms such as the triangle. Scale it by the change in slope per sample.
+pub fn poly_blamp(phase: f64, increment: f64) -> f64 {
+    if phase < increment {
+        let t = 1.0 - phase / increment;
+        return t * t * t / 6.0;
+    }
+
+    if phase > 1.0 - increment {
+        let t = 1.0 + (phase - 1.0) / increment;
+        return t * t * t / 6.0;
+    }
+
+    0.0
+}
+
+/// Replace tiny values with zero, so that the state of a recursive filter
+/// doesn't decay into denormal numbers, which are slow to process
+pub fn flush_denormal(value: f64) -> f64 {
+    let denormal_threshold = 1e-8;
+
+    if -denormal_threshold <= value && value <= denormal_threshold {
+        return 0.0;
+    }
+
+    value
+}
+
+/// The gain of a trapezoidal integrator with a cutoff at `frequency`, as used
+/// by zero-delay feedback filters
+///
+/// The cutoff is prewarped, so the analog and digital responses match at the
+/// cutoff. It is clamped below Nyquist, where the gain would be infinite.
+pub fn integrator_gain(frequency: f64, sample_rate: f64) -> f64 {
+    let maximum_frequency = 0.49 * sample_rate;
+    (std::f64::consts::PI * frequency.clamp(0.0, maximum_frequency) / sample_rate).tan()
+}
+
+/// Evaluate `s` for a zero-delay feedback filter with a cutoff at `cutoff`,
+/// at `frequency`, as a numerator and denominator
+///
+/// The analog prototype has its cutoff at 1 rad/s, and the trapezoidal
+/// integrators map it to `s = (1 - z^-1) / (g (1 + z^-1))`. Keeping the two
+/// parts separate lets responses be written as ratios of polynomials, which
+/// stay finite at Nyquist.
+pub fn integrator_response_terms(
+    frequency: f64,
+    cutoff: f64,
+    sample_rate: f64,
+) -> (Complex64, Complex64) {
+    let delay = Complex64::from_polar(1.0, -std::f64::consts::TAU * frequency / sample_rate);
+    let g = integrator_gain(cutoff, sample_rate);
+
+    (1.0 - delay, g * (1.0 + delay))
+}
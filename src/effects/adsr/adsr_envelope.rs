@@ -63,6 +63,24 @@ impl AdsrEnvelope {
         };
     }
 
+    pub fn reset(&mut self) {
+        self.phase = Phase::Idle;
+        self.envelope = 0.0;
+    }
+
+    pub fn is_idle(&self) -> bool {
+        matches!(self.phase, Phase::Idle)
+    }
+
+    pub fn is_releasing(&self) -> bool {
+        matches!(self.phase, Phase::Release)
+    }
+
+    /// The most recent value of the envelope
+    pub fn value(&self) -> f64 {
+        self.envelope
+    }
+
     pub fn set_attack_time(&mut self, attack_time: Duration) {
         self.attack_coefficient = calculate_attack_coefficient(attack_time, self.sample_rate);
     }
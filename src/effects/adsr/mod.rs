@@ -3,4 +3,5 @@ mod adsr_event;
 mod adsr_node;
 mod adsr_processor;
 
+pub(crate) use adsr_envelope::AdsrEnvelope;
 pub use adsr_node::Adsr;
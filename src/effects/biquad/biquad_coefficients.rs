@@ -1,8 +1,10 @@
 use std::f64::consts::PI;
 
-use crate::Level;
+use super::filter_type::BiquadFilterType;
+use crate::{dsp::flush_denormal, Level};
+use rustfft::num_complex::Complex64;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct BiquadCoefficients {
     a1: f64,
     a2: f64,
@@ -12,6 +14,42 @@ pub struct BiquadCoefficients {
 }
 
 impl BiquadCoefficients {
+    /// Create coefficients from a transfer function, normalising by `a0`
+    pub fn new(b0: f64, b1: f64, b2: f64, a0: f64, a1: f64, a2: f64) -> Self {
+        let scale = 1.0 / a0;
+
+        Self {
+            a1: a1 * scale,
+            a2: a2 * scale,
+            b0: b0 * scale,
+            b1: b1 * scale,
+            b2: b2 * scale,
+        }
+    }
+
+    /// Calculate the coefficients for a type of filter
+    ///
+    /// `q` is used by the pass, notch and peaking filters, and `shelf_gain` is
+    /// used by the shelving and peaking filters
+    pub fn for_filter_type(
+        filter_type: BiquadFilterType,
+        frequency: f64,
+        sample_rate: f64,
+        q: f64,
+        shelf_gain: Level,
+    ) -> Self {
+        match filter_type {
+            BiquadFilterType::HighPass => Self::high_pass(frequency, sample_rate, q),
+            BiquadFilterType::LowPass => Self::low_pass(frequency, sample_rate, q),
+            BiquadFilterType::BandPass => Self::band_pass(frequency, sample_rate, q),
+            BiquadFilterType::Notch => Self::notch(frequency, sample_rate, q),
+            BiquadFilterType::HighShelf => Self::high_shelf(frequency, sample_rate, shelf_gain),
+            BiquadFilterType::LowShelf => Self::low_shelf(frequency, sample_rate, shelf_gain),
+            BiquadFilterType::AllPass => Self::all_pass(frequency, sample_rate, q),
+            BiquadFilterType::Peaking => Self::peaking(frequency, sample_rate, q, shelf_gain),
+        }
+    }
+
     pub fn low_pass(center_frequency: f64, sample_rate: f64, q: f64) -> Self {
         let omega = omega(center_frequency, sample_rate);
 
@@ -104,6 +142,38 @@ impl BiquadCoefficients {
         }
     }
 
+    pub fn all_pass(center_frequency: f64, sample_rate: f64, q: f64) -> Self {
+        let omega = omega(center_frequency, sample_rate);
+        let alpha = omega.sin() / (2.0 * q);
+
+        let b0 = 1.0 - alpha;
+        let b1 = -2.0 * omega.cos();
+        let b2 = 1.0 + alpha;
+
+        let a0 = 1.0 + alpha;
+        let a1 = -2.0 * omega.cos();
+        let a2 = 1.0 - alpha;
+
+        Self::new(b0, b1, b2, a0, a1, a2)
+    }
+
+    pub fn peaking(center_frequency: f64, sample_rate: f64, q: f64, level: Level) -> Self {
+        let a = level.as_linear().sqrt();
+
+        let omega = omega(center_frequency, sample_rate);
+        let alpha = omega.sin() / (2.0 * q);
+
+        let b0 = 1.0 + alpha * a;
+        let b1 = -2.0 * omega.cos();
+        let b2 = 1.0 - alpha * a;
+
+        let a0 = 1.0 + alpha / a;
+        let a1 = -2.0 * omega.cos();
+        let a2 = 1.0 - alpha / a;
+
+        Self::new(b0, b1, b2, a0, a1, a2)
+    }
+
     pub fn low_shelf(center_frequency: f64, sample_rate: f64, level: Level) -> Self {
         let a = level.as_linear().sqrt();
 
@@ -164,6 +234,31 @@ impl BiquadCoefficients {
         }
     }
 
+    /// The complex response of the filter at a frequency
+    pub fn frequency_response(&self, frequency: f64, sample_rate: f64) -> Complex64 {
+        let z = Complex64::from_polar(1.0, -omega(frequency, sample_rate));
+        let z2 = z * z;
+
+        (self.b0 + self.b1 * z + self.b2 * z2) / (1.0 + self.a1 * z + self.a2 * z2)
+    }
+
+    /// Filter a single sample
+    ///
+    /// `delays` holds the previous two inputs followed by the previous two
+    /// outputs
+    pub fn process_sample(&self, input: f64, delays: &mut [f64; 4]) -> f64 {
+        let [x1, x2, y1, y2] = *delays;
+
+        let output = self.b0 * input + self.b1 * x1 + self.b2 * x2 - self.a1 * y1 - self.a2 * y2;
+
+        *delays = [input, x1, output, y1].map(flush_denormal);
+
+        output
+    }
+}
+
+#[cfg(test)]
+impl BiquadCoefficients {
     pub fn a1(&self) -> f64 {
         self.a1
     }
@@ -256,4 +351,53 @@ mod test {
         assert_relative_eq!(coefficients.b1(), -2.14483965, epsilon = 1e-6);
         assert_relative_eq!(coefficients.b2(), 0.88769198, epsilon = 1e-6);
     }
+
+    #[test]
+    fn frequency_response() {
+        let sample_rate = 48_000.0;
+        let coefficients = BiquadCoefficients::low_pass(1_000.0, sample_rate, 1.0);
+
+        assert_relative_eq!(
+            coefficients.frequency_response(0.0, sample_rate).norm(),
+            1.0,
+            epsilon = 1e-9
+        );
+        assert_relative_eq!(
+            coefficients.frequency_response(1_000.0, sample_rate).norm(),
+            1.0,
+            epsilon = 1e-9
+        );
+        assert_relative_eq!(
+            coefficients.frequency_response(1_000.0, sample_rate).arg(),
+            -PI / 2.0,
+            epsilon = 1e-9
+        );
+        assert_relative_eq!(
+            coefficients
+                .frequency_response(24_000.0, sample_rate)
+                .norm(),
+            0.0,
+            epsilon = 1e-9
+        );
+    }
+
+    #[test]
+    fn all_pass() {
+        let coefficients = BiquadCoefficients::all_pass(1_000.0, 48_000.0, 0.707);
+        assert_relative_eq!(coefficients.a1(), -1.81531792, epsilon = 1e-6);
+        assert_relative_eq!(coefficients.a2(), 0.83098222, epsilon = 1e-6);
+        assert_relative_eq!(coefficients.b0(), 0.83098222, epsilon = 1e-6);
+        assert_relative_eq!(coefficients.b1(), -1.81531792, epsilon = 1e-6);
+        assert_relative_eq!(coefficients.b2(), 1.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn peaking() {
+        let coefficients = BiquadCoefficients::peaking(2_000.0, 48_000.0, 2.0, Level::from_db(6.0));
+        assert_relative_eq!(coefficients.a1(), -1.84723453, epsilon = 1e-6);
+        assert_relative_eq!(coefficients.a2(), 0.91239790, epsilon = 1e-6);
+        assert_relative_eq!(coefficients.b0(), 1.04359353, epsilon = 1e-6);
+        assert_relative_eq!(coefficients.b1(), -1.84723453, epsilon = 1e-6);
+        assert_relative_eq!(coefficients.b2(), 0.86880437, epsilon = 1e-6);
+    }
 }
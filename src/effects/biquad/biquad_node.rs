@@ -1,4 +1,7 @@
-use super::{biquad_processor::BiquadProcessor, filter_type::BiquadFilterType};
+use super::{
+    biquad_coefficients::BiquadCoefficients, biquad_processor::BiquadProcessor,
+    filter_type::BiquadFilterType, frequency_response::write_frequency_response,
+};
 use crate::{commands::Id, graph::DspNode, parameter::*, prelude::*, utility::create_parameters};
 
 /// A biquad filter
@@ -8,13 +11,15 @@ use crate::{commands::Id, graph::DspNode, parameter::*, prelude::*, utility::cre
 /// # Parameters
 /// - frequency
 /// - q
-/// - shelf-gain
+/// - shelf-gain (used by the shelving and peaking filters)
 /// - gain
 pub struct Biquad {
     /// The node to connect into the audio graph
     pub node: GraphNode,
 
     parameters: Parameters,
+    filter_type: BiquadFilterType,
+    sample_rate: usize,
 }
 
 impl DspNode for Biquad {
@@ -69,6 +74,8 @@ impl Biquad {
                 realtime_params,
             ),
             parameters: params,
+            filter_type,
+            sample_rate: context.get_sample_rate(),
         }
     }
 
@@ -84,11 +91,39 @@ impl Biquad {
 
     /// Get the shelf gain parameter
     pub fn shelf_gain(&mut self) -> &mut AudioParameter {
-        self.get_parameter_mut("shelf_gain")
+        self.get_parameter_mut("shelf-gain")
     }
 
     /// Get the gain parameter
     pub fn gain(&mut self) -> &mut AudioParameter {
         self.get_parameter_mut("gain")
     }
+
+    /// Calculate the response of the filter at a set of frequencies
+    ///
+    /// This is calculated from the last parameter values used by the audio
+    /// thread, including the output gain. For each of the `frequencies`, the
+    /// linear magnitude is written to `magnitudes` and the phase in radians is
+    /// written to `phases`.
+    pub fn get_frequency_response(
+        &self,
+        frequencies: &[f64],
+        magnitudes: &mut [f64],
+        phases: &mut [f64],
+    ) {
+        let sample_rate = self.sample_rate as f64;
+
+        let coefficients = BiquadCoefficients::for_filter_type(
+            self.filter_type,
+            self.parameters.current_value("frequency"),
+            sample_rate,
+            self.parameters.current_value("q"),
+            Level::from_linear(self.parameters.current_value("shelf-gain")),
+        );
+        let gain = self.parameters.current_value("gain");
+
+        write_frequency_response(frequencies, magnitudes, phases, |frequency| {
+            gain * coefficients.frequency_response(frequency, sample_rate)
+        });
+    }
 }
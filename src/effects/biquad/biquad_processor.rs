@@ -22,30 +22,13 @@ fn calculate_coefficients(
     parameters: &Parameters,
     sample_rate: usize,
 ) -> BiquadCoefficients {
-    match filter_type {
-        BiquadFilterType::HighPass => {
-            BiquadCoefficients::high_pass(parameters.frequency, sample_rate as f64, parameters.q)
-        }
-        BiquadFilterType::LowPass => {
-            BiquadCoefficients::low_pass(parameters.frequency, sample_rate as f64, parameters.q)
-        }
-        BiquadFilterType::BandPass => {
-            BiquadCoefficients::band_pass(parameters.frequency, sample_rate as f64, parameters.q)
-        }
-        BiquadFilterType::Notch => {
-            BiquadCoefficients::notch(parameters.frequency, sample_rate as f64, parameters.q)
-        }
-        BiquadFilterType::HighShelf => BiquadCoefficients::high_shelf(
-            parameters.frequency,
-            sample_rate as f64,
-            Level::from_linear(parameters.shelf_gain),
-        ),
-        BiquadFilterType::LowShelf => BiquadCoefficients::low_shelf(
-            parameters.frequency,
-            sample_rate as f64,
-            Level::from_linear(parameters.shelf_gain),
-        ),
-    }
+    BiquadCoefficients::for_filter_type(
+        filter_type,
+        parameters.frequency,
+        sample_rate as f64,
+        parameters.q,
+        Level::from_linear(parameters.shelf_gain),
+    )
 }
 
 impl BiquadProcessor {
@@ -101,25 +84,9 @@ impl DspProcessor for BiquadProcessor {
                 let location = SampleLocation::new(channel, frame);
                 let input_sample = context.input_buffer.get_sample(location) as f64;
 
-                let x1 = self.delays[channel][0];
-                let x2 = self.delays[channel][1];
-                let y1 = self.delays[channel][2];
-                let y2 = self.delays[channel][3];
-
-                let out = self.coefficients.b0() * input_sample
-                    + self.coefficients.b1() * x1
-                    + self.coefficients.b2() * x2
-                    - self.coefficients.a1() * y1
-                    - self.coefficients.a2() * y2;
-
-                self.delays[channel][1] = x1;
-                self.delays[channel][0] = input_sample;
-                self.delays[channel][3] = y1;
-                self.delays[channel][2] = out;
-
-                for delay in self.delays[channel].iter_mut() {
-                    *delay = denormal(*delay);
-                }
+                let out = self
+                    .coefficients
+                    .process_sample(input_sample, &mut self.delays[channel]);
 
                 context.output_buffer.set_sample(location, out as f32);
             }
@@ -128,13 +95,3 @@ impl DspProcessor for BiquadProcessor {
         context.output_buffer.apply_gain(gain);
     }
 }
-
-fn denormal(sample: f64) -> f64 {
-    let denormal_threshold = 1e-8;
-
-    if -denormal_threshold <= sample && sample <= denormal_threshold {
-        return 0.0;
-    }
-
-    sample
-}
@@ -2,7 +2,7 @@
 ///
 /// This will determine how the parameters are used to set the coefficients
 /// in the filter
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BiquadFilterType {
     /// High pass filter
     ///
@@ -33,4 +33,15 @@ pub enum BiquadFilterType {
     ///
     /// This applies a fixed gain to frequencies below the cutoff
     LowShelf,
+
+    /// All-pass filter
+    ///
+    /// This passes all frequencies at the same level, but changes the phase
+    /// around the centre frequency
+    AllPass,
+
+    /// Peaking (bell) filter
+    ///
+    /// This applies a gain to a band around the centre frequency
+    Peaking,
 }
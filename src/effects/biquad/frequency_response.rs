@@ -0,0 +1,37 @@
+use super::BiquadCoefficients;
+use rustfft::num_complex::Complex64;
+
+/// The combined response of a cascade of sections at a frequency
+pub fn cascade_response(
+    coefficients: &[BiquadCoefficients],
+    frequency: f64,
+    sample_rate: f64,
+) -> Complex64 {
+    coefficients
+        .iter()
+        .map(|coefficients| coefficients.frequency_response(frequency, sample_rate))
+        .product()
+}
+
+/// Write the magnitude and phase of a response at each frequency, in the same
+/// form as `getFrequencyResponse` in the Web Audio API
+pub fn write_frequency_response(
+    frequencies: &[f64],
+    magnitudes: &mut [f64],
+    phases: &mut [f64],
+    response: impl Fn(f64) -> Complex64,
+) {
+    debug_assert_eq!(frequencies.len(), magnitudes.len());
+    debug_assert_eq!(frequencies.len(), phases.len());
+
+    for ((frequency, magnitude), phase) in frequencies
+        .iter()
+        .zip(magnitudes.iter_mut())
+        .zip(phases.iter_mut())
+    {
+        let response = response(*frequency);
+
+        *magnitude = response.norm();
+        *phase = response.arg();
+    }
+}
@@ -2,6 +2,9 @@ mod biquad_coefficients;
 mod biquad_node;
 mod biquad_processor;
 mod filter_type;
+mod frequency_response;
 
+pub(crate) use biquad_coefficients::BiquadCoefficients;
 pub use biquad_node::Biquad;
 pub use filter_type::BiquadFilterType;
+pub(crate) use frequency_response::{cascade_response, write_frequency_response};
@@ -2,16 +2,23 @@ use crate::{
     commands::Id, graph::DspNode, parameter::Parameters, prelude::*, utility::create_parameters,
 };
 
-use super::{compressor_parameters::get_range, compressor_processor::CompressorProcessor};
+use super::{
+    compressor_parameters::get_range, compressor_processor::CompressorProcessor, CompressorOptions,
+};
 
 /// A basic dynamics compressor
 ///
+/// Use [CompressorOptions] to add a sidechain, or to change how the levels of
+/// the channels are combined. The sidechain inputs come after the main inputs,
+/// starting at [Compressor::first_sidechain_channel].
+///
 /// # Parameters
 /// - attack
 /// - release
 /// - ratio
 /// - threshold
 /// - knee
+/// - sidechain-high-pass (in Hz, 0 turns the filter off)
 /// - wet
 /// - dry
 pub struct Compressor {
@@ -19,6 +26,7 @@ pub struct Compressor {
     pub node: GraphNode,
 
     params: Parameters,
+    channel_count: usize,
 }
 
 impl DspNode for Compressor {
@@ -28,8 +36,33 @@ impl DspNode for Compressor {
 }
 
 impl Compressor {
-    /// Create a new compressor node
+    /// Create a new compressor node, without a sidechain
     pub fn new(context: &dyn Context, channel_count: usize) -> Self {
+        Self::new_with_options(context, channel_count, CompressorOptions::default())
+    }
+
+    /// Create a new compressor node with options
+    ///
+    /// The node has `channel_count` inputs, followed by the sidechain inputs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more inputs than the engine's maximum channel count
+    /// (see [EngineOptions::with_maximum_channel_count]). A stereo compressor
+    /// with a stereo sidechain needs a maximum of at least 4.
+    pub fn new_with_options(
+        context: &dyn Context,
+        channel_count: usize,
+        options: CompressorOptions,
+    ) -> Self {
+        let input_count = channel_count + options.sidechain_channel_count;
+
+        assert!(
+            input_count <= context.maximum_channel_count(),
+            "A compressor with {input_count} inputs needs an engine with a maximum channel count \
+             of at least {input_count}, see EngineOptions::with_maximum_channel_count"
+        );
+
         let id = Id::generate();
 
         let param_ids = [
@@ -38,6 +71,7 @@ impl Compressor {
             "ratio",
             "threshold",
             "knee",
+            "sidechain-high-pass",
             "wet",
             "dry",
         ];
@@ -49,18 +83,20 @@ impl Compressor {
             channel_count,
             context.get_sample_rate(),
             context.maximum_frame_count(),
+            options,
         ));
 
         Self {
             node: GraphNode::new(
                 id,
                 context,
-                channel_count,
+                input_count,
                 channel_count,
                 processor,
                 realtime_params,
             ),
             params,
+            channel_count,
         }
     }
 
@@ -84,6 +120,13 @@ impl Compressor {
     pub fn knee(&mut self) -> &mut AudioParameter {
         self.get_parameter_mut("knee")
     }
+    /// Get the sidechain high-pass parameter
+    ///
+    /// This filters the signal that the level is detected from, so that low
+    /// frequencies cause less gain reduction
+    pub fn sidechain_high_pass(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("sidechain-high-pass")
+    }
     /// Get the wet parameter
     pub fn wet(&mut self) -> &mut AudioParameter {
         self.get_parameter_mut("wet")
@@ -92,4 +135,9 @@ impl Compressor {
     pub fn dry(&mut self) -> &mut AudioParameter {
         self.get_parameter_mut("dry")
     }
+
+    /// The input channel that the sidechain starts at
+    pub fn first_sidechain_channel(&self) -> usize {
+        self.channel_count
+    }
 }
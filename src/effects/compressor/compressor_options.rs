@@ -0,0 +1,39 @@
+/// How the levels of the detected channels control the gain reduction
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CompressorDetection {
+    /// The mean level of the channels sets the gain of every channel
+    #[default]
+    Average,
+
+    /// The loudest channel sets the gain of every channel
+    Maximum,
+
+    /// Each channel is compressed separately
+    Unlinked,
+}
+
+/// Options that are fixed when a [crate::Compressor] is created
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CompressorOptions {
+    pub(super) sidechain_channel_count: usize,
+    pub(super) detection: CompressorDetection,
+}
+
+impl CompressorOptions {
+    /// Add inputs for an external sidechain
+    ///
+    /// The level of the sidechain is used to compress the main input instead
+    /// of its own level, for example to duck music under a voice
+    pub fn with_sidechain(mut self, channel_count: usize) -> Self {
+        self.sidechain_channel_count = channel_count;
+        self
+    }
+
+    /// Choose how the levels of the detected channels are combined
+    ///
+    /// By default the mean level of all channels is used
+    pub fn with_detection(mut self, detection: CompressorDetection) -> Self {
+        self.detection = detection;
+        self
+    }
+}
@@ -7,6 +7,7 @@ pub fn get_range(parameter: &'static str) -> ParameterRange {
         "ratio" => ParameterRange::new(3.0, 1.0, f64::MAX),
         "threshold" => ParameterRange::new(0.0, -128.0, 24.0),
         "knee" => ParameterRange::new(0.0, 0.0, 24.0),
+        "sidechain-high-pass" => ParameterRange::new(0.0, 0.0, 2_000.0),
         "wet" => ParameterRange::new(
             Level::unity().as_linear(),
             Level::zero().as_linear(),
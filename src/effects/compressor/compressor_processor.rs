@@ -2,21 +2,46 @@ use std::time::Duration;
 
 use itertools::izip;
 
-use super::compressor_parameters::get_range;
+use super::{compressor_parameters::get_range, CompressorDetection, CompressorOptions};
 use crate::{
-    dsp::mix_into_with_gains, effects::utility::EnvelopeFollower, graph::*, parameter::ParameterId,
-    prelude::*, ProcessContext,
+    dsp::mix_into_with_gains,
+    effects::{biquad::BiquadCoefficients, utility::EnvelopeFollower},
+    graph::*,
+    parameter::ParameterId,
+    prelude::*,
+    ProcessContext,
 };
 
 pub struct CompressorProcessor {
+    channel_count: usize,
+    detection_offset: usize,
+    detection: CompressorDetection,
     envelopes: Vec<EnvelopeFollower>,
+    high_pass_delays: Vec<[f64; 4]>,
+    high_pass: Option<BiquadCoefficients>,
+    high_pass_frequency: f32,
+    sample_rate: f64,
     gain_reduction_buffer: OwnedAudioBuffer,
 }
 
 impl CompressorProcessor {
-    pub fn new(channel_count: usize, sample_rate: usize, maximum_frame_count: usize) -> Self {
+    pub fn new(
+        channel_count: usize,
+        sample_rate: usize,
+        maximum_frame_count: usize,
+        options: CompressorOptions,
+    ) -> Self {
+        let (detection_offset, detection_channel_count) = if options.sidechain_channel_count > 0 {
+            (channel_count, options.sidechain_channel_count)
+        } else {
+            (0, channel_count)
+        };
+
         Self {
-            envelopes: (0..channel_count)
+            channel_count,
+            detection_offset,
+            detection: options.detection,
+            envelopes: (0..detection_channel_count)
                 .map(|_| {
                     EnvelopeFollower::new(
                         sample_rate as f64,
@@ -25,9 +50,13 @@ impl CompressorProcessor {
                     )
                 })
                 .collect(),
+            high_pass_delays: vec![[0.0; 4]; detection_channel_count],
+            high_pass: None,
+            high_pass_frequency: 0.0,
+            sample_rate: sample_rate as f64,
             gain_reduction_buffer: OwnedAudioBuffer::new(
                 maximum_frame_count,
-                channel_count,
+                detection_channel_count,
                 sample_rate,
             ),
         }
@@ -42,34 +71,68 @@ impl CompressorProcessor {
         parameters.get_parameter_values(parameter, frame_count)
     }
 
+    fn update_high_pass(&mut self, frequency: f32) {
+        if frequency == self.high_pass_frequency {
+            return;
+        }
+
+        // Clear the filter when it turns on, so it doesn't start from the
+        // state it was left in when it was turned off
+        if self.high_pass.is_none() {
+            self.high_pass_delays.fill([0.0; 4]);
+        }
+
+        self.high_pass_frequency = frequency;
+        self.high_pass = (frequency > 0.0).then(|| {
+            BiquadCoefficients::high_pass(
+                frequency as f64,
+                self.sample_rate,
+                std::f64::consts::FRAC_1_SQRT_2,
+            )
+        });
+    }
+
     fn process_envelope(&mut self, context: &mut ProcessContext) {
-        let attack = self.get_parameter_values(
-            "attack",
-            context.output_buffer.frame_count(),
-            context.parameters,
-        );
+        let frame_count = context.output_buffer.frame_count();
 
-        let release = self.get_parameter_values(
-            "release",
-            context.output_buffer.frame_count(),
-            context.parameters,
-        );
+        let attack = self.get_parameter_values("attack", frame_count, context.parameters);
+        let release = self.get_parameter_values("release", frame_count, context.parameters);
+        let high_pass_frequency =
+            self.get_parameter_values("sidechain-high-pass", frame_count, context.parameters);
 
-        for channel in 0..context.input_buffer.channel_count() {
-            let location = SampleLocation::channel(channel);
-            let input_samples = context.input_buffer.get_channel_data(location);
-            let gr_samples = self.gain_reduction_buffer.get_channel_data_mut(location);
-            let envelope = &mut self.envelopes[channel];
-
-            for (input_sample, gr_sample, attack, release) in izip!(
-                input_samples.iter(),
-                gr_samples.iter_mut(),
-                attack.iter(),
-                release.iter(),
-            ) {
-                envelope.set_attack_time(Duration::from_secs_f32(attack / 1_000.0));
-                envelope.set_release_time(Duration::from_secs_f32(release / 1_000.0));
-                *gr_sample = envelope.process(*input_sample);
+        for frame in 0..frame_count {
+            self.update_high_pass(high_pass_frequency[frame]);
+
+            for (channel, (envelope, high_pass_delays)) in self
+                .envelopes
+                .iter_mut()
+                .zip(self.high_pass_delays.iter_mut())
+                .enumerate()
+            {
+                let input_channel = self.detection_offset + channel;
+
+                let input_sample = if input_channel < context.input_buffer.channel_count() {
+                    context
+                        .input_buffer
+                        .get_sample(SampleLocation::new(input_channel, frame))
+                } else {
+                    0.0
+                };
+
+                let input_sample = match &self.high_pass {
+                    Some(high_pass) => {
+                        high_pass.process_sample(input_sample as f64, high_pass_delays) as f32
+                    }
+                    None => input_sample,
+                };
+
+                envelope.set_attack_time(Duration::from_secs_f32(attack[frame] / 1_000.0));
+                envelope.set_release_time(Duration::from_secs_f32(release[frame] / 1_000.0));
+
+                self.gain_reduction_buffer.set_sample(
+                    SampleLocation::new(channel, frame),
+                    envelope.process(input_sample),
+                );
             }
         }
     }
@@ -92,6 +155,21 @@ impl CompressorProcessor {
         Level::from_db_f32(output - envelope_db).as_linear_f32()
     }
 
+    /// Combine the envelopes of the detected channels, as set by the detection
+    /// mode
+    fn combine_envelopes(&self, frame: usize) -> f64 {
+        let channel_count = self.gain_reduction_buffer.channel_count();
+        let envelopes = (0..channel_count).map(|channel| {
+            self.gain_reduction_buffer
+                .get_sample(SampleLocation::new(channel, frame)) as f64
+        });
+
+        match self.detection {
+            CompressorDetection::Maximum => envelopes.fold(0.0, f64::max),
+            _ => envelopes.sum::<f64>() / channel_count as f64,
+        }
+    }
+
     fn process_gain_reduction(&mut self, context: &mut ProcessContext) {
         let knee = self.get_parameter_values(
             "knee",
@@ -117,45 +195,59 @@ impl CompressorProcessor {
             context.parameters,
         );
 
-        let channel_count = context.output_buffer.channel_count();
         let frame_count = context.output_buffer.frame_count();
+        let detection_channel_count = self.gain_reduction_buffer.channel_count();
 
         for frame in 0..frame_count {
-            let mut envelope = 0.0;
+            if self.detection == CompressorDetection::Unlinked {
+                for channel in 0..detection_channel_count {
+                    let location = SampleLocation::new(channel, frame);
+                    let envelope = self.gain_reduction_buffer.get_sample(location);
+
+                    let gain = Self::calculate_gain_reduction(
+                        Level::from_linear(envelope as f64),
+                        knee[frame],
+                        threshold[frame],
+                        ratio[frame],
+                    );
+
+                    self.gain_reduction_buffer.set_sample(location, gain);
+                }
+            } else {
+                let gain = Self::calculate_gain_reduction(
+                    Level::from_linear(self.combine_envelopes(frame)),
+                    knee[frame],
+                    threshold[frame],
+                    ratio[frame],
+                );
 
-            for channel in 0..channel_count {
-                let location = SampleLocation::new(channel, frame);
-                envelope +=
-                    self.gain_reduction_buffer.get_sample(location) as f64 / channel_count as f64;
+                self.gain_reduction_buffer
+                    .set_sample(SampleLocation::new(0, frame), gain);
             }
+        }
 
-            let gain = Self::calculate_gain_reduction(
-                Level::from_linear(envelope),
-                knee[frame],
-                threshold[frame],
-                ratio[frame],
-            );
+        for channel in 0..self
+            .channel_count
+            .min(context.output_buffer.channel_count())
+        {
+            let gain_channel = match self.detection {
+                CompressorDetection::Unlinked => channel % detection_channel_count,
+                _ => 0,
+            };
 
-            for channel in 0..channel_count {
-                let location = SampleLocation::new(channel, frame);
-                self.gain_reduction_buffer.set_sample(location, gain);
+            let location = SampleLocation::channel(channel);
+            let input = context.input_buffer.get_channel_data(location);
+            let output = context.output_buffer.get_channel_data_mut(location);
+            let gain_reduction = self
+                .gain_reduction_buffer
+                .get_channel_data(SampleLocation::channel(gain_channel));
+
+            for (output, input, gain_reduction, wet) in
+                izip!(output.iter_mut(), input, gain_reduction, wet)
+            {
+                *output = input * gain_reduction * wet;
             }
         }
-
-        context.output_buffer.copy_from(
-            context.input_buffer,
-            SampleLocation::origin(),
-            SampleLocation::origin(),
-            context.output_buffer.channel_count(),
-            context.output_buffer.frame_count(),
-        );
-
-        let gain_reduction = self
-            .gain_reduction_buffer
-            .get_channel_data(SampleLocation::channel(0));
-        let gain_reduction = &gain_reduction[..frame_count];
-        context.output_buffer.apply_gain(gain_reduction);
-        context.output_buffer.apply_gain(wet);
     }
 
     fn process_dry_signal(&mut self, context: &mut ProcessContext) {
@@ -165,7 +257,9 @@ impl CompressorProcessor {
             context.parameters,
         );
 
-        let channel_count = context.output_buffer.channel_count();
+        let channel_count = self
+            .channel_count
+            .min(context.output_buffer.channel_count());
 
         for channel in 0..channel_count {
             let location = SampleLocation::channel(channel);
@@ -183,10 +277,7 @@ impl DspProcessor for CompressorProcessor {
             context.input_buffer.frame_count()
         );
 
-        debug_assert_eq!(
-            context.output_buffer.channel_count(),
-            context.input_buffer.channel_count()
-        );
+        debug_assert!(context.input_buffer.channel_count() >= self.channel_count);
 
         self.process_envelope(context);
         self.process_gain_reduction(context);
@@ -216,7 +307,12 @@ mod tests {
 
     impl Default for Fixture {
         fn default() -> Self {
-            let channel_count = 1;
+            Self::new(1, CompressorOptions::default())
+        }
+    }
+
+    impl Fixture {
+        fn new(channel_count: usize, options: CompressorOptions) -> Self {
             let sample_rate = 48_000;
             let maximum_frame_count = 512;
 
@@ -226,6 +322,7 @@ mod tests {
                 "ratio",
                 "threshold",
                 "knee",
+                "sidechain-high-pass",
                 "wet",
                 "dry",
             ];
@@ -243,14 +340,13 @@ mod tests {
                     channel_count,
                     sample_rate,
                     maximum_frame_count,
+                    options,
                 ),
                 parameters: realtime_params,
                 maximum_frame_count,
             }
         }
-    }
 
-    impl Fixture {
         fn set_value(&mut self, parameter: ParameterId, value: f64) {
             self.parameters
                 .get_parameter_mut(parameter)
@@ -263,7 +359,7 @@ mod tests {
             start_time: Timestamp,
         ) -> OwnedAudioBuffer {
             let frame_count = input_signal.frame_count();
-            let channel_count = input_signal.channel_count();
+            let channel_count = self.compressor.channel_count;
             let sample_rate = input_signal.sample_rate();
 
             let mut output_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
@@ -291,6 +387,7 @@ mod tests {
                     output_buffer: &mut output_slice,
                     start_time: &start_time,
                     parameters: &self.parameters,
+                    midi_events: &[],
                 });
             }
 
@@ -359,4 +456,125 @@ mod tests {
             assert_relative_eq!(*input_sample, *output_sample, epsilon = 1e-6);
         }
     }
+
+    fn create_input(channels: &[(f64, f64)]) -> OwnedAudioBuffer {
+        let frame_count = 48_000;
+        let sample_rate = 48_000;
+
+        let mut input = OwnedAudioBuffer::new(frame_count, channels.len(), sample_rate);
+
+        for (channel, (level_db, frequency)) in channels.iter().enumerate() {
+            let sine = OwnedAudioBuffer::sine(
+                frame_count,
+                1,
+                sample_rate,
+                *frequency,
+                Level::from_db(*level_db).as_linear(),
+            );
+
+            input.fill_from_slice(
+                sine.get_channel_data(SampleLocation::origin()),
+                SampleLocation::channel(channel),
+            );
+        }
+
+        input
+    }
+
+    /// The gain applied to a channel, once the envelope has settled
+    fn settled_gain(input: &dyn AudioBuffer, output: &dyn AudioBuffer, channel: usize) -> f32 {
+        let peak = |buffer: &dyn AudioBuffer| {
+            let data = buffer.get_channel_data(SampleLocation::channel(channel));
+            data[data.len() / 2..]
+                .iter()
+                .fold(0.0_f32, |peak, sample| sample.abs().max(peak))
+        };
+
+        peak(output) / peak(input)
+    }
+
+    fn compress_hard(fixture: &mut Fixture) {
+        fixture.set_value("threshold", -20.0);
+        fixture.set_value("ratio", 20.0);
+    }
+
+    #[test]
+    fn sidechain_controls_the_gain() {
+        let options = CompressorOptions::default().with_sidechain(1);
+
+        let mut fixture = Fixture::new(1, options);
+        compress_hard(&mut fixture);
+        let input = create_input(&[(-30.0, 1_000.0), (0.0, 1_000.0)]);
+        let output = fixture.process(&input, Timestamp::zero());
+        assert!(settled_gain(&input, &output, 0) < Level::from_db(-15.0).as_linear_f32());
+
+        let mut fixture = Fixture::new(1, options);
+        compress_hard(&mut fixture);
+        let input = create_input(&[(-30.0, 1_000.0), (-60.0, 1_000.0)]);
+        let output = fixture.process(&input, Timestamp::zero());
+        assert_relative_eq!(settled_gain(&input, &output, 0), 1.0, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn detection_modes() {
+        let input = create_input(&[(0.0, 1_000.0), (-40.0, 1_000.0)]);
+
+        let gains = |detection| {
+            let mut fixture =
+                Fixture::new(2, CompressorOptions::default().with_detection(detection));
+            compress_hard(&mut fixture);
+            let output = fixture.process(&input, Timestamp::zero());
+            (
+                settled_gain(&input, &output, 0),
+                settled_gain(&input, &output, 1),
+            )
+        };
+
+        let (loud, quiet) = gains(CompressorDetection::Unlinked);
+        assert!(loud < Level::from_db(-15.0).as_linear_f32());
+        assert_relative_eq!(quiet, 1.0, epsilon = 1e-3);
+
+        let (loud, quiet) = gains(CompressorDetection::Maximum);
+        assert_relative_eq!(loud, quiet, epsilon = 1e-3);
+        let maximum_gain = loud;
+
+        let (loud, quiet) = gains(CompressorDetection::Average);
+        assert_relative_eq!(loud, quiet, epsilon = 1e-3);
+        assert!(loud > maximum_gain);
+    }
+
+    #[test]
+    fn sidechain_high_pass_ignores_low_frequencies() {
+        let input = create_input(&[(-30.0, 1_000.0), (0.0, 40.0)]);
+
+        let gain = |high_pass_frequency| {
+            let mut fixture = Fixture::new(1, CompressorOptions::default().with_sidechain(1));
+            compress_hard(&mut fixture);
+            fixture.set_value("sidechain-high-pass", high_pass_frequency);
+            let output = fixture.process(&input, Timestamp::zero());
+            settled_gain(&input, &output, 0)
+        };
+
+        let unfiltered = gain(0.0);
+        let filtered = gain(1_000.0);
+
+        assert!(unfiltered < Level::from_db(-15.0).as_linear_f32());
+        assert!(filtered > Level::from_db(-6.0).as_linear_f32());
+    }
+
+    #[test]
+    fn sidechain_high_pass_is_cleared_when_turned_on() {
+        let mut fixture = Fixture::new(1, CompressorOptions::default().with_sidechain(1));
+        let compressor = &mut fixture.compressor;
+
+        compressor.update_high_pass(1_000.0);
+        compressor.high_pass_delays[0] = [1.0; 4];
+
+        compressor.update_high_pass(500.0);
+        assert_eq!(compressor.high_pass_delays[0], [1.0; 4]);
+
+        compressor.update_high_pass(0.0);
+        compressor.update_high_pass(1_000.0);
+        assert_eq!(compressor.high_pass_delays[0], [0.0; 4]);
+    }
 }
@@ -1,5 +1,7 @@
 mod compressor_node;
+mod compressor_options;
 mod compressor_parameters;
 mod compressor_processor;
 
 pub use compressor_node::Compressor;
+pub use compressor_options::{CompressorDetection, CompressorOptions};
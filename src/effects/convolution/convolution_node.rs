@@ -1,9 +1,26 @@
-use crate::{commands::Id, graph::DspNode, parameter::*, prelude::*, utility::create_parameters};
+use crate::{
+    commands::Id, effects::Channel, graph::DspNode, parameter::*, prelude::*,
+    utility::create_parameters,
+};
+use std::time::Duration;
 
-use super::convolution_processor::ConvolutionProcessor;
+use super::{
+    convolution_processor::{ConvolutionProcessor, ImpulseChange},
+    partitioned_convolver::PartitionedConvolver,
+};
 
 /// A convolution node to convolve the input signal with an impulse response
 ///
+/// The convolution doesn't add any latency, and the cost of processing grows
+/// slowly with the length of the impulse, so long reverb impulses can be used
+/// in realtime. The impulse can be changed while the node is running with
+/// [Convolution::set_impulse].
+///
+/// By default each input channel is convolved with the matching channel of
+/// the impulse. [Convolution::new_matrix] instead applies an impulse for every
+/// pair of input and output channels, which is needed for true stereo and
+/// multichannel impulse responses.
+///
 /// # Parameters
 /// - wet
 /// - dry
@@ -12,8 +29,38 @@ pub struct Convolution {
     pub node: GraphNode,
 
     params: Parameters,
+    routing: Routing,
+    impulse_channel_count: usize,
+    sample_rate: usize,
+    change_transmitter: Channel::Sender<ImpulseChange>,
+    retired_receiver: Channel::Receiver<Box<PartitionedConvolver>>,
+}
+
+/// How the channels of the impulse are applied
+#[derive(Clone, Copy)]
+enum Routing {
+    Diagonal,
+    Matrix {
+        input_count: usize,
+        output_count: usize,
+    },
+}
+
+impl Routing {
+    fn create_convolver(&self, impulse: &dyn AudioBuffer) -> PartitionedConvolver {
+        match self {
+            Routing::Diagonal => PartitionedConvolver::new(impulse),
+            Routing::Matrix {
+                input_count,
+                output_count,
+            } => PartitionedConvolver::matrix(impulse, *input_count, *output_count),
+        }
+    }
 }
 
+static CHANGE_CHANNEL_CAPACITY: usize = 4;
+static RETIRED_CHANNEL_CAPACITY: usize = 8;
+
 impl DspNode for Convolution {
     fn get_parameters_mut(&mut self) -> &mut crate::parameter::Parameters {
         &mut self.params
@@ -22,10 +69,64 @@ impl DspNode for Convolution {
 
 impl Convolution {
     /// Create a new convolution node using the given impulse response
+    ///
+    /// Each input channel is convolved with the same channel of the impulse,
+    /// and the node has an output for each channel of the impulse.
     pub fn new(context: &dyn Context, input_count: usize, impulse: OwnedAudioBuffer) -> Self {
-        let id = Id::generate();
-
         let output_count = impulse.channel_count();
+        Self::with_routing(
+            context,
+            input_count,
+            output_count,
+            Routing::Diagonal,
+            impulse,
+        )
+    }
+
+    /// Create a new convolution node that convolves every input channel with
+    /// every output channel
+    ///
+    /// The impulse must have `input_count * output_count` channels, ordered by
+    /// input and then by output. For a true stereo impulse this is left to
+    /// left, left to right, right to left, then right to right. The spectrum of
+    /// each input is shared between all of the outputs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the impulse doesn't have `input_count * output_count`
+    /// channels
+    pub fn new_matrix(
+        context: &dyn Context,
+        input_count: usize,
+        output_count: usize,
+        impulse: OwnedAudioBuffer,
+    ) -> Self {
+        assert_eq!(
+            impulse.channel_count(),
+            input_count * output_count,
+            "The impulse must have a channel for each input and output pair"
+        );
+
+        Self::with_routing(
+            context,
+            input_count,
+            output_count,
+            Routing::Matrix {
+                input_count,
+                output_count,
+            },
+            impulse,
+        )
+    }
+
+    fn with_routing(
+        context: &dyn Context,
+        input_count: usize,
+        output_count: usize,
+        routing: Routing,
+        impulse: OwnedAudioBuffer,
+    ) -> Self {
+        let id = Id::generate();
 
         let (params, realtime_params) = create_parameters(
             id,
@@ -36,9 +137,15 @@ impl Convolution {
             ],
         );
 
+        let (change_transmitter, change_receiver) = Channel::bounded(CHANGE_CHANNEL_CAPACITY);
+        let (retired_transmitter, retired_receiver) = Channel::bounded(RETIRED_CHANNEL_CAPACITY);
+
         let processor = Box::new(ConvolutionProcessor::new(
-            &impulse,
+            routing.create_convolver(&impulse),
+            context.get_sample_rate(),
             context.maximum_frame_count(),
+            change_receiver,
+            retired_transmitter,
         ));
 
         Self {
@@ -51,9 +158,43 @@ impl Convolution {
                 realtime_params,
             ),
             params,
+            routing,
+            impulse_channel_count: impulse.channel_count(),
+            sample_rate: context.get_sample_rate(),
+            change_transmitter,
+            retired_receiver,
         }
     }
 
+    /// Replace the impulse response, fading from the old one over
+    /// `crossfade_time`
+    ///
+    /// The new impulse is prepared on the calling thread, so this can take a
+    /// while for long impulses, but the audio thread isn't interrupted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the impulse doesn't have the same number of channels as the
+    /// original
+    pub fn set_impulse(&mut self, impulse: OwnedAudioBuffer, crossfade_time: Duration) {
+        assert_eq!(
+            impulse.channel_count(),
+            self.impulse_channel_count,
+            "The new impulse must have the same number of channels"
+        );
+
+        while self.retired_receiver.try_recv().is_ok() {}
+
+        let crossfade_frames =
+            (crossfade_time.as_secs_f64() * self.sample_rate as f64).round() as usize;
+
+        debug_assert!(!self.change_transmitter.is_full());
+        let _ = self.change_transmitter.send(ImpulseChange {
+            convolver: Box::new(self.routing.create_convolver(&impulse)),
+            crossfade_frames,
+        });
+    }
+
     /// Get the wet parameter
     pub fn wet(&mut self) -> &mut AudioParameter {
         self.get_parameter_mut("wet")
@@ -1,100 +1,126 @@
-use crate::{dsp::mix_into_with_gains, graph::DspProcessor, prelude::*};
+use super::partitioned_convolver::PartitionedConvolver;
+use crate::{dsp::mix_into_with_gains, effects::Channel, graph::DspProcessor, prelude::*};
 use itertools::izip;
-use rustfft::{num_complex::Complex, num_traits::Zero, Fft, FftPlanner};
-use std::sync::Arc;
 
-type ComplexAudioBuffer = Vec<Vec<Complex<f32>>>;
+/// A new impulse response for the processor to fade to
+pub struct ImpulseChange {
+    pub convolver: Box<PartitionedConvolver>,
+    pub crossfade_frames: usize,
+}
+
+/// A convolver that is being faded out
+struct Crossfade {
+    convolver: Box<PartitionedConvolver>,
+    position: usize,
+    length: usize,
+}
 
 pub struct ConvolutionProcessor {
-    fft: Arc<dyn Fft<f32>>,
-    ifft: Arc<dyn Fft<f32>>,
-    impulse_fft: ComplexAudioBuffer,
-    input_fft: ComplexAudioBuffer,
-    complex_input: ComplexAudioBuffer,
-    complex_output: ComplexAudioBuffer,
-    output_scale: f32,
+    convolver: Box<PartitionedConvolver>,
+    crossfade: Option<Crossfade>,
+    wet_buffer: OwnedAudioBuffer,
+    crossfade_buffer: OwnedAudioBuffer,
     maximum_frame_count: usize,
+    change_receiver: Channel::Receiver<ImpulseChange>,
+    retired_transmitter: Channel::Sender<Box<PartitionedConvolver>>,
 }
 
 impl ConvolutionProcessor {
-    pub fn new(impulse: &dyn AudioBuffer, maximum_frame_count: usize) -> Self {
-        let convolution_length =
-            (impulse.frame_count() + maximum_frame_count - 1).next_power_of_two();
-        let output_channel_count = impulse.channel_count();
-
-        let mut planner = FftPlanner::new();
-        let fft = planner.plan_fft_forward(convolution_length);
+    pub fn new(
+        convolver: PartitionedConvolver,
+        sample_rate: usize,
+        maximum_frame_count: usize,
+        change_receiver: Channel::Receiver<ImpulseChange>,
+        retired_transmitter: Channel::Sender<Box<PartitionedConvolver>>,
+    ) -> Self {
+        let channel_count = convolver.output_count();
 
         Self {
-            fft: fft.clone(),
-            ifft: planner.plan_fft_inverse(convolution_length),
-            impulse_fft: fft_impulse(impulse, fft.as_ref(), convolution_length),
-            input_fft: create_complex_audio_buffer(output_channel_count, convolution_length),
-            complex_input: create_complex_audio_buffer(output_channel_count, convolution_length),
-            complex_output: create_complex_audio_buffer(output_channel_count, convolution_length),
-            output_scale: 1.0 / convolution_length as f32,
+            convolver: Box::new(convolver),
+            crossfade: None,
+            wet_buffer: OwnedAudioBuffer::new(maximum_frame_count, channel_count, sample_rate),
+            crossfade_buffer: OwnedAudioBuffer::new(
+                maximum_frame_count,
+                channel_count,
+                sample_rate,
+            ),
             maximum_frame_count,
+            change_receiver,
+            retired_transmitter,
         }
     }
 
-    fn consume_input(&mut self, input: &dyn AudioBuffer) {
-        debug_assert_eq!(input.channel_count(), self.complex_input.len());
-        for channel in 0..input.channel_count() {
-            let complex_input = self
-                .complex_input
-                .get_mut(channel)
-                .expect("Invalid input channel count");
-
-            complex_input.copy_within(input.frame_count().., 0);
+    /// Hand a convolver that is no longer used back to the node, so that it
+    /// isn't freed on the audio thread
+    fn retire(&mut self, convolver: Box<PartitionedConvolver>) {
+        let _ = self.retired_transmitter.try_send(convolver);
+    }
 
-            let destination_index = complex_input.len() - input.frame_count();
+    fn receive_changes(&mut self) {
+        while let Ok(change) = self.change_receiver.try_recv() {
+            if let Some(crossfade) = self.crossfade.take() {
+                self.retire(crossfade.convolver);
+            }
 
-            let complex_input_slice = &mut complex_input[destination_index..];
-            let audio_data = input.get_channel_data(SampleLocation::channel(channel));
-            debug_assert_eq!(complex_input_slice.len(), audio_data.len());
+            let previous = std::mem::replace(&mut self.convolver, change.convolver);
 
-            for (sample, complex_sample) in izip!(audio_data.iter(), complex_input_slice.iter_mut())
-            {
-                *complex_sample = Complex::new(*sample, 0.0_f32);
+            if change.crossfade_frames > 0 {
+                self.crossfade = Some(Crossfade {
+                    convolver: previous,
+                    position: 0,
+                    length: change.crossfade_frames,
+                });
+            } else {
+                self.retire(previous);
             }
         }
     }
 
-    fn fft_input(&mut self) {
-        for (complex_input, input_fft) in
-            izip!(self.complex_input.iter(), self.input_fft.iter_mut())
-        {
-            input_fft.copy_from_slice(complex_input);
-            self.fft.process(input_fft);
-        }
-    }
+    /// Convolve the input into the wet buffer, fading from the previous
+    /// impulse if it has just changed
+    fn convolve(&mut self, input: &dyn AudioBuffer) {
+        let frame_count = input.frame_count();
 
-    fn perform_fft_multiplication(&mut self) {
-        for (input_fft, impulse_fft, output_fft) in izip!(
-            self.input_fft.iter(),
-            self.impulse_fft.iter(),
-            self.complex_output.iter_mut()
-        ) {
-            for (input_value, impulse_value, output_value) in
-                izip!(input_fft, impulse_fft, output_fft)
-            {
-                *output_value = *input_value * *impulse_value;
+        let mut wet_buffer =
+            MutableBorrowedAudioBuffer::slice_frames(&mut self.wet_buffer, 0, frame_count);
+        self.convolver.process(input, &mut wet_buffer);
+
+        let Some(crossfade) = self.crossfade.as_mut() else {
+            return;
+        };
+
+        let mut crossfade_buffer =
+            MutableBorrowedAudioBuffer::slice_frames(&mut self.crossfade_buffer, 0, frame_count);
+        crossfade.convolver.process(input, &mut crossfade_buffer);
+
+        for channel in 0..wet_buffer.channel_count() {
+            let location = SampleLocation::channel(channel);
+            let previous_output = crossfade_buffer.get_channel_data(location);
+            let output = wet_buffer.get_channel_data_mut(location);
+
+            for (frame, (output, previous)) in output.iter_mut().zip(previous_output).enumerate() {
+                let fade_in =
+                    ((crossfade.position + frame) as f32 / crossfade.length as f32).min(1.0);
+                *output = fade_in * *output + (1.0 - fade_in) * previous;
             }
         }
-    }
 
-    fn ifft_output(&mut self) {
-        for output_fft in self.complex_output.iter_mut() {
-            self.ifft.process(output_fft);
+        crossfade.position += frame_count;
+
+        if crossfade.position >= crossfade.length {
+            if let Some(crossfade) = self.crossfade.take() {
+                self.retire(crossfade.convolver);
+            }
         }
     }
 
+    /// Mix each input channel into the output channel with the same index,
+    /// ignoring channels that only exist on one side
     fn copy_dry_to_output(input: &dyn AudioBuffer, output: &mut dyn AudioBuffer, dry: &[f32]) {
-        debug_assert_eq!(input.channel_count(), output.channel_count());
         debug_assert_eq!(input.frame_count(), output.frame_count());
         debug_assert_eq!(input.frame_count(), dry.len());
 
-        for channel in 0..output.channel_count() {
+        for channel in 0..output.channel_count().min(input.channel_count()) {
             let output = output.get_channel_data_mut(SampleLocation::channel(channel));
             let input = input.get_channel_data(SampleLocation::channel(channel));
             mix_into_with_gains(input, output, dry);
@@ -102,26 +128,22 @@ impl ConvolutionProcessor {
     }
 
     fn copy_processed_to_output(
-        complex_output: &ComplexAudioBuffer,
-        output_scale: f32,
+        wet_buffer: &dyn AudioBuffer,
         output: &mut dyn AudioBuffer,
         wet: &[f32],
     ) {
-        debug_assert_eq!(output.channel_count(), complex_output.len());
+        debug_assert_eq!(output.channel_count(), wet_buffer.channel_count());
         debug_assert!(wet.len() >= output.frame_count());
 
         for channel in 0..output.channel_count() {
-            let audio_data = output.get_channel_data_mut(SampleLocation::channel(channel));
-
-            let convolution_output = complex_output.get(channel).expect("Invalid channel");
-
-            let index = convolution_output.len() - audio_data.len();
-            let convolution_output = &convolution_output[index..];
+            let location = SampleLocation::channel(channel);
+            let convolution_output = wet_buffer.get_channel_data(location);
+            let audio_data = output.get_channel_data_mut(location);
 
-            for (output_sample, complex_convolution_output, wet) in
+            for (output_sample, convolution_sample, wet) in
                 izip!(audio_data, convolution_output, wet)
             {
-                *output_sample += complex_convolution_output.re * output_scale * *wet;
+                *output_sample += convolution_sample * *wet;
             }
         }
     }
@@ -133,11 +155,12 @@ impl ConvolutionProcessor {
         wet: &[f32],
         dry: &[f32],
     ) {
-        debug_assert_eq!(input.channel_count(), output.channel_count());
         debug_assert_eq!(input.frame_count(), output.frame_count());
         debug_assert_eq!(input.frame_count(), wet.len());
         debug_assert_eq!(input.frame_count(), dry.len());
 
+        self.receive_changes();
+
         for offset in (0..input.frame_count()).step_by(self.maximum_frame_count) {
             let frame_count = std::cmp::min(self.maximum_frame_count, input.frame_count() - offset);
 
@@ -146,15 +169,11 @@ impl ConvolutionProcessor {
             let input = BorrowedAudioBuffer::slice_frames(input, offset, frame_count);
             let mut output = MutableBorrowedAudioBuffer::slice_frames(output, offset, frame_count);
 
-            self.consume_input(&input);
-            self.fft_input();
-            self.perform_fft_multiplication();
-            self.ifft_output();
+            self.convolve(&input);
 
             Self::copy_dry_to_output(&input, &mut output, dry);
             Self::copy_processed_to_output(
-                &self.complex_output,
-                self.output_scale,
+                &BorrowedAudioBuffer::slice_frames(&self.wet_buffer, 0, frame_count),
                 &mut output,
                 wet,
             );
@@ -162,36 +181,6 @@ impl ConvolutionProcessor {
     }
 }
 
-fn create_complex_audio_buffer(channel_count: usize, length: usize) -> ComplexAudioBuffer {
-    (0..channel_count)
-        .map(|_| vec![Complex::zero(); length])
-        .collect()
-}
-
-fn fft_impulse(
-    impulse: &dyn AudioBuffer,
-    fft: &dyn Fft<f32>,
-    convolution_length: usize,
-) -> ComplexAudioBuffer {
-    let mut impulse_fft = Vec::new();
-
-    for channel in 0..impulse.channel_count() {
-        let impulse_data = impulse.get_channel_data(SampleLocation::channel(channel));
-
-        let mut impulse_data: Vec<Complex<f32>> = impulse_data
-            .iter()
-            .map(|sample| Complex::new(*sample, 0.0_f32))
-            .collect();
-
-        impulse_data.resize(convolution_length, Complex::zero());
-
-        fft.process(&mut impulse_data);
-        impulse_fft.push(impulse_data);
-    }
-
-    impulse_fft
-}
-
 impl DspProcessor for ConvolutionProcessor {
     fn process_audio(&mut self, context: &mut crate::ProcessContext) {
         let wet = context
@@ -241,13 +230,15 @@ mod tests {
     }
 
     fn random_signal(length: usize) -> Vec<f32> {
-        let mut rng = rand::thread_rng();
-        (0..length).map(|_| rng.gen_range(-1.0..=1.0)).collect()
+        let mut rng = rand::rng();
+        (0..length).map(|_| rng.random_range(-1.0..=1.0)).collect()
     }
 
     struct Fixture {
         processor: ConvolutionProcessor,
         sample_rate: usize,
+        change_transmitter: Channel::Sender<ImpulseChange>,
+        _retired_receiver: Channel::Receiver<Box<PartitionedConvolver>>,
     }
 
     impl Fixture {
@@ -256,12 +247,32 @@ mod tests {
             let sample_rate = 48_000;
             let impulse = OwnedAudioBuffer::from_slice(impulse, channel_count, sample_rate);
 
+            let (change_transmitter, change_receiver) = Channel::bounded(4);
+            let (retired_transmitter, retired_receiver) = Channel::bounded(4);
+
             Self {
-                processor: ConvolutionProcessor::new(&impulse, maximum_frame_count),
+                processor: ConvolutionProcessor::new(
+                    PartitionedConvolver::new(&impulse),
+                    sample_rate,
+                    maximum_frame_count,
+                    change_receiver,
+                    retired_transmitter,
+                ),
                 sample_rate,
+                change_transmitter,
+                _retired_receiver: retired_receiver,
             }
         }
 
+        fn change_impulse(&mut self, impulse: &[f32], crossfade_frames: usize) {
+            let impulse = OwnedAudioBuffer::from_slice(impulse, 1, self.sample_rate);
+
+            let _ = self.change_transmitter.send(ImpulseChange {
+                convolver: Box::new(PartitionedConvolver::new(&impulse)),
+                crossfade_frames,
+            });
+        }
+
         fn process(&mut self, input: &[f32], wet: f32, dry: f32) -> Vec<f32> {
             let channel_count = 1;
 
@@ -397,4 +408,73 @@ mod tests {
             assert_relative_eq!(expected, *processed_sample, epsilon = 1e-3);
         }
     }
+
+    #[test]
+    fn long_impulse_in_uneven_blocks() {
+        let taps = [
+            (0, 0.5),
+            (63, -0.25),
+            (64, 0.75),
+            (1_000, 0.3),
+            (20_000, -0.6),
+            (70_001, 0.4),
+        ];
+
+        let mut impulse = vec![0.0; 80_000];
+        for (frame, gain) in taps {
+            impulse[frame] = gain;
+        }
+
+        let input = random_signal(512);
+        let mut expected = vec![0.0; impulse.len() + input.len()];
+        for (frame, gain) in taps {
+            for (index, sample) in input.iter().enumerate() {
+                expected[frame + index] += gain * sample;
+            }
+        }
+
+        let mut fixture = Fixture::new(&impulse, 1024);
+        let mut padded_input = input.clone();
+        padded_input.resize(expected.len(), 0.0);
+
+        let mut result = vec![];
+        let mut offset = 0;
+
+        for block_size in [1, 37, 300, 511, 1024].into_iter().cycle() {
+            if offset == padded_input.len() {
+                break;
+            }
+
+            let frames = block_size.min(padded_input.len() - offset);
+            result.extend(fixture.process(&padded_input[offset..offset + frames], 1.0, 0.0));
+            offset += frames;
+        }
+
+        for (expected_sample, processed_sample) in zip(expected.iter(), result.iter()) {
+            assert_relative_eq!(*expected_sample, *processed_sample, epsilon = 1e-3);
+        }
+    }
+
+    #[test]
+    fn crossfades_to_a_new_impulse() {
+        let mut fixture = Fixture::new(&[1.0], 1024);
+        let input = vec![1.0; 1024];
+
+        assert!(fixture
+            .process(&input, 1.0, 0.0)
+            .iter()
+            .all(|sample| (sample - 1.0).abs() < 1e-6));
+
+        let crossfade_frames = 512;
+        fixture.change_impulse(&[0.5], crossfade_frames);
+        let output = fixture.process(&input, 1.0, 0.0);
+
+        assert_relative_eq!(output[0], 1.0, epsilon = 1e-6);
+        assert_relative_eq!(output[crossfade_frames / 2], 0.75, epsilon = 1e-6);
+        assert!(output[crossfade_frames..]
+            .iter()
+            .all(|sample| (sample - 0.5).abs() < 1e-6));
+
+        assert!(output.windows(2).all(|pair| pair[1] <= pair[0]));
+    }
 }
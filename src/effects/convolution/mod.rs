@@ -1,4 +1,5 @@
 mod convolution_node;
 mod convolution_processor;
+mod partitioned_convolver;
 
 pub use convolution_node::Convolution;
@@ -0,0 +1,671 @@
+use crate::prelude::*;
+use itertools::izip;
+use rustfft::{num_complex::Complex, num_traits::Zero, Fft, FftPlanner};
+use std::sync::Arc;
+
+/// The number of taps at the start of the impulse that are convolved directly
+///
+/// This is also the size of the smallest partition
+const HEAD_LENGTH: usize = 64;
+
+/// Each stage uses partitions this many times bigger than the stage before it
+const STAGE_GROWTH: usize = 4;
+
+/// The partition size of the last stage, which covers the rest of the impulse
+const MAXIMUM_PARTITION_SIZE: usize = 16_384;
+
+/// The partitions of the impulse that are handled by one stage
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct StageLayout {
+    partition_size: usize,
+    offset: usize,
+    partition_count: usize,
+}
+
+/// Split an impulse into stages of increasing partition size
+///
+/// The first stage starts at an offset equal to its partition size, so its
+/// output has to be calculated as soon as each partition of input arrives.
+/// Every later stage starts at twice its partition size, which leaves a whole
+/// partition of time to calculate each output, so the work can be spread out.
+/// No latency is added either way. The cost per sample grows with the
+/// logarithm of the impulse length, until the largest partition size is
+/// reached.
+fn stage_layouts(impulse_length: usize) -> Vec<StageLayout> {
+    let mut layouts = Vec::new();
+    let mut partition_size = HEAD_LENGTH;
+    let mut offset = HEAD_LENGTH;
+
+    while offset < impulse_length {
+        let end = if partition_size < MAXIMUM_PARTITION_SIZE {
+            impulse_length.min(2 * STAGE_GROWTH * partition_size)
+        } else {
+            impulse_length
+        };
+
+        let partition_count = (end - offset).div_ceil(partition_size);
+
+        layouts.push(StageLayout {
+            partition_size,
+            offset,
+            partition_count,
+        });
+
+        offset += partition_count * partition_size;
+        partition_size *= STAGE_GROWTH;
+    }
+
+    layouts
+}
+
+/// The recent input to a channel
+struct InputHistory {
+    samples: Vec<f32>,
+    write_position: usize,
+}
+
+impl InputHistory {
+    fn new(length: usize) -> Self {
+        Self {
+            samples: vec![0.0; length.next_power_of_two()],
+            write_position: 0,
+        }
+    }
+
+    fn mask(&self) -> usize {
+        self.samples.len() - 1
+    }
+
+    fn write(&mut self, sample: f32) {
+        self.write_position = (self.write_position + 1) & self.mask();
+        self.samples[self.write_position] = sample;
+    }
+
+    /// The sample written `delay` frames before the last one
+    fn get(&self, delay: usize) -> f32 {
+        self.samples[self.write_position.wrapping_sub(delay) & self.mask()]
+    }
+
+    /// Copy the input that ended `delay` frames before the last one into
+    /// `destination`, oldest first
+    fn copy_before(&self, destination: &mut [Complex<f32>], delay: usize) {
+        let length = destination.len();
+
+        for (index, value) in destination.iter_mut().enumerate() {
+            *value = Complex::new(self.get(delay + length - 1 - index), 0.0);
+        }
+    }
+}
+
+/// The spectra of the recent input to one channel, for one stage
+struct InputSpectra {
+    spectra: Vec<Vec<Complex<f32>>>,
+    newest: usize,
+}
+
+impl InputSpectra {
+    fn push(&mut self, spectrum: &[Complex<f32>]) {
+        self.newest = (self.newest + 1) % self.spectra.len();
+        self.spectra[self.newest].copy_from_slice(spectrum);
+    }
+
+    /// The spectrum from `age` partitions before the newest
+    fn get(&self, age: usize) -> &[Complex<f32>] {
+        let count = self.spectra.len();
+        &self.spectra[(self.newest + count - age) % count]
+    }
+}
+
+/// Which impulse channel connects an input channel to an output channel
+#[derive(Clone, Copy)]
+struct Route {
+    input: usize,
+    output: usize,
+    impulse_channel: usize,
+}
+
+/// The partitions of one route's impulse that belong to a stage
+struct StageImpulse {
+    route: Route,
+    spectra: Vec<Vec<Complex<f32>>>,
+}
+
+/// A share of the work to calculate one partition of a stage's output
+enum StageTask {
+    /// Transform the latest partition of an input channel
+    Transform { input: usize },
+
+    /// Add the products for one partition of the impulse to an output
+    MultiplyAccumulate { output: usize, age: usize },
+
+    /// Transform an output back into samples
+    InverseTransform { output: usize },
+}
+
+/// Uniformly partitioned overlap-save convolution for one partition size
+///
+/// The spectrum of each input partition is calculated once, and kept in a
+/// frequency-domain delay line that is shared by every output. The products
+/// for each output are summed before a single inverse FFT.
+///
+/// Stages that start at twice their partition size calculate the output for
+/// the next partition while the current one plays, so their work is split
+/// into tasks that are spread evenly over the partition. This keeps the cost
+/// of each block close to the average, rather than every stage doing all of
+/// its work in the same block.
+struct ConvolutionStage {
+    partition_size: usize,
+    partition_count: usize,
+    is_deferred: bool,
+    fft: Arc<dyn Fft<f32>>,
+    ifft: Arc<dyn Fft<f32>>,
+    impulses: Vec<StageImpulse>,
+    inputs: Vec<InputSpectra>,
+    sums: Vec<Vec<Complex<f32>>>,
+    outputs: Vec<Vec<f32>>,
+    pending_outputs: Vec<Vec<f32>>,
+    work: Vec<Complex<f32>>,
+    scratch: Vec<Complex<f32>>,
+}
+
+impl ConvolutionStage {
+    fn new(
+        layout: &StageLayout,
+        impulse: &dyn AudioBuffer,
+        routes: &[Route],
+        input_count: usize,
+        output_count: usize,
+        planner: &mut FftPlanner<f32>,
+    ) -> Self {
+        let fft_length = 2 * layout.partition_size;
+        let output_scale = 1.0 / fft_length as f32;
+
+        let fft = planner.plan_fft_forward(fft_length);
+        let ifft = planner.plan_fft_inverse(fft_length);
+
+        let impulses = routes
+            .iter()
+            .map(|route| {
+                let impulse =
+                    impulse.get_channel_data(SampleLocation::channel(route.impulse_channel));
+
+                let spectra = (0..layout.partition_count)
+                    .map(|partition| {
+                        let start =
+                            (layout.offset + partition * layout.partition_size).min(impulse.len());
+                        let end = (start + layout.partition_size).min(impulse.len());
+
+                        let mut spectrum = vec![Complex::zero(); fft_length];
+                        for (value, sample) in spectrum.iter_mut().zip(impulse[start..end].iter()) {
+                            *value = Complex::new(*sample * output_scale, 0.0);
+                        }
+
+                        fft.process(&mut spectrum);
+                        spectrum
+                    })
+                    .collect();
+
+                StageImpulse {
+                    route: *route,
+                    spectra,
+                }
+            })
+            .collect();
+
+        let scratch_length = fft
+            .get_inplace_scratch_len()
+            .max(ifft.get_inplace_scratch_len());
+
+        let is_deferred = layout.offset >= 2 * layout.partition_size;
+        debug_assert!(is_deferred || layout.partition_size == HEAD_LENGTH);
+
+        Self {
+            partition_size: layout.partition_size,
+            partition_count: layout.partition_count,
+            is_deferred,
+            fft,
+            ifft,
+            impulses,
+            inputs: (0..input_count)
+                .map(|_| InputSpectra {
+                    spectra: vec![vec![Complex::zero(); fft_length]; layout.partition_count],
+                    newest: 0,
+                })
+                .collect(),
+            sums: vec![vec![Complex::zero(); fft_length]; output_count],
+            outputs: vec![vec![0.0; layout.partition_size]; output_count],
+            pending_outputs: vec![vec![0.0; layout.partition_size]; output_count],
+            work: vec![Complex::zero(); fft_length],
+            scratch: vec![Complex::zero(); scratch_length],
+        }
+    }
+
+    fn task_count(&self) -> usize {
+        self.inputs.len() + self.outputs.len() * (self.partition_count + 1)
+    }
+
+    fn task(&self, index: usize) -> StageTask {
+        let input_count = self.inputs.len();
+
+        if index < input_count {
+            return StageTask::Transform { input: index };
+        }
+
+        let index = index - input_count;
+        let output = index / (self.partition_count + 1);
+        let age = index % (self.partition_count + 1);
+
+        if age < self.partition_count {
+            StageTask::MultiplyAccumulate { output, age }
+        } else {
+            StageTask::InverseTransform { output }
+        }
+    }
+
+    /// The tasks to run at the start of each block of `HEAD_LENGTH` frames
+    /// in a partition
+    fn tasks_for_block(&self, block: usize) -> std::ops::Range<usize> {
+        let block_count = self.partition_size / HEAD_LENGTH;
+        let task_count = self.task_count();
+
+        (block * task_count).div_ceil(block_count)..((block + 1) * task_count).div_ceil(block_count)
+    }
+
+    /// Do the stage's share of the work at the start of a block of
+    /// `HEAD_LENGTH` frames
+    fn process_block(&mut self, position: usize, histories: &[InputHistory]) {
+        let delay = position % self.partition_size;
+
+        if !self.is_deferred {
+            for task in 0..self.task_count() {
+                self.run_task(task, histories, delay);
+            }
+
+            std::mem::swap(&mut self.outputs, &mut self.pending_outputs);
+            return;
+        }
+
+        if delay == 0 {
+            std::mem::swap(&mut self.outputs, &mut self.pending_outputs);
+        }
+
+        for task in self.tasks_for_block(delay / HEAD_LENGTH) {
+            self.run_task(task, histories, delay);
+        }
+    }
+
+    /// Run a task, `delay` frames after the latest partition of input ended
+    fn run_task(&mut self, index: usize, histories: &[InputHistory], delay: usize) {
+        match self.task(index) {
+            StageTask::Transform { input } => {
+                histories[input].copy_before(&mut self.work, delay);
+                self.fft
+                    .process_with_scratch(&mut self.work, &mut self.scratch);
+                self.inputs[input].push(&self.work);
+            }
+            StageTask::MultiplyAccumulate { output, age } => {
+                let sum = &mut self.sums[output];
+
+                if age == 0 {
+                    sum.fill(Complex::zero());
+                }
+
+                for impulse in self
+                    .impulses
+                    .iter()
+                    .filter(|impulse| impulse.route.output == output)
+                {
+                    let input = self.inputs[impulse.route.input].get(age);
+
+                    for (value, input, impulse) in
+                        izip!(sum.iter_mut(), input, &impulse.spectra[age])
+                    {
+                        *value += *input * *impulse;
+                    }
+                }
+            }
+            StageTask::InverseTransform { output } => {
+                let sum = &mut self.sums[output];
+                self.ifft.process_with_scratch(sum, &mut self.scratch);
+
+                for (output, value) in self.pending_outputs[output]
+                    .iter_mut()
+                    .zip(sum[self.partition_size..].iter())
+                {
+                    *output = value.re;
+                }
+            }
+        }
+    }
+}
+
+/// The start of one route's impulse, which is convolved directly
+struct HeadImpulse {
+    route: Route,
+    taps: Vec<f32>,
+}
+
+/// Convolves a set of input channels with a set of impulse responses, without
+/// adding any latency
+///
+/// The start of each impulse is convolved directly, and the rest is split into
+/// partitions that grow in size along the impulse. Each partition size is
+/// handled with overlap-save convolution, so the cost per block depends on
+/// the logarithm of the impulse length rather than the length itself.
+pub struct PartitionedConvolver {
+    histories: Vec<InputHistory>,
+    heads: Vec<HeadImpulse>,
+    stages: Vec<ConvolutionStage>,
+    output_count: usize,
+    position: usize,
+    cycle_length: usize,
+}
+
+impl PartitionedConvolver {
+    /// Convolve each input channel with the same channel of the impulse
+    pub fn new(impulse: &dyn AudioBuffer) -> Self {
+        let channel_count = impulse.channel_count();
+
+        let routes: Vec<_> = (0..channel_count)
+            .map(|channel| Route {
+                input: channel,
+                output: channel,
+                impulse_channel: channel,
+            })
+            .collect();
+
+        Self::with_routes(impulse, &routes, channel_count, channel_count)
+    }
+
+    /// Convolve every input channel with every output channel
+    ///
+    /// The impulse has a channel for each pair, ordered by input and then by
+    /// output. Silent impulse channels are skipped.
+    pub fn matrix(impulse: &dyn AudioBuffer, input_count: usize, output_count: usize) -> Self {
+        debug_assert_eq!(impulse.channel_count(), input_count * output_count);
+
+        let routes: Vec<_> = (0..input_count)
+            .flat_map(|input| {
+                (0..output_count).map(move |output| Route {
+                    input,
+                    output,
+                    impulse_channel: input * output_count + output,
+                })
+            })
+            .filter(|route| {
+                impulse
+                    .get_channel_data(SampleLocation::channel(route.impulse_channel))
+                    .iter()
+                    .any(|sample| *sample != 0.0)
+            })
+            .collect();
+
+        Self::with_routes(impulse, &routes, input_count, output_count)
+    }
+
+    fn with_routes(
+        impulse: &dyn AudioBuffer,
+        routes: &[Route],
+        input_count: usize,
+        output_count: usize,
+    ) -> Self {
+        let layouts = stage_layouts(impulse.frame_count());
+
+        let largest_partition = layouts
+            .last()
+            .map_or(HEAD_LENGTH, |layout| layout.partition_size);
+
+        let mut planner = FftPlanner::new();
+
+        Self {
+            // Deferred stages read a window of two partitions, up to a
+            // partition after it ended
+            histories: (0..input_count)
+                .map(|_| InputHistory::new(3 * largest_partition))
+                .collect(),
+            heads: routes
+                .iter()
+                .map(|route| {
+                    let impulse =
+                        impulse.get_channel_data(SampleLocation::channel(route.impulse_channel));
+
+                    HeadImpulse {
+                        route: *route,
+                        taps: impulse[..impulse.len().min(HEAD_LENGTH)].to_vec(),
+                    }
+                })
+                .collect(),
+            stages: layouts
+                .iter()
+                .map(|layout| {
+                    ConvolutionStage::new(
+                        layout,
+                        impulse,
+                        routes,
+                        input_count,
+                        output_count,
+                        &mut planner,
+                    )
+                })
+                .collect(),
+            output_count,
+            position: 0,
+            cycle_length: largest_partition,
+        }
+    }
+
+    pub fn output_count(&self) -> usize {
+        self.output_count
+    }
+
+    /// Convolve the input, replacing the contents of the output
+    pub fn process(&mut self, input: &dyn AudioBuffer, output: &mut dyn AudioBuffer) {
+        debug_assert_eq!(input.frame_count(), output.frame_count());
+
+        let frame_count = input.frame_count();
+        let mut offset = 0;
+
+        while offset < frame_count {
+            if self.position.is_multiple_of(HEAD_LENGTH) {
+                for stage in self.stages.iter_mut() {
+                    stage.process_block(self.position, &self.histories);
+                }
+            }
+
+            let chunk_length =
+                (frame_count - offset).min(HEAD_LENGTH - self.position % HEAD_LENGTH);
+
+            self.write_input(input, offset, chunk_length);
+            self.write_output(output, offset, chunk_length);
+
+            offset += chunk_length;
+            self.position = (self.position + chunk_length) % self.cycle_length;
+        }
+    }
+
+    fn write_input(&mut self, input: &dyn AudioBuffer, offset: usize, frame_count: usize) {
+        for (channel, history) in self.histories.iter_mut().enumerate() {
+            if channel < input.channel_count() {
+                let data = input.get_channel_data(SampleLocation::new(channel, offset));
+
+                for sample in data[..frame_count].iter() {
+                    history.write(*sample);
+                }
+            } else {
+                for _ in 0..frame_count {
+                    history.write(0.0);
+                }
+            }
+        }
+    }
+
+    fn write_output(&self, output: &mut dyn AudioBuffer, offset: usize, frame_count: usize) {
+        for channel in 0..self.output_count.min(output.channel_count()) {
+            let data = &mut output.get_channel_data_mut(SampleLocation::new(channel, offset))
+                [..frame_count];
+
+            for (frame, value) in data.iter_mut().enumerate() {
+                *value = self
+                    .stages
+                    .iter()
+                    .map(|stage| {
+                        stage.outputs[channel][(self.position + frame) % stage.partition_size]
+                    })
+                    .sum();
+            }
+        }
+
+        for head in self.heads.iter() {
+            if head.route.output >= output.channel_count() {
+                continue;
+            }
+
+            let history = &self.histories[head.route.input];
+            let data = &mut output
+                .get_channel_data_mut(SampleLocation::new(head.route.output, offset))
+                [..frame_count];
+
+            for (frame, value) in data.iter_mut().enumerate() {
+                let newest_delay = frame_count - 1 - frame;
+
+                *value += head
+                    .taps
+                    .iter()
+                    .enumerate()
+                    .map(|(delay, tap)| tap * history.get(newest_delay + delay))
+                    .sum::<f32>();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OwnedAudioBuffer;
+    use approx::assert_relative_eq;
+    use rand::Rng;
+
+    fn random_signal(length: usize) -> Vec<f32> {
+        let mut rng = rand::rng();
+        (0..length).map(|_| rng.random_range(-1.0..=1.0)).collect()
+    }
+
+    fn buffer_from_channels(channels: &[Vec<f32>]) -> OwnedAudioBuffer {
+        let mut buffer = OwnedAudioBuffer::new(channels[0].len(), channels.len(), 48_000);
+
+        for (channel, data) in channels.iter().enumerate() {
+            buffer.fill_from_slice(data, SampleLocation::channel(channel));
+        }
+
+        buffer
+    }
+
+    #[test]
+    fn stages_cover_the_impulse() {
+        assert!(stage_layouts(HEAD_LENGTH).is_empty());
+
+        let impulse_length = 5 * 48_000;
+        let layouts = stage_layouts(impulse_length);
+
+        let mut expected_offset = HEAD_LENGTH;
+        for (index, layout) in layouts.iter().enumerate() {
+            let offset_in_partitions = if index == 0 { 1 } else { 2 };
+
+            assert_eq!(layout.offset, expected_offset);
+            assert_eq!(layout.offset, offset_in_partitions * layout.partition_size);
+            expected_offset += layout.partition_count * layout.partition_size;
+        }
+
+        assert!(expected_offset >= impulse_length);
+        assert_eq!(
+            layouts.last().map(|layout| layout.partition_size),
+            Some(MAXIMUM_PARTITION_SIZE)
+        );
+    }
+
+    #[test]
+    fn spreads_the_work_of_deferred_stages() {
+        let impulse = buffer_from_channels(&[random_signal(5 * 48_000)]);
+        let convolver = PartitionedConvolver::new(&impulse);
+
+        for stage in convolver.stages.iter().filter(|stage| stage.is_deferred) {
+            let block_count = stage.partition_size / HEAD_LENGTH;
+            let most_per_block = stage.task_count().div_ceil(block_count);
+
+            let mut next_task = 0;
+            for block in 0..block_count {
+                let tasks = stage.tasks_for_block(block);
+
+                assert_eq!(tasks.start, next_task);
+                assert!(tasks.len() <= most_per_block);
+                next_task = tasks.end;
+            }
+
+            assert_eq!(next_task, stage.task_count());
+        }
+    }
+
+    #[test]
+    fn matrix_routes_every_input_to_every_output() {
+        let impulse_length = 3_000;
+        let frame_count = 4_096;
+
+        let left_to_left = random_signal(impulse_length);
+        let left_to_right = random_signal(impulse_length);
+        let right_to_left = vec![0.0; impulse_length];
+        let right_to_right = random_signal(impulse_length);
+
+        let impulse = buffer_from_channels(&[
+            left_to_left.clone(),
+            left_to_right.clone(),
+            right_to_left,
+            right_to_right.clone(),
+        ]);
+
+        let left = random_signal(frame_count);
+        let right = random_signal(frame_count);
+        let input = buffer_from_channels(&[left.clone(), right.clone()]);
+
+        let mut convolver = PartitionedConvolver::matrix(&impulse, 2, 2);
+        assert_eq!(convolver.output_count(), 2);
+
+        let mut output = OwnedAudioBuffer::new(frame_count, 2, 48_000);
+        let mut offset = 0;
+        for block_size in [100, 1_000, 7, 512].into_iter().cycle() {
+            if offset == frame_count {
+                break;
+            }
+
+            let block_size = block_size.min(frame_count - offset);
+            let input = BorrowedAudioBuffer::slice_frames(&input, offset, block_size);
+            let mut output =
+                MutableBorrowedAudioBuffer::slice_frames(&mut output, offset, block_size);
+            convolver.process(&input, &mut output);
+            offset += block_size;
+        }
+
+        let convolve = |signal: &[f32], impulse: &[f32], frame: usize| -> f32 {
+            (0..impulse.len().min(frame + 1))
+                .map(|delay| signal[frame - delay] * impulse[delay])
+                .sum()
+        };
+
+        for frame in (0..frame_count).step_by(61) {
+            let expected_left = convolve(&left, &left_to_left, frame);
+            let expected_right =
+                convolve(&left, &left_to_right, frame) + convolve(&right, &right_to_right, frame);
+
+            assert_relative_eq!(
+                output.get_sample(SampleLocation::new(0, frame)),
+                expected_left,
+                epsilon = 1e-3
+            );
+            assert_relative_eq!(
+                output.get_sample(SampleLocation::new(1, frame)),
+                expected_right,
+                epsilon = 1e-3
+            );
+        }
+    }
+}
@@ -0,0 +1,25 @@
+/// The interpolation used to read between samples in a delay line
+///
+/// This is used when the delay time isn't a whole number of samples
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DelayInterpolation {
+    /// Linear interpolation between the two nearest samples
+    ///
+    /// This is cheap, but will attenuate high frequencies when the delay time
+    /// is between samples
+    Linear,
+
+    /// Cubic (Hermite) interpolation using the four nearest samples
+    ///
+    /// This has a flatter frequency response than linear interpolation, and
+    /// works well when the delay time is modulated (e.g. chorus and flanger).
+    /// Delays of less than one sample have no newer sample to read, so they
+    /// use linear interpolation.
+    Cubic,
+
+    /// First order all-pass interpolation
+    ///
+    /// This has a flat magnitude response, which makes it suitable for
+    /// feedback networks. It works best when the delay time changes slowly.
+    Allpass,
+}
@@ -0,0 +1,162 @@
+use crate::dsp::read_cubic;
+
+use super::delay_interpolation::DelayInterpolation;
+
+pub struct DelayLine {
+    buffer: Vec<f32>,
+    write_position: usize,
+    allpass_output: f32,
+}
+
+const INTERPOLATION_FRAME_COUNT: usize = 3;
+
+impl DelayLine {
+    pub fn new(maximum_delay_frames: usize) -> Self {
+        Self {
+            buffer: vec![0.0; maximum_delay_frames + INTERPOLATION_FRAME_COUNT],
+            write_position: 0,
+            allpass_output: 0.0,
+        }
+    }
+
+    pub fn maximum_delay(&self) -> f32 {
+        (self.buffer.len() - INTERPOLATION_FRAME_COUNT) as f32
+    }
+
+    pub fn write(&mut self, value: f32) {
+        self.write_position = (self.write_position + 1) % self.buffer.len();
+        self.buffer[self.write_position] = value;
+    }
+
+    pub fn add_to_last_written(&mut self, value: f32) {
+        self.buffer[self.write_position] += value;
+    }
+
+    fn sample_at_delay(&self, delay: usize) -> f32 {
+        debug_assert!(delay < self.buffer.len());
+        let index = (self.write_position + self.buffer.len() - delay) % self.buffer.len();
+        self.buffer[index]
+    }
+
+    /// Read from the delay line, `delay` samples behind the last written value
+    pub fn read(&mut self, delay: f32, interpolation: DelayInterpolation) -> f32 {
+        let delay = delay.clamp(0.0, self.maximum_delay());
+
+        let whole = delay.floor() as usize;
+        let fraction = delay - whole as f32;
+
+        match interpolation {
+            DelayInterpolation::Linear => self.read_linear(whole, fraction),
+            DelayInterpolation::Cubic => self.read_cubic(whole, fraction),
+            DelayInterpolation::Allpass => self.read_allpass(whole, fraction),
+        }
+    }
+
+    fn read_linear(&self, whole: usize, fraction: f32) -> f32 {
+        let newer = self.sample_at_delay(whole);
+        let older = self.sample_at_delay(whole + 1);
+        newer + fraction * (older - newer)
+    }
+
+    fn read_cubic(&self, whole: usize, fraction: f32) -> f32 {
+        // There is no sample newer than the last one written, and repeating it
+        // would flatten the curve, so fall back to linear
+        if whole == 0 {
+            return self.read_linear(whole, fraction);
+        }
+
+        let window = [
+            self.sample_at_delay(whole - 1),
+            self.sample_at_delay(whole),
+            self.sample_at_delay(whole + 1),
+            self.sample_at_delay(whole + 2),
+        ];
+
+        read_cubic(&window, 1.0 + fraction as f64)
+    }
+
+    fn read_allpass(&mut self, whole: usize, fraction: f32) -> f32 {
+        // Keep the fractional delay between 0.5 and 1.5 where possible, so the
+        // pole of the filter stays away from the unit circle
+        let (whole, fraction) = if fraction < 0.5 && whole > 0 {
+            (whole - 1, fraction + 1.0)
+        } else {
+            (whole, fraction)
+        };
+
+        let coefficient = (1.0 - fraction) / (1.0 + fraction);
+
+        let newer = self.sample_at_delay(whole);
+        let older = self.sample_at_delay(whole + 1);
+
+        self.allpass_output = coefficient * newer + older - coefficient * self.allpass_output;
+        self.allpass_output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_ramp(delay_line: &mut DelayLine, length: usize) {
+        for value in 0..length {
+            delay_line.write(value as f32);
+        }
+    }
+
+    #[test]
+    fn reads_whole_sample_delay() {
+        let mut delay_line = DelayLine::new(16);
+        write_ramp(&mut delay_line, 10);
+
+        for interpolation in [DelayInterpolation::Linear, DelayInterpolation::Cubic] {
+            assert_relative_eq!(delay_line.read(0.0, interpolation), 9.0);
+            assert_relative_eq!(delay_line.read(4.0, interpolation), 5.0);
+        }
+    }
+
+    #[test]
+    fn interpolates_between_samples() {
+        let mut delay_line = DelayLine::new(16);
+        write_ramp(&mut delay_line, 10);
+
+        assert_relative_eq!(delay_line.read(2.25, DelayInterpolation::Linear), 6.75);
+        assert_relative_eq!(delay_line.read(2.25, DelayInterpolation::Cubic), 6.75);
+    }
+
+    #[test]
+    fn cubic_is_linear_within_the_first_sample() {
+        let mut delay_line = DelayLine::new(16);
+
+        for value in 0..10 {
+            delay_line.write((value * value) as f32);
+        }
+
+        assert_relative_eq!(delay_line.read(0.5, DelayInterpolation::Cubic), 72.5);
+        assert_relative_eq!(
+            delay_line.read(0.5, DelayInterpolation::Cubic),
+            delay_line.read(0.5, DelayInterpolation::Linear)
+        );
+    }
+
+    #[test]
+    fn allpass_converges_on_ramp() {
+        let mut delay_line = DelayLine::new(16);
+
+        let mut value = 0.0;
+        for index in 0..64 {
+            delay_line.write(index as f32);
+            value = delay_line.read(2.25, DelayInterpolation::Allpass);
+        }
+
+        assert_relative_eq!(value, 63.0 - 2.25, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn clamps_to_maximum_delay() {
+        let mut delay_line = DelayLine::new(4);
+        write_ramp(&mut delay_line, 10);
+
+        assert_relative_eq!(delay_line.read(100.0, DelayInterpolation::Linear), 5.0);
+    }
+}
@@ -0,0 +1,102 @@
+use super::{delay_interpolation::DelayInterpolation, delay_processor::DelayProcessor};
+use crate::{commands::Id, graph::DspNode, parameter::*, prelude::*, utility::create_parameters};
+use std::time::Duration;
+
+/// A delay line
+///
+/// The delay time can be changed at audio-rate, and fractional delay times
+/// will be interpolated. This can be used to create echoes, chorus and
+/// flanger effects.
+///
+/// # Parameters
+/// - delay-time (seconds)
+/// - feedback
+/// - wet
+/// - dry
+pub struct Delay {
+    /// The node to connect into the audio graph
+    pub node: GraphNode,
+
+    params: Parameters,
+}
+
+impl DspNode for Delay {
+    fn get_parameters_mut(&mut self) -> &mut Parameters {
+        &mut self.params
+    }
+}
+
+impl Delay {
+    /// Create a new delay node
+    ///
+    /// The delay time can't be set any higher than `maximum_delay_time`
+    pub fn new(
+        context: &dyn Context,
+        channel_count: usize,
+        maximum_delay_time: Duration,
+        interpolation: DelayInterpolation,
+    ) -> Self {
+        let id = Id::generate();
+
+        let maximum_delay_seconds = maximum_delay_time.as_secs_f64();
+
+        let (params, realtime_params) = create_parameters(
+            id,
+            context,
+            [
+                (
+                    "delay-time",
+                    ParameterRange::new(0.0, 0.0, maximum_delay_seconds),
+                ),
+                ("feedback", ParameterRange::new(0.0, -1.0, 1.0)),
+                ("wet", ParameterRange::new(1.0, 0.0, 1.0)),
+                ("dry", ParameterRange::new(0.0, 0.0, 1.0)),
+            ],
+        );
+
+        let sample_rate = context.get_sample_rate();
+        let maximum_delay_frames = (maximum_delay_seconds * sample_rate as f64).ceil() as usize;
+
+        let processor = Box::new(DelayProcessor::new(
+            channel_count,
+            sample_rate,
+            maximum_delay_frames,
+            interpolation,
+        ));
+
+        Self {
+            node: GraphNode::new(
+                id,
+                context,
+                channel_count,
+                channel_count,
+                processor,
+                realtime_params,
+            ),
+            params,
+        }
+    }
+
+    /// Get the delay time parameter, in seconds
+    pub fn delay_time(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("delay-time")
+    }
+
+    /// Get the feedback parameter
+    ///
+    /// This is the amount of the delayed signal that is fed back into the
+    /// delay line
+    pub fn feedback(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("feedback")
+    }
+
+    /// Get the wet parameter
+    pub fn wet(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("wet")
+    }
+
+    /// Get the dry parameter
+    pub fn dry(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("dry")
+    }
+}
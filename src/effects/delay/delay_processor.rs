@@ -0,0 +1,197 @@
+use super::{delay_interpolation::DelayInterpolation, delay_line::DelayLine};
+use crate::{graph::DspProcessor, prelude::*, ProcessContext};
+use itertools::izip;
+
+pub struct DelayProcessor {
+    delay_lines: Vec<DelayLine>,
+    interpolation: DelayInterpolation,
+    sample_rate: f32,
+}
+
+impl DelayProcessor {
+    pub fn new(
+        channel_count: usize,
+        sample_rate: usize,
+        maximum_delay_frames: usize,
+        interpolation: DelayInterpolation,
+    ) -> Self {
+        Self {
+            delay_lines: (0..channel_count)
+                .map(|_| DelayLine::new(maximum_delay_frames))
+                .collect(),
+            interpolation,
+            sample_rate: sample_rate as f32,
+        }
+    }
+}
+
+impl DspProcessor for DelayProcessor {
+    fn process_audio(&mut self, context: &mut ProcessContext) {
+        let frame_count = context.output_buffer.frame_count();
+
+        let delay_time = context
+            .parameters
+            .get_parameter_values("delay-time", frame_count);
+        let feedback = context
+            .parameters
+            .get_parameter_values("feedback", frame_count);
+        let wet = context.parameters.get_parameter_values("wet", frame_count);
+        let dry = context.parameters.get_parameter_values("dry", frame_count);
+
+        let channel_count = context
+            .output_buffer
+            .channel_count()
+            .min(context.input_buffer.channel_count());
+
+        for (channel, delay_line) in self.delay_lines.iter_mut().enumerate().take(channel_count) {
+            let location = SampleLocation::channel(channel);
+            let input = context.input_buffer.get_channel_data(location);
+            let output = context.output_buffer.get_channel_data_mut(location);
+
+            for (input, output, delay_time, feedback, wet, dry) in izip!(
+                input.iter(),
+                output.iter_mut(),
+                delay_time.iter(),
+                feedback.iter(),
+                wet.iter(),
+                dry.iter()
+            ) {
+                delay_line.write(*input);
+
+                let delayed = delay_line.read(delay_time * self.sample_rate, self.interpolation);
+
+                delay_line.add_to_last_written(feedback * delayed);
+
+                *output = wet * delayed + dry * input;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{graph::DspParameters, parameter::RealtimeAudioParameter};
+    use atomic_float::AtomicF64;
+    use std::sync::Arc;
+
+    const MAXIMUM_FRAME_COUNT: usize = 1_024;
+
+    struct Fixture {
+        processor: DelayProcessor,
+        parameters: DspParameters,
+        sample_rate: usize,
+    }
+
+    impl Fixture {
+        fn new(interpolation: DelayInterpolation) -> Self {
+            let sample_rate = 1_000;
+            let maximum_delay_frames = 1_000;
+
+            let make_parameter = |name, value| {
+                RealtimeAudioParameter::new(
+                    name,
+                    Arc::new(AtomicF64::new(value)),
+                    MAXIMUM_FRAME_COUNT,
+                )
+            };
+
+            Self {
+                processor: DelayProcessor::new(1, sample_rate, maximum_delay_frames, interpolation),
+                parameters: DspParameters::new([
+                    make_parameter("delay-time", 0.1),
+                    make_parameter("feedback", 0.0),
+                    make_parameter("wet", 1.0),
+                    make_parameter("dry", 0.0),
+                ]),
+                sample_rate,
+            }
+        }
+
+        fn set_parameter(&mut self, name: &'static str, value: f64) {
+            self.parameters.get_parameter_mut(name).set_value(value);
+        }
+
+        fn process(&mut self, input: &OwnedAudioBuffer) -> OwnedAudioBuffer {
+            let frame_count = input.frame_count();
+            assert!(frame_count <= MAXIMUM_FRAME_COUNT);
+
+            let mut output =
+                OwnedAudioBuffer::new(frame_count, input.channel_count(), self.sample_rate);
+
+            for (_, parameter) in self.parameters.iter_mut() {
+                parameter.process(&Timestamp::zero(), frame_count, self.sample_rate);
+            }
+
+            self.processor.process_audio(&mut ProcessContext {
+                input_buffer: input,
+                output_buffer: &mut output,
+                start_time: &Timestamp::zero(),
+                parameters: &self.parameters,
+                midi_events: &[],
+            });
+
+            output
+        }
+
+        fn impulse(&self, frame_count: usize) -> OwnedAudioBuffer {
+            let mut buffer = OwnedAudioBuffer::new(frame_count, 1, self.sample_rate);
+            buffer.set_sample(SampleLocation::origin(), 1.0);
+            buffer
+        }
+    }
+
+    #[test]
+    fn delays_impulse() {
+        let mut fixture = Fixture::new(DelayInterpolation::Linear);
+
+        let input = fixture.impulse(256);
+        let output = fixture.process(&input);
+
+        let samples = output.get_channel_data(SampleLocation::origin());
+
+        for (frame, sample) in samples.iter().enumerate() {
+            let expected = if frame == 100 { 1.0 } else { 0.0 };
+            assert_relative_eq!(*sample, expected);
+        }
+    }
+
+    #[test]
+    fn feeds_back_into_delay_line() {
+        let mut fixture = Fixture::new(DelayInterpolation::Linear);
+        fixture.set_parameter("feedback", 0.5);
+
+        let input = fixture.impulse(512);
+        let output = fixture.process(&input);
+
+        assert_relative_eq!(output.get_sample(SampleLocation::frame(100)), 1.0);
+        assert_relative_eq!(output.get_sample(SampleLocation::frame(200)), 0.5);
+        assert_relative_eq!(output.get_sample(SampleLocation::frame(300)), 0.25);
+        assert_relative_eq!(output.get_sample(SampleLocation::frame(400)), 0.125);
+    }
+
+    #[test]
+    fn mixes_wet_and_dry() {
+        let mut fixture = Fixture::new(DelayInterpolation::Cubic);
+        fixture.set_parameter("wet", 0.5);
+        fixture.set_parameter("dry", 0.25);
+
+        let input = fixture.impulse(256);
+        let output = fixture.process(&input);
+
+        assert_relative_eq!(output.get_sample(SampleLocation::frame(0)), 0.25);
+        assert_relative_eq!(output.get_sample(SampleLocation::frame(100)), 0.5);
+    }
+
+    #[test]
+    fn fractional_delay_splits_impulse() {
+        let mut fixture = Fixture::new(DelayInterpolation::Linear);
+        fixture.set_parameter("delay-time", 0.1005);
+
+        let input = fixture.impulse(256);
+        let output = fixture.process(&input);
+
+        assert_relative_eq!(output.get_sample(SampleLocation::frame(100)), 0.5);
+        assert_relative_eq!(output.get_sample(SampleLocation::frame(101)), 0.5);
+    }
+}
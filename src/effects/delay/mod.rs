@@ -0,0 +1,8 @@
+mod delay_interpolation;
+mod delay_line;
+mod delay_node;
+mod delay_processor;
+
+pub use delay_interpolation::DelayInterpolation;
+pub(crate) use delay_line::DelayLine;
+pub use delay_node::Delay;
@@ -119,6 +119,7 @@ mod tests {
                 output_buffer: &mut output,
                 start_time: &Timestamp::from_samples(offset as f64, sample_rate),
                 parameters: &DspParameters::empty(),
+                midi_events: &[],
             });
 
             total_frames -= frames;
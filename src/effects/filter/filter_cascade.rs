@@ -0,0 +1,201 @@
+use super::filter_design::{AnalogSection, FilterDesign};
+use crate::effects::biquad::BiquadCoefficients;
+use std::f64::consts::PI;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FilterPass {
+    LowPass,
+    HighPass,
+}
+
+/// A filter design, as a cascade of second order sections
+#[derive(Clone)]
+pub struct FilterCascade {
+    sections: Vec<AnalogSection>,
+}
+
+const MAXIMUM_CUTOFF_RATIO: f64 = 0.49;
+
+impl FilterCascade {
+    pub fn new(design: FilterDesign, order: usize, pass: FilterPass) -> Self {
+        let sections = design
+            .analog_sections(order)
+            .into_iter()
+            .map(|section| match pass {
+                FilterPass::LowPass => section,
+                FilterPass::HighPass => to_high_pass(section),
+            })
+            .collect();
+
+        Self { sections }
+    }
+
+    pub fn section_count(&self) -> usize {
+        self.sections.len()
+    }
+
+    /// Calculate the digital coefficients of each section, with the cutoff at
+    /// `frequency`
+    pub fn calculate_coefficients(
+        &self,
+        frequency: f64,
+        sample_rate: f64,
+        coefficients: &mut [BiquadCoefficients],
+    ) {
+        debug_assert_eq!(coefficients.len(), self.sections.len());
+
+        let frequency = frequency.clamp(0.0, MAXIMUM_CUTOFF_RATIO * sample_rate);
+        let warped_frequency = (PI * frequency / sample_rate).tan();
+
+        for (section, coefficients) in self.sections.iter().zip(coefficients.iter_mut()) {
+            *coefficients = bilinear_transform(section, warped_frequency);
+        }
+    }
+
+    pub fn create_coefficients(&self, frequency: f64, sample_rate: f64) -> Vec<BiquadCoefficients> {
+        let mut coefficients =
+            vec![BiquadCoefficients::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0); self.sections.len()];
+        self.calculate_coefficients(frequency, sample_rate, &mut coefficients);
+        coefficients
+    }
+}
+
+/// Replace `s` with `1 / s`, which mirrors the response around the cutoff
+fn to_high_pass(section: AnalogSection) -> AnalogSection {
+    let reverse = |[c0, c1, c2]: [f64; 3]| {
+        if section.is_first_order() {
+            [c1, c0, 0.0]
+        } else {
+            [c2, c1, c0]
+        }
+    };
+
+    AnalogSection {
+        numerator: reverse(section.numerator),
+        denominator: reverse(section.denominator),
+    }
+}
+
+/// Map a section with its cutoff at 1 rad/s to a digital filter, using the
+/// bilinear transform with the cutoff prewarped to `warped_frequency`
+fn bilinear_transform(section: &AnalogSection, warped_frequency: f64) -> BiquadCoefficients {
+    let c = 1.0 / warped_frequency;
+
+    let transform = |[p0, p1, p2]: [f64; 3]| {
+        if section.is_first_order() {
+            [p1 * c + p0, p0 - p1 * c, 0.0]
+        } else {
+            let p2 = p2 * c * c;
+            let p1 = p1 * c;
+            [p2 + p1 + p0, 2.0 * (p0 - p2), p2 - p1 + p0]
+        }
+    };
+
+    let [b0, b1, b2] = transform(section.numerator);
+    let [a0, a1, a2] = transform(section.denominator);
+
+    BiquadCoefficients::new(b0, b1, b2, a0, a1, a2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::biquad::cascade_response;
+    use approx::assert_relative_eq;
+    use rustfft::num_complex::Complex64;
+
+    const SAMPLE_RATE: f64 = 48_000.0;
+
+    fn response(coefficients: &[BiquadCoefficients], frequency: f64) -> Complex64 {
+        cascade_response(coefficients, frequency, SAMPLE_RATE)
+    }
+
+    fn magnitude_db(coefficients: &[BiquadCoefficients], frequency: f64) -> f64 {
+        20.0 * response(coefficients, frequency).norm().log10()
+    }
+
+    #[test]
+    fn butterworth_low_pass() {
+        let cascade = FilterCascade::new(FilterDesign::Butterworth, 4, FilterPass::LowPass);
+        let coefficients = cascade.create_coefficients(1_000.0, SAMPLE_RATE);
+
+        assert_relative_eq!(magnitude_db(&coefficients, 1.0), 0.0, epsilon = 1e-3);
+        assert_relative_eq!(magnitude_db(&coefficients, 1_000.0), -3.01, epsilon = 1e-2);
+
+        let two_octaves_up = magnitude_db(&coefficients, 4_000.0);
+        assert!((-50.0..-46.0).contains(&two_octaves_up));
+    }
+
+    #[test]
+    fn butterworth_high_pass() {
+        let cascade = FilterCascade::new(FilterDesign::Butterworth, 3, FilterPass::HighPass);
+        let coefficients = cascade.create_coefficients(1_000.0, SAMPLE_RATE);
+
+        assert_relative_eq!(magnitude_db(&coefficients, 20_000.0), 0.0, epsilon = 1e-2);
+        assert_relative_eq!(magnitude_db(&coefficients, 1_000.0), -3.01, epsilon = 1e-2);
+
+        let two_octaves_down = magnitude_db(&coefficients, 250.0);
+        assert!((-38.0..-35.0).contains(&two_octaves_down));
+    }
+
+    #[test]
+    fn linkwitz_riley_crossover_sums_flat() {
+        let frequency = 2_000.0;
+
+        let low_pass = FilterCascade::new(FilterDesign::LinkwitzRiley, 4, FilterPass::LowPass)
+            .create_coefficients(frequency, SAMPLE_RATE);
+        let high_pass = FilterCascade::new(FilterDesign::LinkwitzRiley, 4, FilterPass::HighPass)
+            .create_coefficients(frequency, SAMPLE_RATE);
+
+        assert_relative_eq!(magnitude_db(&low_pass, frequency), -6.02, epsilon = 1e-2);
+        assert_relative_eq!(magnitude_db(&high_pass, frequency), -6.02, epsilon = 1e-2);
+
+        for test_frequency in [20.0, 200.0, 1_000.0, 2_000.0, 5_000.0, 15_000.0] {
+            let sum = response(&low_pass, test_frequency) + response(&high_pass, test_frequency);
+            assert_relative_eq!(sum.norm(), 1.0, epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn chebyshev_i_high_order() {
+        let ripple = 0.5;
+        let cascade =
+            FilterCascade::new(FilterDesign::ChebyshevI { ripple }, 8, FilterPass::LowPass);
+        let coefficients = cascade.create_coefficients(5_000.0, SAMPLE_RATE);
+
+        assert_relative_eq!(
+            magnitude_db(&coefficients, 5_000.0),
+            -ripple,
+            epsilon = 1e-3
+        );
+        assert!(magnitude_db(&coefficients, 10_000.0) < -60.0);
+    }
+
+    #[test]
+    fn chebyshev_ii_high_pass() {
+        let cascade = FilterCascade::new(
+            FilterDesign::ChebyshevII {
+                stopband_attenuation: 60.0,
+            },
+            6,
+            FilterPass::HighPass,
+        );
+        let coefficients = cascade.create_coefficients(1_000.0, SAMPLE_RATE);
+
+        assert_relative_eq!(magnitude_db(&coefficients, 12_000.0), 0.0, epsilon = 0.1);
+
+        for frequency in [100.0, 500.0, 1_000.0] {
+            assert!(magnitude_db(&coefficients, frequency) < -60.0 + 1e-3);
+        }
+    }
+
+    #[test]
+    fn clamps_the_cutoff_below_nyquist() {
+        let cascade = FilterCascade::new(FilterDesign::Bessel, 4, FilterPass::LowPass);
+        let coefficients = cascade.create_coefficients(30_000.0, SAMPLE_RATE);
+
+        assert!(coefficients
+            .iter()
+            .all(|c| c.a1().is_finite() && c.a2().is_finite()));
+    }
+}
@@ -0,0 +1,447 @@
+use rustfft::num_complex::Complex64;
+use std::f64::consts::PI;
+
+/// The analog prototype that a [crate::Filter] is designed from
+///
+/// Each design trades the flatness of the passband against the steepness of
+/// the transition and the shape of the phase response.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FilterDesign {
+    /// A maximally flat passband, which is 3dB down at the cutoff
+    Butterworth,
+
+    /// A steeper transition, with ripple in the passband
+    ///
+    /// The response is `ripple` dB down at the cutoff
+    ChebyshevI {
+        /// The passband ripple in dB, which is at least 0.01dB
+        ripple: f64,
+    },
+
+    /// A steeper transition, with ripple in the stopband
+    ///
+    /// The cutoff is the start of the stopband, where the response first
+    /// reaches the stopband attenuation
+    ChebyshevII {
+        /// The minimum attenuation in the stopband in dB, which is at least
+        /// 0.01dB
+        stopband_attenuation: f64,
+    },
+
+    /// A gentle transition with a nearly linear phase response, which is 3dB
+    /// down at the cutoff. The order is at most 20.
+    Bessel,
+
+    /// Two Butterworth filters of half the order in series, which is 6dB down
+    /// at the cutoff
+    ///
+    /// The low and high pass filters sum to a flat response, so they're used
+    /// for crossovers. Odd orders are rounded up to the next even order.
+    LinkwitzRiley,
+}
+
+/// A first or second order section of a low-pass prototype with its cutoff
+/// at 1 rad/s
+///
+/// The coefficients are indexed by the power of `s`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnalogSection {
+    pub numerator: [f64; 3],
+    pub denominator: [f64; 3],
+}
+
+impl AnalogSection {
+    pub fn is_first_order(&self) -> bool {
+        self.numerator[2] == 0.0 && self.denominator[2] == 0.0
+    }
+
+    fn from_real_pole(pole: f64) -> Self {
+        Self {
+            numerator: [-pole, 0.0, 0.0],
+            denominator: [-pole, 1.0, 0.0],
+        }
+    }
+
+    /// A section from a pair of complex conjugate poles, with an optional
+    /// pair of zeros on the imaginary axis
+    fn from_pole_pair(pole: Complex64, zero: Option<f64>) -> Self {
+        let pole_magnitude = pole.norm_sqr();
+
+        let numerator = match zero {
+            Some(zero) => [pole_magnitude, 0.0, pole_magnitude / (zero * zero)],
+            None => [pole_magnitude, 0.0, 0.0],
+        };
+
+        Self {
+            numerator,
+            denominator: [pole_magnitude, -2.0 * pole.re, 1.0],
+        }
+    }
+
+    fn scale(&mut self, gain: f64) {
+        self.numerator = self.numerator.map(|value| value * gain);
+    }
+}
+
+/// The smallest passband ripple or stopband attenuation in dB, so that the
+/// Chebyshev designs stay finite
+const MINIMUM_RIPPLE: f64 = 0.01;
+
+/// The highest order Bessel filter, as the roots of the Bessel polynomial lose
+/// accuracy at higher orders and stop converging above order 22
+const MAXIMUM_BESSEL_ORDER: usize = 20;
+
+impl FilterDesign {
+    /// The order that is used for a requested `order`
+    ///
+    /// Orders are at least 1, Linkwitz-Riley filters are rounded up to an even
+    /// order, and Bessel filters are at most [MAXIMUM_BESSEL_ORDER]
+    fn supported_order(&self, order: usize) -> usize {
+        let order = order.max(1);
+
+        match self {
+            FilterDesign::Bessel => order.min(MAXIMUM_BESSEL_ORDER),
+            FilterDesign::LinkwitzRiley => order.next_multiple_of(2),
+            _ => order,
+        }
+    }
+
+    /// Design the low-pass prototype as a cascade of sections, each with unity
+    /// gain at DC
+    ///
+    /// Orders and ripples that can't be designed are clamped to the nearest
+    /// ones that can
+    pub fn analog_sections(&self, order: usize) -> Vec<AnalogSection> {
+        let order = self.supported_order(order);
+
+        match *self {
+            FilterDesign::Butterworth => all_pole_sections(&butterworth_poles(order)),
+            FilterDesign::ChebyshevI { ripple } => {
+                chebyshev_i_sections(order, ripple.max(MINIMUM_RIPPLE))
+            }
+            FilterDesign::ChebyshevII {
+                stopband_attenuation,
+            } => chebyshev_ii_sections(order, stopband_attenuation.max(MINIMUM_RIPPLE)),
+            FilterDesign::Bessel => all_pole_sections(&bessel_poles(order)),
+            FilterDesign::LinkwitzRiley => {
+                let poles = butterworth_poles(order / 2);
+                let poles: Vec<Complex64> = poles.iter().chain(poles.iter()).copied().collect();
+                all_pole_sections(&poles)
+            }
+        }
+    }
+}
+
+/// The angle of each pole pair (and the real pole, for odd orders) from the
+/// imaginary axis
+fn pole_angles(order: usize) -> impl Iterator<Item = f64> {
+    (0..order.div_ceil(2)).map(move |index| PI * (2 * index + 1) as f64 / (2 * order) as f64)
+}
+
+/// The poles in the upper half of the s-plane, with one real pole for odd
+/// orders
+fn butterworth_poles(order: usize) -> Vec<Complex64> {
+    pole_angles(order)
+        .map(|angle| Complex64::new(-angle.sin(), angle.cos()))
+        .collect()
+}
+
+fn all_pole_sections(poles: &[Complex64]) -> Vec<AnalogSection> {
+    poles
+        .iter()
+        .map(|pole| {
+            if is_real(*pole) {
+                AnalogSection::from_real_pole(pole.re)
+            } else {
+                AnalogSection::from_pole_pair(*pole, None)
+            }
+        })
+        .collect()
+}
+
+fn is_real(value: Complex64) -> bool {
+    value.im.abs() <= 1e-9 * value.norm()
+}
+
+fn chebyshev_i_sections(order: usize, ripple: f64) -> Vec<AnalogSection> {
+    debug_assert!(ripple > 0.0);
+
+    let epsilon = (10.0_f64.powf(ripple / 10.0) - 1.0).sqrt();
+    let mu = (1.0 / epsilon).asinh() / order as f64;
+
+    let poles: Vec<Complex64> = pole_angles(order)
+        .map(|angle| Complex64::new(-mu.sinh() * angle.sin(), mu.cosh() * angle.cos()))
+        .collect();
+
+    let mut sections = all_pole_sections(&poles);
+
+    // Even orders start the passband at the bottom of the ripple
+    if order.is_multiple_of(2) {
+        sections[0].scale(1.0 / (1.0 + epsilon * epsilon).sqrt());
+    }
+
+    sections
+}
+
+fn chebyshev_ii_sections(order: usize, stopband_attenuation: f64) -> Vec<AnalogSection> {
+    debug_assert!(stopband_attenuation > 0.0);
+
+    let epsilon = 1.0 / (10.0_f64.powf(stopband_attenuation / 10.0) - 1.0).sqrt();
+    let mu = (1.0 / epsilon).asinh() / order as f64;
+
+    pole_angles(order)
+        .map(|angle| {
+            let pole = Complex64::new(-mu.sinh() * angle.sin(), mu.cosh() * angle.cos()).inv();
+
+            if is_real(pole) {
+                AnalogSection::from_real_pole(pole.re)
+            } else {
+                AnalogSection::from_pole_pair(pole, Some(1.0 / angle.cos()))
+            }
+        })
+        .collect()
+}
+
+/// The poles of the Bessel filter, scaled so that the response is 3dB down at
+/// 1 rad/s
+fn bessel_poles(order: usize) -> Vec<Complex64> {
+    let polynomial = reverse_bessel_polynomial(order);
+    let roots = polynomial_roots(&polynomial);
+
+    let cutoff = half_power_frequency(&polynomial);
+
+    roots
+        .into_iter()
+        .filter(|root| root.im >= 0.0 || is_real(*root))
+        .map(|root| root / cutoff)
+        .map(|root| {
+            if is_real(root) {
+                Complex64::new(root.re, 0.0)
+            } else {
+                root
+            }
+        })
+        .collect()
+}
+
+/// The coefficients of the reverse Bessel polynomial, indexed by the power of
+/// `s`
+fn reverse_bessel_polynomial(order: usize) -> Vec<f64> {
+    let mut coefficients = vec![1.0; order + 1];
+
+    // Each coefficient is (2n - k)! / (2^(n - k) k! (n - k)!), which can be
+    // built up from the leading coefficient of 1
+    for power in (0..order).rev() {
+        let next = coefficients[power + 1];
+        let k = power as f64;
+        let n = order as f64;
+        coefficients[power] = next * (2.0 * n - k) * (k + 1.0) / (2.0 * (n - k));
+    }
+
+    coefficients
+}
+
+fn evaluate_polynomial(coefficients: &[f64], value: Complex64) -> Complex64 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(Complex64::new(0.0, 0.0), |result, coefficient| {
+            result * value + coefficient
+        })
+}
+
+/// Find the roots of a monic polynomial with the Durand-Kerner method
+fn polynomial_roots(coefficients: &[f64]) -> Vec<Complex64> {
+    let order = coefficients.len() - 1;
+    let radius = coefficients[0].abs().powf(1.0 / order as f64);
+    let seed = Complex64::new(0.4, 0.9);
+
+    let mut roots: Vec<Complex64> = (0..order)
+        .map(|index| radius * seed.powu(index as u32))
+        .collect();
+
+    const MAXIMUM_ITERATIONS: usize = 1_000;
+
+    for _ in 0..MAXIMUM_ITERATIONS {
+        let mut largest_change: f64 = 0.0;
+
+        for index in 0..order {
+            let root = roots[index];
+
+            let denominator = roots
+                .iter()
+                .enumerate()
+                .filter(|(other_index, _)| *other_index != index)
+                .fold(Complex64::new(1.0, 0.0), |product, (_, other)| {
+                    product * (root - other)
+                });
+
+            let change = evaluate_polynomial(coefficients, root) / denominator;
+            roots[index] -= change;
+
+            largest_change = largest_change.max(change.norm() / root.norm().max(1.0));
+        }
+
+        if largest_change < 1e-14 {
+            break;
+        }
+    }
+
+    roots
+}
+
+/// The frequency where the magnitude of `H(s) = P(0) / P(s)` is 3dB down
+fn half_power_frequency(polynomial: &[f64]) -> f64 {
+    let magnitude = |frequency: f64| {
+        polynomial[0] / evaluate_polynomial(polynomial, Complex64::new(0.0, frequency)).norm()
+    };
+
+    let target = 0.5_f64.sqrt();
+    let mut low = 0.0;
+    let mut high = 1.0;
+
+    while magnitude(high) > target {
+        high *= 2.0;
+    }
+
+    for _ in 0..100 {
+        let middle = 0.5 * (low + high);
+
+        if magnitude(middle) > target {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+
+    0.5 * (low + high)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn magnitude(sections: &[AnalogSection], frequency: f64) -> f64 {
+        let s = Complex64::new(0.0, frequency);
+
+        sections
+            .iter()
+            .map(|section| {
+                (evaluate_polynomial(&section.numerator, s)
+                    / evaluate_polynomial(&section.denominator, s))
+                .norm()
+            })
+            .product()
+    }
+
+    fn magnitude_db(sections: &[AnalogSection], frequency: f64) -> f64 {
+        20.0 * magnitude(sections, frequency).log10()
+    }
+
+    #[test]
+    fn butterworth_is_3db_down_at_the_cutoff() {
+        for order in 1..=8 {
+            let sections = FilterDesign::Butterworth.analog_sections(order);
+
+            assert_eq!(sections.len(), order.div_ceil(2));
+            assert_relative_eq!(magnitude(&sections, 0.0), 1.0, epsilon = 1e-12);
+            assert_relative_eq!(magnitude(&sections, 1.0), 0.5_f64.sqrt(), epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn reverse_bessel_polynomial_coefficients() {
+        assert_eq!(reverse_bessel_polynomial(3), vec![15.0, 15.0, 6.0, 1.0]);
+        assert_eq!(
+            reverse_bessel_polynomial(4),
+            vec![105.0, 105.0, 45.0, 10.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn bessel_is_3db_down_at_the_cutoff() {
+        for order in 1..=10 {
+            let sections = FilterDesign::Bessel.analog_sections(order);
+
+            assert_eq!(sections.len(), order.div_ceil(2));
+            assert_relative_eq!(magnitude(&sections, 0.0), 1.0, epsilon = 1e-9);
+            assert_relative_eq!(magnitude(&sections, 1.0), 0.5_f64.sqrt(), epsilon = 1e-6);
+        }
+    }
+
+    #[test]
+    fn chebyshev_i_ripples_in_the_passband() {
+        let ripple = 1.0;
+
+        for order in [3, 4] {
+            let sections = FilterDesign::ChebyshevI { ripple }.analog_sections(order);
+
+            assert_relative_eq!(magnitude_db(&sections, 1.0), -ripple, epsilon = 1e-6);
+
+            for step in 0..100 {
+                let level = magnitude_db(&sections, step as f64 / 100.0);
+                assert!(level <= 1e-9 && level >= -ripple - 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn chebyshev_ii_attenuates_the_stopband() {
+        let stopband_attenuation = 40.0;
+
+        for order in [3, 4] {
+            let sections = FilterDesign::ChebyshevII {
+                stopband_attenuation,
+            }
+            .analog_sections(order);
+
+            assert_relative_eq!(magnitude(&sections, 0.0), 1.0, epsilon = 1e-9);
+
+            for step in 0..100 {
+                let frequency = 1.0 + step as f64 / 10.0;
+                assert!(magnitude_db(&sections, frequency) <= -stopband_attenuation + 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn linkwitz_riley_is_6db_down_at_the_cutoff() {
+        let sections = FilterDesign::LinkwitzRiley.analog_sections(4);
+
+        assert_eq!(sections.len(), 2);
+        assert_relative_eq!(magnitude(&sections, 1.0), 0.5, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn clamps_orders_that_cant_be_designed() {
+        assert_eq!(FilterDesign::Butterworth.analog_sections(0).len(), 1);
+        assert_eq!(FilterDesign::LinkwitzRiley.analog_sections(3).len(), 2);
+    }
+
+    #[test]
+    fn clamps_high_order_bessel() {
+        let sections = FilterDesign::Bessel.analog_sections(30);
+
+        assert_eq!(sections.len(), MAXIMUM_BESSEL_ORDER / 2);
+        assert_relative_eq!(magnitude(&sections, 1.0), 0.5_f64.sqrt(), epsilon = 1e-6);
+        assert!(magnitude_db(&sections, 4.0) < -60.0);
+    }
+
+    #[test]
+    fn clamps_chebyshev_ripple() {
+        for ripple in [0.0, -3.0, f64::NAN] {
+            let designs = [
+                FilterDesign::ChebyshevI { ripple },
+                FilterDesign::ChebyshevII {
+                    stopband_attenuation: ripple,
+                },
+            ];
+
+            for design in designs {
+                let sections = design.analog_sections(4);
+                assert!(magnitude(&sections, 0.5).is_finite());
+            }
+        }
+    }
+}
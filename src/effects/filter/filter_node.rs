@@ -0,0 +1,149 @@
+use super::{
+    filter_cascade::{FilterCascade, FilterPass},
+    filter_processor::FilterProcessor,
+    FilterDesign,
+};
+use crate::{
+    commands::Id,
+    effects::biquad::{cascade_response, write_frequency_response},
+    graph::DspNode,
+    parameter::*,
+    prelude::*,
+    utility::create_parameters,
+};
+
+/// A high order low or high pass filter
+///
+/// The filter is built from a cascade of second order sections, designed from
+/// an analog prototype. Each order adds 6dB per octave to the slope, so an
+/// 8th order filter falls at 48dB per octave.
+///
+/// # Parameters
+/// - frequency
+/// - gain
+pub struct Filter {
+    /// The node to connect into the audio graph
+    pub node: GraphNode,
+
+    parameters: Parameters,
+    cascade: FilterCascade,
+    sample_rate: usize,
+}
+
+impl DspNode for Filter {
+    fn get_parameters_mut(&mut self) -> &mut Parameters {
+        &mut self.parameters
+    }
+}
+
+impl Filter {
+    /// Create a low pass filter, which removes frequencies above the cutoff
+    ///
+    /// The `order` is at least 1, odd orders are rounded up for
+    /// [FilterDesign::LinkwitzRiley], and orders above 20 are reduced to 20 for
+    /// [FilterDesign::Bessel]
+    pub fn low_pass(
+        context: &dyn Context,
+        channel_count: usize,
+        design: FilterDesign,
+        order: usize,
+    ) -> Self {
+        Self::new(
+            context,
+            channel_count,
+            FilterCascade::new(design, order, FilterPass::LowPass),
+        )
+    }
+
+    /// Create a high pass filter, which removes frequencies below the cutoff
+    ///
+    /// The `order` is at least 1, odd orders are rounded up for
+    /// [FilterDesign::LinkwitzRiley], and orders above 20 are reduced to 20 for
+    /// [FilterDesign::Bessel]
+    pub fn high_pass(
+        context: &dyn Context,
+        channel_count: usize,
+        design: FilterDesign,
+        order: usize,
+    ) -> Self {
+        Self::new(
+            context,
+            channel_count,
+            FilterCascade::new(design, order, FilterPass::HighPass),
+        )
+    }
+
+    fn new(context: &dyn Context, channel_count: usize, cascade: FilterCascade) -> Self {
+        let id = Id::generate();
+
+        let (params, realtime_params) = create_parameters(
+            id,
+            context,
+            [
+                ("frequency", ParameterRange::new(1_000.0, 20.0, 20_000.0)),
+                (
+                    "gain",
+                    ParameterRange::new(
+                        Level::unity().as_linear(),
+                        0.0,
+                        Level::from_db(100.0).as_linear(),
+                    ),
+                ),
+            ],
+        );
+
+        let processor = Box::new(FilterProcessor::new(
+            context.get_sample_rate(),
+            channel_count,
+            cascade.clone(),
+        ));
+
+        Self {
+            node: GraphNode::new(
+                id,
+                context,
+                channel_count,
+                channel_count,
+                processor,
+                realtime_params,
+            ),
+            parameters: params,
+            cascade,
+            sample_rate: context.get_sample_rate(),
+        }
+    }
+
+    /// Get the frequency parameter
+    pub fn frequency(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("frequency")
+    }
+
+    /// Get the gain parameter
+    pub fn gain(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("gain")
+    }
+
+    /// Calculate the response of the filter at a set of frequencies
+    ///
+    /// This is calculated from the last parameter values used by the audio
+    /// thread, including the output gain. For each of the `frequencies`, the
+    /// linear magnitude is written to `magnitudes` and the phase in radians is
+    /// written to `phases`.
+    pub fn get_frequency_response(
+        &self,
+        frequencies: &[f64],
+        magnitudes: &mut [f64],
+        phases: &mut [f64],
+    ) {
+        let sample_rate = self.sample_rate as f64;
+
+        let coefficients = self
+            .cascade
+            .create_coefficients(self.parameters.current_value("frequency"), sample_rate);
+        let gain = self.parameters.current_value("gain");
+
+        write_frequency_response(frequencies, magnitudes, phases, |frequency| {
+            gain * cascade_response(&coefficients, frequency, sample_rate)
+        });
+    }
+}
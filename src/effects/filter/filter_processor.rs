@@ -0,0 +1,129 @@
+use super::filter_cascade::FilterCascade;
+use crate::{effects::biquad::BiquadCoefficients, graph::DspProcessor, prelude::*};
+
+pub struct FilterProcessor {
+    cascade: FilterCascade,
+    sample_rate: usize,
+    coefficients: Vec<BiquadCoefficients>,
+    last_frequency: f32,
+    delays: Vec<Vec<[f64; 4]>>,
+}
+
+impl FilterProcessor {
+    pub fn new(sample_rate: usize, channel_count: usize, cascade: FilterCascade) -> Self {
+        let last_frequency = 1_000.0;
+        let coefficients = cascade.create_coefficients(last_frequency as f64, sample_rate as f64);
+
+        Self {
+            delays: (0..channel_count)
+                .map(|_| vec![[0.0; 4]; cascade.section_count()])
+                .collect(),
+            cascade,
+            sample_rate,
+            coefficients,
+            last_frequency,
+        }
+    }
+}
+
+impl DspProcessor for FilterProcessor {
+    fn process_audio(&mut self, context: &mut crate::ProcessContext) {
+        let frame_count = context.output_buffer.frame_count();
+
+        let frequency = context
+            .parameters
+            .get_parameter_values("frequency", frame_count);
+        let gain = context.parameters.get_parameter_values("gain", frame_count);
+
+        let channel_count = std::cmp::min(
+            context.output_buffer.channel_count(),
+            context.input_buffer.channel_count(),
+        );
+
+        for (frame, frequency) in frequency.iter().enumerate() {
+            if *frequency != self.last_frequency {
+                self.cascade.calculate_coefficients(
+                    *frequency as f64,
+                    self.sample_rate as f64,
+                    &mut self.coefficients,
+                );
+                self.last_frequency = *frequency;
+            }
+
+            for (channel, delays) in self.delays.iter_mut().enumerate().take(channel_count) {
+                let location = SampleLocation::new(channel, frame);
+                let input_sample = context.input_buffer.get_sample(location) as f64;
+
+                let output_sample = self
+                    .coefficients
+                    .iter()
+                    .zip(delays.iter_mut())
+                    .fold(input_sample, |sample, (coefficients, delays)| {
+                        coefficients.process_sample(sample, delays)
+                    });
+
+                context
+                    .output_buffer
+                    .set_sample(location, output_sample as f32);
+            }
+        }
+
+        context.output_buffer.apply_gain(gain);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        effects::filter::{filter_cascade::FilterPass, FilterDesign},
+        graph::DspParameters,
+        parameter::RealtimeAudioParameter,
+        ProcessContext,
+    };
+    use approx::assert_relative_eq;
+    use atomic_float::AtomicF64;
+    use std::sync::Arc;
+
+    const SAMPLE_RATE: usize = 48_000;
+    const FRAME_COUNT: usize = 4_800;
+
+    fn process_sine(processor: &mut FilterProcessor, frequency: f64) -> f32 {
+        let input = OwnedAudioBuffer::sine(FRAME_COUNT, 1, SAMPLE_RATE, frequency, 1.0);
+        let mut output = OwnedAudioBuffer::new(FRAME_COUNT, 1, SAMPLE_RATE);
+
+        let mut parameters =
+            DspParameters::new([("frequency", 1_000.0), ("gain", 1.0)].map(|(id, value)| {
+                RealtimeAudioParameter::new(id, Arc::new(AtomicF64::new(value)), FRAME_COUNT)
+            }));
+
+        parameters.iter_mut().for_each(|(_, parameter)| {
+            parameter.process(&Timestamp::zero(), FRAME_COUNT, SAMPLE_RATE);
+        });
+
+        processor.process_audio(&mut ProcessContext {
+            input_buffer: &input,
+            output_buffer: &mut output,
+            start_time: &Timestamp::zero(),
+            parameters: &parameters,
+            midi_events: &[],
+        });
+
+        output.get_channel_data(SampleLocation::origin())[FRAME_COUNT / 2..]
+            .iter()
+            .fold(0.0, |peak, value| value.abs().max(peak))
+    }
+
+    #[test]
+    fn attenuates_above_the_cutoff() {
+        let cascade = FilterCascade::new(FilterDesign::Butterworth, 8, FilterPass::LowPass);
+        let mut processor = FilterProcessor::new(SAMPLE_RATE, 1, cascade);
+
+        assert_relative_eq!(process_sine(&mut processor, 100.0), 1.0, epsilon = 1e-2);
+
+        let cascade = FilterCascade::new(FilterDesign::Butterworth, 8, FilterPass::LowPass);
+        let mut processor = FilterProcessor::new(SAMPLE_RATE, 1, cascade);
+
+        assert!(process_sine(&mut processor, 4_000.0) < 0.001);
+    }
+}
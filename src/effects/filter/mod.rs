@@ -0,0 +1,8 @@
+mod filter_cascade;
+mod filter_design;
+mod filter_node;
+mod filter_processor;
+
+pub(crate) use filter_cascade::{FilterCascade, FilterPass};
+pub use filter_design::FilterDesign;
+pub use filter_node::Filter;
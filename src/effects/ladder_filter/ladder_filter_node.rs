@@ -0,0 +1,129 @@
+use super::ladder_filter_processor::{LadderFilterProcessor, MAXIMUM_FEEDBACK};
+use crate::{
+    commands::Id, dsp::integrator_response_terms, effects::biquad::write_frequency_response,
+    graph::DspNode, parameter::*, prelude::*, utility::create_parameters,
+};
+
+/// A Moog-style four pole ladder low pass filter
+///
+/// The filter falls at 24dB per octave above the cutoff. Raising the resonance
+/// boosts the cutoff, and thins out the low frequencies, until the filter
+/// oscillates on its own at full resonance. The input is saturated, and the
+/// drive pushes it harder into the saturation.
+///
+/// The filter uses zero-delay feedback, so it stays stable when the frequency
+/// and resonance are modulated at audio rate.
+///
+/// # Parameters
+/// - frequency
+/// - resonance (from 0 to 1)
+/// - drive (linear gain into the saturation)
+/// - gain
+pub struct LadderFilter {
+    /// The node to connect into the audio graph
+    pub node: GraphNode,
+
+    parameters: Parameters,
+    sample_rate: usize,
+}
+
+impl DspNode for LadderFilter {
+    fn get_parameters_mut(&mut self) -> &mut Parameters {
+        &mut self.parameters
+    }
+}
+
+impl LadderFilter {
+    /// Create a new ladder filter
+    pub fn new(context: &dyn Context, channel_count: usize) -> Self {
+        let id = Id::generate();
+
+        let (params, realtime_params) = create_parameters(
+            id,
+            context,
+            [
+                ("frequency", ParameterRange::new(1_000.0, 20.0, 20_000.0)),
+                ("resonance", ParameterRange::new(0.0, 0.0, 1.0)),
+                (
+                    "drive",
+                    ParameterRange::new(
+                        Level::unity().as_linear(),
+                        Level::from_db(-24.0).as_linear(),
+                        Level::from_db(36.0).as_linear(),
+                    ),
+                ),
+                (
+                    "gain",
+                    ParameterRange::new(
+                        Level::unity().as_linear(),
+                        0.0,
+                        Level::from_db(100.0).as_linear(),
+                    ),
+                ),
+            ],
+        );
+
+        let processor = Box::new(LadderFilterProcessor::new(
+            context.get_sample_rate(),
+            channel_count,
+        ));
+
+        Self {
+            node: GraphNode::new(
+                id,
+                context,
+                channel_count,
+                channel_count,
+                processor,
+                realtime_params,
+            ),
+            parameters: params,
+            sample_rate: context.get_sample_rate(),
+        }
+    }
+
+    /// Get the frequency parameter
+    pub fn frequency(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("frequency")
+    }
+
+    /// Get the resonance parameter
+    pub fn resonance(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("resonance")
+    }
+
+    /// Get the drive parameter
+    pub fn drive(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("drive")
+    }
+
+    /// Get the gain parameter
+    pub fn gain(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("gain")
+    }
+
+    /// Calculate the response of the filter at a set of frequencies
+    ///
+    /// This is the response to quiet signals, where the saturation has no
+    /// effect, so it includes the drive. It is calculated from the last
+    /// parameter values used by the audio thread, including the output gain.
+    /// For each of the `frequencies`, the linear magnitude is written to
+    /// `magnitudes` and the phase in radians is written to `phases`.
+    pub fn get_frequency_response(
+        &self,
+        frequencies: &[f64],
+        magnitudes: &mut [f64],
+        phases: &mut [f64],
+    ) {
+        let cutoff = self.parameters.current_value("frequency");
+        let feedback = MAXIMUM_FEEDBACK * self.parameters.current_value("resonance");
+        let gain = self.parameters.current_value("drive") * self.parameters.current_value("gain");
+
+        write_frequency_response(frequencies, magnitudes, phases, |frequency| {
+            let (s, one) = integrator_response_terms(frequency, cutoff, self.sample_rate as f64);
+
+            let ladder = one.powi(4);
+            gain * ladder / ((s + one).powi(4) + feedback * ladder)
+        });
+    }
+}
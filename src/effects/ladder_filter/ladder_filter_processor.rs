@@ -0,0 +1,187 @@
+use crate::{
+    dsp::{flush_denormal, integrator_gain},
+    graph::DspProcessor,
+    prelude::*,
+};
+
+/// The feedback at full resonance
+///
+/// The filter starts to self-oscillate with a feedback of 4, so this is just
+/// past that, to keep the oscillation going against the saturation
+pub const MAXIMUM_FEEDBACK: f64 = 4.2;
+
+/// The state of a four pole ladder filter, built from zero-delay feedback one
+/// pole stages
+///
+/// The feedback loop is solved for the linear filter, and the input to the
+/// ladder is saturated. This keeps the filter stable with any cutoff, even
+/// when it is self-oscillating.
+#[derive(Clone, Copy, Default)]
+struct LadderState {
+    stages: [f64; 4],
+}
+
+impl LadderState {
+    fn process(&mut self, input: f64, g: f64, feedback: f64, drive: f64) -> f64 {
+        let stage_gain = g / (1.0 + g);
+
+        let state_contribution = self
+            .stages
+            .iter()
+            .fold(0.0, |sum, state| sum * stage_gain + state / (1.0 + g));
+
+        let ladder_gain = stage_gain.powi(4);
+        let ladder_input =
+            (drive * input - feedback * state_contribution) / (1.0 + feedback * ladder_gain);
+
+        self.stages
+            .iter_mut()
+            .fold(ladder_input.tanh(), |stage_input, state| {
+                let v = stage_gain * (stage_input - *state);
+                let output = v + *state;
+                *state = flush_denormal(output + v);
+                output
+            })
+    }
+}
+
+pub struct LadderFilterProcessor {
+    sample_rate: usize,
+    states: Vec<LadderState>,
+}
+
+impl LadderFilterProcessor {
+    pub fn new(sample_rate: usize, channel_count: usize) -> Self {
+        Self {
+            sample_rate,
+            states: vec![LadderState::default(); channel_count],
+        }
+    }
+}
+
+impl DspProcessor for LadderFilterProcessor {
+    fn process_audio(&mut self, context: &mut crate::ProcessContext) {
+        let frame_count = context.output_buffer.frame_count();
+
+        let frequency = context
+            .parameters
+            .get_parameter_values("frequency", frame_count);
+        let resonance = context
+            .parameters
+            .get_parameter_values("resonance", frame_count);
+        let drive = context
+            .parameters
+            .get_parameter_values("drive", frame_count);
+        let gain = context.parameters.get_parameter_values("gain", frame_count);
+
+        let channel_count = self
+            .states
+            .len()
+            .min(context.input_buffer.channel_count())
+            .min(context.output_buffer.channel_count());
+
+        for (channel, state) in self.states.iter_mut().enumerate().take(channel_count) {
+            for frame in 0..frame_count {
+                let location = SampleLocation::new(channel, frame);
+                let input = context.input_buffer.get_sample(location) as f64;
+
+                let output = state.process(
+                    input,
+                    integrator_gain(frequency[frame] as f64, self.sample_rate as f64),
+                    MAXIMUM_FEEDBACK * resonance[frame] as f64,
+                    drive[frame] as f64,
+                );
+
+                context
+                    .output_buffer
+                    .set_sample(location, (gain[frame] as f64 * output) as f32);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use std::f64::consts::TAU;
+
+    const SAMPLE_RATE: f64 = 48_000.0;
+    const FRAME_COUNT: usize = 48_000;
+
+    fn render(
+        input: impl Fn(usize) -> f64,
+        cutoff: impl Fn(usize) -> f64,
+        resonance: f64,
+    ) -> Vec<f64> {
+        let mut state = LadderState::default();
+
+        (0..FRAME_COUNT)
+            .map(|frame| {
+                let g = integrator_gain(cutoff(frame), SAMPLE_RATE);
+                state.process(input(frame), g, MAXIMUM_FEEDBACK * resonance, 1.0)
+            })
+            .collect()
+    }
+
+    fn sine(frequency: f64, amplitude: f64) -> impl Fn(usize) -> f64 {
+        move |frame| amplitude * (TAU * frequency * frame as f64 / SAMPLE_RATE).sin()
+    }
+
+    fn peak(values: &[f64]) -> f64 {
+        values.iter().fold(0.0, |peak, value| value.abs().max(peak))
+    }
+
+    #[test]
+    fn falls_at_24_db_per_octave() {
+        let amplitude = 0.01;
+
+        let pass_band = render(sine(50.0, amplitude), |_| 1_000.0, 0.0);
+        assert_relative_eq!(
+            peak(&pass_band[FRAME_COUNT / 2..]),
+            amplitude,
+            epsilon = 1e-4
+        );
+
+        let at_cutoff = render(sine(1_000.0, amplitude), |_| 1_000.0, 0.0);
+        assert_relative_eq!(
+            Level::from_linear(peak(&at_cutoff[FRAME_COUNT / 2..]) / amplitude).as_db(),
+            -12.0,
+            epsilon = 0.1
+        );
+
+        let above = render(sine(8_000.0, amplitude), |_| 1_000.0, 0.0);
+        assert!(peak(&above[FRAME_COUNT / 2..]) / amplitude < Level::from_db(-65.0).as_linear());
+    }
+
+    #[test]
+    fn resonance_boosts_the_cutoff() {
+        let amplitude = 0.01;
+
+        let flat = render(sine(1_000.0, amplitude), |_| 1_000.0, 0.0);
+        let resonant = render(sine(1_000.0, amplitude), |_| 1_000.0, 0.9);
+
+        assert!(peak(&resonant[FRAME_COUNT / 2..]) > 4.0 * peak(&flat[FRAME_COUNT / 2..]));
+    }
+
+    #[test]
+    fn self_oscillates_at_full_resonance() {
+        let output = render(|frame| if frame == 0 { 0.1 } else { 0.0 }, |_| 1_000.0, 1.0);
+        let tail = &output[FRAME_COUNT - 4_800..];
+
+        assert!(peak(tail) > 0.1);
+        assert!(peak(tail) < 2.0);
+    }
+
+    #[test]
+    fn is_stable_under_audio_rate_modulation() {
+        let output = render(
+            sine(110.0, 1.0),
+            |frame| 10_000.0 + 9_990.0 * (TAU * 5_000.0 * frame as f64 / SAMPLE_RATE).sin(),
+            1.0,
+        );
+
+        assert!(output.iter().all(|value| value.is_finite()));
+        assert!(peak(&output) < 2.0);
+    }
+}
@@ -0,0 +1,4 @@
+mod ladder_filter_node;
+mod ladder_filter_processor;
+
+pub use ladder_filter_node::LadderFilter;
@@ -0,0 +1,160 @@
+/// Turns the gain needed for each sample into a smooth gain, that never goes
+/// above the gain needed for any sample within the look-ahead
+///
+/// The lowest gain within the look-ahead is held, recovers at the release
+/// rate, and is then averaged over the look-ahead. Every value that is
+/// averaged is no higher than the gain needed for the sample leaving the
+/// look-ahead, so the ceiling is never exceeded.
+pub struct GainSmoother {
+    minimum: SlidingMinimum,
+    average: MovingAverage,
+    released_gain: f32,
+}
+
+impl GainSmoother {
+    pub fn new(look_ahead_frames: usize) -> Self {
+        Self {
+            minimum: SlidingMinimum::new(look_ahead_frames + 1),
+            average: MovingAverage::new(look_ahead_frames + 1),
+            released_gain: 1.0,
+        }
+    }
+
+    /// Process the gain needed for the newest sample, returning the gain to
+    /// apply to the sample from `look_ahead_frames` ago
+    pub fn process(&mut self, target_gain: f32, release_coefficient: f32) -> f32 {
+        let held_gain = self.minimum.process(target_gain);
+
+        self.released_gain = if held_gain <= self.released_gain {
+            held_gain
+        } else {
+            held_gain + (self.released_gain - held_gain) * release_coefficient
+        };
+
+        self.average.process(self.released_gain)
+    }
+}
+
+/// The minimum over a window of the most recent values
+///
+/// Values that can no longer be the minimum are discarded as new values
+/// arrive, so the cost per value is constant on average.
+struct SlidingMinimum {
+    candidates: Vec<(usize, f32)>,
+    front: usize,
+    count: usize,
+    index: usize,
+}
+
+impl SlidingMinimum {
+    fn new(window_length: usize) -> Self {
+        debug_assert!(window_length > 0);
+
+        Self {
+            candidates: vec![(0, 1.0); window_length],
+            front: 0,
+            count: 0,
+            index: 0,
+        }
+    }
+
+    fn process(&mut self, value: f32) -> f32 {
+        let window_length = self.candidates.len();
+
+        if self.count > 0 && self.index.wrapping_sub(self.candidates[self.front].0) >= window_length
+        {
+            self.front = (self.front + 1) % window_length;
+            self.count -= 1;
+        }
+
+        while self.count > 0 {
+            let back = (self.front + self.count - 1) % window_length;
+            if self.candidates[back].1 < value {
+                break;
+            }
+
+            self.count -= 1;
+        }
+
+        let back = (self.front + self.count) % window_length;
+        self.candidates[back] = (self.index, value);
+        self.count += 1;
+        self.index = self.index.wrapping_add(1);
+
+        self.candidates[self.front].1
+    }
+}
+
+/// The mean over a window of the most recent values
+struct MovingAverage {
+    values: Vec<f32>,
+    position: usize,
+    sum: f64,
+}
+
+impl MovingAverage {
+    fn new(window_length: usize) -> Self {
+        Self {
+            values: vec![1.0; window_length],
+            position: 0,
+            sum: window_length as f64,
+        }
+    }
+
+    fn process(&mut self, value: f32) -> f32 {
+        self.sum += value as f64 - self.values[self.position] as f64;
+        self.values[self.position] = value;
+        self.position = (self.position + 1) % self.values.len();
+
+        // Recalculate the sum once per window, so rounding errors can't build up
+        if self.position == 0 {
+            self.sum = self.values.iter().map(|value| *value as f64).sum();
+        }
+
+        (self.sum / self.values.len() as f64) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn sliding_minimum_forgets_old_values() {
+        let mut minimum = SlidingMinimum::new(3);
+
+        let output: Vec<f32> = [5.0, 3.0, 4.0, 6.0, 7.0, 8.0, 2.0]
+            .iter()
+            .map(|value| minimum.process(*value))
+            .collect();
+
+        assert_eq!(output, [5.0, 3.0, 3.0, 3.0, 4.0, 6.0, 2.0]);
+    }
+
+    #[test]
+    fn reaches_the_target_before_it_leaves_the_look_ahead() {
+        let look_ahead = 10;
+        let mut smoother = GainSmoother::new(look_ahead);
+
+        let targets: Vec<f32> = (0..100)
+            .map(|frame| if frame == 50 { 0.5 } else { 1.0 })
+            .collect();
+
+        let gains: Vec<f32> = targets
+            .iter()
+            .map(|target| smoother.process(*target, 0.0))
+            .collect();
+
+        for frame in look_ahead..targets.len() {
+            assert!(gains[frame] <= targets[frame - look_ahead] + 1e-6);
+        }
+
+        assert_relative_eq!(gains[50 + look_ahead], 0.5, epsilon = 1e-6);
+        assert!(gains[50 + look_ahead - 1] > 0.5);
+        assert!(gains
+            .windows(2)
+            .take(50 + look_ahead)
+            .all(|pair| pair[1] <= pair[0]));
+    }
+}
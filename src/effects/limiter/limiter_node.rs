@@ -0,0 +1,102 @@
+use super::{limiter_options::LimiterOptions, limiter_processor::LimiterProcessor};
+use crate::{commands::Id, graph::DspNode, parameter::*, prelude::*, utility::create_parameters};
+
+/// A look-ahead brickwall limiter
+///
+/// The limiter looks ahead for peaks and reduces the gain smoothly before they
+/// arrive, so that the output never goes above the ceiling. The output is
+/// delayed by [Limiter::latency] frames.
+///
+/// The look-ahead, true-peak detection and stereo linking are set with
+/// [LimiterOptions] when the limiter is created.
+///
+/// # Parameters
+/// - release (in milliseconds)
+/// - ceiling
+pub struct Limiter {
+    /// The node to connect into the audio graph
+    pub node: GraphNode,
+
+    params: Parameters,
+    latency: usize,
+}
+
+impl DspNode for Limiter {
+    fn get_parameters_mut(&mut self) -> &mut Parameters {
+        &mut self.params
+    }
+}
+
+impl Limiter {
+    /// Create a new limiter
+    pub fn new(context: &dyn Context, channel_count: usize, options: LimiterOptions) -> Self {
+        let id = Id::generate();
+
+        let (params, realtime_params) = create_parameters(
+            id,
+            context,
+            [
+                ("release", ParameterRange::new(50.0, 1.0, 5_000.0)),
+                (
+                    "ceiling",
+                    ParameterRange::new(
+                        Level::unity().as_linear(),
+                        Level::zero().as_linear(),
+                        Level::unity().as_linear(),
+                    ),
+                ),
+            ],
+        );
+
+        let sample_rate = context.get_sample_rate();
+        let look_ahead_frames =
+            (options.look_ahead.as_secs_f64() * sample_rate as f64).round() as usize;
+
+        let processor = Box::new(LimiterProcessor::new(
+            sample_rate,
+            channel_count,
+            look_ahead_frames,
+            options.true_peak,
+            options.stereo_link,
+        ));
+
+        let latency = processor.latency();
+
+        Self {
+            node: GraphNode::new(
+                id,
+                context,
+                channel_count,
+                channel_count,
+                processor,
+                realtime_params,
+            ),
+            params,
+            latency,
+        }
+    }
+
+    /// Get the release parameter
+    ///
+    /// This is the time taken for the gain to recover after a peak
+    pub fn release(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("release")
+    }
+
+    /// Get the ceiling parameter
+    ///
+    /// This is the highest level of the output, as linear gain
+    pub fn ceiling(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("ceiling")
+    }
+
+    /// Set the ceiling immediately
+    pub fn set_ceiling(&mut self, ceiling: Level) {
+        self.ceiling().set_value_now(ceiling.as_linear());
+    }
+
+    /// The number of frames that the output is delayed by
+    pub fn latency(&self) -> usize {
+        self.latency
+    }
+}
@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+/// Options that are fixed when a [crate::Limiter] is created
+#[derive(Clone, Copy, Debug)]
+pub struct LimiterOptions {
+    pub(super) look_ahead: Duration,
+    pub(super) true_peak: bool,
+    pub(super) stereo_link: bool,
+}
+
+impl Default for LimiterOptions {
+    fn default() -> Self {
+        Self {
+            look_ahead: Duration::from_millis(5),
+            true_peak: false,
+            stereo_link: true,
+        }
+    }
+}
+
+impl LimiterOptions {
+    /// Specify how far ahead the limiter looks for peaks
+    ///
+    /// The gain is reduced smoothly over this time before each peak arrives.
+    /// The output is delayed by the look-ahead, so longer times give smoother
+    /// limiting at the cost of more latency.
+    pub fn with_look_ahead(mut self, look_ahead: Duration) -> Self {
+        self.look_ahead = look_ahead;
+        self
+    }
+
+    /// Specify whether peaks between samples are detected
+    ///
+    /// The input is oversampled to find the peaks that would appear once the
+    /// signal is converted to analogue. This adds a few frames of latency.
+    pub fn with_true_peak(mut self, true_peak: bool) -> Self {
+        self.true_peak = true_peak;
+        self
+    }
+
+    /// Specify whether the same gain reduction is applied to every channel
+    ///
+    /// Linking the channels keeps the stereo image stable. Otherwise each
+    /// channel is limited separately.
+    pub fn with_stereo_link(mut self, stereo_link: bool) -> Self {
+        self.stereo_link = stereo_link;
+        self
+    }
+}
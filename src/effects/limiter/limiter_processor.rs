@@ -0,0 +1,311 @@
+use super::{
+    gain_smoother::GainSmoother,
+    true_peak_detector::{TruePeakDetector, TRUE_PEAK_LATENCY},
+};
+use itertools::izip;
+
+use crate::{
+    effects::delay::{DelayInterpolation, DelayLine},
+    graph::DspProcessor,
+    prelude::*,
+};
+
+pub struct LimiterProcessor {
+    delay_lines: Vec<DelayLine>,
+    detectors: Vec<Option<TruePeakDetector>>,
+    peaks: Vec<f32>,
+    gains: Vec<f32>,
+    smoothers: Vec<GainSmoother>,
+    latency: usize,
+    sample_rate: f32,
+    release: f32,
+    release_coefficient: f32,
+}
+
+impl LimiterProcessor {
+    pub fn new(
+        sample_rate: usize,
+        channel_count: usize,
+        look_ahead_frames: usize,
+        true_peak: bool,
+        stereo_link: bool,
+    ) -> Self {
+        let latency = look_ahead_frames + if true_peak { TRUE_PEAK_LATENCY } else { 0 };
+        let smoother_count = if stereo_link { 1 } else { channel_count };
+
+        Self {
+            delay_lines: (0..channel_count)
+                .map(|_| DelayLine::new(latency))
+                .collect(),
+            detectors: (0..channel_count)
+                .map(|_| true_peak.then(TruePeakDetector::new))
+                .collect(),
+            peaks: vec![0.0; channel_count],
+            gains: vec![1.0; channel_count],
+            smoothers: (0..smoother_count)
+                .map(|_| GainSmoother::new(look_ahead_frames))
+                .collect(),
+            latency,
+            sample_rate: sample_rate as f32,
+            release: 0.0,
+            release_coefficient: 0.0,
+        }
+    }
+
+    /// The number of frames that the output is delayed by
+    pub fn latency(&self) -> usize {
+        self.latency
+    }
+
+    fn update_release(&mut self, release: f32) {
+        if release != self.release {
+            self.release = release;
+            self.release_coefficient = (-1.0 / (self.sample_rate * release / 1_000.0)).exp();
+        }
+    }
+}
+
+fn calculate_target_gain(peak: f32, ceiling: f32) -> f32 {
+    if peak > ceiling {
+        ceiling / peak
+    } else {
+        1.0
+    }
+}
+
+impl DspProcessor for LimiterProcessor {
+    fn process_audio(&mut self, context: &mut crate::ProcessContext) {
+        let frame_count = context.output_buffer.frame_count();
+
+        let release = context
+            .parameters
+            .get_parameter_values("release", frame_count);
+        let ceiling = context
+            .parameters
+            .get_parameter_values("ceiling", frame_count);
+
+        let input_channel_count = context
+            .input_buffer
+            .channel_count()
+            .min(self.delay_lines.len());
+        let output_channel_count = context
+            .output_buffer
+            .channel_count()
+            .min(self.delay_lines.len());
+
+        for frame in 0..frame_count {
+            self.update_release(release[frame]);
+
+            for (channel, (delay_line, detector, peak)) in izip!(
+                self.delay_lines.iter_mut(),
+                self.detectors.iter_mut(),
+                self.peaks.iter_mut()
+            )
+            .enumerate()
+            {
+                let sample = if channel < input_channel_count {
+                    context
+                        .input_buffer
+                        .get_sample(SampleLocation::new(channel, frame))
+                } else {
+                    0.0
+                };
+
+                delay_line.write(sample);
+
+                *peak = match detector {
+                    Some(detector) => detector.process(sample),
+                    None => sample.abs(),
+                };
+            }
+
+            if let [smoother] = self.smoothers.as_mut_slice() {
+                let peak = self.peaks.iter().fold(0.0, |peak, value| value.max(peak));
+                let target_gain = calculate_target_gain(peak, ceiling[frame]);
+                let gain = smoother.process(target_gain, self.release_coefficient);
+                self.gains.fill(gain);
+            } else {
+                for (gain, peak, smoother) in izip!(
+                    self.gains.iter_mut(),
+                    self.peaks.iter(),
+                    self.smoothers.iter_mut()
+                ) {
+                    let target_gain = calculate_target_gain(*peak, ceiling[frame]);
+                    *gain = smoother.process(target_gain, self.release_coefficient);
+                }
+            }
+
+            for (channel, (delay_line, gain)) in self
+                .delay_lines
+                .iter_mut()
+                .zip(self.gains.iter())
+                .take(output_channel_count)
+                .enumerate()
+            {
+                let delayed = delay_line.read(self.latency as f32, DelayInterpolation::Linear);
+
+                context
+                    .output_buffer
+                    .set_sample(SampleLocation::new(channel, frame), delayed * gain);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{graph::DspParameters, parameter::RealtimeAudioParameter, ProcessContext};
+    use approx::assert_relative_eq;
+    use atomic_float::AtomicF64;
+    use std::{f32::consts::PI, sync::Arc};
+
+    const SAMPLE_RATE: usize = 48_000;
+    const FRAME_COUNT: usize = 4_800;
+    const LOOK_AHEAD: usize = 240;
+
+    struct Fixture {
+        processor: LimiterProcessor,
+        parameters: DspParameters,
+    }
+
+    impl Fixture {
+        fn new(true_peak: bool, stereo_link: bool) -> Self {
+            let make_parameter = |(name, value)| {
+                RealtimeAudioParameter::new(name, Arc::new(AtomicF64::new(value)), FRAME_COUNT)
+            };
+
+            Self {
+                processor: LimiterProcessor::new(
+                    SAMPLE_RATE,
+                    2,
+                    LOOK_AHEAD,
+                    true_peak,
+                    stereo_link,
+                ),
+                parameters: DspParameters::new(
+                    [("release", 50.0), ("ceiling", 0.5)].map(make_parameter),
+                ),
+            }
+        }
+
+        fn process(&mut self, input: &OwnedAudioBuffer) -> OwnedAudioBuffer {
+            let mut output =
+                OwnedAudioBuffer::new(input.frame_count(), input.channel_count(), SAMPLE_RATE);
+
+            self.parameters.iter_mut().for_each(|(_, parameter)| {
+                parameter.process(&Timestamp::zero(), input.frame_count(), SAMPLE_RATE)
+            });
+
+            self.processor.process_audio(&mut ProcessContext {
+                input_buffer: input,
+                output_buffer: &mut output,
+                start_time: &Timestamp::zero(),
+                parameters: &self.parameters,
+                midi_events: &[],
+            });
+
+            output
+        }
+    }
+
+    fn create_input(left: impl Fn(usize) -> f32, right: impl Fn(usize) -> f32) -> OwnedAudioBuffer {
+        let mut input = OwnedAudioBuffer::new(FRAME_COUNT, 2, SAMPLE_RATE);
+
+        for frame in 0..FRAME_COUNT {
+            input.set_sample(SampleLocation::new(0, frame), left(frame));
+            input.set_sample(SampleLocation::new(1, frame), right(frame));
+        }
+
+        input
+    }
+
+    fn peak(buffer: &OwnedAudioBuffer, channel: usize, frames: std::ops::Range<usize>) -> f32 {
+        buffer.get_channel_data(SampleLocation::channel(channel))[frames]
+            .iter()
+            .fold(0.0, |peak, sample| sample.abs().max(peak))
+    }
+
+    fn sine(amplitude: f32, frequency: f32) -> impl Fn(usize) -> f32 {
+        move |frame| amplitude * (2.0 * PI * frequency * frame as f32 / SAMPLE_RATE as f32).sin()
+    }
+
+    #[test]
+    fn never_exceeds_the_ceiling() {
+        let mut fixture = Fixture::new(false, true);
+
+        let input = create_input(
+            |frame| if frame % 1_000 == 500 { 2.0 } else { 0.1 },
+            sine(1.5, 100.0),
+        );
+        let output = fixture.process(&input);
+
+        assert!(peak(&output, 0, 0..FRAME_COUNT) <= 0.5 + 1e-6);
+        assert!(peak(&output, 1, 0..FRAME_COUNT) <= 0.5 + 1e-6);
+    }
+
+    #[test]
+    fn output_is_delayed_by_the_look_ahead() {
+        let mut fixture = Fixture::new(false, true);
+        assert_eq!(fixture.processor.latency(), LOOK_AHEAD);
+
+        let input = create_input(|frame| if frame == 100 { 0.25 } else { 0.0 }, |_| 0.0);
+        let output = fixture.process(&input);
+
+        assert_relative_eq!(
+            output.get_sample(SampleLocation::new(0, 100 + LOOK_AHEAD)),
+            0.25
+        );
+        assert_relative_eq!(peak(&output, 0, 0..100 + LOOK_AHEAD), 0.0);
+    }
+
+    #[test]
+    fn leaves_quiet_signals_alone() {
+        let mut fixture = Fixture::new(true, true);
+        let latency = fixture.processor.latency();
+
+        let input = create_input(sine(0.4, 1_000.0), sine(0.3, 500.0));
+        let output = fixture.process(&input);
+
+        for channel in 0..2 {
+            let input = input.get_channel_data(SampleLocation::channel(channel));
+            let output = output.get_channel_data(SampleLocation::channel(channel));
+
+            for (input, output) in input.iter().zip(output[latency..].iter()) {
+                assert_relative_eq!(input, output, epsilon = 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn linked_channels_share_the_gain_reduction() {
+        let quiet = sine(0.2, 1_000.0);
+        let input = create_input(sine(1.0, 1_000.0), &quiet);
+
+        let linked = Fixture::new(false, true).process(&input);
+        let unlinked = Fixture::new(false, false).process(&input);
+
+        let settled = FRAME_COUNT / 2..FRAME_COUNT;
+
+        assert_relative_eq!(peak(&linked, 0, settled.clone()), 0.5, epsilon = 0.01);
+        assert_relative_eq!(peak(&linked, 1, settled.clone()), 0.1, epsilon = 0.01);
+
+        assert_relative_eq!(peak(&unlinked, 0, settled.clone()), 0.5, epsilon = 0.01);
+        assert_relative_eq!(peak(&unlinked, 1, settled), 0.2, epsilon = 0.01);
+    }
+
+    #[test]
+    fn true_peak_detection_catches_peaks_between_samples() {
+        // A quarter of the sample rate, with every sample halfway between the peaks
+        let quarter_rate = |frame: usize| (0.5 * PI * frame as f32 + 0.25 * PI).sin();
+        let input = create_input(quarter_rate, quarter_rate);
+
+        let settled = FRAME_COUNT / 2..FRAME_COUNT;
+
+        let sample_peak = Fixture::new(false, true).process(&input);
+        assert_relative_eq!(peak(&sample_peak, 0, settled.clone()), 0.5, epsilon = 0.01);
+
+        let true_peak = Fixture::new(true, true).process(&input);
+        assert!(peak(&true_peak, 0, settled) < 0.5 * 0.5_f32.sqrt() + 0.01);
+    }
+}
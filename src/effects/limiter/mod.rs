@@ -0,0 +1,8 @@
+mod gain_smoother;
+mod limiter_node;
+mod limiter_options;
+mod limiter_processor;
+mod true_peak_detector;
+
+pub use limiter_node::Limiter;
+pub use limiter_options::LimiterOptions;
@@ -0,0 +1,129 @@
+use std::f32::consts::PI;
+
+/// How many points are checked for each sample
+const OVERSAMPLING_RATIO: usize = 4;
+
+/// The length of the interpolation filter for each point between samples
+const TAPS_PER_PHASE: usize = 12;
+
+/// The number of frames that the detected peaks are behind the input
+pub const TRUE_PEAK_LATENCY: usize = TAPS_PER_PHASE / 2;
+
+/// Finds the peak level around each sample, including the peaks between
+/// samples that appear once the signal is reconstructed
+///
+/// The points between each pair of samples are found with windowed sinc
+/// interpolation, in the same way as ITU-R BS.1770 true-peak meters.
+pub struct TruePeakDetector {
+    history: [f32; TAPS_PER_PHASE],
+    position: usize,
+    phases: [[f32; TAPS_PER_PHASE]; OVERSAMPLING_RATIO - 1],
+    previous_interval_peak: f32,
+}
+
+impl TruePeakDetector {
+    pub fn new() -> Self {
+        Self {
+            history: [0.0; TAPS_PER_PHASE],
+            position: 0,
+            phases: std::array::from_fn(|phase| {
+                create_phase((phase + 1) as f32 / OVERSAMPLING_RATIO as f32)
+            }),
+            previous_interval_peak: 0.0,
+        }
+    }
+
+    /// Add a sample, returning the peak around the sample from
+    /// [TRUE_PEAK_LATENCY] frames ago
+    pub fn process(&mut self, sample: f32) -> f32 {
+        self.history[self.position] = sample;
+        self.position = (self.position + 1) % TAPS_PER_PHASE;
+
+        let tap = |index: usize| self.history[(self.position + index) % TAPS_PER_PHASE];
+
+        let start = tap(TAPS_PER_PHASE / 2 - 1);
+        let end = tap(TAPS_PER_PHASE / 2);
+
+        let interval_peak = self
+            .phases
+            .iter()
+            .map(|coefficients| {
+                coefficients
+                    .iter()
+                    .enumerate()
+                    .map(|(index, coefficient)| coefficient * tap(index))
+                    .sum::<f32>()
+                    .abs()
+            })
+            .fold(start.abs().max(end.abs()), f32::max);
+
+        let peak = interval_peak.max(self.previous_interval_peak);
+        self.previous_interval_peak = interval_peak;
+        peak
+    }
+}
+
+/// Create the filter that interpolates the point `fraction` of the way between
+/// the two samples in the middle of the history
+fn create_phase(fraction: f32) -> [f32; TAPS_PER_PHASE] {
+    let centre = (TAPS_PER_PHASE / 2 - 1) as f32 + fraction;
+    let half_span = (TAPS_PER_PHASE / 2) as f32;
+
+    let mut coefficients: [f32; TAPS_PER_PHASE] = std::array::from_fn(|index| {
+        let x = index as f32 - centre;
+        let sinc = if x == 0.0 {
+            1.0
+        } else {
+            (PI * x).sin() / (PI * x)
+        };
+
+        let window =
+            0.42 + 0.5 * (PI * x / half_span).cos() + 0.08 * (2.0 * PI * x / half_span).cos();
+
+        sinc * window
+    });
+
+    let sum: f32 = coefficients.iter().sum();
+    coefficients
+        .iter_mut()
+        .for_each(|coefficient| *coefficient /= sum);
+
+    coefficients
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn finds_peaks_between_samples() {
+        let mut detector = TruePeakDetector::new();
+
+        // A quarter of the sample rate, with every sample halfway between the peaks
+        let peaks: Vec<f32> = (0..256)
+            .map(|frame| {
+                let phase = 0.5 * PI * frame as f32 + 0.25 * PI;
+                detector.process(phase.sin())
+            })
+            .collect();
+
+        for peak in peaks[64..].iter() {
+            assert!(*peak > 0.95);
+            assert!(*peak < 1.05);
+        }
+    }
+
+    #[test]
+    fn is_delayed_by_the_latency() {
+        let mut detector = TruePeakDetector::new();
+
+        let peaks: Vec<f32> = (0..32)
+            .map(|frame| detector.process(if frame == 10 { 1.0 } else { 0.0 }))
+            .collect();
+
+        assert_relative_eq!(peaks[10 + TRUE_PEAK_LATENCY], 1.0);
+        assert!(peaks[10 + TRUE_PEAK_LATENCY - 2] < 0.5);
+        assert!(peaks[10 + TRUE_PEAK_LATENCY + 2] < 0.5);
+    }
+}
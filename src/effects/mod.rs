@@ -2,28 +2,55 @@ mod adsr;
 mod biquad;
 mod compressor;
 mod convolution;
+mod delay;
 mod envelope;
+mod filter;
 mod gain;
+mod ladder_filter;
+mod limiter;
 mod mixer;
+mod noise;
 mod oscillator;
 mod pan;
+mod parametric_eq;
 mod recorder;
+mod reverb;
 mod sampler;
+mod sampler_instrument;
+mod state_variable_filter;
+mod streaming_sampler;
+mod synth;
 mod utility;
+mod varispeed;
 mod waveshaper;
 
 pub use adsr::Adsr;
 pub use biquad::Biquad;
 pub use biquad::BiquadFilterType;
-pub use compressor::Compressor;
+pub use compressor::{Compressor, CompressorDetection, CompressorOptions};
 pub use convolution::Convolution;
+pub use delay::Delay;
+pub use delay::DelayInterpolation;
 pub use envelope::Envelope;
+pub use filter::{Filter, FilterDesign};
 pub use gain::Gain;
+pub use ladder_filter::LadderFilter;
+pub use limiter::{Limiter, LimiterOptions};
 pub use mixer::Mixer;
+pub use noise::{Noise, NoiseColour, NoiseOptions};
 pub use oscillator::Oscillator;
+pub use oscillator::OscillatorWaveform;
 pub use pan::Pan;
+pub use parametric_eq::{EqBand, EqBandType, ParametricEq};
 pub use recorder::Recorder;
+pub use reverb::Reverb;
 pub use sampler::Sampler;
+pub use sampler_instrument::{SampleZone, SamplerInstrument};
+pub use state_variable_filter::{StateVariableFilter, StateVariableFilterOutput};
+pub use streaming_sampler::StreamingSampler;
+pub use synth::{Synth, SynthOscillator, SynthVoiceTemplate};
+pub use utility::VoiceStealing;
+pub use varispeed::Varispeed;
 pub use waveshaper::Waveshaper;
 
 use crossbeam::channel as Channel;
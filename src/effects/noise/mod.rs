@@ -0,0 +1,9 @@
+mod noise_colour;
+mod noise_generator;
+mod noise_node;
+mod noise_options;
+mod noise_processor;
+
+pub use noise_colour::NoiseColour;
+pub use noise_node::Noise;
+pub use noise_options::NoiseOptions;
@@ -0,0 +1,16 @@
+/// The spectrum of a [crate::Noise] node
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NoiseColour {
+    /// Equal power at every frequency
+    #[default]
+    White,
+
+    /// Power falls by 3dB per octave, giving equal power in each octave
+    Pink,
+
+    /// Power falls by 6dB per octave (also known as red noise)
+    Brown,
+
+    /// Power rises by 3dB per octave
+    Blue,
+}
@@ -0,0 +1,164 @@
+use super::NoiseColour;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+/// Generates a single stream of noise
+pub struct NoiseGenerator {
+    colour: NoiseColour,
+    random_generator: ChaCha8Rng,
+    pink_state: [f64; 7],
+    brown_state: f64,
+    previous_pink: f64,
+}
+
+const PINK_GAIN: f64 = 0.11;
+const BROWN_LEAK: f64 = 0.02;
+const BROWN_GAIN: f64 = 3.5;
+const BLUE_GAIN: f64 = 1.5;
+
+impl NoiseGenerator {
+    pub fn new(colour: NoiseColour, seed: u64) -> Self {
+        Self {
+            colour,
+            random_generator: ChaCha8Rng::seed_from_u64(seed),
+            pink_state: [0.0; 7],
+            brown_state: 0.0,
+            previous_pink: 0.0,
+        }
+    }
+
+    pub fn fill(&mut self, output: &mut [f32]) {
+        for sample in output.iter_mut() {
+            *sample = self.next_value() as f32;
+        }
+    }
+
+    fn next_value(&mut self) -> f64 {
+        let white = self.white();
+
+        match self.colour {
+            NoiseColour::White => white,
+            NoiseColour::Pink => self.pink(white),
+            NoiseColour::Brown => self.brown(white),
+            NoiseColour::Blue => {
+                let pink = self.pink(white);
+                let blue = BLUE_GAIN * (pink - self.previous_pink);
+                self.previous_pink = pink;
+                blue
+            }
+        }
+    }
+
+    /// A uniform value in [-1, 1), taken from the top 53 bits of the generator
+    ///
+    /// The conversion is done here rather than with `rand`'s distributions so
+    /// that a seed always produces the same noise
+    fn white(&mut self) -> f64 {
+        let unit = (self.random_generator.next_u64() >> 11) as f64 / (1_u64 << 53) as f64;
+        2.0 * unit - 1.0
+    }
+
+    /// Paul Kellet's refined pink noise filter
+    fn pink(&mut self, white: f64) -> f64 {
+        let b = &mut self.pink_state;
+
+        b[0] = 0.99886 * b[0] + white * 0.0555179;
+        b[1] = 0.99332 * b[1] + white * 0.0750759;
+        b[2] = 0.96900 * b[2] + white * 0.1538520;
+        b[3] = 0.86650 * b[3] + white * 0.3104856;
+        b[4] = 0.55000 * b[4] + white * 0.5329522;
+        b[5] = -0.7616 * b[5] - white * 0.0168980;
+
+        let pink = b[0] + b[1] + b[2] + b[3] + b[4] + b[5] + b[6] + white * 0.5362;
+        b[6] = white * 0.115926;
+
+        PINK_GAIN * pink
+    }
+
+    /// A leaky integrator, so that the output doesn't drift away from zero
+    fn brown(&mut self, white: f64) -> f64 {
+        self.brown_state = (self.brown_state + BROWN_LEAK * white) / (1.0 + BROWN_LEAK);
+        BROWN_GAIN * self.brown_state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME_COUNT: usize = 48_000;
+
+    fn render(colour: NoiseColour, seed: u64) -> Vec<f32> {
+        let mut generator = NoiseGenerator::new(colour, seed);
+        let mut output = vec![0.0; FRAME_COUNT];
+        generator.fill(&mut output);
+        output
+    }
+
+    fn power(values: impl Iterator<Item = f32>) -> f64 {
+        let (sum, count) = values.fold((0.0, 0), |(sum, count), value| {
+            (sum + (value as f64).powi(2), count + 1)
+        });
+
+        sum / count as f64
+    }
+
+    /// The power of the difference between neighbouring samples, relative
+    /// to the power of the signal
+    ///
+    /// This is 2 for white noise, and rises with the amount of high frequency
+    /// content
+    fn high_frequency_ratio(values: &[f32]) -> f64 {
+        let difference = values.windows(2).map(|pair| pair[1] - pair[0]);
+        power(difference) / power(values.iter().copied())
+    }
+
+    #[test]
+    fn is_reproducible_with_a_seed() {
+        for colour in [
+            NoiseColour::White,
+            NoiseColour::Pink,
+            NoiseColour::Brown,
+            NoiseColour::Blue,
+        ] {
+            assert_eq!(render(colour, 1234), render(colour, 1234));
+            assert_ne!(render(colour, 1234), render(colour, 5678));
+        }
+    }
+
+    #[test]
+    fn seed_gives_the_same_noise_in_every_build() {
+        assert_eq!(
+            render(NoiseColour::White, 1234)[..4],
+            [-0.22725375, 0.9926512, 0.19376197, -0.36731943]
+        );
+    }
+
+    #[test]
+    fn stays_in_range() {
+        for colour in [
+            NoiseColour::White,
+            NoiseColour::Pink,
+            NoiseColour::Brown,
+            NoiseColour::Blue,
+        ] {
+            let output = render(colour, 1);
+
+            assert!(output.iter().all(|value| value.abs() <= 1.5));
+            assert!(power(output.iter().copied()) > 0.01);
+        }
+    }
+
+    #[test]
+    fn colours_have_different_spectra() {
+        let white = high_frequency_ratio(&render(NoiseColour::White, 1));
+        let pink = high_frequency_ratio(&render(NoiseColour::Pink, 1));
+        let brown = high_frequency_ratio(&render(NoiseColour::Brown, 1));
+        let blue = high_frequency_ratio(&render(NoiseColour::Blue, 1));
+
+        assert!((white - 2.0).abs() < 0.1);
+        assert!(brown < pink);
+        assert!(pink < white);
+        assert!(white < blue);
+    }
+}
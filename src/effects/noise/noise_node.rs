@@ -0,0 +1,81 @@
+use super::{noise_processor::NoiseProcessor, NoiseColour, NoiseOptions};
+use crate::{
+    commands::Id,
+    graph::DspNode,
+    parameter::{ParameterRange, Parameters},
+    prelude::*,
+    utility::create_parameters,
+};
+use rand::Rng;
+
+/// A node that generates noise
+///
+/// Noise nodes don't have inputs, they only produce output. Use
+/// [NoiseOptions] to seed the noise for reproducible renders, or to play the
+/// same noise on every channel.
+///
+/// # Parameters
+/// - gain
+pub struct Noise {
+    /// The node to connect to the audio graph
+    pub node: GraphNode,
+
+    params: Parameters,
+}
+
+const MIN_GAIN: f64 = f64::NEG_INFINITY;
+const MAX_GAIN: f64 = f64::INFINITY;
+const DEFAULT_GAIN: f64 = 1.0;
+
+impl DspNode for Noise {
+    fn get_parameters_mut(&mut self) -> &mut Parameters {
+        &mut self.params
+    }
+}
+
+impl Noise {
+    /// Create a new noise node, with an independent stream on each channel
+    pub fn new(context: &dyn Context, channel_count: usize, colour: NoiseColour) -> Self {
+        Self::new_with_options(context, channel_count, colour, NoiseOptions::default())
+    }
+
+    /// Create a new noise node with options
+    pub fn new_with_options(
+        context: &dyn Context,
+        channel_count: usize,
+        colour: NoiseColour,
+        options: NoiseOptions,
+    ) -> Self {
+        debug_assert!(channel_count > 0);
+
+        let id = Id::generate();
+
+        let (params, realtime_params) = create_parameters(
+            id,
+            context,
+            [(
+                "gain",
+                ParameterRange::new(DEFAULT_GAIN, MIN_GAIN, MAX_GAIN),
+            )],
+        );
+
+        let seed = options.seed.unwrap_or_else(|| rand::rng().random());
+        let stream_count = if options.correlated_channels {
+            1
+        } else {
+            channel_count
+        };
+
+        let processor = Box::new(NoiseProcessor::new(colour, stream_count, seed));
+
+        Self {
+            node: GraphNode::new(id, context, 0, channel_count, processor, realtime_params),
+            params,
+        }
+    }
+
+    /// Get the gain parameter
+    pub fn gain(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("gain")
+    }
+}
@@ -0,0 +1,25 @@
+/// Options to control how a [crate::Noise] node generates its output
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoiseOptions {
+    pub(super) seed: Option<u64>,
+    pub(super) correlated_channels: bool,
+}
+
+impl NoiseOptions {
+    /// Seed the random number generator, so that the node produces the same
+    /// output each time it is rendered, on any platform
+    ///
+    /// Without a seed, a random one is chosen when the node is created
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Play the same noise on every channel
+    ///
+    /// By default, each channel gets its own independent stream
+    pub fn with_correlated_channels(mut self) -> Self {
+        self.correlated_channels = true;
+        self
+    }
+}
@@ -0,0 +1,132 @@
+use super::{noise_generator::NoiseGenerator, NoiseColour};
+use crate::{graph::DspProcessor, prelude::*, ProcessContext};
+
+pub struct NoiseProcessor {
+    generators: Vec<NoiseGenerator>,
+}
+
+impl NoiseProcessor {
+    /// Create a processor with one generator for each stream
+    ///
+    /// Each stream is seeded differently, so that they are independent
+    pub fn new(colour: NoiseColour, stream_count: usize, seed: u64) -> Self {
+        Self {
+            generators: (0..stream_count)
+                .map(|stream| NoiseGenerator::new(colour, seed.wrapping_add(stream as u64)))
+                .collect(),
+        }
+    }
+}
+
+impl DspProcessor for NoiseProcessor {
+    fn process_audio(&mut self, context: &mut ProcessContext) {
+        let frame_count = context.output_buffer.frame_count();
+        let channel_count = context.output_buffer.channel_count();
+
+        let gain = context.parameters.get_parameter_values("gain", frame_count);
+
+        for (channel, generator) in self.generators.iter_mut().enumerate().take(channel_count) {
+            let location = SampleLocation::channel(channel);
+            generator.fill(context.output_buffer.get_channel_data_mut(location));
+        }
+
+        (self.generators.len()..channel_count).for_each(|channel| {
+            context.output_buffer.duplicate_channel(
+                SampleLocation::channel(0),
+                channel,
+                frame_count,
+            );
+        });
+
+        context.output_buffer.apply_gain(gain);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{graph::DspParameters, parameter::RealtimeAudioParameter};
+    use atomic_float::AtomicF64;
+    use std::sync::Arc;
+
+    const SAMPLE_RATE: usize = 48_000;
+    const CHANNEL_COUNT: usize = 2;
+
+    fn render(processor: &mut NoiseProcessor, block_sizes: &[usize]) -> OwnedAudioBuffer {
+        let frame_count = block_sizes.iter().sum();
+        let mut output = OwnedAudioBuffer::new(frame_count, CHANNEL_COUNT, SAMPLE_RATE);
+        let input = OwnedAudioBuffer::new(frame_count, 0, SAMPLE_RATE);
+
+        let mut parameters = DspParameters::new([RealtimeAudioParameter::new(
+            "gain",
+            Arc::new(AtomicF64::new(0.5)),
+            frame_count,
+        )]);
+
+        let mut offset = 0;
+
+        for block_size in block_sizes {
+            let start_time = Timestamp::from_samples(offset as f64, SAMPLE_RATE);
+
+            parameters.iter_mut().for_each(|(_, parameter)| {
+                parameter.process(&start_time, *block_size, SAMPLE_RATE);
+            });
+
+            let input = BorrowedAudioBuffer::slice_frames(&input, offset, *block_size);
+            let mut output =
+                MutableBorrowedAudioBuffer::slice_frames(&mut output, offset, *block_size);
+
+            processor.process_audio(&mut ProcessContext {
+                input_buffer: &input,
+                output_buffer: &mut output,
+                start_time: &start_time,
+                parameters: &parameters,
+                midi_events: &[],
+            });
+
+            offset += block_size;
+        }
+
+        output
+    }
+
+    fn channel(buffer: &OwnedAudioBuffer, channel: usize) -> &[f32] {
+        buffer.get_channel_data(SampleLocation::channel(channel))
+    }
+
+    #[test]
+    fn renders_the_same_noise_regardless_of_block_size() {
+        let mut processor = NoiseProcessor::new(NoiseColour::Pink, CHANNEL_COUNT, 42);
+        let first = render(&mut processor, &[1_024]);
+
+        let mut processor = NoiseProcessor::new(NoiseColour::Pink, CHANNEL_COUNT, 42);
+        let second = render(&mut processor, &[100, 512, 412]);
+
+        assert_eq!(channel(&first, 0), channel(&second, 0));
+        assert_eq!(channel(&first, 1), channel(&second, 1));
+    }
+
+    #[test]
+    fn channels_are_independent() {
+        let mut processor = NoiseProcessor::new(NoiseColour::White, CHANNEL_COUNT, 42);
+        let output = render(&mut processor, &[1_024]);
+
+        assert_ne!(channel(&output, 0), channel(&output, 1));
+    }
+
+    #[test]
+    fn correlated_channels_are_identical() {
+        let mut processor = NoiseProcessor::new(NoiseColour::White, 1, 42);
+        let output = render(&mut processor, &[1_024]);
+
+        assert_eq!(channel(&output, 0), channel(&output, 1));
+    }
+
+    #[test]
+    fn applies_gain() {
+        let mut processor = NoiseProcessor::new(NoiseColour::White, CHANNEL_COUNT, 42);
+        let output = render(&mut processor, &[1_024]);
+
+        assert!(channel(&output, 0).iter().all(|value| value.abs() <= 0.5));
+    }
+}
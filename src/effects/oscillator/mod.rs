@@ -1,4 +1,7 @@
+mod oscillator_event;
 mod oscillator_node;
 mod oscillator_processor;
+mod oscillator_waveform;
 
 pub use oscillator_node::Oscillator;
+pub use oscillator_waveform::OscillatorWaveform;
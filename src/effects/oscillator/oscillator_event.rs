@@ -0,0 +1,48 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{effects::utility::EventProcessorEvent, prelude::*};
+
+#[derive(Debug, PartialEq)]
+pub enum OscillatorEventType {
+    ResetPhase,
+}
+
+fn next_sequence_number() -> usize {
+    static SEQUENCE_NUMBER: AtomicUsize = AtomicUsize::new(0);
+    SEQUENCE_NUMBER.fetch_add(1, Ordering::Relaxed)
+}
+
+#[derive(Debug)]
+pub struct OscillatorEvent {
+    sequence_number: usize,
+    time: Timestamp,
+    event_type: OscillatorEventType,
+}
+
+impl OscillatorEvent {
+    pub fn new(time: Timestamp, event_type: OscillatorEventType) -> Self {
+        Self {
+            sequence_number: next_sequence_number(),
+            time,
+            event_type,
+        }
+    }
+
+    pub fn get_event_type(&self) -> &OscillatorEventType {
+        &self.event_type
+    }
+}
+
+impl EventProcessorEvent for OscillatorEvent {
+    fn get_time(&self) -> Timestamp {
+        self.time
+    }
+
+    fn should_clear_queue(&self) -> bool {
+        false
+    }
+
+    fn sequence_number(&self) -> usize {
+        self.sequence_number
+    }
+}
@@ -1,6 +1,11 @@
-use super::oscillator_processor::OscillatorProcessor;
+use super::{
+    oscillator_event::{OscillatorEvent, OscillatorEventType},
+    oscillator_processor::{EventTransmitter, OscillatorProcessor},
+    oscillator_waveform::{OscillatorSource, OscillatorWaveform},
+};
 use crate::{
     commands::Id,
+    effects::Channel,
     graph::DspNode,
     parameter::{ParameterRange, Parameters},
     prelude::*,
@@ -12,15 +17,22 @@ use itertools::izip;
 ///
 /// Oscillator nodes don't have inputs, they only produce output
 ///
+/// The oscillator can be hard-synced to an internal master oscillator by
+/// setting `sync-frequency` above zero. The phase is reset to the start of the
+/// cycle each time the master completes a cycle.
+///
 /// # Parameters
 ///
 /// - frequency
 /// - gain
+/// - pulse-width (only used by [OscillatorWaveform::Pulse])
+/// - sync-frequency (0 disables hard sync)
 pub struct Oscillator {
     /// The node to connect to the audio graph
     pub node: GraphNode,
 
     params: Parameters,
+    event_transmitter: EventTransmitter,
 }
 
 const MIN_GAIN: f64 = f64::NEG_INFINITY;
@@ -28,6 +40,11 @@ const MAX_GAIN: f64 = f64::INFINITY;
 const MIN_FREQUENCY: f64 = 20.0;
 const MAX_FREQUENCY: f64 = 20000.0;
 const DEFAULT_GAIN: f64 = 1.0;
+const MIN_PULSE_WIDTH: f64 = 0.01;
+const MAX_PULSE_WIDTH: f64 = 0.99;
+const DEFAULT_PULSE_WIDTH: f64 = 0.5;
+const MAX_SYNC_FREQUENCY: f64 = 20000.0;
+static EVENT_CHANNEL_CAPACITY: usize = 16;
 
 fn make_sine_wavetable(length: usize, harmonic: usize) -> Vec<f64> {
     let mut values = Vec::with_capacity(length);
@@ -55,6 +72,51 @@ impl Oscillator {
         Self::new(context, frequency, output_count, sine_wavetable)
     }
 
+    /// Create a band-limited oscillator with one of the standard waveforms
+    ///
+    /// Unlike a wavetable, the waveform is generated for the current frequency,
+    /// so it doesn't alias as the frequency rises
+    pub fn with_waveform(
+        context: &dyn Context,
+        frequency: f64,
+        output_count: usize,
+        waveform: OscillatorWaveform,
+    ) -> Self {
+        Self::create(
+            context,
+            frequency,
+            output_count,
+            OscillatorSource::Waveform(waveform),
+        )
+    }
+
+    /// Create a band-limited sawtooth oscillator at the given frequency
+    pub fn saw(context: &dyn Context, frequency: f64, output_count: usize) -> Self {
+        Self::with_waveform(context, frequency, output_count, OscillatorWaveform::Saw)
+    }
+
+    /// Create a band-limited square wave oscillator at the given frequency
+    pub fn square(context: &dyn Context, frequency: f64, output_count: usize) -> Self {
+        Self::with_waveform(context, frequency, output_count, OscillatorWaveform::Square)
+    }
+
+    /// Create a band-limited triangle wave oscillator at the given frequency
+    pub fn triangle(context: &dyn Context, frequency: f64, output_count: usize) -> Self {
+        Self::with_waveform(
+            context,
+            frequency,
+            output_count,
+            OscillatorWaveform::Triangle,
+        )
+    }
+
+    /// Create a band-limited pulse wave oscillator at the given frequency
+    ///
+    /// The width of the pulse is controlled with the `pulse-width` parameter
+    pub fn pulse(context: &dyn Context, frequency: f64, output_count: usize) -> Self {
+        Self::with_waveform(context, frequency, output_count, OscillatorWaveform::Pulse)
+    }
+
     /// Create an oscillator that is made up of various sine waves
     ///
     /// Each entry in the `harmonics` array represents a harmonic in the output
@@ -96,9 +158,24 @@ impl Oscillator {
         output_count: usize,
         wavetable: Vec<f64>,
     ) -> Self {
-        debug_assert!(output_count > 0);
         debug_assert!(wavetable.len() > 2);
 
+        Self::create(
+            context,
+            frequency,
+            output_count,
+            OscillatorSource::Wavetable(wavetable),
+        )
+    }
+
+    fn create(
+        context: &dyn Context,
+        frequency: f64,
+        output_count: usize,
+        source: OscillatorSource,
+    ) -> Self {
+        debug_assert!(output_count > 0);
+
         let id = Id::generate();
 
         let (params, realtime_params) = create_parameters(
@@ -113,12 +190,26 @@ impl Oscillator {
                     "gain",
                     ParameterRange::new(DEFAULT_GAIN, MIN_GAIN, MAX_GAIN),
                 ),
+                (
+                    "pulse-width",
+                    ParameterRange::new(DEFAULT_PULSE_WIDTH, MIN_PULSE_WIDTH, MAX_PULSE_WIDTH),
+                ),
+                (
+                    "sync-frequency",
+                    ParameterRange::new(0.0, 0.0, MAX_SYNC_FREQUENCY),
+                ),
             ],
         );
 
         let input_count = 0;
 
-        let processor = Box::new(OscillatorProcessor::new(wavetable));
+        let (event_transmitter, event_receiver) = Channel::bounded(EVENT_CHANNEL_CAPACITY);
+
+        let processor = Box::new(OscillatorProcessor::new(
+            source,
+            context.get_sample_rate(),
+            event_receiver,
+        ));
 
         Self {
             node: GraphNode::new(
@@ -130,6 +221,7 @@ impl Oscillator {
                 realtime_params,
             ),
             params,
+            event_transmitter,
         }
     }
 
@@ -142,4 +234,30 @@ impl Oscillator {
     pub fn gain(&mut self) -> &mut AudioParameter {
         self.get_parameter_mut("gain")
     }
+
+    /// Get the pulse width parameter
+    ///
+    /// This is the fraction of each cycle that a pulse wave is high for
+    pub fn pulse_width(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("pulse-width")
+    }
+
+    /// Get the sync frequency parameter
+    pub fn sync_frequency(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("sync-frequency")
+    }
+
+    /// Restart the waveform from the beginning of its cycle at a particular
+    /// time
+    pub fn reset_phase_at_time(&mut self, time: Timestamp) {
+        debug_assert!(!self.event_transmitter.is_full());
+        let _ = self
+            .event_transmitter
+            .send(OscillatorEvent::new(time, OscillatorEventType::ResetPhase));
+    }
+
+    /// Restart the waveform from the beginning of its cycle now
+    pub fn reset_phase(&mut self) {
+        self.reset_phase_at_time(Timestamp::zero());
+    }
 }
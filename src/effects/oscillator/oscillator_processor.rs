@@ -1,73 +1,182 @@
 use itertools::izip;
 
-use crate::{graph::DspProcessor, prelude::*};
+use crate::{
+    effects::{utility::*, Channel},
+    graph::DspProcessor,
+    prelude::*,
+};
+
+use super::{
+    oscillator_event::{OscillatorEvent, OscillatorEventType},
+    oscillator_waveform::OscillatorSource,
+};
+
+pub type EventReceiver = Channel::Receiver<OscillatorEvent>;
+pub type EventTransmitter = Channel::Sender<OscillatorEvent>;
 
 pub struct OscillatorProcessor {
+    source: OscillatorSource,
     phase: f64,
-    wavetable: Vec<f64>,
+    sync_phase: f64,
+    pending_sync: Option<SyncStep>,
+    event_processor: EventProcessor<OscillatorEvent>,
+    sample_rate: usize,
+}
+
+/// A step caused by a hard-sync reset between the previous sample and the
+/// next one
+struct SyncStep {
+    /// The size of the step in the naive waveform
+    size: f64,
+
+    /// The time from the previous sample to the reset, as a fraction of a
+    /// sample
+    delay: f64,
 }
 
+const MAX_PENDING_EVENTS: usize = 16;
+const MAXIMUM_PHASE_INCREMENT: f64 = 0.5;
+
 impl OscillatorProcessor {
-    pub fn new(wavetable: Vec<f64>) -> Self {
+    pub fn new(
+        source: OscillatorSource,
+        sample_rate: usize,
+        event_receiver: EventReceiver,
+    ) -> Self {
         Self {
+            source,
             phase: 0.0,
-            wavetable,
+            sync_phase: 0.0,
+            pending_sync: None,
+            event_processor: EventProcessor::with_capacity(
+                MAX_PENDING_EVENTS,
+                event_receiver,
+                sample_rate,
+            ),
+            sample_rate,
         }
     }
 
-    fn increment_phase(&mut self, frequency: f32, sample_rate: usize) {
-        self.phase += (frequency as f64) / (sample_rate as f64);
-        while self.phase >= 1.0 {
-            self.phase -= 1.0;
+    fn process_event(&mut self, event: &OscillatorEvent) {
+        match event.get_event_type() {
+            OscillatorEventType::ResetPhase => {
+                self.phase = 0.0;
+                self.sync_phase = 0.0;
+                self.pending_sync = None;
+            }
         }
+    }
 
-        debug_assert!(0.0 <= self.phase && self.phase < 1.0);
+    fn render(
+        &mut self,
+        output: &mut [f32],
+        frequency: &[f32],
+        pulse_width: &[f32],
+        sync_frequency: &[f32],
+    ) {
+        for (sample, frequency, pulse_width, sync_frequency) in
+            izip!(output, frequency, pulse_width, sync_frequency)
+        {
+            *sample = self.next_value(
+                *frequency as f64,
+                *pulse_width as f64,
+                *sync_frequency as f64,
+            ) as f32;
+        }
     }
 
-    fn get_value(&self) -> f64 {
-        let offset = self.phase * self.wavetable.len() as f64;
+    fn next_value(&mut self, frequency: f64, pulse_width: f64, sync_frequency: f64) -> f64 {
+        let sample_rate = self.sample_rate as f64;
+        let increment = (frequency / sample_rate).min(MAXIMUM_PHASE_INCREMENT);
+
+        let mut value = self.source.value(self.phase, increment, pulse_width);
+
+        // The source has already smoothed the start of its cycle, so only
+        // the difference from that step needs correcting after a reset
+        if let Some(step) = self.pending_sync.take() {
+            let size = step.size - self.source.cycle_step();
+            value -= size * step.delay * step.delay / 2.0;
+        }
+
+        let sync_increment = (sync_frequency / sample_rate).min(MAXIMUM_PHASE_INCREMENT);
+
+        if sync_increment > 0.0 && self.sync_phase + sync_increment >= 1.0 {
+            let delay = (1.0 - self.sync_phase) / sync_increment;
+            let phase_at_reset = (self.phase + increment * delay).fract();
 
-        let offset_before = offset.floor() as usize;
-        let offset_after = offset.ceil() as usize;
+            let size = self.source.naive_value(0.0, pulse_width)
+                - self.source.naive_value(phase_at_reset, pulse_width);
 
-        debug_assert!(offset_before < self.wavetable.len());
-        debug_assert!(offset_after <= self.wavetable.len());
+            let remaining = 1.0 - delay;
+            value += size * remaining * remaining / 2.0;
 
-        let value_before = self.wavetable[offset_before];
-        let value_after = if offset_after < self.wavetable.len() {
-            self.wavetable[offset_after]
+            self.pending_sync = Some(SyncStep { size, delay });
+            self.sync_phase = self.sync_phase + sync_increment - 1.0;
+            self.phase = increment * remaining;
         } else {
-            self.wavetable[0]
-        };
+            self.sync_phase += sync_increment;
+            self.increment_phase(increment);
+        }
 
-        let weighting = offset - offset.floor();
-        interpolate(value_before, value_after, weighting)
+        value
     }
-}
 
-fn interpolate(a: f64, b: f64, amount_of_b: f64) -> f64 {
-    (1.0 - amount_of_b) * a + amount_of_b * b
+    fn increment_phase(&mut self, increment: f64) {
+        self.phase += increment;
+        while self.phase >= 1.0 {
+            self.phase -= 1.0;
+        }
+
+        debug_assert!(0.0 <= self.phase && self.phase < 1.0);
+    }
 }
 
 impl DspProcessor for OscillatorProcessor {
     fn process_audio(&mut self, context: &mut crate::ProcessContext) {
-        let sample_rate = context.output_buffer.sample_rate();
+        debug_assert_eq!(self.sample_rate, context.output_buffer.sample_rate());
+
+        self.event_processor.receive_events();
+
+        let frame_count = context.output_buffer.frame_count();
 
         let frequency_values = context
             .parameters
-            .get_parameter_values("frequency", context.output_buffer.frame_count());
-        let gain_values = context
+            .get_parameter_values("frequency", frame_count);
+        let gain_values = context.parameters.get_parameter_values("gain", frame_count);
+        let pulse_width_values = context
+            .parameters
+            .get_parameter_values("pulse-width", frame_count);
+        let sync_frequency_values = context
             .parameters
-            .get_parameter_values("gain", context.output_buffer.frame_count());
+            .get_parameter_values("sync-frequency", frame_count);
 
         let channel_count = context.output_buffer.channel_count();
 
         let location = SampleLocation::channel(0);
         let channel_data = context.output_buffer.get_channel_data_mut(location);
 
-        for (sample, frequency) in izip!(channel_data.iter_mut(), frequency_values.iter()) {
-            *sample = self.get_value() as f32;
-            self.increment_phase(*frequency, sample_rate);
+        let mut current_time = *context.start_time;
+        let mut position = 0;
+
+        while position < frame_count {
+            let (end_frame, event) =
+                self.event_processor
+                    .next_event(context.start_time, &current_time, frame_count);
+
+            self.render(
+                &mut channel_data[position..end_frame],
+                &frequency_values[position..end_frame],
+                &pulse_width_values[position..end_frame],
+                &sync_frequency_values[position..end_frame],
+            );
+
+            current_time =
+                current_time.incremented_by_samples(end_frame - position, self.sample_rate);
+            position = end_frame;
+
+            if let Some(event) = event {
+                self.process_event(&event);
+            }
         }
 
         context.output_buffer.apply_gain(gain_values);
@@ -96,6 +205,7 @@ mod tests {
     use crate::{graph::DspParameters, parameter::RealtimeAudioParameter, ProcessContext};
 
     use super::*;
+    use crate::effects::oscillator::oscillator_waveform::OscillatorWaveform;
 
     fn process(
         processor: &mut impl DspProcessor,
@@ -137,6 +247,7 @@ mod tests {
                 output_buffer: &mut output_buffer,
                 start_time: &start_time,
                 parameters,
+                midi_events: &[],
             };
 
             processor.process_audio(&mut context);
@@ -149,21 +260,47 @@ mod tests {
             .to_vec()
     }
 
+    fn create_parameters(
+        frequency: f64,
+        pulse_width: f64,
+        sync_frequency: f64,
+        frame_count: usize,
+    ) -> DspParameters {
+        DspParameters::new(
+            [
+                ("frequency", frequency),
+                ("gain", 1.0),
+                ("pulse-width", pulse_width),
+                ("sync-frequency", sync_frequency),
+            ]
+            .map(|(id, value)| {
+                RealtimeAudioParameter::new(id, Arc::new(AtomicF64::new(value)), frame_count)
+            }),
+        )
+    }
+
+    fn create_processor(
+        source: OscillatorSource,
+        sample_rate: usize,
+    ) -> (OscillatorProcessor, EventTransmitter) {
+        let (event_transmitter, event_receiver) = crossbeam::channel::unbounded();
+        let processor = OscillatorProcessor::new(source, sample_rate, event_receiver);
+        (processor, event_transmitter)
+    }
+
     #[test]
     fn test_oscillator() {
         let wavetable = vec![0.0, 0.25, 0.5, 0.75, 1.0, 0.75, 0.5, 0.25];
-        let mut processor = OscillatorProcessor::new(wavetable);
 
         let frame_count = 1024;
         let sample_rate = 48_000;
 
-        let frequency = Arc::new(AtomicF64::new(100.0));
-        let frequency_param =
-            RealtimeAudioParameter::new("frequency", frequency.clone(), frame_count);
-        let gain = Arc::new(AtomicF64::new(1.0));
-        let gain_param = RealtimeAudioParameter::new("gain", gain, frame_count);
+        let (mut processor, _event_transmitter) =
+            create_processor(OscillatorSource::Wavetable(wavetable), sample_rate);
 
-        let mut parameters = DspParameters::new([frequency_param, gain_param]);
+        let frequency = Arc::new(AtomicF64::new(100.0));
+        let mut parameters =
+            create_parameters(frequency.load(Ordering::Relaxed), 0.5, 0.0, frame_count);
 
         let duration = Duration::from_secs(1);
         let actual = process(
@@ -199,4 +336,69 @@ mod tests {
             assert_relative_eq!(actual, expected, epsilon = 1e-6);
         }
     }
+
+    #[test]
+    fn hard_sync_follows_the_sync_frequency() {
+        let frame_count = 512;
+        let sample_rate = 48_000;
+
+        let (mut processor, _event_transmitter) = create_processor(
+            OscillatorSource::Waveform(OscillatorWaveform::Saw),
+            sample_rate,
+        );
+
+        let sync_frequency = 375.0;
+        let mut parameters = create_parameters(1_234.0, 0.5, sync_frequency, frame_count);
+
+        let output = process(
+            &mut processor,
+            Duration::from_millis(100),
+            sample_rate,
+            frame_count,
+            &mut parameters,
+        );
+
+        let sync_period = (sample_rate as f64 / sync_frequency) as usize;
+
+        let first_cycle = &output[sync_period..];
+        let later_cycle = &output[2 * sync_period..];
+
+        for (first, second) in first_cycle.iter().zip(later_cycle.iter()) {
+            assert_relative_eq!(first, second, epsilon = 1e-3);
+        }
+    }
+
+    #[test]
+    fn resets_the_phase() {
+        let frame_count = 512;
+        let sample_rate = 48_000;
+
+        let (mut processor, event_transmitter) = create_processor(
+            OscillatorSource::Waveform(OscillatorWaveform::Triangle),
+            sample_rate,
+        );
+
+        let mut parameters = create_parameters(333.0, 0.5, 0.0, frame_count);
+
+        let reset_frame = 1_000;
+        let _ = event_transmitter.send(OscillatorEvent::new(
+            Timestamp::from_samples(reset_frame as f64, sample_rate),
+            OscillatorEventType::ResetPhase,
+        ));
+
+        let output = process(
+            &mut processor,
+            Duration::from_millis(50),
+            sample_rate,
+            frame_count,
+            &mut parameters,
+        );
+
+        let from_start = &output[..reset_frame];
+        let from_reset = &output[reset_frame..];
+
+        for (from_start, from_reset) in from_start.iter().zip(from_reset.iter()) {
+            assert_relative_eq!(from_start, from_reset, epsilon = 1e-6);
+        }
+    }
 }
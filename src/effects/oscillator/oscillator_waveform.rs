@@ -0,0 +1,233 @@
+use crate::dsp::{poly_blamp, poly_blep};
+use std::f64::consts::TAU;
+
+/// A band-limited oscillator waveform
+///
+/// The waveforms are generated with PolyBLEP corrections at each
+/// discontinuity, so they stay free of most aliasing as the frequency changes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OscillatorWaveform {
+    /// A pure tone
+    Sine,
+
+    /// A rising sawtooth
+    Saw,
+
+    /// A square wave
+    Square,
+
+    /// A triangle wave
+    Triangle,
+
+    /// A pulse wave, with the width set by the oscillator's `pulse-width`
+    /// parameter, or [crate::SynthOscillator::with_pulse_width] in a synth
+    Pulse,
+}
+
+/// Where an oscillator gets its values from
+pub enum OscillatorSource {
+    Wavetable(Vec<f64>),
+    Waveform(OscillatorWaveform),
+}
+
+impl OscillatorWaveform {
+    fn naive_value(&self, phase: f64, pulse_width: f64) -> f64 {
+        match self {
+            OscillatorWaveform::Sine => (TAU * phase).sin(),
+            OscillatorWaveform::Saw => 2.0 * phase - 1.0,
+            OscillatorWaveform::Square => square(phase, 0.5),
+            OscillatorWaveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+            OscillatorWaveform::Pulse => square(phase, pulse_width),
+        }
+    }
+
+    pub(crate) fn band_limited_value(&self, phase: f64, increment: f64, pulse_width: f64) -> f64 {
+        let value = self.naive_value(phase, pulse_width);
+
+        match self {
+            OscillatorWaveform::Sine => value,
+            OscillatorWaveform::Saw => value - poly_blep(phase, increment),
+            OscillatorWaveform::Square => value + pulse_correction(phase, increment, 0.5),
+            OscillatorWaveform::Triangle => {
+                let slope_change = 8.0 * increment;
+                value
+                    + slope_change
+                        * (poly_blamp(phase, increment)
+                            - poly_blamp((phase + 0.5).fract(), increment))
+            }
+            OscillatorWaveform::Pulse => value + pulse_correction(phase, increment, pulse_width),
+        }
+    }
+
+    /// The size of the step in the waveform at the start of each cycle
+    fn cycle_step(&self) -> f64 {
+        match self {
+            OscillatorWaveform::Sine | OscillatorWaveform::Triangle => 0.0,
+            OscillatorWaveform::Saw => -2.0,
+            OscillatorWaveform::Square | OscillatorWaveform::Pulse => 2.0,
+        }
+    }
+}
+
+fn square(phase: f64, pulse_width: f64) -> f64 {
+    if phase < pulse_width {
+        1.0
+    } else {
+        -1.0
+    }
+}
+
+fn pulse_correction(phase: f64, increment: f64, pulse_width: f64) -> f64 {
+    poly_blep(phase, increment) - poly_blep((phase - pulse_width).rem_euclid(1.0), increment)
+}
+
+impl OscillatorSource {
+    /// The value at a phase, smoothed around any discontinuities
+    pub fn value(&self, phase: f64, increment: f64, pulse_width: f64) -> f64 {
+        match self {
+            OscillatorSource::Wavetable(wavetable) => wavetable_value(wavetable, phase),
+            OscillatorSource::Waveform(waveform) => {
+                waveform.band_limited_value(phase, increment, pulse_width)
+            }
+        }
+    }
+
+    /// The value at a phase, without any band-limiting
+    pub fn naive_value(&self, phase: f64, pulse_width: f64) -> f64 {
+        match self {
+            OscillatorSource::Wavetable(wavetable) => wavetable_value(wavetable, phase),
+            OscillatorSource::Waveform(waveform) => waveform.naive_value(phase, pulse_width),
+        }
+    }
+
+    /// The step at the start of each cycle that [OscillatorSource::value]
+    /// already corrects for
+    pub fn cycle_step(&self) -> f64 {
+        match self {
+            OscillatorSource::Wavetable(_) => 0.0,
+            OscillatorSource::Waveform(waveform) => waveform.cycle_step(),
+        }
+    }
+}
+
+fn wavetable_value(wavetable: &[f64], phase: f64) -> f64 {
+    let offset = phase * wavetable.len() as f64;
+
+    let offset_before = offset.floor() as usize;
+    let offset_after = offset.ceil() as usize;
+
+    debug_assert!(offset_before < wavetable.len());
+    debug_assert!(offset_after <= wavetable.len());
+
+    let value_before = wavetable[offset_before];
+    let value_after = if offset_after < wavetable.len() {
+        wavetable[offset_after]
+    } else {
+        wavetable[0]
+    };
+
+    let weighting = offset - offset.floor();
+    interpolate(value_before, value_after, weighting)
+}
+
+fn interpolate(a: f64, b: f64, amount_of_b: f64) -> f64 {
+    (1.0 - amount_of_b) * a + amount_of_b * b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn render(waveform: OscillatorWaveform, increment: f64, pulse_width: f64) -> Vec<f64> {
+        let cycle_length = (1.0 / increment).round() as usize;
+
+        (0..cycle_length)
+            .map(|frame| {
+                let phase = frame as f64 * increment;
+                waveform.band_limited_value(phase, increment, pulse_width)
+            })
+            .collect()
+    }
+
+    fn largest_step(values: &[f64]) -> f64 {
+        values
+            .windows(2)
+            .map(|pair| (pair[1] - pair[0]).abs())
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn matches_the_naive_waveforms_away_from_edges() {
+        let increment = 0.01;
+
+        assert_relative_eq!(
+            OscillatorWaveform::Saw.band_limited_value(0.25, increment, 0.5),
+            -0.5
+        );
+        assert_relative_eq!(
+            OscillatorWaveform::Square.band_limited_value(0.25, increment, 0.5),
+            1.0
+        );
+        assert_relative_eq!(
+            OscillatorWaveform::Triangle.band_limited_value(0.75, increment, 0.5),
+            0.0
+        );
+        assert_relative_eq!(
+            OscillatorWaveform::Pulse.band_limited_value(0.25, increment, 0.2),
+            -1.0
+        );
+    }
+
+    #[test]
+    fn smooths_the_steps() {
+        let increment = 0.05;
+
+        for waveform in [
+            OscillatorWaveform::Saw,
+            OscillatorWaveform::Square,
+            OscillatorWaveform::Pulse,
+        ] {
+            let values = render(waveform, increment, 0.3);
+            assert!(largest_step(&values) < 1.5);
+        }
+    }
+
+    #[test]
+    fn smooths_the_triangle_corners() {
+        let increment = 0.05;
+        let values = render(OscillatorWaveform::Triangle, increment, 0.5);
+
+        assert!(values[0] > -1.0);
+        assert!(values[10] < 1.0);
+        assert_relative_eq!(values[5], 0.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn pulse_width_sets_the_duty_cycle() {
+        let increment = 0.001;
+        let values = render(OscillatorWaveform::Pulse, increment, 0.25);
+
+        let high_count = values.iter().filter(|value| **value > 0.0).count();
+        assert_relative_eq!(
+            high_count as f64 / values.len() as f64,
+            0.25,
+            epsilon = 0.01
+        );
+    }
+
+    #[test]
+    fn keeps_the_average_of_symmetric_waveforms_at_zero() {
+        let increment = 1.0 / 64.0;
+
+        for waveform in [
+            OscillatorWaveform::Saw,
+            OscillatorWaveform::Square,
+            OscillatorWaveform::Triangle,
+        ] {
+            let values = render(waveform, increment, 0.5);
+            let average = values.iter().sum::<f64>() / values.len() as f64;
+            assert_relative_eq!(average, 0.0, epsilon = 1e-9);
+        }
+    }
+}
@@ -0,0 +1,72 @@
+/// The shape of a band in a [crate::ParametricEq]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EqBandType {
+    /// Boost or cut a band around the frequency, with a width set by the Q
+    Bell,
+
+    /// Boost or cut the frequencies below the frequency
+    LowShelf,
+
+    /// Boost or cut the frequencies above the frequency
+    HighShelf,
+
+    /// Remove the frequencies above the frequency
+    ///
+    /// This is a Butterworth filter, and each order adds 6dB per octave to
+    /// the slope
+    LowPass {
+        /// The order of the filter, which is at least 1
+        order: usize,
+    },
+
+    /// Remove the frequencies below the frequency
+    ///
+    /// This is a Butterworth filter, and each order adds 6dB per octave to
+    /// the slope
+    HighPass {
+        /// The order of the filter, which is at least 1
+        order: usize,
+    },
+
+    /// Remove a narrow band around the frequency, with a width set by the Q
+    Notch,
+}
+
+/// The initial settings of a band in a [crate::ParametricEq]
+///
+/// Once the EQ has been created, each band is controlled with its parameters
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EqBand {
+    pub(super) band_type: EqBandType,
+    pub(super) frequency: f64,
+    pub(super) gain: f64,
+    pub(super) q: f64,
+}
+
+impl EqBand {
+    /// Create a band at a frequency, with no gain and a Q of 0.707
+    pub fn new(band_type: EqBandType, frequency: f64) -> Self {
+        Self {
+            band_type,
+            frequency,
+            gain: 0.0,
+            q: 1.0 / 2.0_f64.sqrt(),
+        }
+    }
+
+    /// Set the gain of the band in dB
+    ///
+    /// This is used by the bell and shelf bands
+    pub fn with_gain(mut self, gain_in_db: f64) -> Self {
+        self.gain = gain_in_db;
+        self
+    }
+
+    /// Set the Q of the band
+    ///
+    /// This is used by the bell and notch bands
+    pub fn with_q(mut self, q: f64) -> Self {
+        self.q = q;
+        self
+    }
+}
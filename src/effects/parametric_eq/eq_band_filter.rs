@@ -0,0 +1,170 @@
+use super::EqBandType;
+use crate::{
+    effects::{
+        biquad::BiquadCoefficients,
+        filter::{FilterCascade, FilterPass},
+    },
+    FilterDesign, Level,
+};
+
+/// The settings of a band at a moment in time
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BandValues {
+    pub frequency: f64,
+    pub gain: f64,
+    pub q: f64,
+}
+
+/// Calculates the coefficients of a band
+pub struct EqBandFilter {
+    band_type: EqBandType,
+    cascade: Option<FilterCascade>,
+}
+
+const MAXIMUM_FREQUENCY_RATIO: f64 = 0.49;
+
+impl EqBandFilter {
+    pub fn new(band_type: EqBandType) -> Self {
+        let cascade = match band_type {
+            EqBandType::LowPass { order } => Some(FilterCascade::new(
+                FilterDesign::Butterworth,
+                order,
+                FilterPass::LowPass,
+            )),
+            EqBandType::HighPass { order } => Some(FilterCascade::new(
+                FilterDesign::Butterworth,
+                order,
+                FilterPass::HighPass,
+            )),
+            _ => None,
+        };
+
+        Self { band_type, cascade }
+    }
+
+    pub fn section_count(&self) -> usize {
+        self.cascade
+            .as_ref()
+            .map_or(1, |cascade| cascade.section_count())
+    }
+
+    pub fn calculate_coefficients(
+        &self,
+        values: &BandValues,
+        sample_rate: f64,
+        coefficients: &mut [BiquadCoefficients],
+    ) {
+        debug_assert_eq!(coefficients.len(), self.section_count());
+
+        if let Some(cascade) = &self.cascade {
+            cascade.calculate_coefficients(values.frequency, sample_rate, coefficients);
+            return;
+        }
+
+        let frequency = values.frequency.min(MAXIMUM_FREQUENCY_RATIO * sample_rate);
+        let level = Level::from_db(values.gain);
+
+        coefficients[0] = match self.band_type {
+            EqBandType::Bell => {
+                BiquadCoefficients::peaking(frequency, sample_rate, values.q, level)
+            }
+            EqBandType::LowShelf => BiquadCoefficients::low_shelf(frequency, sample_rate, level),
+            EqBandType::HighShelf => BiquadCoefficients::high_shelf(frequency, sample_rate, level),
+            EqBandType::Notch => BiquadCoefficients::notch(frequency, sample_rate, values.q),
+            EqBandType::LowPass { .. } | EqBandType::HighPass { .. } => unreachable!(),
+        };
+    }
+
+    pub fn create_coefficients(
+        &self,
+        values: &BandValues,
+        sample_rate: f64,
+    ) -> Vec<BiquadCoefficients> {
+        let mut coefficients =
+            vec![BiquadCoefficients::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0); self.section_count()];
+        self.calculate_coefficients(values, sample_rate, &mut coefficients);
+        coefficients
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::biquad::cascade_response;
+    use approx::assert_relative_eq;
+
+    const SAMPLE_RATE: f64 = 48_000.0;
+
+    fn magnitude_db(band_type: EqBandType, values: BandValues, frequency: f64) -> f64 {
+        let filter = EqBandFilter::new(band_type);
+        let coefficients = filter.create_coefficients(&values, SAMPLE_RATE);
+        20.0 * cascade_response(&coefficients, frequency, SAMPLE_RATE)
+            .norm()
+            .log10()
+    }
+
+    #[test]
+    fn bell_applies_its_gain_at_the_centre() {
+        let values = BandValues {
+            frequency: 1_000.0,
+            gain: -9.0,
+            q: 2.0,
+        };
+
+        assert_relative_eq!(
+            magnitude_db(EqBandType::Bell, values, 1_000.0),
+            -9.0,
+            epsilon = 1e-6
+        );
+        assert_relative_eq!(
+            magnitude_db(EqBandType::Bell, values, 20.0),
+            0.0,
+            epsilon = 1e-2
+        );
+    }
+
+    #[test]
+    fn shelves_apply_their_gain_away_from_the_frequency() {
+        let values = BandValues {
+            frequency: 1_000.0,
+            gain: 6.0,
+            q: 1.0,
+        };
+
+        assert_relative_eq!(
+            magnitude_db(EqBandType::LowShelf, values, 20.0),
+            6.0,
+            epsilon = 1e-2
+        );
+        assert_relative_eq!(
+            magnitude_db(EqBandType::HighShelf, values, 20_000.0),
+            6.0,
+            epsilon = 1e-2
+        );
+    }
+
+    #[test]
+    fn passes_use_the_order_for_their_slope() {
+        let values = BandValues {
+            frequency: 1_000.0,
+            gain: 0.0,
+            q: 1.0,
+        };
+
+        let low_pass = EqBandType::LowPass { order: 6 };
+        assert_eq!(EqBandFilter::new(low_pass).section_count(), 3);
+        assert_relative_eq!(
+            magnitude_db(low_pass, values, 1_000.0),
+            -3.01,
+            epsilon = 1e-2
+        );
+        assert!(magnitude_db(low_pass, values, 2_000.0) < -36.0);
+
+        let high_pass = EqBandType::HighPass { order: 1 };
+        assert_relative_eq!(
+            magnitude_db(high_pass, values, 1_000.0),
+            -3.01,
+            epsilon = 1e-2
+        );
+    }
+}
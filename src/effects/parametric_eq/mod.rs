@@ -0,0 +1,7 @@
+mod eq_band;
+mod eq_band_filter;
+mod parametric_eq_node;
+mod parametric_eq_processor;
+
+pub use eq_band::{EqBand, EqBandType};
+pub use parametric_eq_node::ParametricEq;
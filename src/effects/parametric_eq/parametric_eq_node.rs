@@ -0,0 +1,152 @@
+use super::{
+    eq_band_filter::{BandValues, EqBandFilter},
+    parametric_eq_processor::{BandParameterIds, ParametricEqProcessor},
+    EqBand, EqBandType,
+};
+use crate::{
+    commands::Id,
+    effects::biquad::{cascade_response, write_frequency_response},
+    graph::DspNode,
+    parameter::{intern_parameter_id, *},
+    prelude::*,
+    utility::create_parameters,
+};
+use rustfft::num_complex::Complex64;
+
+/// A parametric equaliser with a configurable set of bands
+///
+/// Each band is a bell, shelf, pass or notch filter, and the bands are applied
+/// one after the other. Changes to the band parameters are smoothed, so they
+/// can be automated without clicks.
+///
+/// # Parameters
+/// For each band `n`, counting from zero:
+/// - band-n-frequency (in Hz)
+/// - band-n-gain (in dB, for the bell and shelf bands)
+/// - band-n-q (for the bell and notch bands)
+pub struct ParametricEq {
+    /// The node to connect into the audio graph
+    pub node: GraphNode,
+
+    parameters: Parameters,
+    bands: Vec<(EqBandType, BandParameterIds)>,
+    sample_rate: usize,
+}
+
+impl DspNode for ParametricEq {
+    fn get_parameters_mut(&mut self) -> &mut Parameters {
+        &mut self.parameters
+    }
+}
+
+impl ParametricEq {
+    /// Create a new parametric EQ with a set of bands
+    pub fn new(context: &dyn Context, channel_count: usize, bands: Vec<EqBand>) -> Self {
+        let id = Id::generate();
+
+        let band_ids: Vec<_> = (0..bands.len())
+            .map(|index| BandParameterIds {
+                frequency: intern_parameter_id(&format!("band-{index}-frequency")),
+                gain: intern_parameter_id(&format!("band-{index}-gain")),
+                q: intern_parameter_id(&format!("band-{index}-q")),
+            })
+            .collect();
+
+        let (params, realtime_params) = create_parameters(
+            id,
+            context,
+            bands.iter().zip(band_ids.iter()).flat_map(|(band, ids)| {
+                [
+                    (
+                        ids.frequency,
+                        ParameterRange::new(band.frequency, 20.0, 20_000.0),
+                    ),
+                    (ids.gain, ParameterRange::new(band.gain, -24.0, 24.0)),
+                    (ids.q, ParameterRange::new(band.q, 0.1, 10.0)),
+                ]
+            }),
+        );
+
+        let bands: Vec<_> = bands
+            .iter()
+            .map(|band| band.band_type)
+            .zip(band_ids)
+            .collect();
+
+        let processor = Box::new(ParametricEqProcessor::new(
+            context.get_sample_rate(),
+            channel_count,
+            bands.clone(),
+        ));
+
+        Self {
+            node: GraphNode::new(
+                id,
+                context,
+                channel_count,
+                channel_count,
+                processor,
+                realtime_params,
+            ),
+            parameters: params,
+            bands,
+            sample_rate: context.get_sample_rate(),
+        }
+    }
+
+    /// The number of bands in the EQ
+    pub fn band_count(&self) -> usize {
+        self.bands.len()
+    }
+
+    /// Get the frequency parameter of a band
+    pub fn frequency(&mut self, band: usize) -> &mut AudioParameter {
+        self.get_parameter_mut(self.bands[band].1.frequency)
+    }
+
+    /// Get the gain parameter of a band
+    pub fn gain(&mut self, band: usize) -> &mut AudioParameter {
+        self.get_parameter_mut(self.bands[band].1.gain)
+    }
+
+    /// Get the Q parameter of a band
+    pub fn q(&mut self, band: usize) -> &mut AudioParameter {
+        self.get_parameter_mut(self.bands[band].1.q)
+    }
+
+    /// Calculate the combined response of all the bands, for drawing the EQ
+    /// curve
+    ///
+    /// The response is calculated from the last parameter values used by the
+    /// audio thread. For each of the `frequencies`, the linear magnitude is
+    /// written to `magnitudes` and the phase in radians is written to `phases`.
+    pub fn get_frequency_response(
+        &self,
+        frequencies: &[f64],
+        magnitudes: &mut [f64],
+        phases: &mut [f64],
+    ) {
+        let sample_rate = self.sample_rate as f64;
+
+        let band_coefficients: Vec<_> = self
+            .bands
+            .iter()
+            .map(|(band_type, ids)| {
+                let values = BandValues {
+                    frequency: self.parameters.current_value(ids.frequency),
+                    gain: self.parameters.current_value(ids.gain),
+                    q: self.parameters.current_value(ids.q),
+                };
+
+                EqBandFilter::new(*band_type).create_coefficients(&values, sample_rate)
+            })
+            .collect();
+
+        write_frequency_response(frequencies, magnitudes, phases, |frequency| {
+            band_coefficients
+                .iter()
+                .map(|coefficients| cascade_response(coefficients, frequency, sample_rate))
+                .product::<Complex64>()
+        });
+    }
+}
@@ -0,0 +1,245 @@
+use super::{
+    eq_band_filter::{BandValues, EqBandFilter},
+    EqBandType,
+};
+use crate::{
+    effects::biquad::BiquadCoefficients, graph::DspProcessor, parameter::ParameterId, prelude::*,
+};
+
+/// The parameter ids of a band
+#[derive(Clone, Copy)]
+pub struct BandParameterIds {
+    pub frequency: ParameterId,
+    pub gain: ParameterId,
+    pub q: ParameterId,
+}
+
+struct ProcessorBand {
+    filter: EqBandFilter,
+    ids: BandParameterIds,
+    values: Option<BandValues>,
+    coefficients: Vec<BiquadCoefficients>,
+    delays: Vec<Vec<[f64; 4]>>,
+}
+
+pub struct ParametricEqProcessor {
+    sample_rate: usize,
+    bands: Vec<ProcessorBand>,
+    smoothing: f64,
+}
+
+/// The number of frames between each coefficient update
+const CONTROL_BLOCK_SIZE: usize = 32;
+
+/// The time taken for the smoothed values to move most of the way to a new
+/// value
+const SMOOTHING_TIME_IN_SECONDS: f64 = 0.02;
+
+impl ParametricEqProcessor {
+    pub fn new(
+        sample_rate: usize,
+        channel_count: usize,
+        bands: Vec<(EqBandType, BandParameterIds)>,
+    ) -> Self {
+        let control_rate = sample_rate as f64 / CONTROL_BLOCK_SIZE as f64;
+
+        Self {
+            sample_rate,
+            bands: bands
+                .into_iter()
+                .map(|(band_type, ids)| {
+                    let filter = EqBandFilter::new(band_type);
+                    let section_count = filter.section_count();
+
+                    ProcessorBand {
+                        filter,
+                        ids,
+                        values: None,
+                        coefficients: vec![
+                            BiquadCoefficients::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+                            section_count
+                        ],
+                        delays: vec![vec![[0.0; 4]; section_count]; channel_count],
+                    }
+                })
+                .collect(),
+            smoothing: (-1.0 / (SMOOTHING_TIME_IN_SECONDS * control_rate)).exp(),
+        }
+    }
+}
+
+impl ProcessorBand {
+    /// Move the band towards its target values, and update the coefficients
+    /// if anything changed
+    fn update(&mut self, target: BandValues, smoothing: f64, sample_rate: f64) {
+        let values = match self.values {
+            Some(current) => {
+                let smooth = |current: f64, target: f64| target + smoothing * (current - target);
+
+                BandValues {
+                    frequency: smooth(current.frequency.ln(), target.frequency.ln()).exp(),
+                    gain: smooth(current.gain, target.gain),
+                    q: smooth(current.q.ln(), target.q.ln()).exp(),
+                }
+            }
+            None => target,
+        };
+
+        if self.values != Some(values) {
+            self.filter
+                .calculate_coefficients(&values, sample_rate, &mut self.coefficients);
+            self.values = Some(values);
+        }
+    }
+
+    fn process(&mut self, channel: usize, samples: &mut [f32]) {
+        let delays = &mut self.delays[channel];
+
+        for sample in samples.iter_mut() {
+            *sample = self
+                .coefficients
+                .iter()
+                .zip(delays.iter_mut())
+                .fold(*sample as f64, |sample, (coefficients, delays)| {
+                    coefficients.process_sample(sample, delays)
+                }) as f32;
+        }
+    }
+}
+
+impl DspProcessor for ParametricEqProcessor {
+    fn process_audio(&mut self, context: &mut crate::ProcessContext) {
+        let frame_count = context.output_buffer.frame_count();
+
+        let channel_count = std::cmp::min(
+            context.output_buffer.channel_count(),
+            context.input_buffer.channel_count(),
+        );
+
+        context.output_buffer.copy_from(
+            context.input_buffer,
+            SampleLocation::origin(),
+            SampleLocation::origin(),
+            channel_count,
+            frame_count,
+        );
+
+        for band in self.bands.iter_mut() {
+            let frequency = context
+                .parameters
+                .get_parameter_values(band.ids.frequency, frame_count);
+            let gain = context
+                .parameters
+                .get_parameter_values(band.ids.gain, frame_count);
+            let q = context
+                .parameters
+                .get_parameter_values(band.ids.q, frame_count);
+
+            let mut start_frame = 0;
+
+            while start_frame < frame_count {
+                let end_frame = (start_frame + CONTROL_BLOCK_SIZE).min(frame_count);
+
+                band.update(
+                    BandValues {
+                        frequency: frequency[start_frame] as f64,
+                        gain: gain[start_frame] as f64,
+                        q: q[start_frame] as f64,
+                    },
+                    self.smoothing,
+                    self.sample_rate as f64,
+                );
+
+                for channel in 0..channel_count.min(band.delays.len()) {
+                    let data = context
+                        .output_buffer
+                        .get_channel_data_mut(SampleLocation::channel(channel));
+                    band.process(channel, &mut data[start_frame..end_frame]);
+                }
+
+                start_frame = end_frame;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{graph::DspParameters, parameter::RealtimeAudioParameter, ProcessContext};
+    use approx::assert_relative_eq;
+    use atomic_float::AtomicF64;
+    use std::sync::Arc;
+
+    const SAMPLE_RATE: usize = 48_000;
+    const FRAME_COUNT: usize = 4_800;
+
+    const IDS: BandParameterIds = BandParameterIds {
+        frequency: "band-0-frequency",
+        gain: "band-0-gain",
+        q: "band-0-q",
+    };
+
+    fn create_parameters(gain: f64) -> DspParameters {
+        DspParameters::new(
+            [(IDS.frequency, 1_000.0), (IDS.gain, gain), (IDS.q, 1.0)].map(|(id, value)| {
+                RealtimeAudioParameter::new(id, Arc::new(AtomicF64::new(value)), FRAME_COUNT)
+            }),
+        )
+    }
+
+    fn process(processor: &mut ParametricEqProcessor, gain: f64) -> OwnedAudioBuffer {
+        let input = OwnedAudioBuffer::sine(FRAME_COUNT, 1, SAMPLE_RATE, 1_000.0, 1.0);
+        let mut output = OwnedAudioBuffer::new(FRAME_COUNT, 1, SAMPLE_RATE);
+
+        let mut parameters = create_parameters(gain);
+        parameters.iter_mut().for_each(|(_, parameter)| {
+            parameter.process(&Timestamp::zero(), FRAME_COUNT, SAMPLE_RATE);
+        });
+
+        processor.process_audio(&mut ProcessContext {
+            input_buffer: &input,
+            output_buffer: &mut output,
+            start_time: &Timestamp::zero(),
+            parameters: &parameters,
+            midi_events: &[],
+        });
+
+        output
+    }
+
+    fn peak(buffer: &OwnedAudioBuffer, start_frame: usize, end_frame: usize) -> f32 {
+        buffer.get_channel_data(SampleLocation::origin())[start_frame..end_frame]
+            .iter()
+            .fold(0.0, |peak, value| value.abs().max(peak))
+    }
+
+    #[test]
+    fn applies_the_band_gain() {
+        let mut processor =
+            ParametricEqProcessor::new(SAMPLE_RATE, 1, vec![(EqBandType::Bell, IDS)]);
+
+        let output = process(&mut processor, 12.0);
+
+        assert_relative_eq!(
+            peak(&output, FRAME_COUNT / 2, FRAME_COUNT),
+            Level::from_db(12.0).as_linear() as f32,
+            epsilon = 1e-2
+        );
+    }
+
+    #[test]
+    fn smooths_parameter_changes() {
+        let mut processor =
+            ParametricEqProcessor::new(SAMPLE_RATE, 1, vec![(EqBandType::Bell, IDS)]);
+
+        process(&mut processor, 0.0);
+        let output = process(&mut processor, 12.0);
+
+        let start = peak(&output, 0, 48);
+        let end = peak(&output, FRAME_COUNT - 480, FRAME_COUNT);
+
+        assert!(start < 1.2);
+        assert_relative_eq!(end, Level::from_db(12.0).as_linear() as f32, epsilon = 0.05);
+    }
+}
@@ -0,0 +1,51 @@
+use crate::effects::delay::{DelayInterpolation, DelayLine};
+
+/// A Schroeder all-pass filter, which smears the input over time without
+/// changing its spectrum
+pub struct AllpassDiffuser {
+    delay_line: DelayLine,
+    delay_frames: usize,
+    coefficient: f32,
+}
+
+impl AllpassDiffuser {
+    pub fn new(delay_frames: usize, coefficient: f32) -> Self {
+        Self {
+            delay_line: DelayLine::new(delay_frames),
+            delay_frames,
+            coefficient,
+        }
+    }
+
+    pub fn process(&mut self, input: f32) -> f32 {
+        let delayed = self
+            .delay_line
+            .read((self.delay_frames - 1) as f32, DelayInterpolation::Linear);
+
+        let value = input + self.coefficient * delayed;
+        self.delay_line.write(value);
+
+        delayed - self.coefficient * value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn keeps_the_energy_of_an_impulse() {
+        let mut diffuser = AllpassDiffuser::new(7, 0.6);
+
+        let output: Vec<_> = (0..1_000)
+            .map(|frame| diffuser.process(if frame == 0 { 1.0 } else { 0.0 }))
+            .collect();
+
+        assert_relative_eq!(output[0], -0.6);
+        assert_relative_eq!(output[7], 1.0 - 0.36);
+
+        let energy: f32 = output.iter().map(|value| value * value).sum();
+        assert_relative_eq!(energy, 1.0, epsilon = 1e-4);
+    }
+}
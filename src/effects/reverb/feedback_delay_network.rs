@@ -0,0 +1,182 @@
+use super::allpass_diffuser::AllpassDiffuser;
+use crate::{
+    dsp::flush_denormal,
+    effects::delay::{DelayInterpolation, DelayLine},
+};
+
+/// The delay times of the feedback lines at the largest size, in seconds
+///
+/// These are chosen to be far from any simple ratio of each other, so that the
+/// echoes don't line up
+const LINE_DELAY_TIMES: [f64; LINE_COUNT] = [
+    0.0503, 0.0569, 0.0637, 0.0713, 0.0791, 0.0877, 0.0953, 0.1049,
+];
+
+const LINE_COUNT: usize = 8;
+
+/// The delay times of the diffusers on each input, in seconds
+const DIFFUSER_DELAY_TIMES: [[f64; 4]; 2] = [
+    [0.00477, 0.00359, 0.01273, 0.00931],
+    [0.00493, 0.00331, 0.01311, 0.00887],
+];
+
+const DIFFUSER_COEFFICIENT: f32 = 0.625;
+
+/// The smallest size, as a fraction of the largest
+const MINIMUM_SIZE: f64 = 0.25;
+
+/// The pole of the damping filters at full damping
+const MAXIMUM_DAMPING: f64 = 0.9;
+
+/// Scales the sum of the lines to roughly the level of the input
+const OUTPUT_GAIN: f32 = 0.5;
+
+/// The settings of the network for a single frame
+pub struct NetworkSettings {
+    /// How much the high frequencies are damped, from 0 to 1
+    pub damping: f64,
+
+    /// The size of the space, from 0 to 1
+    pub size: f64,
+}
+
+/// A feedback delay network with two inputs and two outputs
+///
+/// Each input is diffused by a chain of all-pass filters and fed into half of
+/// the lines. The lines are mixed through a Hadamard matrix, which keeps the
+/// energy of the network, and then each line is damped and scaled so that it
+/// decays at the same rate.
+pub struct FeedbackDelayNetwork {
+    lines: Vec<DelayLine>,
+    damping_states: [f32; LINE_COUNT],
+    line_gains: [f32; LINE_COUNT],
+    diffusers: [Vec<AllpassDiffuser>; 2],
+    sample_rate: f64,
+}
+
+impl FeedbackDelayNetwork {
+    pub fn new(sample_rate: usize) -> Self {
+        let sample_rate = sample_rate as f64;
+        let to_frames = |seconds: f64| (seconds * sample_rate).ceil() as usize;
+
+        Self {
+            lines: LINE_DELAY_TIMES
+                .iter()
+                .map(|delay_time| DelayLine::new(to_frames(*delay_time) + 1))
+                .collect(),
+            damping_states: [0.0; LINE_COUNT],
+            line_gains: [0.0; LINE_COUNT],
+            diffusers: DIFFUSER_DELAY_TIMES.map(|delay_times| {
+                delay_times
+                    .iter()
+                    .map(|delay_time| {
+                        AllpassDiffuser::new(to_frames(*delay_time), DIFFUSER_COEFFICIENT)
+                    })
+                    .collect()
+            }),
+            sample_rate,
+        }
+    }
+
+    /// Update the gain of each line, so that it falls by 60dB over `decay`
+    /// seconds at the current size
+    pub fn set_decay(&mut self, decay: f64, size: f64) {
+        let size_scale = size_scale(size);
+
+        for (gain, delay_time) in self.line_gains.iter_mut().zip(LINE_DELAY_TIMES.iter()) {
+            *gain = 10.0_f64.powf(-3.0 * delay_time * size_scale / decay) as f32;
+        }
+    }
+
+    pub fn process(&mut self, input: [f32; 2], settings: &NetworkSettings) -> [f32; 2] {
+        let size_scale = size_scale(settings.size);
+        let damping = (MAXIMUM_DAMPING * settings.damping) as f32;
+
+        let mut outputs = [0.0; LINE_COUNT];
+
+        for (index, output) in outputs.iter_mut().enumerate() {
+            let delay_frames = LINE_DELAY_TIMES[index] * size_scale * self.sample_rate;
+
+            *output = self.lines[index].read(
+                (delay_frames - 1.0).max(0.0) as f32,
+                DelayInterpolation::Linear,
+            );
+        }
+
+        let mut feedback = outputs;
+
+        for ((value, state), gain) in feedback
+            .iter_mut()
+            .zip(self.damping_states.iter_mut())
+            .zip(self.line_gains.iter())
+        {
+            *state = flush_denormal((*value + damping * (*state - *value)) as f64) as f32;
+            *value = gain * *state;
+        }
+
+        hadamard(&mut feedback);
+
+        let diffused = [0, 1].map(|side| {
+            self.diffusers[side]
+                .iter_mut()
+                .fold(input[side], |value, diffuser| diffuser.process(value))
+        });
+
+        for (index, (line, value)) in self.lines.iter_mut().zip(feedback).enumerate() {
+            line.write(value + diffused[index % 2]);
+        }
+
+        [0, 1].map(|side| {
+            OUTPUT_GAIN
+                * outputs
+                    .iter()
+                    .skip(side)
+                    .step_by(2)
+                    .enumerate()
+                    .map(|(index, value)| if index % 2 == 0 { *value } else { -*value })
+                    .sum::<f32>()
+        })
+    }
+}
+
+fn size_scale(size: f64) -> f64 {
+    MINIMUM_SIZE + (1.0 - MINIMUM_SIZE) * size
+}
+
+/// Mix the values with a normalised Hadamard matrix
+fn hadamard(values: &mut [f32; LINE_COUNT]) {
+    let mut width = 1;
+
+    while width < LINE_COUNT {
+        for start in (0..LINE_COUNT).step_by(2 * width) {
+            for index in start..start + width {
+                let a = values[index];
+                let b = values[index + width];
+                values[index] = a + b;
+                values[index + width] = a - b;
+            }
+        }
+
+        width *= 2;
+    }
+
+    let scale = 1.0 / (LINE_COUNT as f32).sqrt();
+    values.iter_mut().for_each(|value| *value *= scale);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    #[test]
+    fn hadamard_keeps_the_energy() {
+        let mut values = [1.0, -2.0, 0.5, 3.0, 0.0, 1.5, -1.0, 2.0];
+        let energy = |values: &[f32]| values.iter().map(|value| value * value).sum::<f32>();
+
+        let before = energy(&values);
+        hadamard(&mut values);
+
+        assert_relative_eq!(energy(&values), before, epsilon = 1e-5);
+    }
+}
@@ -0,0 +1,6 @@
+mod allpass_diffuser;
+mod feedback_delay_network;
+mod reverb_node;
+mod reverb_processor;
+
+pub use reverb_node::Reverb;
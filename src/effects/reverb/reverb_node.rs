@@ -0,0 +1,115 @@
+use super::reverb_processor::ReverbProcessor;
+use crate::{commands::Id, graph::DspNode, parameter::*, prelude::*, utility::create_parameters};
+
+/// An algorithmic reverb
+///
+/// The reverb is a feedback delay network, with diffused inputs and damping in
+/// the feedback paths. It doesn't need an impulse response, and the cost per
+/// sample is fixed, so it is suitable for realtime use.
+///
+/// Stereo inputs are fed into separate halves of the network. With more than
+/// two channels, even channels are treated as left and odd channels as right.
+///
+/// # Parameters
+/// - decay (the time taken to fall by 60dB, in seconds)
+/// - pre-delay (in seconds)
+/// - damping (from 0 to 1, how much faster the high frequencies decay)
+/// - size (from 0 to 1)
+/// - width (from 0 for mono to 1 for full stereo)
+/// - wet
+/// - dry
+pub struct Reverb {
+    /// The node to connect into the audio graph
+    pub node: GraphNode,
+
+    parameters: Parameters,
+}
+
+/// The longest pre-delay, in seconds
+const MAXIMUM_PRE_DELAY: f64 = 0.5;
+
+impl DspNode for Reverb {
+    fn get_parameters_mut(&mut self) -> &mut Parameters {
+        &mut self.parameters
+    }
+}
+
+impl Reverb {
+    /// Create a new reverb
+    pub fn new(context: &dyn Context, channel_count: usize) -> Self {
+        let id = Id::generate();
+
+        let (params, realtime_params) = create_parameters(
+            id,
+            context,
+            [
+                ("decay", ParameterRange::new(2.0, 0.1, 30.0)),
+                (
+                    "pre-delay",
+                    ParameterRange::new(0.01, 0.0, MAXIMUM_PRE_DELAY),
+                ),
+                ("damping", ParameterRange::new(0.5, 0.0, 1.0)),
+                ("size", ParameterRange::new(0.5, 0.0, 1.0)),
+                ("width", ParameterRange::new(1.0, 0.0, 1.0)),
+                ("wet", ParameterRange::new(0.3, 0.0, 1.0)),
+                ("dry", ParameterRange::new(1.0, 0.0, 1.0)),
+            ],
+        );
+
+        let sample_rate = context.get_sample_rate();
+        let maximum_pre_delay_frames = (MAXIMUM_PRE_DELAY * sample_rate as f64).ceil() as usize;
+
+        let processor = Box::new(ReverbProcessor::new(
+            sample_rate,
+            channel_count,
+            maximum_pre_delay_frames,
+        ));
+
+        Self {
+            node: GraphNode::new(
+                id,
+                context,
+                channel_count,
+                channel_count,
+                processor,
+                realtime_params,
+            ),
+            parameters: params,
+        }
+    }
+
+    /// Get the decay parameter, in seconds
+    pub fn decay(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("decay")
+    }
+
+    /// Get the pre-delay parameter, in seconds
+    pub fn pre_delay(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("pre-delay")
+    }
+
+    /// Get the damping parameter
+    pub fn damping(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("damping")
+    }
+
+    /// Get the size parameter
+    pub fn size(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("size")
+    }
+
+    /// Get the width parameter
+    pub fn width(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("width")
+    }
+
+    /// Get the wet parameter
+    pub fn wet(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("wet")
+    }
+
+    /// Get the dry parameter
+    pub fn dry(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("dry")
+    }
+}
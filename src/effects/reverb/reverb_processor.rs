@@ -0,0 +1,312 @@
+use super::feedback_delay_network::{FeedbackDelayNetwork, NetworkSettings};
+use crate::{
+    effects::delay::{DelayInterpolation, DelayLine},
+    graph::DspProcessor,
+    prelude::*,
+};
+
+pub struct ReverbProcessor {
+    pre_delay_lines: Vec<DelayLine>,
+    network: FeedbackDelayNetwork,
+    sample_rate: f32,
+}
+
+/// The number of frames between each update of the decay
+const CONTROL_BLOCK_SIZE: usize = 32;
+
+impl ReverbProcessor {
+    pub fn new(sample_rate: usize, channel_count: usize, maximum_pre_delay_frames: usize) -> Self {
+        Self {
+            pre_delay_lines: (0..channel_count)
+                .map(|_| DelayLine::new(maximum_pre_delay_frames))
+                .collect(),
+            network: FeedbackDelayNetwork::new(sample_rate),
+            sample_rate: sample_rate as f32,
+        }
+    }
+}
+
+impl DspProcessor for ReverbProcessor {
+    fn process_audio(&mut self, context: &mut crate::ProcessContext) {
+        let frame_count = context.output_buffer.frame_count();
+
+        let decay = context
+            .parameters
+            .get_parameter_values("decay", frame_count);
+        let pre_delay = context
+            .parameters
+            .get_parameter_values("pre-delay", frame_count);
+        let damping = context
+            .parameters
+            .get_parameter_values("damping", frame_count);
+        let size = context.parameters.get_parameter_values("size", frame_count);
+        let width = context
+            .parameters
+            .get_parameter_values("width", frame_count);
+        let wet = context.parameters.get_parameter_values("wet", frame_count);
+        let dry = context.parameters.get_parameter_values("dry", frame_count);
+
+        let input_channel_count = context
+            .input_buffer
+            .channel_count()
+            .min(self.pre_delay_lines.len());
+        let output_channel_count = context
+            .output_buffer
+            .channel_count()
+            .min(self.pre_delay_lines.len());
+
+        for frame in 0..frame_count {
+            if frame % CONTROL_BLOCK_SIZE == 0 {
+                self.network
+                    .set_decay(decay[frame] as f64, size[frame] as f64);
+            }
+
+            let mut network_input = [0.0; 2];
+
+            for (channel, pre_delay_line) in self
+                .pre_delay_lines
+                .iter_mut()
+                .enumerate()
+                .take(input_channel_count)
+            {
+                let input = context
+                    .input_buffer
+                    .get_sample(SampleLocation::new(channel, frame));
+
+                pre_delay_line.write(input);
+                let delayed = pre_delay_line.read(
+                    pre_delay[frame] * self.sample_rate,
+                    DelayInterpolation::Linear,
+                );
+
+                if input_channel_count == 1 {
+                    network_input = [delayed; 2];
+                } else {
+                    network_input[channel % 2] += delayed;
+                }
+            }
+
+            let [left, right] = self.network.process(
+                network_input,
+                &NetworkSettings {
+                    damping: damping[frame] as f64,
+                    size: size[frame] as f64,
+                },
+            );
+
+            let mid = 0.5 * (left + right);
+            let side = 0.5 * (left - right) * width[frame];
+            let reverb = [mid + side, mid - side];
+
+            for channel in 0..output_channel_count {
+                let location = SampleLocation::new(channel, frame);
+
+                let dry_value = if channel < context.input_buffer.channel_count() {
+                    context.input_buffer.get_sample(location)
+                } else {
+                    0.0
+                };
+
+                let wet_value = if output_channel_count == 1 {
+                    mid
+                } else {
+                    reverb[channel % 2]
+                };
+
+                context
+                    .output_buffer
+                    .set_sample(location, wet[frame] * wet_value + dry[frame] * dry_value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{graph::DspParameters, parameter::RealtimeAudioParameter, ProcessContext};
+    use approx::assert_relative_eq;
+    use atomic_float::AtomicF64;
+    use std::sync::Arc;
+
+    const SAMPLE_RATE: usize = 48_000;
+    const FRAME_COUNT: usize = 4_800;
+
+    struct Fixture {
+        processor: ReverbProcessor,
+        parameters: DspParameters,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let make_parameter = |(name, value)| {
+                RealtimeAudioParameter::new(name, Arc::new(AtomicF64::new(value)), FRAME_COUNT)
+            };
+
+            Self {
+                processor: ReverbProcessor::new(SAMPLE_RATE, 2, SAMPLE_RATE),
+                parameters: DspParameters::new(
+                    [
+                        ("decay", 1.0),
+                        ("pre-delay", 0.0),
+                        ("damping", 0.0),
+                        ("size", 1.0),
+                        ("width", 1.0),
+                        ("wet", 1.0),
+                        ("dry", 0.0),
+                    ]
+                    .map(make_parameter),
+                ),
+            }
+        }
+
+        fn set_parameter(&mut self, name: &'static str, value: f64) {
+            self.parameters.get_parameter_mut(name).set_value(value);
+        }
+
+        fn process(&mut self, input: &OwnedAudioBuffer) -> OwnedAudioBuffer {
+            let mut output = OwnedAudioBuffer::new(FRAME_COUNT, 2, SAMPLE_RATE);
+
+            self.parameters.iter_mut().for_each(|(_, parameter)| {
+                parameter.process(&Timestamp::zero(), FRAME_COUNT, SAMPLE_RATE);
+            });
+
+            self.processor.process_audio(&mut ProcessContext {
+                input_buffer: input,
+                output_buffer: &mut output,
+                start_time: &Timestamp::zero(),
+                parameters: &self.parameters,
+                midi_events: &[],
+            });
+
+            output
+        }
+
+        /// Process an impulse, and return the energy of each block of the
+        /// response
+        fn impulse_response_energy(&mut self, block_count: usize) -> Vec<f64> {
+            let mut impulse = OwnedAudioBuffer::new(FRAME_COUNT, 2, SAMPLE_RATE);
+            impulse.set_sample(SampleLocation::origin(), 1.0);
+            impulse.set_sample(SampleLocation::channel(1), 1.0);
+
+            let silence = OwnedAudioBuffer::new(FRAME_COUNT, 2, SAMPLE_RATE);
+
+            (0..block_count)
+                .map(|block| {
+                    let output = self.process(if block == 0 { &impulse } else { &silence });
+                    energy(&output, 0) + energy(&output, 1)
+                })
+                .collect()
+        }
+    }
+
+    fn energy(buffer: &OwnedAudioBuffer, channel: usize) -> f64 {
+        buffer
+            .get_channel_data(SampleLocation::channel(channel))
+            .iter()
+            .map(|value| (*value as f64).powi(2))
+            .sum()
+    }
+
+    fn to_db(energy: f64) -> f64 {
+        10.0 * energy.log10()
+    }
+
+    #[test]
+    fn decays_by_60_db_over_the_decay_time() {
+        let mut fixture = Fixture::new();
+
+        let energy = fixture.impulse_response_energy(14);
+
+        // Each block is 100ms, so there is 1 second between the 3rd and 13th
+        let decay = to_db(energy[2]) - to_db(energy[12]);
+        assert!((50.0..70.0).contains(&decay), "{decay}");
+    }
+
+    #[test]
+    fn damping_shortens_the_tail() {
+        let mut undamped = Fixture::new();
+        let mut damped = Fixture::new();
+        damped.set_parameter("damping", 1.0);
+
+        let undamped = undamped.impulse_response_energy(5);
+        let damped = damped.impulse_response_energy(5);
+
+        assert!(damped[4] < undamped[4]);
+    }
+
+    #[test]
+    fn delays_the_reverb_by_the_pre_delay() {
+        let first_sound = |pre_delay| {
+            let mut fixture = Fixture::new();
+            fixture.set_parameter("size", 0.0);
+            fixture.set_parameter("pre-delay", pre_delay);
+
+            let mut impulse = OwnedAudioBuffer::new(FRAME_COUNT, 2, SAMPLE_RATE);
+            impulse.set_sample(SampleLocation::origin(), 1.0);
+
+            let output = fixture.process(&impulse);
+            output
+                .get_channel_data(SampleLocation::origin())
+                .iter()
+                .position(|value| value.abs() > 1e-6)
+                .expect("The reverb should start within the block")
+        };
+
+        assert_eq!(first_sound(0.02) - first_sound(0.0), SAMPLE_RATE / 50);
+    }
+
+    #[test]
+    fn width_controls_the_stereo_spread() {
+        let mut fixture = Fixture::new();
+        fixture.set_parameter("width", 0.0);
+
+        let mut impulse = OwnedAudioBuffer::new(FRAME_COUNT, 2, SAMPLE_RATE);
+        impulse.set_sample(SampleLocation::origin(), 1.0);
+
+        let output = fixture.process(&impulse);
+        assert_eq!(
+            output.get_channel_data(SampleLocation::channel(0)),
+            output.get_channel_data(SampleLocation::channel(1))
+        );
+
+        let mut fixture = Fixture::new();
+        let output = fixture.process(&impulse);
+        assert_ne!(
+            output.get_channel_data(SampleLocation::channel(0)),
+            output.get_channel_data(SampleLocation::channel(1))
+        );
+    }
+
+    #[test]
+    fn passes_the_dry_signal() {
+        let mut fixture = Fixture::new();
+        fixture.set_parameter("wet", 0.0);
+        fixture.set_parameter("dry", 1.0);
+
+        let input = OwnedAudioBuffer::sine(FRAME_COUNT, 2, SAMPLE_RATE, 440.0, 0.5);
+        let output = fixture.process(&input);
+
+        for channel in 0..2 {
+            let location = SampleLocation::channel(channel);
+            for (a, b) in output
+                .get_channel_data(location)
+                .iter()
+                .zip(input.get_channel_data(location))
+            {
+                assert_relative_eq!(a, b);
+            }
+        }
+    }
+
+    #[test]
+    fn stays_stable_with_a_long_decay() {
+        let mut fixture = Fixture::new();
+        fixture.set_parameter("decay", 30.0);
+
+        let energy = fixture.impulse_response_energy(50);
+
+        assert!(energy.iter().all(|energy| energy.is_finite()));
+        assert!(energy[49] <= energy[1]);
+    }
+}
@@ -1,14 +1,34 @@
 use super::{sampler_event::*, sampler_processor::*};
-use crate::{commands::Id, effects::Channel, graph::DspParameters, prelude::*};
+use crate::{
+    commands::Id, effects::Channel, graph::DspNode, parameter::*, prelude::*,
+    utility::create_parameters,
+};
 
 /// A node that can play or loop a sample
+///
+/// The sample can be played at a different speed, which changes its pitch,
+/// using the playback rate and detune parameters. If the sample has a
+/// different sample rate to the context, it will be converted as it plays.
+///
+/// # Parameters
+/// - playback-rate
+/// - detune (cents)
 pub struct Sampler {
     /// The node to connect to the audio graph
     pub node: GraphNode,
     event_transmitter: EventTransmitter,
+    params: Parameters,
+}
+
+impl DspNode for Sampler {
+    fn get_parameters_mut(&mut self) -> &mut Parameters {
+        &mut self.params
+    }
 }
 
 static EVENT_CHANNEL_CAPACITY: usize = 32;
+const MAXIMUM_PLAYBACK_RATE: f64 = 16.0;
+const MAXIMUM_DETUNE: f64 = 4_800.0;
 
 impl Sampler {
     /// Create a Sampler with a specified capacity of events in the event queue
@@ -21,9 +41,24 @@ impl Sampler {
 
         let (event_transmitter, event_receiver) = Channel::bounded(capacity);
 
+        let (params, realtime_params) = create_parameters(
+            id,
+            context,
+            [
+                (
+                    "playback-rate",
+                    ParameterRange::new(1.0, 0.0, MAXIMUM_PLAYBACK_RATE),
+                ),
+                (
+                    "detune",
+                    ParameterRange::new(0.0, -MAXIMUM_DETUNE, MAXIMUM_DETUNE),
+                ),
+            ],
+        );
+
         let input_count = 0;
         let output_count = sample.channel_count();
-        let sample_rate = sample.sample_rate();
+        let sample_rate = context.get_sample_rate();
         let processor = Box::new(SamplerDspProcess::new(sample_rate, sample, event_receiver));
 
         Self {
@@ -33,9 +68,10 @@ impl Sampler {
                 input_count,
                 output_count,
                 processor,
-                DspParameters::empty(),
+                realtime_params,
             ),
             event_transmitter,
+            params,
         }
     }
 
@@ -115,6 +151,22 @@ impl Sampler {
         self.send_event(SamplerEvent::cancel_loop_at_time(cancel_time));
     }
 
+    /// Get the playback rate parameter
+    ///
+    /// This is the speed that the sample is played at, where 2.0 plays twice
+    /// as fast and an octave higher
+    pub fn playback_rate(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("playback-rate")
+    }
+
+    /// Get the detune parameter, in cents
+    ///
+    /// This is combined with the playback rate, so 1200 cents plays an octave
+    /// higher
+    pub fn detune(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("detune")
+    }
+
     fn send_event(&mut self, event: SamplerEvent) {
         debug_assert!(!self.event_transmitter.is_full());
         let _ = self.event_transmitter.send(event);
@@ -24,16 +24,18 @@ pub struct SamplerDspProcess {
     event_processor: EventProcessor<SamplerEvent>,
     sample_rate: usize,
 
-    loop_points: Option<(Timestamp, Timestamp)>,
+    loop_points: Option<(f64, f64)>,
 
-    position: Timestamp,
-    start_position_in_sample: Timestamp,
+    playhead: Option<f64>,
+    increment: f64,
     completed_loops: usize,
 }
 
 const NUM_VOICES: usize = 2;
 const FADE_LENGTH: Duration = Duration::from_millis(50);
 const MAX_PENDING_EVENTS: usize = 16;
+const INCREMENT_BLOCK_SIZE: usize = 64;
+const SUB_FRAME_RESOLUTION: f64 = 10_000.0;
 
 impl DspProcessor for SamplerDspProcess {
     fn process_audio(&mut self, context: &mut ProcessContext) {
@@ -41,17 +43,25 @@ impl DspProcessor for SamplerDspProcess {
 
         self.event_processor.receive_events();
 
+        let output_frame_count = context.output_buffer.frame_count();
+        let playback_rate = context
+            .parameters
+            .get_parameter_values("playback-rate", output_frame_count);
+        let detune = context
+            .parameters
+            .get_parameter_values("detune", output_frame_count);
+
         let mut current_time = *context.start_time;
         let mut position = 0;
 
-        while position < context.output_buffer.frame_count() {
+        while position < output_frame_count {
             let (end_frame, event) = self.event_processor.next_event(
                 context.start_time,
                 &current_time,
-                context.output_buffer.frame_count(),
+                output_frame_count,
             );
 
-            debug_assert!(end_frame <= context.output_buffer.frame_count());
+            debug_assert!(end_frame <= output_frame_count);
 
             let frame_count = end_frame - position;
 
@@ -61,7 +71,11 @@ impl DspProcessor for SamplerDspProcess {
                 frame_count,
             );
 
-            self.process_sample(&mut slice);
+            self.process_sample(
+                &mut slice,
+                &playback_rate[position..end_frame],
+                &detune[position..end_frame],
+            );
 
             position += frame_count;
             current_time = current_time.incremented_by_samples(frame_count, self.sample_rate);
@@ -93,6 +107,8 @@ impl SamplerDspProcess {
         event_receiver: EventReceiver,
         fade: Fade,
     ) -> Self {
+        let increment = buffer.sample_rate() as f64 / sample_rate as f64;
+
         Self {
             fade,
             voices: (0..NUM_VOICES).map(|_| Voice::default()).collect(),
@@ -104,8 +120,8 @@ impl SamplerDspProcess {
                 sample_rate,
             ),
             loop_points: None,
-            position: Timestamp::zero(),
-            start_position_in_sample: Timestamp::zero(),
+            playhead: None,
+            increment,
             completed_loops: 0,
             sample_rate,
         }
@@ -120,89 +136,114 @@ impl SamplerDspProcess {
         Self::new_wth_fade(sample_rate, buffer, event_receiver, Fade::bypass())
     }
 
-    fn next_loop_position(&self) -> Timestamp {
-        let (loop_start, loop_end) = match self.loop_points {
-            Some(loop_points) => loop_points,
-            None => {
-                return Timestamp::from_samples(self.buffer.frame_count() as f64, self.sample_rate)
-            }
-        };
-
-        let first_loop_length = loop_end - self.start_position_in_sample;
-        let subsequent_loop_length = loop_end - loop_start;
-
-        let sample_position = first_loop_length.as_samples(self.sample_rate)
-            + subsequent_loop_length.as_samples(self.sample_rate) * self.completed_loops as f64;
-
-        Timestamp::from_samples(sample_position, self.sample_rate)
+    /// Convert a position in the sample into a (fractional) frame in the sample
+    ///
+    /// This is rounded to remove the error from converting to and from a
+    /// fixed-point timestamp, which would otherwise build up over many loops
+    fn to_sample_frames(&self, position: Timestamp) -> f64 {
+        let frames = position.as_samples(self.buffer.sample_rate());
+        (frames * SUB_FRAME_RESOLUTION).round() / SUB_FRAME_RESOLUTION
     }
 
-    fn get_render_interval(&self, samples_remaining: usize) -> Timestamp {
-        let end_of_frame = self
-            .position
-            .incremented_by_samples(samples_remaining, self.sample_rate);
+    fn process_sample(
+        &mut self,
+        output_buffer: &mut dyn AudioBuffer,
+        playback_rate: &[f32],
+        detune: &[f32],
+    ) {
+        let sample_rate_ratio = self.buffer.sample_rate() as f64 / self.sample_rate as f64;
+        let mut increments = [0.0_f32; INCREMENT_BLOCK_SIZE];
+        let mut frame_position = 0;
 
-        let end_of_sample = self.next_loop_position();
+        while frame_position < output_buffer.frame_count() {
+            let frame_count =
+                (output_buffer.frame_count() - frame_position).min(INCREMENT_BLOCK_SIZE);
+
+            for (increment, (rate, cents)) in increments.iter_mut().zip(
+                playback_rate[frame_position..frame_position + frame_count]
+                    .iter()
+                    .zip(detune[frame_position..frame_position + frame_count].iter()),
+            ) {
+                let detune_ratio = if *cents == 0.0 {
+                    1.0
+                } else {
+                    2.0_f64.powf(*cents as f64 / 1_200.0)
+                };
+
+                *increment = (*rate as f64 * detune_ratio * sample_rate_ratio) as f32;
+            }
 
-        let end_point = if end_of_frame < end_of_sample {
-            end_of_frame
-        } else {
-            end_of_sample
-        };
+            self.increment = increments[frame_count - 1] as f64;
 
-        end_point - self.position
-    }
+            self.process_increments(
+                &mut MutableBorrowedAudioBuffer::slice_frames(
+                    output_buffer,
+                    frame_position,
+                    frame_count,
+                ),
+                &increments[..frame_count],
+            );
 
-    fn finish_sample(&mut self) {
-        if let Some((loop_start, _)) = self.loop_points {
-            self.loop_back(loop_start);
+            frame_position += frame_count;
         }
     }
 
-    fn is_looping(&self) -> bool {
-        self.loop_points.is_some()
-    }
-
-    fn process_sample(&mut self, output_buffer: &mut dyn AudioBuffer) {
+    fn process_increments(&mut self, output_buffer: &mut dyn AudioBuffer, increments: &[f32]) {
         let mut frame_position = 0;
 
         while frame_position < output_buffer.frame_count() {
-            let samples_remaining = output_buffer.frame_count() - frame_position;
+            if let (Some(playhead), Some((_, loop_end))) = (self.playhead, self.loop_points) {
+                if playhead >= loop_end {
+                    self.loop_back();
+                }
+            }
 
-            let render_interval = self.get_render_interval(samples_remaining);
-            let render_interval = render_interval.as_samples(self.sample_rate).round() as usize;
+            let render_frame_count = self
+                .frames_until_loop_end(&increments[frame_position..output_buffer.frame_count()]);
 
-            let render_frame_count = std::cmp::min(render_interval, samples_remaining);
+            self.process_voices(
+                &mut MutableBorrowedAudioBuffer::slice_frames(
+                    output_buffer,
+                    frame_position,
+                    render_frame_count,
+                ),
+                &increments[frame_position..frame_position + render_frame_count],
+            );
 
-            if render_frame_count == 0 && self.is_looping() {
-                self.finish_sample();
-                continue;
+            if let Some(playhead) = self.playhead.as_mut() {
+                *playhead += increments[frame_position..frame_position + render_frame_count]
+                    .iter()
+                    .map(|increment| *increment as f64)
+                    .sum::<f64>();
             }
 
-            if render_frame_count == 0 && !self.is_looping() {
-                break;
-            }
+            frame_position += render_frame_count;
+        }
+    }
 
-            self.process_voices(&mut MutableBorrowedAudioBuffer::slice_frames(
-                output_buffer,
-                frame_position,
-                render_frame_count,
-            ));
+    /// The number of frames that can be rendered before the playhead reaches the end of the loop
+    fn frames_until_loop_end(&self, increments: &[f32]) -> usize {
+        let (mut playhead, loop_end) = match (self.playhead, self.loop_points) {
+            (Some(playhead), Some((_, loop_end))) => (playhead, loop_end),
+            _ => return increments.len(),
+        };
 
-            frame_position += render_frame_count;
+        let mut frame_count = 0;
 
-            self.position = self
-                .position
-                .incremented_by_samples(render_frame_count, self.sample_rate);
+        while frame_count < increments.len() && playhead < loop_end {
+            playhead += increments[frame_count] as f64;
+            frame_count += 1;
         }
+
+        frame_count.max(1)
     }
 
-    fn process_voices(&mut self, output_buffer: &mut dyn AudioBuffer) {
+    fn process_voices(&mut self, output_buffer: &mut dyn AudioBuffer, increments: &[f32]) {
         let fade = &self.fade;
         let sample = &self.buffer;
         self.voices
             .iter_mut()
-            .for_each(|voice| voice.render(output_buffer, sample, fade));
+            .for_each(|voice| voice.render(output_buffer, sample, fade, increments));
     }
 
     fn process_event(&mut self, event: &SamplerEvent, current_time: &Timestamp) {
@@ -222,7 +263,14 @@ impl SamplerDspProcess {
     }
 
     fn set_loop_points(&mut self, loop_start: Timestamp, loop_end: Timestamp) {
-        self.loop_points = Some((loop_start, loop_end));
+        let loop_start = self.to_sample_frames(loop_start);
+        let loop_end = self.to_sample_frames(loop_end);
+
+        self.loop_points = if loop_start < loop_end {
+            Some((loop_start, loop_end))
+        } else {
+            None
+        };
     }
 
     fn clear_loop_points(&mut self) {
@@ -234,16 +282,14 @@ impl SamplerDspProcess {
         self.clear_loop_points();
     }
 
-    fn assign_voice(&mut self, start_position: Timestamp) {
-        let sample_position = start_position.as_samples(self.sample_rate).round() as usize;
-
+    fn assign_voice(&mut self, sample_position: f64) {
         if let Some(current_position) = self.get_active_voice_position() {
             if current_position == sample_position {
                 return;
             }
         }
 
-        self.stop();
+        self.stop_voices();
 
         if let Some((index, free_voice)) = self
             .voices
@@ -264,34 +310,76 @@ impl SamplerDspProcess {
         None
     }
 
-    fn get_active_voice_position(&self) -> Option<usize> {
+    fn get_active_voice_position(&self) -> Option<f64> {
         self.get_active_voice().map(|voice| voice.get_position())
     }
 
     fn start(&mut self, from_position: Timestamp, delay: Timestamp) {
-        self.assign_voice(from_position + delay);
+        let delay_frames = delay.as_samples(self.sample_rate) * self.increment;
+        let sample_position = self.to_sample_frames(from_position) + delay_frames;
+
+        self.assign_voice(sample_position);
         self.completed_loops = 0;
-        self.position = delay;
-        self.start_position_in_sample = from_position;
+        self.playhead = Some(sample_position);
     }
 
-    fn loop_back(&mut self, from_position: Timestamp) {
+    fn loop_back(&mut self) {
+        let (playhead, (loop_start, loop_end)) = match (self.playhead, self.loop_points) {
+            (Some(playhead), Some(loop_points)) => (playhead, loop_points),
+            _ => return,
+        };
+
+        let overshoot = (playhead - loop_end) % (loop_end - loop_start);
+        let sample_position = loop_start + overshoot;
+
         self.completed_loops += 1;
-        self.assign_voice(from_position);
+        self.assign_voice(sample_position);
+        self.playhead = Some(sample_position);
     }
 
-    fn stop(&mut self) {
+    fn stop_voices(&mut self) {
         self.voices.iter_mut().for_each(|voice| voice.stop());
         self.active_voice = None
     }
+
+    fn stop(&mut self) {
+        self.stop_voices();
+        self.playhead = None;
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{graph::DspParameters, AudioBuffer, ProcessContext, SampleLocation};
+    use crate::{
+        graph::DspParameters, parameter::RealtimeAudioParameter, AudioBuffer, ProcessContext,
+        SampleLocation,
+    };
     use approx::assert_relative_eq;
-    use std::ops::Range;
+    use atomic_float::AtomicF64;
+    use std::{ops::Range, sync::Arc};
+
+    fn create_parameters(
+        playback_rate: f64,
+        detune: f64,
+        frame_count: usize,
+        sample_rate: usize,
+    ) -> DspParameters {
+        let make_parameter = |name, value| {
+            RealtimeAudioParameter::new(name, Arc::new(AtomicF64::new(value)), frame_count)
+        };
+
+        let mut parameters = DspParameters::new([
+            make_parameter("playback-rate", playback_rate),
+            make_parameter("detune", detune),
+        ]);
+
+        for (_, parameter) in parameters.iter_mut() {
+            parameter.process(&Timestamp::zero(), frame_count, sample_rate);
+        }
+
+        parameters
+    }
 
     fn create_sample_with_value(
         frame_count: usize,
@@ -333,7 +421,8 @@ mod tests {
             input_buffer: &input_buffer,
             output_buffer: &mut output_buffer,
             start_time: &start_time,
-            parameters: &DspParameters::empty(),
+            parameters: &create_parameters(1.0, 0.0, frame_count, sample_rate),
+            midi_events: &[],
         });
 
         output_buffer
@@ -644,4 +733,149 @@ mod tests {
         expect_sample_in_range(1.0, &output, 3_000..4_000, 0);
         expect_sample_in_range(0.0, &output, 4_000..5_000, 0);
     }
+
+    fn create_ramp(frame_count: usize, sample_rate: usize) -> OwnedAudioBuffer {
+        let mut sample = OwnedAudioBuffer::new(frame_count, 1, sample_rate);
+
+        for frame in 0..frame_count {
+            sample.set_sample(SampleLocation::frame(frame), frame as f32 / 1_000.0);
+        }
+
+        sample
+    }
+
+    fn process_ramp_with_parameters(
+        sample: OwnedAudioBuffer,
+        sample_rate: usize,
+        playback_rate: f64,
+        detune: f64,
+        frame_count: usize,
+    ) -> OwnedAudioBuffer {
+        let (event_transmitter, event_receiver) = crossbeam::channel::unbounded();
+        let mut sampler = SamplerDspProcess::new_without_fade(sample_rate, sample, event_receiver);
+
+        let _ = event_transmitter.send(SamplerEvent::start_now());
+
+        let mut output_buffer = OwnedAudioBuffer::new(frame_count, 1, sample_rate);
+        let input_buffer = OwnedAudioBuffer::new(frame_count, 1, sample_rate);
+
+        sampler.process_audio(&mut ProcessContext {
+            input_buffer: &input_buffer,
+            output_buffer: &mut output_buffer,
+            start_time: &Timestamp::zero(),
+            parameters: &create_parameters(playback_rate, detune, frame_count, sample_rate),
+            midi_events: &[],
+        });
+
+        output_buffer
+    }
+
+    #[test]
+    fn plays_at_double_speed() {
+        let sample_rate = 48_000;
+        let sample = create_ramp(1_000, sample_rate);
+
+        let output = process_ramp_with_parameters(sample, sample_rate, 2.0, 0.0, 1_000);
+
+        for frame in 0..500 {
+            expect_sample(2.0 * frame as f32 / 1_000.0, &output, frame, 0);
+        }
+
+        expect_sample_in_range(0.0, &output, 500..1_000, 0);
+    }
+
+    #[test]
+    fn interpolates_when_playing_slower() {
+        let sample_rate = 48_000;
+        let sample = create_ramp(1_000, sample_rate);
+
+        let output = process_ramp_with_parameters(sample, sample_rate, 0.5, 0.0, 1_000);
+
+        // The interpolation for the second frame uses the silence before the
+        // sample, so it bends away from the ramp
+        for frame in (0..1_000).filter(|frame| *frame != 1) {
+            assert_relative_eq!(
+                0.5 * frame as f32 / 1_000.0,
+                output.get_sample(SampleLocation::frame(frame)),
+                epsilon = 1e-5
+            );
+        }
+    }
+
+    #[test]
+    fn detunes_by_an_octave() {
+        let sample_rate = 48_000;
+
+        let detuned = process_ramp_with_parameters(
+            create_ramp(1_000, sample_rate),
+            sample_rate,
+            1.0,
+            1_200.0,
+            400,
+        );
+
+        let double_speed = process_ramp_with_parameters(
+            create_ramp(1_000, sample_rate),
+            sample_rate,
+            2.0,
+            0.0,
+            400,
+        );
+
+        for frame in 0..400 {
+            assert_relative_eq!(
+                detuned.get_sample(SampleLocation::frame(frame)),
+                double_speed.get_sample(SampleLocation::frame(frame)),
+                epsilon = 1e-4
+            );
+        }
+    }
+
+    #[test]
+    fn converts_sample_rate() {
+        let sample = create_ramp(1_000, 24_000);
+
+        let output = process_ramp_with_parameters(sample, 48_000, 1.0, 0.0, 1_000);
+
+        // The interpolation for the second frame uses the silence before the
+        // sample, so it bends away from the ramp
+        for frame in (0..1_000).filter(|frame| *frame != 1) {
+            assert_relative_eq!(
+                0.5 * frame as f32 / 1_000.0,
+                output.get_sample(SampleLocation::frame(frame)),
+                epsilon = 1e-5
+            );
+        }
+    }
+
+    #[test]
+    fn loops_at_double_speed() {
+        let sample_rate = 48_000;
+        let sample = create_ramp(1_000, sample_rate);
+
+        let (event_transmitter, event_receiver) = crossbeam::channel::unbounded();
+        let mut sampler = SamplerDspProcess::new_without_fade(sample_rate, sample, event_receiver);
+
+        let _ = event_transmitter.send(SamplerEvent::start_now());
+        let _ = event_transmitter.send(SamplerEvent::enable_loop(
+            Timestamp::zero(),
+            Timestamp::from_samples(100.0, sample_rate),
+        ));
+
+        let frame_count = 200;
+        let mut output = OwnedAudioBuffer::new(frame_count, 1, sample_rate);
+
+        sampler.process_audio(&mut ProcessContext {
+            input_buffer: &OwnedAudioBuffer::new(frame_count, 1, sample_rate),
+            output_buffer: &mut output,
+            start_time: &Timestamp::zero(),
+            parameters: &create_parameters(2.0, 0.0, frame_count, sample_rate),
+            midi_events: &[],
+        });
+
+        assert_eq!(sampler.completed_loops, 3);
+        expect_sample(0.098, &output, 49, 0);
+        expect_sample(0.0, &output, 50, 0);
+        expect_sample(0.098, &output, 199, 0);
+    }
 }
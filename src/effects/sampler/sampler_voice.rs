@@ -1,26 +1,21 @@
-use crate::{AudioBuffer, SampleLocation};
+use crate::{dsp::read_cubic, AudioBuffer, SampleLocation};
 
 use super::sampler_fade::Fade;
 
 use std::cmp::min;
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Default)]
 enum Phase {
+    #[default]
     Stopped,
     FadingIn(usize),
     Playing,
     FadingOut(usize),
 }
 
-impl Default for Phase {
-    fn default() -> Self {
-        Self::Stopped
-    }
-}
-
 #[derive(Default)]
 pub struct Voice {
-    position: usize,
+    position: f64,
     phase: Phase,
 }
 
@@ -29,15 +24,16 @@ impl Voice {
         self.phase == Phase::Stopped
     }
 
-    pub fn start_from_position(&mut self, position: usize) {
+    pub fn start_from_position(&mut self, position: f64) {
         self.position = position;
-        self.phase = match position {
-            0 => Phase::Playing,
-            _ => Phase::FadingIn(0),
+        self.phase = if position == 0.0 {
+            Phase::Playing
+        } else {
+            Phase::FadingIn(0)
         };
     }
 
-    pub fn get_position(&self) -> usize {
+    pub fn get_position(&self) -> f64 {
         self.position
     }
 
@@ -50,7 +46,17 @@ impl Voice {
         }
     }
 
-    pub fn render(&mut self, output: &mut dyn AudioBuffer, sample: &dyn AudioBuffer, fade: &Fade) {
+    /// Render the voice, advancing through the sample by `increments[frame]`
+    /// for each output frame
+    pub fn render(
+        &mut self,
+        output: &mut dyn AudioBuffer,
+        sample: &dyn AudioBuffer,
+        fade: &Fade,
+        increments: &[f32],
+    ) {
+        debug_assert!(increments.len() >= output.frame_count());
+
         if self.is_stopped() {
             return;
         }
@@ -61,16 +67,20 @@ impl Voice {
             match self.phase {
                 Phase::Stopped => break,
                 Phase::FadingIn(fade_position) => {
-                    let frame_count = self.render_fade(
+                    let frame_count = min(
+                        fade.len() - fade_position,
+                        output.frame_count() - destination_offset,
+                    );
+
+                    self.render_frames(
                         output,
                         destination_offset,
+                        frame_count,
                         sample,
-                        fade,
-                        fade_position,
-                        true,
+                        increments,
+                        |frame| fade.fade_in_value(fade_position + frame),
                     );
 
-                    self.position += frame_count;
                     destination_offset += frame_count;
 
                     let fade_position = fade_position + frame_count;
@@ -82,25 +92,37 @@ impl Voice {
                     }
                 }
                 Phase::Playing => {
-                    self.render_playing(output, destination_offset, sample);
+                    let frame_count = output.frame_count() - destination_offset;
 
-                    self.position += output.frame_count() - destination_offset;
-                    destination_offset = output.frame_count();
+                    self.render_playing(
+                        output,
+                        destination_offset,
+                        frame_count,
+                        sample,
+                        increments,
+                    );
+
+                    destination_offset += frame_count;
                 }
                 Phase::FadingOut(fade_position) => {
-                    let frame_count = self.render_fade(
+                    let frame_count = min(
+                        fade.len() - fade_position,
+                        output.frame_count() - destination_offset,
+                    );
+
+                    self.render_frames(
                         output,
                         destination_offset,
+                        frame_count,
                         sample,
-                        fade,
-                        fade_position,
-                        false,
+                        increments,
+                        |frame| fade.fade_out_value(fade_position + frame),
                     );
 
-                    self.position += frame_count;
                     destination_offset += frame_count;
 
                     let fade_position = fade_position + frame_count;
+
                     if fade_position < fade.len() {
                         self.phase = Phase::FadingOut(fade_position);
                     } else {
@@ -111,79 +133,78 @@ impl Voice {
         }
     }
 
-    pub fn render_playing(
+    fn render_playing(
         &mut self,
         output: &mut dyn AudioBuffer,
         destination_offset: usize,
+        frame_count: usize,
         source: &dyn AudioBuffer,
+        increments: &[f32],
     ) {
-        let channel_count = min(source.channel_count(), output.channel_count());
-
-        if self.position >= source.frame_count() {
+        let is_original_speed = increments[destination_offset..destination_offset + frame_count]
+            .iter()
+            .all(|increment| *increment == 1.0);
+
+        if !is_original_speed || self.position.fract() != 0.0 {
+            self.render_frames(
+                output,
+                destination_offset,
+                frame_count,
+                source,
+                increments,
+                |_| 1.0,
+            );
             return;
         }
 
-        let frame_count = std::cmp::min(
-            output.frame_count() - destination_offset,
-            source.frame_count() - self.position,
-        );
+        let position = self.position as usize;
+        self.position += frame_count as f64;
 
-        let source_location = SampleLocation::frame(self.position);
-        let destination_location = SampleLocation::frame(destination_offset);
+        if position >= source.frame_count() {
+            return;
+        }
+
+        let channel_count = min(source.channel_count(), output.channel_count());
+        let frame_count = min(frame_count, source.frame_count() - position);
 
         output.add_from(
             source,
-            source_location,
-            destination_location,
+            SampleLocation::frame(position),
+            SampleLocation::frame(destination_offset),
             channel_count,
             frame_count,
         );
     }
 
-    pub fn render_fade(
+    fn render_frames(
         &mut self,
         output: &mut dyn AudioBuffer,
         destination_offset: usize,
+        frame_count: usize,
         source: &dyn AudioBuffer,
-        fade: &Fade,
-        fade_position: usize,
-        fade_in: bool,
-    ) -> usize {
+        increments: &[f32],
+        gain: impl Fn(usize) -> f32,
+    ) {
         let channel_count = min(source.channel_count(), output.channel_count());
 
-        let frame_count = std::cmp::min(
-            fade.len() - fade_position,
-            output.frame_count() - destination_offset,
-        );
-
         for frame in 0..frame_count {
-            if frame + self.position >= source.frame_count() {
-                break;
-            }
-
-            let fade_value = if fade_in {
-                fade.fade_in_value(fade_position + frame)
-            } else {
-                fade.fade_out_value(fade_position + frame)
-            };
+            let destination_frame = destination_offset + frame;
 
-            for channel in 0..channel_count {
-                let source_location = SampleLocation {
-                    channel,
-                    frame: self.position + frame,
-                };
+            if self.position < source.frame_count() as f64 {
+                let gain = gain(frame);
 
-                let dest_location = SampleLocation {
-                    channel,
-                    frame: destination_offset + frame,
-                };
+                for channel in 0..channel_count {
+                    let source_data = source.get_channel_data(SampleLocation::channel(channel));
+                    let value = read_cubic(source_data, self.position);
 
-                let sample = output.get_sample(dest_location)
-                    + fade_value * source.get_sample(source_location);
-                output.set_sample(dest_location, sample);
+                    output.add_sample(
+                        SampleLocation::new(channel, destination_frame),
+                        gain * value,
+                    );
+                }
             }
-        }
 
-        frame_count
+            self.position += increments[destination_frame] as f64;
+        }
     }
 }
@@ -0,0 +1,169 @@
+use super::sample_zone::SampleZone;
+use crate::{dsp::read_cubic, effects::adsr::AdsrEnvelope, prelude::*};
+use std::time::Duration;
+
+/// The end of a note that was interrupted by voice stealing, which is faded
+/// out quickly to avoid a click
+struct VoiceTail {
+    zone: usize,
+    position: f64,
+    increment: f64,
+    gain: f32,
+    remaining: usize,
+}
+
+pub struct InstrumentVoice {
+    note: u8,
+    zone: usize,
+    position: f64,
+    increment: f64,
+    gain: f32,
+    envelope: AdsrEnvelope,
+    start_order: usize,
+    is_active: bool,
+    tail: Option<VoiceTail>,
+    steal_fade_length: usize,
+}
+
+const STEAL_FADE_LENGTH: Duration = Duration::from_millis(5);
+
+impl InstrumentVoice {
+    pub fn new(sample_rate: usize, envelope: AdsrEnvelope) -> Self {
+        Self {
+            note: 0,
+            zone: 0,
+            position: 0.0,
+            increment: 1.0,
+            gain: 0.0,
+            envelope,
+            start_order: 0,
+            is_active: false,
+            tail: None,
+            steal_fade_length: (STEAL_FADE_LENGTH.as_secs_f64() * sample_rate as f64).ceil()
+                as usize,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    pub fn is_holding(&self, note: u8) -> bool {
+        self.is_active && self.note == note && !self.envelope.is_releasing()
+    }
+
+    pub fn start_order(&self) -> usize {
+        self.start_order
+    }
+
+    /// The current output level of the voice
+    pub fn level(&self) -> f32 {
+        self.gain * self.envelope.value() as f32
+    }
+
+    pub fn envelope_mut(&mut self) -> &mut AdsrEnvelope {
+        &mut self.envelope
+    }
+
+    pub fn start(&mut self, note: u8, gain: f32, zone: usize, increment: f64, start_order: usize) {
+        if self.is_active {
+            self.tail = Some(VoiceTail {
+                zone: self.zone,
+                position: self.position,
+                increment: self.increment,
+                gain: self.level(),
+                remaining: self.steal_fade_length,
+            });
+        }
+
+        self.note = note;
+        self.zone = zone;
+        self.position = 0.0;
+        self.increment = increment;
+        self.gain = gain;
+        self.start_order = start_order;
+        self.is_active = true;
+
+        self.envelope.reset();
+        self.envelope.open();
+    }
+
+    pub fn release(&mut self) {
+        self.envelope.close();
+    }
+
+    pub fn render(&mut self, output: &mut dyn AudioBuffer, zones: &[SampleZone]) {
+        self.render_tail(output, zones);
+
+        if !self.is_active {
+            return;
+        }
+
+        let sample = &zones[self.zone].sample;
+
+        for frame in 0..output.frame_count() {
+            let envelope = self.envelope.process() as f32;
+
+            if self.position >= sample.frame_count() as f64 || self.envelope.is_idle() {
+                self.is_active = false;
+                break;
+            }
+
+            add_frame(output, frame, sample, self.position, self.gain * envelope);
+
+            self.position += self.increment;
+        }
+    }
+
+    fn render_tail(&mut self, output: &mut dyn AudioBuffer, zones: &[SampleZone]) {
+        let tail = match self.tail.as_mut() {
+            Some(tail) => tail,
+            None => return,
+        };
+
+        let sample = &zones[tail.zone].sample;
+        let frame_count = output.frame_count().min(tail.remaining);
+
+        for frame in 0..frame_count {
+            if tail.position >= sample.frame_count() as f64 {
+                break;
+            }
+
+            let fade = (tail.remaining - frame) as f32 / self.steal_fade_length as f32;
+            add_frame(output, frame, sample, tail.position, tail.gain * fade);
+
+            tail.position += tail.increment;
+        }
+
+        tail.remaining -= frame_count;
+
+        if tail.remaining == 0 {
+            self.tail = None;
+        }
+    }
+}
+
+/// Mix a frame of the sample into the output
+///
+/// Mono samples are played on all of the output channels
+fn add_frame(
+    output: &mut dyn AudioBuffer,
+    frame: usize,
+    sample: &dyn AudioBuffer,
+    position: f64,
+    gain: f32,
+) {
+    if sample.channel_count() == 0 {
+        return;
+    }
+
+    for channel in 0..output.channel_count() {
+        let source_channel = channel.min(sample.channel_count() - 1);
+        let source = sample.get_channel_data(SampleLocation::channel(source_channel));
+
+        output.add_sample(
+            SampleLocation::new(channel, frame),
+            gain * read_cubic(source, position),
+        );
+    }
+}
@@ -0,0 +1,8 @@
+mod instrument_voice;
+mod sample_zone;
+mod sampler_instrument_event;
+mod sampler_instrument_node;
+mod sampler_instrument_processor;
+
+pub use sample_zone::SampleZone;
+pub use sampler_instrument_node::SamplerInstrument;
@@ -0,0 +1,45 @@
+use crate::OwnedAudioBuffer;
+use std::ops::RangeInclusive;
+
+/// A sample that is played for a range of MIDI notes and velocities
+///
+/// The sample is pitched relative to its root note, so playing the note above
+/// the root note will play the sample a semitone higher.
+///
+/// If more than one zone matches a note and velocity, the zones take turns to
+/// be played (round-robin), in the order that they were given to the
+/// instrument.
+pub struct SampleZone {
+    pub(super) sample: OwnedAudioBuffer,
+    pub(super) root_note: u8,
+    pub(super) notes: RangeInclusive<u8>,
+    pub(super) velocities: RangeInclusive<u8>,
+}
+
+impl SampleZone {
+    /// Create a zone that plays `sample` for all notes and velocities
+    pub fn new(sample: OwnedAudioBuffer, root_note: u8) -> Self {
+        Self {
+            sample,
+            root_note,
+            notes: 0..=127,
+            velocities: 1..=127,
+        }
+    }
+
+    /// Only play the zone for notes in the range (inclusive)
+    pub fn with_note_range(mut self, lowest_note: u8, highest_note: u8) -> Self {
+        self.notes = lowest_note..=highest_note;
+        self
+    }
+
+    /// Only play the zone for velocities in the range (inclusive)
+    pub fn with_velocity_range(mut self, lowest_velocity: u8, highest_velocity: u8) -> Self {
+        self.velocities = lowest_velocity..=highest_velocity;
+        self
+    }
+
+    pub(super) fn matches(&self, note: u8, velocity: u8) -> bool {
+        self.notes.contains(&note) && self.velocities.contains(&velocity)
+    }
+}
@@ -0,0 +1,170 @@
+use super::{
+    sample_zone::SampleZone,
+    sampler_instrument_event::{SamplerInstrumentEvent, SamplerInstrumentEventType},
+    sampler_instrument_processor::*,
+};
+use crate::{
+    commands::Id,
+    effects::{utility::VoiceStealing, Channel},
+    graph::DspParameters,
+    prelude::*,
+};
+use std::time::Duration;
+
+/// A polyphonic instrument that plays samples in response to notes
+///
+/// Each note is played using the zones that match its note number and
+/// velocity. The sample is pitched relative to the zone's root note, and its
+/// amplitude follows an ADSR envelope that is shared by all voices.
+///
+/// When all of the voices are in use, a new note will take a voice according
+/// to the [`VoiceStealing`] policy.
+///
+/// Notes can also be played by sending MIDI to the node with
+/// [GraphNode::send_midi_event]. Note on and note off messages are handled on
+/// all channels, along with the 'all notes off' and 'all sound off'
+/// controllers.
+pub struct SamplerInstrument {
+    /// The node to connect to the audio graph
+    pub node: GraphNode,
+    event_transmitter: EventTransmitter,
+}
+
+static EVENT_CHANNEL_CAPACITY: usize = 256;
+const DEFAULT_POLYPHONY: usize = 16;
+
+impl SamplerInstrument {
+    /// Create a new instrument with the default polyphony, which steals the
+    /// oldest voice when all voices are in use
+    pub fn new(context: &dyn Context, channel_count: usize, zones: Vec<SampleZone>) -> Self {
+        Self::new_with_polyphony(
+            context,
+            channel_count,
+            zones,
+            DEFAULT_POLYPHONY,
+            VoiceStealing::default(),
+        )
+    }
+
+    /// Create a new instrument that can play up to `polyphony` notes at once
+    pub fn new_with_polyphony(
+        context: &dyn Context,
+        channel_count: usize,
+        zones: Vec<SampleZone>,
+        polyphony: usize,
+        voice_stealing: VoiceStealing,
+    ) -> Self {
+        let id = Id::generate();
+
+        let (event_transmitter, event_receiver) = Channel::bounded(EVENT_CHANNEL_CAPACITY);
+
+        let processor = Box::new(SamplerInstrumentProcessor::new(
+            context.get_sample_rate(),
+            zones,
+            polyphony,
+            voice_stealing,
+            event_receiver,
+        ));
+
+        Self {
+            node: GraphNode::new(
+                id,
+                context,
+                0,
+                channel_count,
+                processor,
+                DspParameters::empty(),
+            ),
+            event_transmitter,
+        }
+    }
+
+    /// Play a note at a particular time
+    ///
+    /// A velocity of zero is treated as a note off
+    pub fn note_on_at_time(&mut self, time: Timestamp, note: u8, velocity: u8) {
+        self.send_event(time, SamplerInstrumentEventType::NoteOn { note, velocity });
+    }
+
+    /// Release a note at a particular time
+    pub fn note_off_at_time(&mut self, time: Timestamp, note: u8) {
+        self.send_event(time, SamplerInstrumentEventType::NoteOff { note });
+    }
+
+    /// Play a note now
+    pub fn note_on(&mut self, note: u8, velocity: u8) {
+        self.note_on_at_time(Timestamp::zero(), note, velocity);
+    }
+
+    /// Release a note now
+    pub fn note_off(&mut self, note: u8) {
+        self.note_off_at_time(Timestamp::zero(), note);
+    }
+
+    /// Release all of the notes that are playing
+    pub fn all_notes_off(&mut self) {
+        self.send_event(Timestamp::zero(), SamplerInstrumentEventType::AllNotesOff);
+    }
+
+    /// Set the attack time
+    ///
+    /// This is the time from the note on until the gain reaches unity
+    pub fn set_attack_time(&mut self, attack_time: Duration) {
+        self.send_event(
+            Timestamp::zero(),
+            SamplerInstrumentEventType::SetAttack(attack_time),
+        );
+    }
+
+    /// Set the decay time
+    ///
+    /// This is the time after the end of the attack time to reach the steady-state sustain level
+    pub fn set_decay_time(&mut self, decay_time: Duration) {
+        self.send_event(
+            Timestamp::zero(),
+            SamplerInstrumentEventType::SetDecay(decay_time),
+        );
+    }
+
+    /// Set the sustain level
+    ///
+    /// This is the level that will be sustained after the attack and decay phase
+    /// until a note off
+    pub fn set_sustain_level(&mut self, sustain_level: Level) {
+        self.send_event(
+            Timestamp::zero(),
+            SamplerInstrumentEventType::SetSustain(sustain_level),
+        );
+    }
+
+    /// Set the release time
+    ///
+    /// This is the time after a note off until gain of 0 is reached
+    pub fn set_release_time(&mut self, release_time: Duration) {
+        self.send_event(
+            Timestamp::zero(),
+            SamplerInstrumentEventType::SetRelease(release_time),
+        );
+    }
+
+    /// Convenience method to set the attack, decay, sustain, and release in one go
+    pub fn set_adsr(
+        &mut self,
+        attack_time: Duration,
+        decay_time: Duration,
+        sustain_level: Level,
+        release_time: Duration,
+    ) {
+        self.set_attack_time(attack_time);
+        self.set_decay_time(decay_time);
+        self.set_sustain_level(sustain_level);
+        self.set_release_time(release_time);
+    }
+
+    fn send_event(&mut self, time: Timestamp, event_type: SamplerInstrumentEventType) {
+        debug_assert!(!self.event_transmitter.is_full());
+        let _ = self
+            .event_transmitter
+            .send(SamplerInstrumentEvent::new(time, event_type));
+    }
+}
@@ -0,0 +1,617 @@
+use std::time::Duration;
+
+use crate::{
+    effects::{adsr::AdsrEnvelope, utility::*, Channel},
+    graph::DspProcessor,
+    midi::{MidiBlockEvent, MidiMessage},
+    prelude::*,
+    ProcessContext,
+};
+
+use super::{
+    instrument_voice::InstrumentVoice,
+    sample_zone::SampleZone,
+    sampler_instrument_event::{SamplerInstrumentEvent, SamplerInstrumentEventType},
+};
+
+pub type EventReceiver = Channel::Receiver<SamplerInstrumentEvent>;
+pub type EventTransmitter = Channel::Sender<SamplerInstrumentEvent>;
+
+pub struct SamplerInstrumentProcessor {
+    zones: Vec<SampleZone>,
+    voices: Vec<InstrumentVoice>,
+    voice_stealing: VoiceStealing,
+    round_robin_counters: [usize; NOTE_COUNT],
+    next_start_order: usize,
+    event_processor: EventProcessor<SamplerInstrumentEvent>,
+    sample_rate: usize,
+}
+
+const NOTE_COUNT: usize = 128;
+const ALL_SOUND_OFF: u8 = 120;
+const ALL_NOTES_OFF: u8 = 123;
+const MAX_PENDING_EVENTS: usize = 256;
+pub const DEFAULT_RELEASE_TIME: Duration = Duration::from_millis(10);
+
+impl DspProcessor for SamplerInstrumentProcessor {
+    fn process_audio(&mut self, context: &mut ProcessContext) {
+        debug_assert_eq!(self.sample_rate, context.output_buffer.sample_rate());
+
+        self.event_processor.receive_events();
+
+        let output_frame_count = context.output_buffer.frame_count();
+        let midi_events = context.midi_events;
+        let mut midi_index = 0;
+
+        let mut current_time = *context.start_time;
+        let mut position = 0;
+
+        while position < output_frame_count {
+            let (end_frame, event) = self.event_processor.next_event(
+                context.start_time,
+                &current_time,
+                output_frame_count,
+            );
+
+            debug_assert!(end_frame <= output_frame_count);
+
+            let frame_count = end_frame - position;
+
+            self.render(
+                context.output_buffer,
+                position,
+                end_frame,
+                midi_events,
+                &mut midi_index,
+            );
+
+            position += frame_count;
+            current_time = current_time.incremented_by_samples(frame_count, self.sample_rate);
+
+            if let Some(event) = event {
+                self.process_event(&event);
+            }
+        }
+
+        for event in &midi_events[midi_index..] {
+            self.process_midi(&event.message);
+        }
+    }
+}
+
+impl SamplerInstrumentProcessor {
+    pub fn new(
+        sample_rate: usize,
+        zones: Vec<SampleZone>,
+        polyphony: usize,
+        voice_stealing: VoiceStealing,
+        event_receiver: EventReceiver,
+    ) -> Self {
+        let voices = (0..polyphony)
+            .map(|_| {
+                InstrumentVoice::new(
+                    sample_rate,
+                    AdsrEnvelope::new(
+                        sample_rate,
+                        Duration::ZERO,
+                        Duration::ZERO,
+                        Level::unity(),
+                        DEFAULT_RELEASE_TIME,
+                    ),
+                )
+            })
+            .collect();
+
+        Self {
+            zones,
+            voices,
+            voice_stealing,
+            round_robin_counters: [0; NOTE_COUNT],
+            next_start_order: 0,
+            event_processor: EventProcessor::with_capacity(
+                MAX_PENDING_EVENTS,
+                event_receiver,
+                sample_rate,
+            ),
+            sample_rate,
+        }
+    }
+
+    /// Render the voices from `start_frame` to `end_frame`, handling any MIDI
+    /// messages that happen in between
+    fn render(
+        &mut self,
+        output: &mut dyn AudioBuffer,
+        start_frame: usize,
+        end_frame: usize,
+        midi_events: &[MidiBlockEvent],
+        midi_index: &mut usize,
+    ) {
+        let mut position = start_frame;
+
+        while position < end_frame {
+            while let Some(event) = midi_events
+                .get(*midi_index)
+                .filter(|event| event.frame <= position)
+            {
+                self.process_midi(&event.message);
+                *midi_index += 1;
+            }
+
+            let next_frame = midi_events
+                .get(*midi_index)
+                .map_or(end_frame, |event| event.frame.min(end_frame));
+
+            let mut slice =
+                MutableBorrowedAudioBuffer::slice_frames(output, position, next_frame - position);
+
+            for voice in self.voices.iter_mut() {
+                voice.render(&mut slice, &self.zones);
+            }
+
+            position = next_frame;
+        }
+    }
+
+    fn process_midi(&mut self, message: &MidiMessage) {
+        match *message {
+            MidiMessage::NoteOn { note, velocity, .. } => self.note_on(note, velocity),
+            MidiMessage::NoteOff { note, .. } => self.note_off(note),
+            MidiMessage::ControlChange {
+                controller: ALL_SOUND_OFF | ALL_NOTES_OFF,
+                ..
+            } => self.all_notes_off(),
+            _ => (),
+        }
+    }
+
+    fn all_notes_off(&mut self) {
+        self.voices.iter_mut().for_each(|voice| voice.release());
+    }
+
+    fn process_event(&mut self, event: &SamplerInstrumentEvent) {
+        match event.get_event_type() {
+            SamplerInstrumentEventType::NoteOn { note, velocity } => self.note_on(*note, *velocity),
+            SamplerInstrumentEventType::NoteOff { note } => self.note_off(*note),
+            SamplerInstrumentEventType::AllNotesOff => self.all_notes_off(),
+            SamplerInstrumentEventType::SetAttack(attack_time) => {
+                self.update_envelopes(|envelope| envelope.set_attack_time(*attack_time))
+            }
+            SamplerInstrumentEventType::SetDecay(decay_time) => {
+                self.update_envelopes(|envelope| envelope.set_decay_time(*decay_time))
+            }
+            SamplerInstrumentEventType::SetSustain(sustain_level) => {
+                self.update_envelopes(|envelope| envelope.set_sustain_level(*sustain_level))
+            }
+            SamplerInstrumentEventType::SetRelease(release_time) => {
+                self.update_envelopes(|envelope| envelope.set_release_time(*release_time))
+            }
+        }
+    }
+
+    fn update_envelopes(&mut self, update: impl Fn(&mut AdsrEnvelope)) {
+        for voice in self.voices.iter_mut() {
+            update(voice.envelope_mut());
+        }
+    }
+
+    fn note_on(&mut self, note: u8, velocity: u8) {
+        if velocity == 0 {
+            self.note_off(note);
+            return;
+        }
+
+        let note_index = note as usize % NOTE_COUNT;
+
+        let zone_count = self
+            .zones
+            .iter()
+            .filter(|zone| zone.matches(note, velocity))
+            .count();
+
+        if zone_count == 0 {
+            return;
+        }
+
+        let round_robin = self.round_robin_counters[note_index] % zone_count;
+        self.round_robin_counters[note_index] = (round_robin + 1) % zone_count;
+
+        let (zone_index, zone) = match self
+            .zones
+            .iter()
+            .enumerate()
+            .filter(|(_, zone)| zone.matches(note, velocity))
+            .nth(round_robin)
+        {
+            Some(zone) => zone,
+            None => return,
+        };
+
+        let semitones = note as f64 - zone.root_note as f64;
+        let increment = 2.0_f64.powf(semitones / 12.0) * zone.sample.sample_rate() as f64
+            / self.sample_rate as f64;
+
+        let voice_index = match self.allocate_voice() {
+            Some(index) => index,
+            None => return,
+        };
+
+        let start_order = self.next_start_order;
+        self.next_start_order += 1;
+
+        self.voices[voice_index].start(
+            note,
+            velocity as f32 / 127.0,
+            zone_index,
+            increment,
+            start_order,
+        );
+    }
+
+    fn note_off(&mut self, note: u8) {
+        self.voices
+            .iter_mut()
+            .filter(|voice| voice.is_holding(note))
+            .for_each(|voice| voice.release());
+    }
+
+    fn allocate_voice(&self) -> Option<usize> {
+        if let Some(index) = self.voices.iter().position(|voice| !voice.is_active()) {
+            return Some(index);
+        }
+
+        let voices = self.voices.iter().enumerate();
+
+        match self.voice_stealing {
+            VoiceStealing::None => None,
+            VoiceStealing::Oldest => voices
+                .min_by_key(|(_, voice)| voice.start_order())
+                .map(|(index, _)| index),
+            VoiceStealing::Quietest => voices
+                .min_by(|(_, a), (_, b)| a.level().total_cmp(&b.level()))
+                .map(|(index, _)| index),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{graph::DspParameters, AudioBuffer, SampleLocation};
+    use approx::assert_relative_eq;
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    struct Fixture {
+        processor: SamplerInstrumentProcessor,
+        event_transmitter: EventTransmitter,
+    }
+
+    impl Fixture {
+        fn new(zones: Vec<SampleZone>, polyphony: usize, voice_stealing: VoiceStealing) -> Self {
+            let (event_transmitter, event_receiver) = crossbeam::channel::unbounded();
+
+            Self {
+                processor: SamplerInstrumentProcessor::new(
+                    SAMPLE_RATE,
+                    zones,
+                    polyphony,
+                    voice_stealing,
+                    event_receiver,
+                ),
+                event_transmitter,
+            }
+        }
+
+        fn send(&self, time: f64, event_type: SamplerInstrumentEventType) {
+            let _ = self.event_transmitter.send(SamplerInstrumentEvent::new(
+                Timestamp::from_seconds(time),
+                event_type,
+            ));
+        }
+
+        fn note_on(&self, time: f64, note: u8, velocity: u8) {
+            self.send(time, SamplerInstrumentEventType::NoteOn { note, velocity });
+        }
+
+        fn note_off(&self, time: f64, note: u8) {
+            self.send(time, SamplerInstrumentEventType::NoteOff { note });
+        }
+
+        fn process(&mut self, start_time: f64, frame_count: usize) -> OwnedAudioBuffer {
+            self.process_with_midi(start_time, frame_count, &[])
+        }
+
+        fn process_with_midi(
+            &mut self,
+            start_time: f64,
+            frame_count: usize,
+            midi_events: &[MidiBlockEvent],
+        ) -> OwnedAudioBuffer {
+            let mut output_buffer = OwnedAudioBuffer::new(frame_count, 2, SAMPLE_RATE);
+            let input_buffer = OwnedAudioBuffer::new(frame_count, 2, SAMPLE_RATE);
+
+            self.processor.process_audio(&mut ProcessContext {
+                input_buffer: &input_buffer,
+                output_buffer: &mut output_buffer,
+                start_time: &Timestamp::from_seconds(start_time),
+                parameters: &DspParameters::empty(),
+                midi_events,
+            });
+
+            output_buffer
+        }
+
+        fn active_voice_count(&self) -> usize {
+            self.processor
+                .voices
+                .iter()
+                .filter(|voice| voice.is_active())
+                .count()
+        }
+    }
+
+    fn constant_zone(value: f32, root_note: u8) -> SampleZone {
+        let mut sample = OwnedAudioBuffer::new(SAMPLE_RATE, 1, SAMPLE_RATE);
+        sample.fill_with_value(value);
+        SampleZone::new(sample, root_note)
+    }
+
+    fn ramp_zone(root_note: u8) -> SampleZone {
+        let mut sample = OwnedAudioBuffer::new(SAMPLE_RATE, 1, SAMPLE_RATE);
+        let data = sample.get_channel_data_mut(SampleLocation::channel(0));
+
+        for (frame, value) in data.iter_mut().enumerate() {
+            *value = frame as f32 / SAMPLE_RATE as f32;
+        }
+
+        SampleZone::new(sample, root_note)
+    }
+
+    fn sample_at(buffer: &OwnedAudioBuffer, frame: usize, channel: usize) -> f32 {
+        buffer.get_sample(SampleLocation::new(channel, frame))
+    }
+
+    #[test]
+    fn plays_the_zone_for_the_note() {
+        let mut fixture = Fixture::new(
+            vec![
+                constant_zone(0.25, 48).with_note_range(0, 59),
+                constant_zone(0.5, 72).with_note_range(60, 127),
+            ],
+            4,
+            VoiceStealing::Oldest,
+        );
+
+        fixture.note_on(0.0, 40, 127);
+        let output = fixture.process(0.0, 128);
+        assert_relative_eq!(sample_at(&output, 64, 0), 0.25, epsilon = 1e-5);
+
+        fixture.send(0.0, SamplerInstrumentEventType::AllNotesOff);
+        fixture.process(0.0, SAMPLE_RATE / 10);
+
+        fixture.note_on(0.0, 80, 127);
+        let output = fixture.process(0.0, 128);
+        assert_relative_eq!(sample_at(&output, 64, 0), 0.5, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn plays_the_zone_for_the_velocity() {
+        let mut fixture = Fixture::new(
+            vec![
+                constant_zone(0.25, 60).with_velocity_range(1, 63),
+                constant_zone(0.5, 60).with_velocity_range(64, 127),
+            ],
+            4,
+            VoiceStealing::Oldest,
+        );
+
+        fixture.note_on(0.0, 60, 127);
+        let output = fixture.process(0.0, 128);
+        assert_relative_eq!(sample_at(&output, 64, 0), 0.5, epsilon = 1e-5);
+
+        fixture.note_on(0.0, 62, 32);
+        let output = fixture.process(0.0, 128);
+        let expected = 0.5 + 0.25 * 32.0 / 127.0;
+        assert_relative_eq!(sample_at(&output, 64, 0), expected, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn ignores_notes_without_a_zone() {
+        let mut fixture = Fixture::new(
+            vec![constant_zone(0.5, 60).with_note_range(60, 72)],
+            4,
+            VoiceStealing::Oldest,
+        );
+
+        fixture.note_on(0.0, 20, 127);
+        let output = fixture.process(0.0, 128);
+
+        assert_eq!(fixture.active_voice_count(), 0);
+        assert_relative_eq!(sample_at(&output, 64, 0), 0.0);
+    }
+
+    #[test]
+    fn plays_mono_samples_on_all_channels() {
+        let mut fixture = Fixture::new(vec![constant_zone(0.5, 60)], 4, VoiceStealing::Oldest);
+
+        fixture.note_on(0.0, 60, 127);
+        let output = fixture.process(0.0, 128);
+
+        assert_relative_eq!(sample_at(&output, 64, 0), 0.5, epsilon = 1e-5);
+        assert_relative_eq!(sample_at(&output, 64, 1), 0.5, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn pitches_relative_to_the_root_note() {
+        let mut fixture = Fixture::new(vec![ramp_zone(60)], 4, VoiceStealing::Oldest);
+
+        fixture.note_on(0.0, 72, 127);
+        let output = fixture.process(0.0, 128);
+
+        for frame in 4..128 {
+            let expected = 2.0 * frame as f32 / SAMPLE_RATE as f32;
+            assert_relative_eq!(sample_at(&output, frame, 0), expected, epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn alternates_between_round_robin_zones() {
+        let mut fixture = Fixture::new(
+            vec![constant_zone(0.25, 60), constant_zone(0.5, 60)],
+            1,
+            VoiceStealing::Oldest,
+        );
+
+        let mut time = 0.0;
+
+        for expected in [0.25, 0.5, 0.25] {
+            fixture.note_on(time, 60, 127);
+            fixture.note_off(time + 0.1, 60);
+
+            let output = fixture.process(time, SAMPLE_RATE / 4);
+            assert_relative_eq!(sample_at(&output, 1_000, 0), expected, epsilon = 1e-5);
+
+            time += 0.25;
+        }
+    }
+
+    #[test]
+    fn starts_notes_at_their_time() {
+        let mut fixture = Fixture::new(vec![constant_zone(0.5, 60)], 4, VoiceStealing::Oldest);
+
+        fixture.note_on(0.001, 60, 127);
+        let output = fixture.process(0.0, 128);
+
+        assert_relative_eq!(sample_at(&output, 40, 0), 0.0);
+        assert_relative_eq!(sample_at(&output, 60, 0), 0.5, epsilon = 1e-5);
+    }
+
+    #[test]
+    fn releases_notes() {
+        let mut fixture = Fixture::new(vec![constant_zone(0.5, 60)], 4, VoiceStealing::Oldest);
+
+        fixture.send(
+            0.0,
+            SamplerInstrumentEventType::SetRelease(Duration::from_millis(100)),
+        );
+        fixture.note_on(0.0, 60, 127);
+        fixture.note_off(0.1, 60);
+
+        let output = fixture.process(0.0, SAMPLE_RATE / 10 + SAMPLE_RATE / 20);
+        let releasing = sample_at(&output, SAMPLE_RATE / 10 + SAMPLE_RATE / 20 - 1, 0);
+        assert!(releasing > 0.0 && releasing < 0.5);
+        assert_eq!(fixture.active_voice_count(), 1);
+
+        let output = fixture.process(0.15, SAMPLE_RATE / 10);
+        assert_relative_eq!(sample_at(&output, SAMPLE_RATE / 10 - 1, 0), 0.0);
+        assert_eq!(fixture.active_voice_count(), 0);
+    }
+
+    #[test]
+    fn steals_the_oldest_voice() {
+        let mut fixture = Fixture::new(vec![constant_zone(0.5, 60)], 2, VoiceStealing::Oldest);
+
+        fixture.note_on(0.0, 60, 127);
+        fixture.note_on(0.0, 62, 127);
+        fixture.process(0.0, 128);
+
+        fixture.note_on(0.0, 64, 127);
+        fixture.process(0.0, 128);
+        assert_eq!(fixture.active_voice_count(), 2);
+
+        let holding = |fixture: &Fixture, note| {
+            fixture
+                .processor
+                .voices
+                .iter()
+                .any(|voice| voice.is_holding(note))
+        };
+
+        assert!(!holding(&fixture, 60));
+        assert!(holding(&fixture, 62));
+        assert!(holding(&fixture, 64));
+    }
+
+    #[test]
+    fn fades_out_stolen_voices() {
+        let mut fixture = Fixture::new(vec![constant_zone(0.5, 60)], 1, VoiceStealing::Oldest);
+
+        fixture.note_on(0.0, 60, 127);
+        fixture.process(0.0, 128);
+
+        fixture.note_on(0.0, 62, 127);
+        let output = fixture.process(0.0, SAMPLE_RATE / 50);
+
+        assert!(sample_at(&output, 0, 0) > 0.5);
+        assert_relative_eq!(
+            sample_at(&output, SAMPLE_RATE / 50 - 1, 0),
+            0.5,
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn steals_the_quietest_voice() {
+        let mut fixture = Fixture::new(vec![constant_zone(0.5, 60)], 2, VoiceStealing::Quietest);
+
+        fixture.note_on(0.0, 60, 127);
+        fixture.note_on(0.0, 62, 20);
+        fixture.process(0.0, 128);
+
+        fixture.note_on(0.0, 64, 127);
+        fixture.process(0.0, 128);
+
+        let voices = &fixture.processor.voices;
+        assert!(voices.iter().any(|voice| voice.is_holding(60)));
+        assert!(!voices.iter().any(|voice| voice.is_holding(62)));
+        assert!(voices.iter().any(|voice| voice.is_holding(64)));
+    }
+
+    #[test]
+    fn ignores_notes_when_stealing_is_disabled() {
+        let mut fixture = Fixture::new(vec![constant_zone(0.5, 60)], 1, VoiceStealing::None);
+
+        fixture.note_on(0.0, 60, 127);
+        fixture.note_on(0.0, 62, 127);
+        fixture.process(0.0, 128);
+
+        let voices = &fixture.processor.voices;
+        assert!(voices.iter().any(|voice| voice.is_holding(60)));
+        assert!(!voices.iter().any(|voice| voice.is_holding(62)));
+    }
+
+    #[test]
+    fn plays_notes_from_midi_events() {
+        let mut fixture = Fixture::new(vec![constant_zone(0.5, 60)], 4, VoiceStealing::Oldest);
+
+        let midi_events = [
+            MidiBlockEvent {
+                frame: 32,
+                message: MidiMessage::NoteOn {
+                    channel: 0,
+                    note: 60,
+                    velocity: 127,
+                },
+            },
+            MidiBlockEvent {
+                frame: 96,
+                message: MidiMessage::ControlChange {
+                    channel: 0,
+                    controller: ALL_NOTES_OFF,
+                    value: 0,
+                },
+            },
+        ];
+
+        let output = fixture.process_with_midi(0.0, 128, &midi_events);
+
+        assert_relative_eq!(sample_at(&output, 31, 0), 0.0);
+        assert_relative_eq!(sample_at(&output, 32, 0), 0.5, epsilon = 1e-5);
+        assert_relative_eq!(sample_at(&output, 95, 0), 0.5, epsilon = 1e-5);
+        assert!(sample_at(&output, 127, 0) < 0.5);
+
+        fixture.process(0.0, SAMPLE_RATE / 10);
+        assert_eq!(fixture.active_voice_count(), 0);
+    }
+}
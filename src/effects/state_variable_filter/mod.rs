@@ -0,0 +1,6 @@
+mod state_variable_filter_node;
+mod state_variable_filter_output;
+mod state_variable_filter_processor;
+
+pub use state_variable_filter_node::StateVariableFilter;
+pub use state_variable_filter_output::StateVariableFilterOutput;
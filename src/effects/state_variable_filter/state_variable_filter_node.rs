@@ -0,0 +1,191 @@
+use super::{
+    state_variable_filter_processor::StateVariableFilterProcessor, StateVariableFilterOutput,
+};
+use crate::{
+    commands::Id, dsp::integrator_response_terms, effects::biquad::write_frequency_response,
+    graph::DspNode, parameter::*, prelude::*, utility::create_parameters,
+};
+
+/// A state variable filter, with low pass, high pass, band pass and notch
+/// outputs
+///
+/// The filter uses zero-delay feedback, so it stays stable when the frequency
+/// and Q are modulated at audio rate, for example by connecting an
+/// [crate::Oscillator] to the frequency parameter.
+///
+/// By default all four outputs are produced at once. The node has
+/// `channel_count` inputs and `4 * channel_count` outputs, with the outputs for
+/// each [StateVariableFilterOutput] grouped together. Use
+/// [StateVariableFilter::first_output_channel] with
+/// [GraphNode::connect_channels_to] to connect a single output. The engine
+/// must be created with a maximum channel count of at least
+/// `4 * channel_count` (see [EngineOptions::with_maximum_channel_count]), or
+/// use [StateVariableFilter::with_outputs] to produce only some of the
+/// outputs.
+///
+/// # Parameters
+/// - frequency
+/// - q
+/// - gain
+pub struct StateVariableFilter {
+    /// The node to connect into the audio graph
+    pub node: GraphNode,
+
+    parameters: Parameters,
+    channel_count: usize,
+    outputs: Vec<StateVariableFilterOutput>,
+    sample_rate: usize,
+}
+
+impl DspNode for StateVariableFilter {
+    fn get_parameters_mut(&mut self) -> &mut Parameters {
+        &mut self.parameters
+    }
+}
+
+impl StateVariableFilter {
+    /// Create a new state variable filter
+    ///
+    /// # Panics
+    ///
+    /// Panics if `4 * channel_count` is more than the engine's maximum channel
+    /// count
+    pub fn new(context: &dyn Context, channel_count: usize) -> Self {
+        Self::with_outputs(context, channel_count, &StateVariableFilterOutput::ALL)
+    }
+
+    /// Create a state variable filter that only produces some of its outputs
+    ///
+    /// The channels for each output are grouped together, in the order of
+    /// `outputs`. A filter with a single output fits in an engine with the
+    /// default maximum channel count.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `outputs` is empty, or if `outputs.len() * channel_count` is
+    /// more than the engine's maximum channel count (see
+    /// [EngineOptions::with_maximum_channel_count])
+    pub fn with_outputs(
+        context: &dyn Context,
+        channel_count: usize,
+        outputs: &[StateVariableFilterOutput],
+    ) -> Self {
+        assert!(
+            !outputs.is_empty(),
+            "A state variable filter needs at least one output"
+        );
+
+        let output_count = outputs.len() * channel_count;
+
+        assert!(
+            output_count <= context.maximum_channel_count(),
+            "A state variable filter with {output_count} outputs needs an engine with a maximum \
+             channel count of at least {output_count}, see \
+             EngineOptions::with_maximum_channel_count"
+        );
+
+        let id = Id::generate();
+
+        let (params, realtime_params) = create_parameters(
+            id,
+            context,
+            [
+                ("frequency", ParameterRange::new(1_000.0, 20.0, 20_000.0)),
+                ("q", ParameterRange::new(1.0 / 2.0_f64.sqrt(), 0.1, 20.0)),
+                (
+                    "gain",
+                    ParameterRange::new(
+                        Level::unity().as_linear(),
+                        0.0,
+                        Level::from_db(100.0).as_linear(),
+                    ),
+                ),
+            ],
+        );
+
+        let processor = Box::new(StateVariableFilterProcessor::new(
+            context.get_sample_rate(),
+            channel_count,
+            outputs.to_vec(),
+        ));
+
+        Self {
+            node: GraphNode::new(
+                id,
+                context,
+                channel_count,
+                output_count,
+                processor,
+                realtime_params,
+            ),
+            parameters: params,
+            channel_count,
+            outputs: outputs.to_vec(),
+            sample_rate: context.get_sample_rate(),
+        }
+    }
+
+    /// The first output channel of one of the filter outputs
+    ///
+    /// The output uses `channel_count` channels from here
+    ///
+    /// # Panics
+    ///
+    /// Panics if the filter was created without `output`
+    pub fn first_output_channel(&self, output: StateVariableFilterOutput) -> usize {
+        let index = self
+            .outputs
+            .iter()
+            .position(|candidate| *candidate == output)
+            .unwrap_or_else(|| panic!("The state variable filter has no {output:?} output"));
+
+        index * self.channel_count
+    }
+
+    /// Get the frequency parameter
+    pub fn frequency(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("frequency")
+    }
+
+    /// Get the Q parameter
+    pub fn q(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("q")
+    }
+
+    /// Get the gain parameter
+    pub fn gain(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("gain")
+    }
+
+    /// Calculate the response of one of the filter outputs at a set of
+    /// frequencies
+    ///
+    /// This is calculated from the last parameter values used by the audio
+    /// thread, including the output gain. For each of the `frequencies`, the
+    /// linear magnitude is written to `magnitudes` and the phase in radians is
+    /// written to `phases`.
+    pub fn get_frequency_response(
+        &self,
+        output: StateVariableFilterOutput,
+        frequencies: &[f64],
+        magnitudes: &mut [f64],
+        phases: &mut [f64],
+    ) {
+        let cutoff = self.parameters.current_value("frequency");
+        let k = 1.0 / self.parameters.current_value("q");
+        let gain = self.parameters.current_value("gain");
+
+        write_frequency_response(frequencies, magnitudes, phases, |frequency| {
+            let (s, one) = integrator_response_terms(frequency, cutoff, self.sample_rate as f64);
+
+            let numerator = match output {
+                StateVariableFilterOutput::LowPass => one * one,
+                StateVariableFilterOutput::HighPass => s * s,
+                StateVariableFilterOutput::BandPass => k * s * one,
+                StateVariableFilterOutput::Notch => s * s + one * one,
+            };
+
+            gain * numerator / (s * s + k * s * one + one * one)
+        });
+    }
+}
@@ -0,0 +1,19 @@
+/// One of the outputs of a [crate::StateVariableFilter]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StateVariableFilterOutput {
+    /// Remove the frequencies above the cutoff
+    LowPass,
+
+    /// Remove the frequencies below the cutoff
+    HighPass,
+
+    /// Keep a band of frequencies around the cutoff
+    BandPass,
+
+    /// Remove a band of frequencies around the cutoff
+    Notch,
+}
+
+impl StateVariableFilterOutput {
+    pub(super) const ALL: [Self; 4] = [Self::LowPass, Self::HighPass, Self::BandPass, Self::Notch];
+}
@@ -0,0 +1,184 @@
+use super::StateVariableFilterOutput;
+use crate::{
+    dsp::{flush_denormal, integrator_gain},
+    graph::DspProcessor,
+    prelude::*,
+};
+
+/// The state of a zero-delay feedback state variable filter, using the
+/// topology-preserving transform described by Vadim Zavalishin and Andrew
+/// Simper
+#[derive(Clone, Copy, Default)]
+struct FilterState {
+    integrator_1: f64,
+    integrator_2: f64,
+}
+
+/// The outputs of the filter, with the band pass scaled to unity gain at the
+/// cutoff
+struct FilterOutputs {
+    low_pass: f64,
+    high_pass: f64,
+    band_pass: f64,
+}
+
+impl FilterState {
+    fn process(&mut self, input: f64, g: f64, k: f64) -> FilterOutputs {
+        let a1 = 1.0 / (1.0 + g * (g + k));
+        let a2 = g * a1;
+        let a3 = g * a2;
+
+        let v3 = input - self.integrator_2;
+        let band_pass = a1 * self.integrator_1 + a2 * v3;
+        let low_pass = self.integrator_2 + a2 * self.integrator_1 + a3 * v3;
+
+        self.integrator_1 = flush_denormal(2.0 * band_pass - self.integrator_1);
+        self.integrator_2 = flush_denormal(2.0 * low_pass - self.integrator_2);
+
+        FilterOutputs {
+            low_pass,
+            high_pass: input - k * band_pass - low_pass,
+            band_pass: k * band_pass,
+        }
+    }
+}
+
+impl FilterOutputs {
+    fn value(&self, output: StateVariableFilterOutput) -> f64 {
+        match output {
+            StateVariableFilterOutput::LowPass => self.low_pass,
+            StateVariableFilterOutput::HighPass => self.high_pass,
+            StateVariableFilterOutput::BandPass => self.band_pass,
+            StateVariableFilterOutput::Notch => self.low_pass + self.high_pass,
+        }
+    }
+}
+
+pub struct StateVariableFilterProcessor {
+    sample_rate: usize,
+    states: Vec<FilterState>,
+    outputs: Vec<StateVariableFilterOutput>,
+}
+
+impl StateVariableFilterProcessor {
+    pub fn new(
+        sample_rate: usize,
+        channel_count: usize,
+        outputs: Vec<StateVariableFilterOutput>,
+    ) -> Self {
+        Self {
+            sample_rate,
+            states: vec![FilterState::default(); channel_count],
+            outputs,
+        }
+    }
+}
+
+impl DspProcessor for StateVariableFilterProcessor {
+    fn process_audio(&mut self, context: &mut crate::ProcessContext) {
+        let frame_count = context.output_buffer.frame_count();
+
+        let frequency = context
+            .parameters
+            .get_parameter_values("frequency", frame_count);
+        let q = context.parameters.get_parameter_values("q", frame_count);
+        let gain = context.parameters.get_parameter_values("gain", frame_count);
+
+        let filter_count = self.states.len();
+        let channel_count = filter_count.min(context.input_buffer.channel_count());
+
+        for (channel, state) in self.states.iter_mut().enumerate().take(channel_count) {
+            for frame in 0..frame_count {
+                let input = context
+                    .input_buffer
+                    .get_sample(SampleLocation::new(channel, frame))
+                    as f64;
+
+                let g = integrator_gain(frequency[frame] as f64, self.sample_rate as f64);
+                let k = 1.0 / q[frame] as f64;
+
+                let outputs = state.process(input, g, k);
+                let gain = gain[frame] as f64;
+
+                for (index, output) in self.outputs.iter().enumerate() {
+                    let location = SampleLocation::new(index * filter_count + channel, frame);
+
+                    if location.channel < context.output_buffer.channel_count() {
+                        context
+                            .output_buffer
+                            .set_sample(location, (gain * outputs.value(*output)) as f32);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+    use std::f64::consts::TAU;
+
+    const SAMPLE_RATE: f64 = 48_000.0;
+
+    fn measure(
+        frequency: f64,
+        cutoff: impl Fn(usize) -> f64,
+        q: f64,
+    ) -> [f64; StateVariableFilterOutput::ALL.len()] {
+        let mut state = FilterState::default();
+        let frame_count = 48_000;
+        let mut peaks = [0.0_f64; 4];
+
+        for frame in 0..frame_count {
+            let input = (TAU * frequency * frame as f64 / SAMPLE_RATE).sin();
+            let g = integrator_gain(cutoff(frame), SAMPLE_RATE);
+            let outputs = state.process(input, g, 1.0 / q);
+
+            if frame > frame_count / 2 {
+                for (peak, output) in peaks.iter_mut().zip(StateVariableFilterOutput::ALL) {
+                    *peak = peak.max(outputs.value(output).abs());
+                }
+            }
+        }
+
+        peaks
+    }
+
+    #[test]
+    fn responds_at_the_cutoff() {
+        let q = 1.0 / 2.0_f64.sqrt();
+        let [low_pass, high_pass, band_pass, notch] = measure(1_000.0, |_| 1_000.0, q);
+
+        assert_relative_eq!(low_pass, q, epsilon = 1e-3);
+        assert_relative_eq!(high_pass, q, epsilon = 1e-3);
+        assert_relative_eq!(band_pass, 1.0, epsilon = 1e-3);
+        assert!(notch < 1e-3);
+    }
+
+    #[test]
+    fn separates_high_and_low_frequencies() {
+        let [low_pass, high_pass, ..] = measure(100.0, |_| 2_000.0, 0.707);
+        assert_relative_eq!(low_pass, 1.0, epsilon = 1e-2);
+        assert!(high_pass < 0.01);
+
+        let [low_pass, high_pass, ..] = measure(10_000.0, |_| 500.0, 0.707);
+        assert!(low_pass < 0.01);
+        assert_relative_eq!(high_pass, 1.0, epsilon = 1e-2);
+    }
+
+    #[test]
+    fn is_stable_under_audio_rate_modulation() {
+        let peaks = measure(
+            440.0,
+            |frame| {
+                let modulation = (TAU * 3_000.0 * frame as f64 / SAMPLE_RATE).sin();
+                10_000.0 + 9_990.0 * modulation
+            },
+            10.0,
+        );
+
+        assert!(peaks.iter().all(|peak| peak.is_finite() && *peak < 20.0));
+    }
+}
@@ -0,0 +1,6 @@
+mod stream_loader;
+mod streaming_sampler_event;
+mod streaming_sampler_node;
+mod streaming_sampler_processor;
+
+pub use streaming_sampler_node::StreamingSampler;
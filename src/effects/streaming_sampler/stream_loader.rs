@@ -0,0 +1,164 @@
+use std::{
+    io::{Read, Seek},
+    thread,
+    time::Duration,
+};
+
+use crossbeam::channel::{RecvTimeoutError, TryRecvError};
+
+use crate::{effects::Channel, io::WavReader, prelude::*};
+
+/// A block of audio read from the stream
+pub struct StreamBlock {
+    pub buffer: OwnedAudioBuffer,
+    pub position: usize,
+    pub frame_count: usize,
+    pub generation: usize,
+}
+
+pub enum LoaderRequest {
+    Seek { position: usize, generation: usize },
+}
+
+pub type LoaderRequestTransmitter = Channel::Sender<LoaderRequest>;
+pub type LoaderRequestReceiver = Channel::Receiver<LoaderRequest>;
+pub type StreamBlockTransmitter = Channel::Sender<StreamBlock>;
+pub type StreamBlockReceiver = Channel::Receiver<StreamBlock>;
+pub type EmptyBlockTransmitter = Channel::Sender<OwnedAudioBuffer>;
+pub type EmptyBlockReceiver = Channel::Receiver<OwnedAudioBuffer>;
+
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// The audio thread side of a stream loader
+pub struct StreamLoaderChannels {
+    pub request_transmitter: LoaderRequestTransmitter,
+    pub block_receiver: StreamBlockReceiver,
+    pub empty_block_transmitter: EmptyBlockTransmitter,
+}
+
+struct StreamLoader<R: Read + Seek> {
+    reader: WavReader<R>,
+    request_receiver: LoaderRequestReceiver,
+    block_transmitter: StreamBlockTransmitter,
+    empty_block_receiver: EmptyBlockReceiver,
+    free_blocks: Vec<OwnedAudioBuffer>,
+    generation: usize,
+    has_failed: bool,
+}
+
+/// Start a thread that reads blocks from the reader ahead of the playback position
+///
+/// The blocks are preallocated and passed back and forth between the loader and
+/// the audio thread, so the audio thread never allocates or waits on the disk.
+/// The thread exits when the audio thread side is dropped.
+pub fn run_stream_loader<R>(
+    reader: WavReader<R>,
+    block_count: usize,
+    block_frame_count: usize,
+) -> StreamLoaderChannels
+where
+    R: Read + Seek + Send + 'static,
+{
+    let (request_transmitter, request_receiver) = Channel::bounded(block_count);
+    let (block_transmitter, block_receiver) = Channel::bounded(block_count);
+    let (empty_block_transmitter, empty_block_receiver) = Channel::bounded(block_count);
+
+    let spec = reader.spec();
+
+    let free_blocks = (0..block_count)
+        .map(|_| OwnedAudioBuffer::new(block_frame_count, spec.channel_count, spec.sample_rate))
+        .collect();
+
+    let mut loader = StreamLoader {
+        reader,
+        request_receiver,
+        block_transmitter,
+        empty_block_receiver,
+        free_blocks,
+        generation: 0,
+        has_failed: false,
+    };
+
+    thread::spawn(move || loader.run());
+
+    StreamLoaderChannels {
+        request_transmitter,
+        block_receiver,
+        empty_block_transmitter,
+    }
+}
+
+impl<R: Read + Seek> StreamLoader<R> {
+    fn run(&mut self) {
+        loop {
+            loop {
+                match self.request_receiver.try_recv() {
+                    Ok(request) => self.handle_request(request),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return,
+                }
+            }
+
+            while let Ok(buffer) = self.empty_block_receiver.try_recv() {
+                self.free_blocks.push(buffer);
+            }
+
+            match self.read_block() {
+                Ok(true) => continue,
+                Ok(false) => (),
+                Err(()) => return,
+            }
+
+            match self.request_receiver.recv_timeout(IDLE_POLL_INTERVAL) {
+                Ok(request) => self.handle_request(request),
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    }
+
+    fn handle_request(&mut self, request: LoaderRequest) {
+        match request {
+            LoaderRequest::Seek {
+                position,
+                generation,
+            } => {
+                self.generation = generation;
+                self.has_failed = self.reader.seek(position).is_err();
+            }
+        }
+    }
+
+    /// Returns whether a block was sent, or an error if the audio thread has gone away
+    fn read_block(&mut self) -> Result<bool, ()> {
+        if self.has_failed || self.reader.position() >= self.reader.frame_count() {
+            return Ok(false);
+        }
+
+        let mut buffer = match self.free_blocks.pop() {
+            Some(buffer) => buffer,
+            None => return Ok(false),
+        };
+
+        let position = self.reader.position();
+
+        let frame_count = match self.reader.read(&mut buffer) {
+            Ok(frame_count) if frame_count > 0 => frame_count,
+            _ => {
+                self.has_failed = true;
+                self.free_blocks.push(buffer);
+                return Ok(false);
+            }
+        };
+
+        self.block_transmitter
+            .send(StreamBlock {
+                buffer,
+                position,
+                frame_count,
+                generation: self.generation,
+            })
+            .map(|_| true)
+            .map_err(|_| ())
+    }
+}
@@ -0,0 +1,82 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{effects::utility::EventProcessorEvent, Timestamp};
+
+#[derive(Debug, PartialEq, PartialOrd)]
+pub enum StreamingSamplerEventType {
+    Start(Timestamp),
+    StartImmediate,
+    Stop,
+    Seek(Timestamp),
+    CancelAll,
+}
+
+fn next_sequence_number() -> usize {
+    static SEQUENCE_NUMBER: AtomicUsize = AtomicUsize::new(0);
+    SEQUENCE_NUMBER.fetch_add(1, Ordering::Relaxed)
+}
+
+#[derive(Debug)]
+pub struct StreamingSamplerEvent {
+    sequence_number: usize,
+    time: Timestamp,
+    event_type: StreamingSamplerEventType,
+}
+
+impl StreamingSamplerEvent {
+    fn new(time: Timestamp, event_type: StreamingSamplerEventType) -> Self {
+        Self {
+            sequence_number: next_sequence_number(),
+            time,
+            event_type,
+        }
+    }
+
+    pub fn get_event_type(&self) -> &StreamingSamplerEventType {
+        &self.event_type
+    }
+
+    pub fn start(start_at_time: Timestamp, position_in_stream: Timestamp) -> Self {
+        Self::new(
+            start_at_time,
+            StreamingSamplerEventType::Start(position_in_stream),
+        )
+    }
+
+    pub fn start_now() -> Self {
+        Self::new(Timestamp::zero(), StreamingSamplerEventType::StartImmediate)
+    }
+
+    pub fn stop(stop_at_time: Timestamp) -> Self {
+        Self::new(stop_at_time, StreamingSamplerEventType::Stop)
+    }
+
+    pub fn stop_now() -> Self {
+        Self::new(Timestamp::zero(), StreamingSamplerEventType::Stop)
+    }
+
+    pub fn seek(seek_at_time: Timestamp, position_in_stream: Timestamp) -> Self {
+        Self::new(
+            seek_at_time,
+            StreamingSamplerEventType::Seek(position_in_stream),
+        )
+    }
+
+    pub fn cancel_all() -> Self {
+        Self::new(Timestamp::zero(), StreamingSamplerEventType::CancelAll)
+    }
+}
+
+impl EventProcessorEvent for StreamingSamplerEvent {
+    fn get_time(&self) -> Timestamp {
+        self.time
+    }
+
+    fn should_clear_queue(&self) -> bool {
+        self.event_type == StreamingSamplerEventType::CancelAll
+    }
+
+    fn sequence_number(&self) -> usize {
+        self.sequence_number
+    }
+}
@@ -0,0 +1,147 @@
+use super::{
+    stream_loader::run_stream_loader, streaming_sampler_event::*, streaming_sampler_processor::*,
+};
+use crate::{
+    commands::Id,
+    effects::Channel,
+    graph::DspParameters,
+    io::{WavError, WavReader},
+    prelude::*,
+};
+use std::{
+    fs::File,
+    io::{BufReader, Read, Seek},
+    path::Path,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+/// A node that plays a sample by streaming it from disk
+///
+/// Unlike the [crate::Sampler], the sample is never fully loaded into memory.
+/// A background thread reads ahead of the playback position, so very long
+/// files can be played. Starts and seeks should be scheduled ahead of time
+/// where possible, so that the audio can be loaded before it is needed.
+///
+/// If the audio isn't loaded in time, the node outputs silence and stays in
+/// time with the timeline. This is counted in `underrun_count()`.
+///
+/// The sample isn't resampled, so the stream should have the same sample rate
+/// as the context.
+pub struct StreamingSampler {
+    /// The node to connect to the audio graph
+    pub node: GraphNode,
+    event_transmitter: EventTransmitter,
+    underrun_count: Arc<AtomicUsize>,
+    frame_count: usize,
+    sample_rate: usize,
+}
+
+static EVENT_CHANNEL_CAPACITY: usize = 32;
+const BLOCK_COUNT: usize = 32;
+const BLOCK_FRAME_COUNT: usize = 4096;
+
+impl StreamingSampler {
+    /// Create a streaming sampler that plays a WAV file
+    pub fn from_file<P: AsRef<Path>>(context: &dyn Context, path: P) -> Result<Self, WavError> {
+        Self::from_reader(context, BufReader::new(File::open(path)?))
+    }
+
+    /// Create a streaming sampler that plays WAV data from a reader
+    pub fn from_reader<R>(context: &dyn Context, reader: R) -> Result<Self, WavError>
+    where
+        R: Read + Seek + Send + 'static,
+    {
+        let reader = WavReader::new(reader)?;
+
+        let id = Id::generate();
+
+        let (event_transmitter, event_receiver) = Channel::bounded(EVENT_CHANNEL_CAPACITY);
+        let underrun_count = Arc::new(AtomicUsize::new(0));
+
+        let spec = reader.spec();
+        let frame_count = reader.frame_count();
+
+        let loader = run_stream_loader(reader, BLOCK_COUNT, BLOCK_FRAME_COUNT);
+
+        let processor = Box::new(StreamingSamplerProcessor::new(
+            spec.sample_rate,
+            frame_count,
+            loader,
+            BLOCK_COUNT * BLOCK_FRAME_COUNT / 2,
+            event_receiver,
+            underrun_count.clone(),
+        ));
+
+        let input_count = 0;
+        let output_count = spec.channel_count;
+
+        Ok(Self {
+            node: GraphNode::new(
+                id,
+                context,
+                input_count,
+                output_count,
+                processor,
+                DspParameters::empty(),
+            ),
+            event_transmitter,
+            underrun_count,
+            frame_count,
+            sample_rate: spec.sample_rate,
+        })
+    }
+
+    /// The length of the stream
+    pub fn duration(&self) -> Timestamp {
+        Timestamp::from_samples(self.frame_count as f64, self.sample_rate)
+    }
+
+    /// The number of times that audio wasn't loaded in time to be played
+    pub fn underrun_count(&self) -> usize {
+        self.underrun_count.load(Ordering::Relaxed)
+    }
+
+    /// Start playing from the start of the stream
+    pub fn start_now(&mut self) {
+        self.send_event(StreamingSamplerEvent::start_now());
+    }
+
+    /// Stop playing
+    pub fn stop_now(&mut self) {
+        self.send_event(StreamingSamplerEvent::stop_now());
+    }
+
+    /// Start from the specified time, at the specified position in the stream
+    pub fn start_from_position_at_time(
+        &mut self,
+        start_time: Timestamp,
+        position_in_stream: Timestamp,
+    ) {
+        self.send_event(StreamingSamplerEvent::start(start_time, position_in_stream));
+    }
+
+    /// Stop at the specified time
+    pub fn stop_at_time(&mut self, stop_time: Timestamp) {
+        self.send_event(StreamingSamplerEvent::stop(stop_time));
+    }
+
+    /// Jump to a position in the stream at the specified time
+    ///
+    /// This has no effect if the sampler isn't playing at that time
+    pub fn seek_at_time(&mut self, seek_time: Timestamp, position_in_stream: Timestamp) {
+        self.send_event(StreamingSamplerEvent::seek(seek_time, position_in_stream));
+    }
+
+    /// Cancel all scheduled events that haven't occurred yet
+    pub fn cancel_all(&mut self) {
+        self.send_event(StreamingSamplerEvent::cancel_all());
+    }
+
+    fn send_event(&mut self, event: StreamingSamplerEvent) {
+        debug_assert!(!self.event_transmitter.is_full());
+        let _ = self.event_transmitter.send(event);
+    }
+}
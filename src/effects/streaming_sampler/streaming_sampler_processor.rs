@@ -0,0 +1,621 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use crate::{
+    effects::{utility::*, Channel},
+    graph::DspProcessor,
+    prelude::*,
+    ProcessContext,
+};
+
+use super::{
+    stream_loader::*,
+    streaming_sampler_event::{StreamingSamplerEvent, StreamingSamplerEventType},
+};
+
+pub type EventReceiver = Channel::Receiver<StreamingSamplerEvent>;
+pub type EventTransmitter = Channel::Sender<StreamingSamplerEvent>;
+
+pub struct StreamingSamplerProcessor {
+    event_processor: EventProcessor<StreamingSamplerEvent>,
+    sample_rate: usize,
+    stream_frame_count: usize,
+
+    loader: StreamLoaderChannels,
+    current_block: Option<StreamBlock>,
+    generation: usize,
+    pending_seek: Option<usize>,
+    stream_position: usize,
+    skip_ahead_limit: usize,
+
+    is_playing: bool,
+    position: usize,
+    underrun_count: Arc<AtomicUsize>,
+}
+
+const MAX_PENDING_EVENTS: usize = 16;
+
+impl DspProcessor for StreamingSamplerProcessor {
+    fn process_audio(&mut self, context: &mut ProcessContext) {
+        self.event_processor.receive_events();
+        self.prefetch();
+        self.send_pending_seek();
+        self.discard_stale_blocks();
+
+        let mut current_time = *context.start_time;
+        let mut position = 0;
+
+        while position < context.output_buffer.frame_count() {
+            let (end_frame, event) = self.event_processor.next_event(
+                context.start_time,
+                &current_time,
+                context.output_buffer.frame_count(),
+            );
+
+            debug_assert!(end_frame <= context.output_buffer.frame_count());
+
+            let frame_count = end_frame - position;
+
+            self.render(context.output_buffer, position, frame_count);
+
+            position += frame_count;
+            current_time = current_time.incremented_by_samples(frame_count, self.sample_rate);
+
+            if let Some(event) = event {
+                self.process_event(&event, &current_time);
+            }
+        }
+    }
+}
+
+impl StreamingSamplerProcessor {
+    pub fn new(
+        sample_rate: usize,
+        stream_frame_count: usize,
+        loader: StreamLoaderChannels,
+        skip_ahead_limit: usize,
+        event_receiver: EventReceiver,
+        underrun_count: Arc<AtomicUsize>,
+    ) -> Self {
+        Self {
+            event_processor: EventProcessor::with_capacity(
+                MAX_PENDING_EVENTS,
+                event_receiver,
+                sample_rate,
+            ),
+            sample_rate,
+            stream_frame_count,
+            loader,
+            current_block: None,
+            generation: 0,
+            pending_seek: None,
+            stream_position: 0,
+            skip_ahead_limit,
+            is_playing: false,
+            position: 0,
+            underrun_count,
+        }
+    }
+
+    fn process_event(&mut self, event: &StreamingSamplerEvent, current_time: &Timestamp) {
+        match event.get_event_type() {
+            StreamingSamplerEventType::StartImmediate => self.start(0),
+            StreamingSamplerEventType::Start(position_in_stream) => {
+                let delay = *current_time - event.get_time();
+                self.start(self.to_frames(*position_in_stream + delay));
+            }
+            StreamingSamplerEventType::Seek(position_in_stream) => {
+                if self.is_playing {
+                    let delay = *current_time - event.get_time();
+                    self.start(self.to_frames(*position_in_stream + delay));
+                }
+            }
+            StreamingSamplerEventType::Stop | StreamingSamplerEventType::CancelAll => self.stop(),
+        }
+    }
+
+    fn to_frames(&self, position: Timestamp) -> usize {
+        position.as_samples(self.sample_rate).round() as usize
+    }
+
+    fn start(&mut self, position: usize) {
+        self.position = position;
+        self.is_playing = true;
+
+        if position < self.stream_position
+            || position - self.stream_position > self.skip_ahead_limit
+        {
+            self.request_seek(position);
+        }
+    }
+
+    fn stop(&mut self) {
+        self.is_playing = false;
+        self.prefetch();
+    }
+
+    /// While stopped, start loading from where the next scheduled start will play
+    fn prefetch(&mut self) {
+        if self.is_playing {
+            return;
+        }
+
+        let position = match self
+            .event_processor
+            .pending_events()
+            .first()
+            .map(|event| event.get_event_type())
+        {
+            Some(StreamingSamplerEventType::StartImmediate) => 0,
+            Some(StreamingSamplerEventType::Start(position_in_stream)) => {
+                self.to_frames(*position_in_stream)
+            }
+            _ => return,
+        };
+
+        if position != self.stream_position {
+            self.request_seek(position);
+        }
+    }
+
+    fn request_seek(&mut self, position: usize) {
+        self.generation += 1;
+        self.stream_position = position;
+
+        if let Some(block) = self.current_block.take() {
+            self.return_block(block);
+        }
+
+        self.pending_seek = Some(position);
+        self.send_pending_seek();
+    }
+
+    /// Ask the loader to seek, keeping the request to retry on the next block if its queue
+    /// is full so that blocks for the current generation always arrive eventually
+    fn send_pending_seek(&mut self) {
+        let Some(position) = self.pending_seek else {
+            return;
+        };
+
+        if self
+            .loader
+            .request_transmitter
+            .try_send(LoaderRequest::Seek {
+                position,
+                generation: self.generation,
+            })
+            .is_ok()
+        {
+            self.pending_seek = None;
+        }
+    }
+
+    fn return_block(&mut self, block: StreamBlock) {
+        let _ = self.loader.empty_block_transmitter.try_send(block.buffer);
+    }
+
+    /// Take the next block to play if there isn't one, handing any blocks loaded before the
+    /// latest seek back to the loader so it can reuse them
+    fn discard_stale_blocks(&mut self) {
+        if self.current_block.is_none() {
+            self.current_block = self.next_block();
+        }
+    }
+
+    fn next_block(&mut self) -> Option<StreamBlock> {
+        while let Ok(block) = self.loader.block_receiver.try_recv() {
+            if block.generation == self.generation {
+                return Some(block);
+            }
+
+            self.return_block(block);
+        }
+
+        None
+    }
+
+    fn render(&mut self, output_buffer: &mut dyn AudioBuffer, offset: usize, frame_count: usize) {
+        if !self.is_playing {
+            return;
+        }
+
+        let frame_count = frame_count.min(self.stream_frame_count.saturating_sub(self.position));
+        let frames_read = self.read_stream(output_buffer, offset, frame_count);
+
+        if frames_read < frame_count {
+            self.underrun_count.fetch_add(1, Ordering::Relaxed);
+            self.position += frame_count - frames_read;
+        }
+
+        if self.position >= self.stream_frame_count {
+            self.stop();
+        }
+    }
+
+    fn read_stream(
+        &mut self,
+        output_buffer: &mut dyn AudioBuffer,
+        offset: usize,
+        frame_count: usize,
+    ) -> usize {
+        let mut frames_read = 0;
+
+        while frames_read < frame_count {
+            let block = match self.current_block.take().or_else(|| self.next_block()) {
+                Some(block) => block,
+                None => break,
+            };
+
+            let position_in_block = self.stream_position - block.position;
+
+            if position_in_block >= block.frame_count {
+                self.return_block(block);
+                continue;
+            }
+
+            let available = block.frame_count - position_in_block;
+
+            if self.position > self.stream_position {
+                self.stream_position += available.min(self.position - self.stream_position);
+                self.current_block = Some(block);
+                continue;
+            }
+
+            let copy_count = available.min(frame_count - frames_read);
+            let channel_count = output_buffer
+                .channel_count()
+                .min(block.buffer.channel_count());
+
+            output_buffer.copy_from(
+                &block.buffer,
+                SampleLocation::frame(position_in_block),
+                SampleLocation::frame(offset + frames_read),
+                channel_count,
+                copy_count,
+            );
+
+            frames_read += copy_count;
+            self.stream_position += copy_count;
+            self.position += copy_count;
+            self.current_block = Some(block);
+        }
+
+        frames_read
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        graph::DspParameters,
+        io::{WavReader, WavSampleFormat, WavSpec, WavWriter},
+    };
+    use std::{
+        io::{Cursor, Read, Seek, SeekFrom},
+        sync::atomic::AtomicBool,
+        thread,
+        time::Duration,
+    };
+
+    const SAMPLE_RATE: usize = 48_000;
+    const BLOCK_COUNT: usize = 4;
+    const BLOCK_FRAME_COUNT: usize = 256;
+
+    /// A reader that blocks until it is opened, to simulate a slow disk
+    struct GatedReader {
+        inner: Cursor<Vec<u8>>,
+        is_open: Arc<AtomicBool>,
+    }
+
+    impl Read for GatedReader {
+        fn read(&mut self, buffer: &mut [u8]) -> std::io::Result<usize> {
+            while !self.is_open.load(Ordering::Acquire) {
+                thread::sleep(Duration::from_millis(1));
+            }
+
+            self.inner.read(buffer)
+        }
+    }
+
+    impl Seek for GatedReader {
+        fn seek(&mut self, position: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(position)
+        }
+    }
+
+    fn ramp_wav(frame_count: usize) -> Vec<u8> {
+        let mut buffer = OwnedAudioBuffer::new(frame_count, 1, SAMPLE_RATE);
+
+        for frame in 0..frame_count {
+            buffer.set_sample(
+                SampleLocation::frame(frame),
+                frame as f32 / frame_count as f32,
+            );
+        }
+
+        let spec = WavSpec {
+            channel_count: 1,
+            sample_rate: SAMPLE_RATE,
+            sample_format: WavSampleFormat::Float32,
+        };
+
+        let mut writer = WavWriter::new(Cursor::new(Vec::new()), spec).unwrap();
+        writer.write(&buffer).unwrap();
+        writer.finalize().unwrap().into_inner()
+    }
+
+    struct Fixture {
+        processor: StreamingSamplerProcessor,
+        event_transmitter: EventTransmitter,
+        underrun_count: Arc<AtomicUsize>,
+        is_open: Arc<AtomicBool>,
+        frame_count: usize,
+    }
+
+    impl Fixture {
+        fn new(frame_count: usize, is_open: bool) -> Self {
+            let gate = Arc::new(AtomicBool::new(true));
+
+            let reader = WavReader::new(GatedReader {
+                inner: Cursor::new(ramp_wav(frame_count)),
+                is_open: gate.clone(),
+            })
+            .unwrap();
+
+            gate.store(is_open, Ordering::Release);
+
+            let loader = run_stream_loader(reader, BLOCK_COUNT, BLOCK_FRAME_COUNT);
+            let (event_transmitter, event_receiver) = Channel::unbounded();
+            let underrun_count = Arc::new(AtomicUsize::new(0));
+
+            Self {
+                processor: StreamingSamplerProcessor::new(
+                    SAMPLE_RATE,
+                    frame_count,
+                    loader,
+                    BLOCK_FRAME_COUNT,
+                    event_receiver,
+                    underrun_count.clone(),
+                ),
+                event_transmitter,
+                underrun_count,
+                is_open: gate,
+                frame_count,
+            }
+        }
+
+        fn wait_for_blocks(&mut self) {
+            let remaining_frames = self.frame_count - self.processor.stream_position;
+            let block_count = remaining_frames
+                .div_ceil(BLOCK_FRAME_COUNT)
+                .min(BLOCK_COUNT);
+
+            loop {
+                self.processor.discard_stale_blocks();
+
+                if self.processor.current_block.is_some()
+                    && self.processor.loader.block_receiver.len() + 1 >= block_count
+                {
+                    return;
+                }
+
+                thread::sleep(Duration::from_millis(1));
+            }
+        }
+
+        fn process(&mut self, start_frame: usize, frame_count: usize) -> OwnedAudioBuffer {
+            let mut output_buffer = OwnedAudioBuffer::new(frame_count, 1, SAMPLE_RATE);
+            let input_buffer = OwnedAudioBuffer::new(frame_count, 1, SAMPLE_RATE);
+
+            self.processor.process_audio(&mut ProcessContext {
+                input_buffer: &input_buffer,
+                output_buffer: &mut output_buffer,
+                start_time: &Timestamp::from_samples(start_frame as f64, SAMPLE_RATE),
+                parameters: &DspParameters::empty(),
+                midi_events: &[],
+            });
+
+            output_buffer
+        }
+
+        fn expected(&self, position: usize) -> f32 {
+            position as f32 / self.frame_count as f32
+        }
+    }
+
+    #[test]
+    fn plays_stream_from_start() {
+        let mut fixture = Fixture::new(4_096, true);
+        fixture.wait_for_blocks();
+
+        let _ = fixture
+            .event_transmitter
+            .send(StreamingSamplerEvent::start_now());
+
+        let output = fixture.process(0, 512);
+
+        for frame in 0..512 {
+            assert_relative_eq!(
+                output.get_sample(SampleLocation::frame(frame)),
+                fixture.expected(frame)
+            );
+        }
+
+        assert_eq!(fixture.underrun_count.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn starts_at_scheduled_time_and_position() {
+        let mut fixture = Fixture::new(4_096, true);
+
+        let _ = fixture.event_transmitter.send(StreamingSamplerEvent::start(
+            Timestamp::from_samples(100.0, SAMPLE_RATE),
+            Timestamp::from_samples(1_000.0, SAMPLE_RATE),
+        ));
+
+        let silent = fixture.process(0, 64);
+        assert!(silent.channel_is_silent(0));
+
+        fixture.wait_for_blocks();
+
+        let output = fixture.process(64, 128);
+
+        for frame in 0..36 {
+            assert_eq!(output.get_sample(SampleLocation::frame(frame)), 0.0);
+        }
+
+        for frame in 36..128 {
+            assert_relative_eq!(
+                output.get_sample(SampleLocation::frame(frame)),
+                fixture.expected(1_000 + frame - 36)
+            );
+        }
+    }
+
+    #[test]
+    fn stops_at_scheduled_time() {
+        let mut fixture = Fixture::new(4_096, true);
+        fixture.wait_for_blocks();
+
+        let _ = fixture
+            .event_transmitter
+            .send(StreamingSamplerEvent::start_now());
+        let _ =
+            fixture
+                .event_transmitter
+                .send(StreamingSamplerEvent::stop(Timestamp::from_samples(
+                    50.0,
+                    SAMPLE_RATE,
+                )));
+
+        let output = fixture.process(0, 128);
+
+        assert_relative_eq!(
+            output.get_sample(SampleLocation::frame(48)),
+            fixture.expected(48)
+        );
+
+        for frame in 50..128 {
+            assert_eq!(output.get_sample(SampleLocation::frame(frame)), 0.0);
+        }
+    }
+
+    #[test]
+    fn seeks_while_playing() {
+        let mut fixture = Fixture::new(4_096, true);
+        fixture.wait_for_blocks();
+
+        let _ = fixture
+            .event_transmitter
+            .send(StreamingSamplerEvent::start_now());
+        let _ = fixture.event_transmitter.send(StreamingSamplerEvent::seek(
+            Timestamp::from_samples(10.0, SAMPLE_RATE),
+            Timestamp::from_samples(200.0, SAMPLE_RATE),
+        ));
+
+        let output = fixture.process(0, 64);
+
+        assert_relative_eq!(
+            output.get_sample(SampleLocation::frame(9)),
+            fixture.expected(9)
+        );
+
+        for frame in 10..64 {
+            assert_relative_eq!(
+                output.get_sample(SampleLocation::frame(frame)),
+                fixture.expected(200 + frame - 10)
+            );
+        }
+    }
+
+    #[test]
+    fn reports_underruns_and_stays_in_time() {
+        let mut fixture = Fixture::new(4_096, false);
+
+        let _ = fixture
+            .event_transmitter
+            .send(StreamingSamplerEvent::start_now());
+
+        let output = fixture.process(0, 128);
+
+        assert!(output.channel_is_silent(0));
+        assert_eq!(fixture.underrun_count.load(Ordering::Relaxed), 1);
+
+        fixture.is_open.store(true, Ordering::Release);
+        fixture.wait_for_blocks();
+
+        let output = fixture.process(128, 128);
+
+        for frame in 0..128 {
+            assert_relative_eq!(
+                output.get_sample(SampleLocation::frame(frame)),
+                fixture.expected(128 + frame)
+            );
+        }
+
+        assert_eq!(fixture.underrun_count.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn retries_seeks_when_the_loader_queue_is_full() {
+        let mut fixture = Fixture::new(4_096, false);
+
+        for position in 1..=BLOCK_COUNT + 2 {
+            fixture.processor.request_seek(position * 100);
+        }
+
+        assert!(fixture.processor.pending_seek.is_some());
+
+        fixture.is_open.store(true, Ordering::Release);
+
+        while fixture.processor.pending_seek.is_some() {
+            fixture.processor.send_pending_seek();
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        fixture.wait_for_blocks();
+
+        let start_position = (BLOCK_COUNT + 2) * 100;
+
+        let _ = fixture.event_transmitter.send(StreamingSamplerEvent::start(
+            Timestamp::zero(),
+            Timestamp::from_samples(start_position as f64, SAMPLE_RATE),
+        ));
+
+        let output = fixture.process(0, 128);
+
+        for frame in 0..128 {
+            assert_relative_eq!(
+                output.get_sample(SampleLocation::frame(frame)),
+                fixture.expected(start_position + frame)
+            );
+        }
+    }
+
+    #[test]
+    fn stops_at_end_of_stream() {
+        let mut fixture = Fixture::new(300, true);
+        fixture.wait_for_blocks();
+
+        let _ = fixture
+            .event_transmitter
+            .send(StreamingSamplerEvent::start_now());
+
+        let output = fixture.process(0, 512);
+
+        assert_relative_eq!(
+            output.get_sample(SampleLocation::frame(299)),
+            fixture.expected(299)
+        );
+
+        for frame in 300..512 {
+            assert_eq!(output.get_sample(SampleLocation::frame(frame)), 0.0);
+        }
+
+        assert_eq!(fixture.underrun_count.load(Ordering::Relaxed), 0);
+        assert!(!fixture.processor.is_playing);
+    }
+}
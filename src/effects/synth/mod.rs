@@ -0,0 +1,9 @@
+mod synth_event;
+mod synth_node;
+mod synth_parameters;
+mod synth_processor;
+mod synth_voice;
+mod voice_template;
+
+pub use synth_node::Synth;
+pub use voice_template::{SynthOscillator, SynthVoiceTemplate};
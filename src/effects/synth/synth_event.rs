@@ -0,0 +1,50 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{effects::utility::EventProcessorEvent, prelude::*};
+
+#[derive(Debug, PartialEq)]
+pub enum SynthEventType {
+    NoteOn { note: u8, velocity: u8 },
+    NoteOff { note: u8 },
+    AllNotesOff,
+}
+
+fn next_sequence_number() -> usize {
+    static SEQUENCE_NUMBER: AtomicUsize = AtomicUsize::new(0);
+    SEQUENCE_NUMBER.fetch_add(1, Ordering::Relaxed)
+}
+
+#[derive(Debug)]
+pub struct SynthEvent {
+    sequence_number: usize,
+    time: Timestamp,
+    event_type: SynthEventType,
+}
+
+impl SynthEvent {
+    pub fn new(time: Timestamp, event_type: SynthEventType) -> Self {
+        Self {
+            sequence_number: next_sequence_number(),
+            time,
+            event_type,
+        }
+    }
+
+    pub fn get_event_type(&self) -> &SynthEventType {
+        &self.event_type
+    }
+}
+
+impl EventProcessorEvent for SynthEvent {
+    fn get_time(&self) -> Timestamp {
+        self.time
+    }
+
+    fn should_clear_queue(&self) -> bool {
+        false
+    }
+
+    fn sequence_number(&self) -> usize {
+        self.sequence_number
+    }
+}
@@ -0,0 +1,176 @@
+use super::{
+    synth_event::{SynthEvent, SynthEventType},
+    synth_parameters::{get_range, PARAMETER_IDS},
+    synth_processor::*,
+    voice_template::SynthVoiceTemplate,
+};
+use crate::{
+    commands::Id,
+    effects::{utility::VoiceStealing, Channel},
+    graph::DspNode,
+    parameter::Parameters,
+    prelude::*,
+    utility::create_parameters,
+};
+
+/// A polyphonic synthesizer
+///
+/// Each note is played by a voice that is built from a
+/// [`SynthVoiceTemplate`]. The voice's oscillators are summed and passed
+/// through the template's filter, and the amplitude follows an ADSR envelope.
+///
+/// When all of the voices are in use, a new note will take a voice according
+/// to the [`VoiceStealing`] policy. A stolen voice is faded out quickly rather
+/// than being cut off.
+///
+/// Notes can also be played by sending MIDI to the node with
+/// [GraphNode::send_midi_event]. Note on, note off and pitch bend messages are
+/// handled on all channels, along with the 'all notes off' and 'all sound
+/// off' controllers.
+///
+/// # Parameters
+/// - gain
+/// - detune (in cents)
+/// - glide (the portamento time in ms)
+/// - filter-frequency (in Hz)
+/// - filter-q
+/// - filter-shelf-gain (for shelf and peak filters)
+/// - attack (in ms)
+/// - decay (in ms)
+/// - sustain (linear gain)
+/// - release (in ms)
+pub struct Synth {
+    /// The node to connect to the audio graph
+    pub node: GraphNode,
+    event_transmitter: EventTransmitter,
+    params: Parameters,
+}
+
+static EVENT_CHANNEL_CAPACITY: usize = 256;
+const DEFAULT_POLYPHONY: usize = 16;
+
+impl DspNode for Synth {
+    fn get_parameters_mut(&mut self) -> &mut Parameters {
+        &mut self.params
+    }
+}
+
+impl Synth {
+    /// Create a new synth with the default polyphony, which steals the oldest
+    /// voice when all voices are in use
+    pub fn new(context: &dyn Context, channel_count: usize, template: SynthVoiceTemplate) -> Self {
+        Self::new_with_polyphony(
+            context,
+            channel_count,
+            template,
+            DEFAULT_POLYPHONY,
+            VoiceStealing::default(),
+        )
+    }
+
+    /// Create a new synth that can play up to `polyphony` notes at once
+    pub fn new_with_polyphony(
+        context: &dyn Context,
+        channel_count: usize,
+        template: SynthVoiceTemplate,
+        polyphony: usize,
+        voice_stealing: VoiceStealing,
+    ) -> Self {
+        let id = Id::generate();
+
+        let (params, realtime_params) =
+            create_parameters(id, context, PARAMETER_IDS.map(|id| (id, get_range(id))));
+
+        let (event_transmitter, event_receiver) = Channel::bounded(EVENT_CHANNEL_CAPACITY);
+
+        let processor = Box::new(SynthProcessor::new(
+            context.get_sample_rate(),
+            context.maximum_frame_count(),
+            template,
+            polyphony,
+            voice_stealing,
+            event_receiver,
+        ));
+
+        Self {
+            node: GraphNode::new(id, context, 0, channel_count, processor, realtime_params),
+            event_transmitter,
+            params,
+        }
+    }
+
+    /// Play a note at a particular time
+    ///
+    /// A velocity of zero is treated as a note off
+    pub fn note_on_at_time(&mut self, time: Timestamp, note: u8, velocity: u8) {
+        self.send_event(time, SynthEventType::NoteOn { note, velocity });
+    }
+
+    /// Release a note at a particular time
+    pub fn note_off_at_time(&mut self, time: Timestamp, note: u8) {
+        self.send_event(time, SynthEventType::NoteOff { note });
+    }
+
+    /// Play a note now
+    pub fn note_on(&mut self, note: u8, velocity: u8) {
+        self.note_on_at_time(Timestamp::zero(), note, velocity);
+    }
+
+    /// Release a note now
+    pub fn note_off(&mut self, note: u8) {
+        self.note_off_at_time(Timestamp::zero(), note);
+    }
+
+    /// Release all of the notes that are playing
+    pub fn all_notes_off(&mut self) {
+        self.send_event(Timestamp::zero(), SynthEventType::AllNotesOff);
+    }
+
+    /// Get the gain parameter
+    pub fn gain(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("gain")
+    }
+    /// Get the detune parameter
+    pub fn detune(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("detune")
+    }
+    /// Get the glide parameter
+    pub fn glide(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("glide")
+    }
+    /// Get the filter frequency parameter
+    pub fn filter_frequency(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("filter-frequency")
+    }
+    /// Get the filter Q parameter
+    pub fn filter_q(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("filter-q")
+    }
+    /// Get the filter shelf gain parameter
+    pub fn filter_shelf_gain(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("filter-shelf-gain")
+    }
+    /// Get the attack parameter
+    pub fn attack(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("attack")
+    }
+    /// Get the decay parameter
+    pub fn decay(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("decay")
+    }
+    /// Get the sustain parameter
+    pub fn sustain(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("sustain")
+    }
+    /// Get the release parameter
+    pub fn release(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("release")
+    }
+
+    fn send_event(&mut self, time: Timestamp, event_type: SynthEventType) {
+        debug_assert!(!self.event_transmitter.is_full());
+        let _ = self
+            .event_transmitter
+            .send(SynthEvent::new(time, event_type));
+    }
+}
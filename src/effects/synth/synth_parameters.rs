@@ -0,0 +1,38 @@
+use crate::{parameter::ParameterRange, Level};
+
+pub const PARAMETER_IDS: [&str; 10] = [
+    "gain",
+    "detune",
+    "glide",
+    "filter-frequency",
+    "filter-q",
+    "filter-shelf-gain",
+    "attack",
+    "decay",
+    "sustain",
+    "release",
+];
+
+pub fn get_range(parameter: &'static str) -> ParameterRange {
+    match parameter {
+        "gain" => ParameterRange::new(
+            Level::unity().as_linear(),
+            0.0,
+            Level::from_db(24.0).as_linear(),
+        ),
+        "detune" => ParameterRange::new(0.0, -4_800.0, 4_800.0),
+        "glide" => ParameterRange::new(0.0, 0.0, 10_000.0),
+        "filter-frequency" => ParameterRange::new(20_000.0, 20.0, 20_000.0),
+        "filter-q" => ParameterRange::new(1.0 / 2.0_f64.sqrt(), 0.1, 10.0),
+        "filter-shelf-gain" => ParameterRange::new(
+            Level::unity().as_linear(),
+            0.0,
+            Level::from_db(24.0).as_linear(),
+        ),
+        "attack" => ParameterRange::new(5.0, 0.0, 10_000.0),
+        "decay" => ParameterRange::new(100.0, 0.0, 10_000.0),
+        "sustain" => ParameterRange::new(1.0, 0.0, 1.0),
+        "release" => ParameterRange::new(50.0, 0.0, 10_000.0),
+        _ => panic!("Unsupported parameter: {parameter}"),
+    }
+}
@@ -0,0 +1,715 @@
+use std::time::Duration;
+
+use crate::{
+    effects::{adsr::AdsrEnvelope, biquad::BiquadCoefficients, utility::*, Channel},
+    graph::{DspParameters, DspProcessor},
+    midi::{MidiBlockEvent, MidiMessage},
+    prelude::*,
+    BiquadFilterType, ProcessContext,
+};
+
+use super::{
+    synth_event::{SynthEvent, SynthEventType},
+    synth_voice::{SynthVoice, VoiceControls, VoiceOscillator},
+    voice_template::SynthVoiceTemplate,
+};
+
+pub type EventReceiver = Channel::Receiver<SynthEvent>;
+pub type EventTransmitter = Channel::Sender<SynthEvent>;
+
+pub struct SynthProcessor {
+    oscillators: Vec<VoiceOscillator>,
+    filter_type: Option<BiquadFilterType>,
+    coefficients: BiquadCoefficients,
+    filter_settings: [f32; 3],
+    envelope_settings: [f32; 4],
+
+    voices: Vec<SynthVoice>,
+    fading_voices: Vec<SynthVoice>,
+    voice_stealing: VoiceStealing,
+    next_start_order: usize,
+    last_pitch: Option<f64>,
+
+    glide_time: f64,
+    pitch_offset: f64,
+    pitch_bend: f64,
+
+    scratch: Vec<f32>,
+    event_processor: EventProcessor<SynthEvent>,
+    sample_rate: usize,
+}
+
+/// The parameter values for a block
+struct ParameterValues<'a> {
+    gain: &'a [f32],
+    detune: &'a [f32],
+    glide: &'a [f32],
+    filter: [&'a [f32]; 3],
+    envelope: [&'a [f32]; 4],
+}
+
+impl<'a> ParameterValues<'a> {
+    fn new(parameters: &'a DspParameters, frame_count: usize) -> Self {
+        let values = |name| parameters.get_parameter_values(name, frame_count);
+
+        Self {
+            gain: values("gain"),
+            detune: values("detune"),
+            glide: values("glide"),
+            filter: [
+                values("filter-frequency"),
+                values("filter-q"),
+                values("filter-shelf-gain"),
+            ],
+            envelope: [
+                values("attack"),
+                values("decay"),
+                values("sustain"),
+                values("release"),
+            ],
+        }
+    }
+}
+
+const MAX_PENDING_EVENTS: usize = 256;
+const CONTROL_BLOCK_SIZE: usize = 32;
+const STEAL_FADE_LENGTH: Duration = Duration::from_millis(5);
+const PITCH_BEND_RANGE: f64 = 2.0;
+const ALL_SOUND_OFF: u8 = 120;
+const ALL_NOTES_OFF: u8 = 123;
+
+impl DspProcessor for SynthProcessor {
+    fn process_audio(&mut self, context: &mut ProcessContext) {
+        debug_assert_eq!(self.sample_rate, context.output_buffer.sample_rate());
+
+        self.event_processor.receive_events();
+
+        let output_frame_count = context.output_buffer.frame_count();
+        let parameters = ParameterValues::new(context.parameters, output_frame_count);
+        let midi_events = context.midi_events;
+        let mut midi_index = 0;
+
+        let mut current_time = *context.start_time;
+        let mut position = 0;
+
+        while position < output_frame_count {
+            let (end_frame, event) = self.event_processor.next_event(
+                context.start_time,
+                &current_time,
+                output_frame_count,
+            );
+
+            debug_assert!(end_frame <= output_frame_count);
+
+            let frame_count = end_frame - position;
+
+            self.render(
+                context.output_buffer,
+                position,
+                end_frame,
+                &parameters,
+                midi_events,
+                &mut midi_index,
+            );
+
+            position += frame_count;
+            current_time = current_time.incremented_by_samples(frame_count, self.sample_rate);
+
+            if let Some(event) = event {
+                self.process_event(&event);
+            }
+        }
+
+        for event in &midi_events[midi_index..] {
+            self.process_midi(&event.message);
+        }
+    }
+}
+
+impl SynthProcessor {
+    pub fn new(
+        sample_rate: usize,
+        maximum_frame_count: usize,
+        template: SynthVoiceTemplate,
+        polyphony: usize,
+        voice_stealing: VoiceStealing,
+        event_receiver: EventReceiver,
+    ) -> Self {
+        let oscillators: Vec<VoiceOscillator> = template
+            .oscillators
+            .iter()
+            .map(VoiceOscillator::from)
+            .collect();
+
+        let create_voices = || {
+            (0..polyphony)
+                .map(|_| {
+                    SynthVoice::new(
+                        oscillators.len(),
+                        AdsrEnvelope::new(
+                            sample_rate,
+                            Duration::ZERO,
+                            Duration::ZERO,
+                            Level::unity(),
+                            Duration::ZERO,
+                        ),
+                    )
+                })
+                .collect()
+        };
+
+        Self {
+            voices: create_voices(),
+            fading_voices: create_voices(),
+            oscillators,
+            filter_type: template.filter_type,
+            coefficients: BiquadCoefficients::low_pass(
+                sample_rate as f64 / 4.0,
+                sample_rate as f64,
+                1.0,
+            ),
+            filter_settings: [f32::NAN; 3],
+            envelope_settings: [f32::NAN; 4],
+            voice_stealing,
+            next_start_order: 0,
+            last_pitch: None,
+            glide_time: 0.0,
+            pitch_offset: 0.0,
+            pitch_bend: 0.0,
+            scratch: vec![0.0; maximum_frame_count],
+            event_processor: EventProcessor::with_capacity(
+                MAX_PENDING_EVENTS,
+                event_receiver,
+                sample_rate,
+            ),
+            sample_rate,
+        }
+    }
+
+    /// Render the voices from `start_frame` to `end_frame`, handling any MIDI
+    /// messages that happen in between
+    ///
+    /// The parameters are read at the start of each control block, apart
+    /// from the gain, which is applied to every frame.
+    fn render(
+        &mut self,
+        output: &mut dyn AudioBuffer,
+        start_frame: usize,
+        end_frame: usize,
+        parameters: &ParameterValues,
+        midi_events: &[MidiBlockEvent],
+        midi_index: &mut usize,
+    ) {
+        let mut position = start_frame;
+
+        while position < end_frame {
+            while let Some(event) = midi_events
+                .get(*midi_index)
+                .filter(|event| event.frame <= position)
+            {
+                self.process_midi(&event.message);
+                *midi_index += 1;
+            }
+
+            let next_frame = midi_events
+                .get(*midi_index)
+                .map_or(end_frame, |event| event.frame.min(end_frame))
+                .min(position + CONTROL_BLOCK_SIZE);
+
+            self.update_controls(parameters, position);
+
+            let frame_count = next_frame - position;
+            let scratch = &mut self.scratch[..frame_count];
+            scratch.fill(0.0);
+
+            let controls = VoiceControls {
+                oscillators: &self.oscillators,
+                coefficients: self.filter_type.map(|_| &self.coefficients),
+                pitch_offset: self.pitch_offset,
+                sample_rate: self.sample_rate as f64,
+            };
+
+            for voice in self.voices.iter_mut().chain(self.fading_voices.iter_mut()) {
+                voice.render(scratch, &controls);
+            }
+
+            let gain = &parameters.gain[position..next_frame];
+
+            for channel in 0..output.channel_count() {
+                let output = &mut output.get_channel_data_mut(SampleLocation::channel(channel))
+                    [position..next_frame];
+
+                for (output, value, gain) in itertools::izip!(output, scratch.iter(), gain) {
+                    *output = value * gain;
+                }
+            }
+
+            position = next_frame;
+        }
+    }
+
+    fn update_controls(&mut self, parameters: &ParameterValues, frame: usize) {
+        self.glide_time = parameters.glide[frame] as f64 / 1_000.0;
+        self.pitch_offset = parameters.detune[frame] as f64 / 100.0 + self.pitch_bend;
+
+        let filter_settings = parameters.filter.map(|values| values[frame]);
+
+        if let Some(filter_type) = self.filter_type {
+            if filter_settings != self.filter_settings {
+                let [frequency, q, shelf_gain] = filter_settings.map(|value| value as f64);
+
+                self.coefficients = BiquadCoefficients::for_filter_type(
+                    filter_type,
+                    frequency.min(0.49 * self.sample_rate as f64),
+                    self.sample_rate as f64,
+                    q,
+                    Level::from_linear(shelf_gain),
+                );
+
+                self.filter_settings = filter_settings;
+            }
+        }
+
+        let envelope_settings = parameters.envelope.map(|values| values[frame]);
+
+        if envelope_settings != self.envelope_settings {
+            let [attack, decay, sustain, release] = envelope_settings.map(|value| value as f64);
+            let milliseconds = |value: f64| Duration::from_secs_f64(value.max(0.0) / 1_000.0);
+
+            for voice in self.voices.iter_mut().chain(self.fading_voices.iter_mut()) {
+                let envelope = voice.envelope_mut();
+                envelope.set_attack_time(milliseconds(attack));
+                envelope.set_decay_time(milliseconds(decay));
+                envelope.set_sustain_level(Level::from_linear(sustain));
+                envelope.set_release_time(milliseconds(release));
+            }
+
+            self.envelope_settings = envelope_settings;
+        }
+    }
+
+    fn process_event(&mut self, event: &SynthEvent) {
+        match event.get_event_type() {
+            SynthEventType::NoteOn { note, velocity } => self.note_on(*note, *velocity),
+            SynthEventType::NoteOff { note } => self.note_off(*note),
+            SynthEventType::AllNotesOff => self.all_notes_off(),
+        }
+    }
+
+    fn process_midi(&mut self, message: &MidiMessage) {
+        match *message {
+            MidiMessage::NoteOn { note, velocity, .. } => self.note_on(note, velocity),
+            MidiMessage::NoteOff { note, .. } => self.note_off(note),
+            MidiMessage::PitchBend { value, .. } => {
+                let bend = PITCH_BEND_RANGE * value as f64 / 8192.0;
+                self.pitch_offset += bend - self.pitch_bend;
+                self.pitch_bend = bend;
+            }
+            MidiMessage::ControlChange {
+                controller: ALL_SOUND_OFF | ALL_NOTES_OFF,
+                ..
+            } => self.all_notes_off(),
+            _ => (),
+        }
+    }
+
+    fn note_on(&mut self, note: u8, velocity: u8) {
+        if velocity == 0 {
+            self.note_off(note);
+            return;
+        }
+
+        let voice_index = match self.allocate_voice() {
+            Some(index) => index,
+            None => return,
+        };
+
+        if self.voices[voice_index].is_active() {
+            self.fade_out_voice(voice_index);
+        }
+
+        let pitch = note as f64;
+
+        let start_pitch = if self.glide_time > 0.0 {
+            self.last_pitch.unwrap_or(pitch)
+        } else {
+            pitch
+        };
+
+        let glide_length = (self.glide_time * self.sample_rate as f64).round() as usize;
+
+        let start_order = self.next_start_order;
+        self.next_start_order += 1;
+
+        self.voices[voice_index].start(
+            note,
+            velocity as f64 / 127.0,
+            start_pitch,
+            glide_length,
+            start_order,
+        );
+
+        self.last_pitch = Some(pitch);
+    }
+
+    /// Move a voice that is being stolen into a free fading slot, so that it
+    /// can fade out while the new note starts
+    fn fade_out_voice(&mut self, voice_index: usize) {
+        let fade_length =
+            (STEAL_FADE_LENGTH.as_secs_f64() * self.sample_rate as f64).ceil() as usize;
+
+        if let Some(fading_voice) = self
+            .fading_voices
+            .iter_mut()
+            .find(|voice| !voice.is_active())
+        {
+            std::mem::swap(fading_voice, &mut self.voices[voice_index]);
+            fading_voice.fade_out(fade_length);
+        }
+    }
+
+    fn note_off(&mut self, note: u8) {
+        self.voices
+            .iter_mut()
+            .filter(|voice| voice.is_holding(note))
+            .for_each(|voice| voice.release());
+    }
+
+    fn all_notes_off(&mut self) {
+        self.voices.iter_mut().for_each(|voice| voice.release());
+    }
+
+    fn allocate_voice(&self) -> Option<usize> {
+        if let Some(index) = self.voices.iter().position(|voice| !voice.is_active()) {
+            return Some(index);
+        }
+
+        let voices = self.voices.iter().enumerate();
+
+        match self.voice_stealing {
+            VoiceStealing::None => None,
+            VoiceStealing::Oldest => voices
+                .min_by_key(|(_, voice)| voice.start_order())
+                .map(|(index, _)| index),
+            VoiceStealing::Quietest => voices
+                .min_by(|(_, a), (_, b)| a.level().total_cmp(&b.level()))
+                .map(|(index, _)| index),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        effects::synth::{
+            synth_parameters::{get_range, PARAMETER_IDS},
+            voice_template::SynthOscillator,
+        },
+        parameter::RealtimeAudioParameter,
+        AudioBuffer, OscillatorWaveform, SampleLocation,
+    };
+    use approx::assert_relative_eq;
+    use atomic_float::AtomicF64;
+    use std::sync::Arc;
+
+    const SAMPLE_RATE: usize = 48_000;
+    const FRAME_COUNT: usize = 4_800;
+
+    struct Fixture {
+        processor: SynthProcessor,
+        event_transmitter: EventTransmitter,
+        parameters: DspParameters,
+        time: Timestamp,
+    }
+
+    impl Fixture {
+        fn new(
+            template: SynthVoiceTemplate,
+            polyphony: usize,
+            voice_stealing: VoiceStealing,
+        ) -> Self {
+            let (event_transmitter, event_receiver) = crossbeam::channel::unbounded();
+
+            let parameters = DspParameters::new(PARAMETER_IDS.map(|id| {
+                RealtimeAudioParameter::new(
+                    id,
+                    Arc::new(AtomicF64::new(get_range(id).default())),
+                    FRAME_COUNT,
+                )
+            }));
+
+            let mut fixture = Self {
+                processor: SynthProcessor::new(
+                    SAMPLE_RATE,
+                    FRAME_COUNT,
+                    template,
+                    polyphony,
+                    voice_stealing,
+                    event_receiver,
+                ),
+                event_transmitter,
+                parameters,
+                time: Timestamp::zero(),
+            };
+
+            fixture.set_parameter("attack", 0.0);
+            fixture.set_parameter("decay", 0.0);
+            fixture.set_parameter("release", 0.0);
+            fixture
+        }
+
+        fn set_parameter(&mut self, name: &'static str, value: f64) {
+            self.parameters.get_parameter_mut(name).set_value(value);
+        }
+
+        fn send(&self, event_type: SynthEventType) {
+            let _ = self
+                .event_transmitter
+                .send(SynthEvent::new(self.time, event_type));
+        }
+
+        fn note_on(&self, note: u8) {
+            self.send(SynthEventType::NoteOn {
+                note,
+                velocity: 127,
+            });
+        }
+
+        fn process_with_midi(&mut self, midi_events: &[MidiBlockEvent]) -> OwnedAudioBuffer {
+            let mut output_buffer = OwnedAudioBuffer::new(FRAME_COUNT, 1, SAMPLE_RATE);
+            let input_buffer = OwnedAudioBuffer::new(FRAME_COUNT, 0, SAMPLE_RATE);
+
+            for (_, parameter) in self.parameters.iter_mut() {
+                parameter.process(&self.time, FRAME_COUNT, SAMPLE_RATE);
+            }
+
+            self.processor.process_audio(&mut ProcessContext {
+                input_buffer: &input_buffer,
+                output_buffer: &mut output_buffer,
+                start_time: &self.time,
+                parameters: &self.parameters,
+                midi_events,
+            });
+
+            self.time = self.time.incremented_by_samples(FRAME_COUNT, SAMPLE_RATE);
+
+            output_buffer
+        }
+
+        fn process(&mut self) -> OwnedAudioBuffer {
+            self.process_with_midi(&[])
+        }
+
+        fn active_notes(&self) -> Vec<u8> {
+            (0..128)
+                .filter(|note| {
+                    self.processor
+                        .voices
+                        .iter()
+                        .any(|voice| voice.is_holding(*note))
+                })
+                .collect()
+        }
+    }
+
+    fn sine_template() -> SynthVoiceTemplate {
+        SynthVoiceTemplate::new().with_oscillator(SynthOscillator::new(OscillatorWaveform::Sine))
+    }
+
+    fn samples(buffer: &OwnedAudioBuffer) -> &[f32] {
+        buffer.get_channel_data(SampleLocation::channel(0))
+    }
+
+    fn rms(values: &[f32]) -> f32 {
+        (values.iter().map(|value| value * value).sum::<f32>() / values.len() as f32).sqrt()
+    }
+
+    /// Estimate the frequency by counting the rising zero crossings
+    fn estimate_frequency(values: &[f32]) -> f64 {
+        let crossings: Vec<usize> = values
+            .windows(2)
+            .enumerate()
+            .filter(|(_, pair)| pair[0] < 0.0 && pair[1] >= 0.0)
+            .map(|(index, _)| index)
+            .collect();
+
+        let cycles = crossings.len() - 1;
+        let frames = crossings[cycles] - crossings[0];
+        cycles as f64 * SAMPLE_RATE as f64 / frames as f64
+    }
+
+    #[test]
+    fn plays_the_note_pitch() {
+        let mut fixture = Fixture::new(sine_template(), 4, VoiceStealing::Oldest);
+
+        fixture.note_on(69);
+        let output = fixture.process();
+
+        assert_relative_eq!(estimate_frequency(samples(&output)), 440.0, epsilon = 1.0);
+        assert_relative_eq!(rms(samples(&output)), 0.5_f32.sqrt(), epsilon = 1e-2);
+    }
+
+    #[test]
+    fn plays_chords() {
+        let mut fixture = Fixture::new(sine_template(), 4, VoiceStealing::Oldest);
+
+        for note in [60, 64, 67] {
+            fixture.note_on(note);
+        }
+
+        fixture.process();
+
+        assert_eq!(fixture.active_notes(), vec![60, 64, 67]);
+    }
+
+    #[test]
+    fn releases_notes() {
+        let mut fixture = Fixture::new(sine_template(), 4, VoiceStealing::Oldest);
+        fixture.set_parameter("release", 10.0);
+
+        fixture.note_on(69);
+        fixture.process();
+
+        fixture.send(SynthEventType::NoteOff { note: 69 });
+        let output = fixture.process();
+
+        assert!(fixture.active_notes().is_empty());
+        assert!(rms(&samples(&output)[..240]) > 0.1);
+        assert_relative_eq!(rms(&samples(&output)[FRAME_COUNT / 2..]), 0.0);
+    }
+
+    #[test]
+    fn detunes_all_voices() {
+        let mut fixture = Fixture::new(sine_template(), 4, VoiceStealing::Oldest);
+        fixture.set_parameter("detune", 1_200.0);
+
+        fixture.note_on(69);
+        let output = fixture.process();
+
+        assert_relative_eq!(estimate_frequency(samples(&output)), 880.0, epsilon = 2.0);
+    }
+
+    #[test]
+    fn bends_the_pitch_from_midi() {
+        let mut fixture = Fixture::new(sine_template(), 4, VoiceStealing::Oldest);
+
+        let bend = MidiBlockEvent {
+            frame: 0,
+            message: MidiMessage::PitchBend {
+                channel: 0,
+                value: 4096,
+            },
+        };
+
+        fixture.note_on(69);
+        let output = fixture.process_with_midi(&[bend]);
+
+        let expected = 440.0 * 2.0_f64.powf(1.0 / 12.0);
+        assert_relative_eq!(
+            estimate_frequency(samples(&output)),
+            expected,
+            epsilon = 1.0
+        );
+    }
+
+    #[test]
+    fn glides_between_notes() {
+        let mut fixture = Fixture::new(sine_template(), 4, VoiceStealing::Oldest);
+        fixture.set_parameter("glide", 100.0);
+
+        fixture.note_on(57);
+        fixture.process();
+
+        fixture.send(SynthEventType::NoteOff { note: 57 });
+        fixture.note_on(69);
+
+        let mut frequencies = Vec::new();
+
+        for _ in 0..3 {
+            let output = fixture.process();
+            frequencies.push(estimate_frequency(samples(&output)));
+        }
+
+        assert!(frequencies[0] > 220.0 && frequencies[0] < 440.0);
+        assert!(frequencies[1] > frequencies[0]);
+        assert_relative_eq!(frequencies[2], 440.0, epsilon = 1.0);
+    }
+
+    #[test]
+    fn plays_pulse_waves_with_the_oscillator_width() {
+        let template = SynthVoiceTemplate::new().with_oscillator(
+            SynthOscillator::new(OscillatorWaveform::Pulse).with_pulse_width(0.25),
+        );
+
+        let mut fixture = Fixture::new(template, 4, VoiceStealing::Oldest);
+
+        fixture.note_on(69);
+        let output = fixture.process();
+
+        let high_count = samples(&output)
+            .iter()
+            .filter(|value| **value > 0.0)
+            .count();
+
+        assert_relative_eq!(high_count as f64 / FRAME_COUNT as f64, 0.25, epsilon = 0.01);
+    }
+
+    #[test]
+    fn filters_each_voice() {
+        let template = SynthVoiceTemplate::new()
+            .with_oscillator(SynthOscillator::new(OscillatorWaveform::Sine))
+            .with_filter(BiquadFilterType::LowPass);
+
+        let mut fixture = Fixture::new(template, 4, VoiceStealing::Oldest);
+        fixture.set_parameter("filter-frequency", 100.0);
+
+        fixture.note_on(93);
+        fixture.process();
+        let output = fixture.process();
+
+        assert!(rms(samples(&output)) < 0.01);
+    }
+
+    #[test]
+    fn steals_the_oldest_voice() {
+        let mut fixture = Fixture::new(sine_template(), 2, VoiceStealing::Oldest);
+
+        for note in [60, 64, 67] {
+            fixture.note_on(note);
+        }
+
+        fixture.process();
+
+        assert_eq!(fixture.active_notes(), vec![64, 67]);
+    }
+
+    #[test]
+    fn fades_out_stolen_voices() {
+        let mut fixture = Fixture::new(sine_template(), 1, VoiceStealing::Oldest);
+
+        fixture.note_on(60);
+        let first = fixture.process();
+
+        fixture.note_on(64);
+        let second = fixture.process();
+
+        let last_value = samples(&first)[FRAME_COUNT - 1];
+        assert!(last_value.abs() > 0.5);
+        assert!((samples(&second)[0] - last_value).abs() < 0.1);
+
+        assert!(!fixture.processor.fading_voices[0].is_active());
+        assert_eq!(fixture.active_notes(), vec![64]);
+    }
+
+    #[test]
+    fn ignores_notes_when_stealing_is_disabled() {
+        let mut fixture = Fixture::new(sine_template(), 1, VoiceStealing::None);
+
+        fixture.note_on(60);
+        fixture.note_on(64);
+        fixture.process();
+
+        assert_eq!(fixture.active_notes(), vec![60]);
+    }
+}
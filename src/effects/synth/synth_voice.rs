@@ -0,0 +1,209 @@
+use super::voice_template::SynthOscillator;
+use crate::{
+    effects::{adsr::AdsrEnvelope, biquad::BiquadCoefficients},
+    OscillatorWaveform,
+};
+use itertools::izip;
+
+/// An oscillator from the voice template, prepared for rendering
+pub struct VoiceOscillator {
+    waveform: OscillatorWaveform,
+    gain: f64,
+    ratio: f64,
+    pulse_width: f64,
+}
+
+impl From<&SynthOscillator> for VoiceOscillator {
+    fn from(oscillator: &SynthOscillator) -> Self {
+        Self {
+            waveform: oscillator.waveform,
+            gain: oscillator.level.as_linear(),
+            ratio: 2.0_f64.powf(oscillator.detune / 1_200.0),
+            pulse_width: oscillator.pulse_width,
+        }
+    }
+}
+
+/// The settings that are shared by all voices while rendering a block
+pub struct VoiceControls<'a> {
+    pub oscillators: &'a [VoiceOscillator],
+    pub coefficients: Option<&'a BiquadCoefficients>,
+    pub pitch_offset: f64,
+    pub sample_rate: f64,
+}
+
+pub struct SynthVoice {
+    note: u8,
+    gain: f64,
+    start_order: usize,
+    is_active: bool,
+
+    pitch: f64,
+    target_pitch: f64,
+    glide_step: f64,
+    glide_remaining: usize,
+
+    phases: Vec<f64>,
+    filter_delays: [f64; 4],
+    envelope: AdsrEnvelope,
+
+    fade_remaining: Option<usize>,
+    fade_length: usize,
+}
+
+const MAXIMUM_PHASE_INCREMENT: f64 = 0.5;
+
+impl SynthVoice {
+    pub fn new(oscillator_count: usize, envelope: AdsrEnvelope) -> Self {
+        Self {
+            note: 0,
+            gain: 0.0,
+            start_order: 0,
+            is_active: false,
+            pitch: 0.0,
+            target_pitch: 0.0,
+            glide_step: 0.0,
+            glide_remaining: 0,
+            phases: vec![0.0; oscillator_count],
+            filter_delays: [0.0; 4],
+            envelope,
+            fade_remaining: None,
+            fade_length: 0,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    pub fn is_holding(&self, note: u8) -> bool {
+        self.is_active
+            && self.note == note
+            && self.fade_remaining.is_none()
+            && !self.envelope.is_releasing()
+    }
+
+    pub fn start_order(&self) -> usize {
+        self.start_order
+    }
+
+    /// The current output level of the voice
+    pub fn level(&self) -> f64 {
+        self.gain * self.envelope.value()
+    }
+
+    pub fn envelope_mut(&mut self) -> &mut AdsrEnvelope {
+        &mut self.envelope
+    }
+
+    /// Start playing a note, gliding from `start_pitch` over `glide_length`
+    /// frames
+    pub fn start(
+        &mut self,
+        note: u8,
+        gain: f64,
+        start_pitch: f64,
+        glide_length: usize,
+        start_order: usize,
+    ) {
+        self.note = note;
+        self.gain = gain;
+        self.start_order = start_order;
+        self.is_active = true;
+
+        self.target_pitch = note as f64;
+
+        if glide_length > 0 && start_pitch != self.target_pitch {
+            self.pitch = start_pitch;
+            self.glide_step = (self.target_pitch - start_pitch) / glide_length as f64;
+            self.glide_remaining = glide_length;
+        } else {
+            self.pitch = self.target_pitch;
+            self.glide_remaining = 0;
+        }
+
+        self.phases.fill(0.0);
+        self.filter_delays = [0.0; 4];
+        self.fade_remaining = None;
+
+        self.envelope.reset();
+        self.envelope.open();
+    }
+
+    pub fn release(&mut self) {
+        self.envelope.close();
+    }
+
+    /// Quickly fade out the voice, so that it can be replaced without a click
+    pub fn fade_out(&mut self, fade_length: usize) {
+        self.fade_length = fade_length.max(1);
+        self.fade_remaining = Some(self.fade_length);
+    }
+
+    pub fn render(&mut self, output: &mut [f32], controls: &VoiceControls) {
+        if !self.is_active {
+            return;
+        }
+
+        let mut frequency = self.frequency(controls.pitch_offset);
+
+        for sample in output.iter_mut() {
+            if self.glide_remaining > 0 {
+                self.glide_remaining -= 1;
+                self.pitch = if self.glide_remaining == 0 {
+                    self.target_pitch
+                } else {
+                    self.pitch + self.glide_step
+                };
+
+                frequency = self.frequency(controls.pitch_offset);
+            }
+
+            let mut value = 0.0;
+
+            for (oscillator, phase) in izip!(controls.oscillators, self.phases.iter_mut()) {
+                let increment = (frequency * oscillator.ratio / controls.sample_rate)
+                    .min(MAXIMUM_PHASE_INCREMENT);
+
+                value += oscillator.gain
+                    * oscillator.waveform.band_limited_value(
+                        *phase,
+                        increment,
+                        oscillator.pulse_width,
+                    );
+
+                *phase += increment;
+                if *phase >= 1.0 {
+                    *phase -= 1.0;
+                }
+            }
+
+            if let Some(coefficients) = controls.coefficients {
+                value = coefficients.process_sample(value, &mut self.filter_delays);
+            }
+
+            let envelope = self.envelope.process();
+
+            let fade = match self.fade_remaining.as_mut() {
+                Some(remaining) => {
+                    let fade = *remaining as f64 / self.fade_length as f64;
+                    *remaining = remaining.saturating_sub(1);
+                    fade
+                }
+                None => 1.0,
+            };
+
+            *sample += (value * envelope * self.gain * fade) as f32;
+
+            if self.envelope.is_idle() || self.fade_remaining == Some(0) {
+                self.is_active = false;
+                self.fade_remaining = None;
+                break;
+            }
+        }
+    }
+
+    fn frequency(&self, pitch_offset: f64) -> f64 {
+        440.0 * 2.0_f64.powf((self.pitch + pitch_offset - 69.0) / 12.0)
+    }
+}
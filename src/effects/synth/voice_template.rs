@@ -0,0 +1,88 @@
+use crate::{BiquadFilterType, Level, OscillatorWaveform};
+
+/// An oscillator in a synthesizer voice
+#[derive(Clone, Copy, Debug)]
+pub struct SynthOscillator {
+    pub(super) waveform: OscillatorWaveform,
+    pub(super) level: Level,
+    pub(super) detune: f64,
+    pub(super) pulse_width: f64,
+}
+
+const MIN_PULSE_WIDTH: f64 = 0.01;
+const MAX_PULSE_WIDTH: f64 = 0.99;
+
+impl SynthOscillator {
+    /// Create an oscillator at unity level, playing the note's pitch
+    pub fn new(waveform: OscillatorWaveform) -> Self {
+        Self {
+            waveform,
+            level: Level::unity(),
+            detune: 0.0,
+            pulse_width: 0.5,
+        }
+    }
+
+    /// Set the level of the oscillator in the voice
+    pub fn with_level(mut self, level: Level) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Offset the pitch of the oscillator from the note, in cents
+    pub fn with_detune(mut self, detune_in_cents: f64) -> Self {
+        self.detune = detune_in_cents;
+        self
+    }
+
+    /// Set the width of [OscillatorWaveform::Pulse], as a fraction of each cycle
+    ///
+    /// The width is clamped between 0.01 and 0.99
+    pub fn with_pulse_width(mut self, pulse_width: f64) -> Self {
+        self.pulse_width = pulse_width.clamp(MIN_PULSE_WIDTH, MAX_PULSE_WIDTH);
+        self
+    }
+}
+
+/// Describes how each voice of a [crate::Synth] is built
+///
+/// Each voice sums its oscillators, passes them through an optional filter,
+/// and then applies an amplitude envelope.
+///
+/// # Example
+///
+/// ```rust
+/// use rawdio::{BiquadFilterType, Level, OscillatorWaveform, SynthOscillator, SynthVoiceTemplate};
+///
+/// let template = SynthVoiceTemplate::new()
+///     .with_oscillator(SynthOscillator::new(OscillatorWaveform::Saw).with_detune(-7.0))
+///     .with_oscillator(SynthOscillator::new(OscillatorWaveform::Saw).with_detune(7.0))
+///     .with_oscillator(SynthOscillator::new(OscillatorWaveform::Square).with_level(Level::from_db(-6.0)))
+///     .with_filter(BiquadFilterType::LowPass);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SynthVoiceTemplate {
+    pub(super) oscillators: Vec<SynthOscillator>,
+    pub(super) filter_type: Option<BiquadFilterType>,
+}
+
+impl SynthVoiceTemplate {
+    /// Create a template without any oscillators or a filter
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an oscillator to the voice
+    pub fn with_oscillator(mut self, oscillator: SynthOscillator) -> Self {
+        self.oscillators.push(oscillator);
+        self
+    }
+
+    /// Filter the oscillators in each voice
+    ///
+    /// The filter is controlled with the synth's filter parameters
+    pub fn with_filter(mut self, filter_type: BiquadFilterType) -> Self {
+        self.filter_type = Some(filter_type);
+        self
+    }
+}
@@ -52,6 +52,10 @@ where
         }
     }
 
+    pub fn pending_events(&self) -> &[Event] {
+        &self.pending_events
+    }
+
     fn next_event_before(&mut self, end_time: &Timestamp) -> Option<Event> {
         if let Some(next_event) = self.pending_events.first() {
             if next_event.get_time() < *end_time {
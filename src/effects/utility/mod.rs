@@ -1,8 +1,10 @@
 mod envelope_follower;
 mod event_processor;
 mod periodic_notification;
+mod voice_stealing;
 
 pub use envelope_follower::EnvelopeFollower;
 pub use event_processor::EventProcessor;
 pub use event_processor::EventProcessorEvent;
 pub use periodic_notification::PeriodicNotification;
+pub use voice_stealing::VoiceStealing;
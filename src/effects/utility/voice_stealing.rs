@@ -0,0 +1,13 @@
+/// What to do when a note is played and all of the voices are in use
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum VoiceStealing {
+    /// Ignore the new note
+    None,
+
+    /// Stop the voice that started playing first
+    #[default]
+    Oldest,
+
+    /// Stop the voice with the lowest output level
+    Quietest,
+}
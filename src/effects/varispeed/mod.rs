@@ -0,0 +1,4 @@
+mod varispeed_node;
+mod varispeed_processor;
+
+pub use varispeed_node::Varispeed;
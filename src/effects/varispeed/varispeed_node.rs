@@ -0,0 +1,97 @@
+use super::varispeed_processor::VarispeedProcessor;
+use crate::{
+    commands::Id, graph::DspNode, parameter::*, prelude::*, resample::ResamplerQuality,
+    utility::create_parameters,
+};
+use std::time::Duration;
+
+/// A node that plays its input back at a different speed
+///
+/// The input is resampled with a ratio that can be changed at audio-rate,
+/// like varying the speed of a tape machine. A ratio of 2 plays twice as fast
+/// (an octave up) and a ratio of 0.5 plays at half speed (an octave down).
+///
+/// As the input arrives in realtime, playing slower than the input builds up
+/// a backlog, up to `maximum_buffer_time`, after which the oldest audio is
+/// dropped. Playing faster than the input uses up the backlog, and the output
+/// will be silent once it runs out.
+///
+/// The output is delayed by the latency of the resampler.
+///
+/// # Parameters
+/// - ratio
+pub struct Varispeed {
+    /// The node to connect into the audio graph
+    pub node: GraphNode,
+
+    params: Parameters,
+    latency: usize,
+}
+
+impl DspNode for Varispeed {
+    fn get_parameters_mut(&mut self) -> &mut Parameters {
+        &mut self.params
+    }
+}
+
+const MINIMUM_RATIO: f64 = 0.125;
+const MAXIMUM_RATIO: f64 = 8.0;
+
+impl Varispeed {
+    /// Create a new varispeed node
+    pub fn new(
+        context: &dyn Context,
+        channel_count: usize,
+        maximum_buffer_time: Duration,
+        quality: ResamplerQuality,
+    ) -> Self {
+        let id = Id::generate();
+
+        let (params, realtime_params) = create_parameters(
+            id,
+            context,
+            [(
+                "ratio",
+                ParameterRange::new(1.0, MINIMUM_RATIO, MAXIMUM_RATIO),
+            )],
+        );
+
+        let sample_rate = context.get_sample_rate();
+        let maximum_buffer_frames =
+            (maximum_buffer_time.as_secs_f64() * sample_rate as f64).ceil() as usize;
+
+        let processor = Box::new(VarispeedProcessor::new(
+            channel_count,
+            sample_rate,
+            maximum_buffer_frames,
+            quality,
+        ));
+
+        let latency = processor.latency();
+
+        Self {
+            node: GraphNode::new(
+                id,
+                context,
+                channel_count,
+                channel_count,
+                processor,
+                realtime_params,
+            ),
+            params,
+            latency,
+        }
+    }
+
+    /// Get the ratio parameter
+    ///
+    /// This is the number of input frames that are played for each output frame
+    pub fn ratio(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("ratio")
+    }
+
+    /// The number of frames that the output is delayed by
+    pub fn latency(&self) -> usize {
+        self.latency
+    }
+}
@@ -0,0 +1,240 @@
+use crate::{
+    graph::DspProcessor,
+    prelude::*,
+    resample::{Resampler, ResamplerQuality},
+    BorrowedAudioBuffer, ProcessContext,
+};
+
+pub struct VarispeedProcessor {
+    resampler: Resampler,
+    backlog: OwnedAudioBuffer,
+    read_position: usize,
+    buffered_frame_count: usize,
+}
+
+impl VarispeedProcessor {
+    pub fn new(
+        channel_count: usize,
+        sample_rate: usize,
+        maximum_buffer_frames: usize,
+        quality: ResamplerQuality,
+    ) -> Self {
+        let resampler = Resampler::new(quality, channel_count, 1.0);
+
+        // Start with the latency of the resampler already buffered, so that the
+        // output is continuous when the ratio is 1
+        let buffered_frame_count = resampler.latency();
+
+        Self {
+            backlog: OwnedAudioBuffer::new(
+                maximum_buffer_frames.max(buffered_frame_count + 1),
+                channel_count,
+                sample_rate,
+            ),
+            resampler,
+            read_position: 0,
+            buffered_frame_count,
+        }
+    }
+
+    pub fn latency(&self) -> usize {
+        self.resampler.latency()
+    }
+
+    fn write_backlog(&mut self, input_buffer: &dyn AudioBuffer) {
+        let capacity = self.backlog.frame_count();
+        let channel_count = self
+            .backlog
+            .channel_count()
+            .min(input_buffer.channel_count());
+
+        let mut input_position = 0;
+
+        while input_position < input_buffer.frame_count() {
+            let write_position = (self.read_position + self.buffered_frame_count) % capacity;
+            let frame_count =
+                (input_buffer.frame_count() - input_position).min(capacity - write_position);
+
+            self.backlog.copy_from(
+                input_buffer,
+                SampleLocation::frame(input_position),
+                SampleLocation::frame(write_position),
+                channel_count,
+                frame_count,
+            );
+
+            input_position += frame_count;
+            self.buffered_frame_count += frame_count;
+
+            if self.buffered_frame_count > capacity {
+                let overflow = self.buffered_frame_count - capacity;
+                self.read_position = (self.read_position + overflow) % capacity;
+                self.buffered_frame_count = capacity;
+            }
+        }
+    }
+}
+
+impl DspProcessor for VarispeedProcessor {
+    fn process_audio(&mut self, context: &mut ProcessContext) {
+        let frame_count = context.output_buffer.frame_count();
+        let ratio = context
+            .parameters
+            .get_parameter_values("ratio", frame_count);
+
+        self.write_backlog(context.input_buffer);
+
+        let capacity = self.backlog.frame_count();
+        let mut position = 0;
+
+        while position < frame_count && self.buffered_frame_count > 0 {
+            let input = BorrowedAudioBuffer::slice_frames(
+                &self.backlog,
+                self.read_position,
+                self.buffered_frame_count.min(capacity - self.read_position),
+            );
+
+            let mut output = MutableBorrowedAudioBuffer::slice_frames(
+                context.output_buffer,
+                position,
+                frame_count - position,
+            );
+
+            let result =
+                self.resampler
+                    .process_with_ratios(&input, &mut output, &ratio[position..]);
+
+            if result.input_frame_count == 0 && result.output_frame_count == 0 {
+                break;
+            }
+
+            self.read_position = (self.read_position + result.input_frame_count) % capacity;
+            self.buffered_frame_count -= result.input_frame_count;
+            position += result.output_frame_count;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{graph::DspParameters, parameter::RealtimeAudioParameter};
+    use atomic_float::AtomicF64;
+    use std::sync::Arc;
+
+    const SAMPLE_RATE: usize = 48_000;
+    const FRAME_COUNT: usize = 512;
+
+    struct Fixture {
+        processor: VarispeedProcessor,
+        parameters: DspParameters,
+    }
+
+    impl Fixture {
+        fn new(ratio: f64, maximum_buffer_frames: usize) -> Self {
+            Self {
+                processor: VarispeedProcessor::new(
+                    1,
+                    SAMPLE_RATE,
+                    maximum_buffer_frames,
+                    ResamplerQuality::Cubic,
+                ),
+                parameters: DspParameters::new([RealtimeAudioParameter::new(
+                    "ratio",
+                    Arc::new(AtomicF64::new(ratio)),
+                    FRAME_COUNT,
+                )]),
+            }
+        }
+
+        fn process(&mut self, input: &dyn AudioBuffer) -> OwnedAudioBuffer {
+            let mut output = OwnedAudioBuffer::new(FRAME_COUNT, 1, SAMPLE_RATE);
+
+            for (_, parameter) in self.parameters.iter_mut() {
+                parameter.process(&Timestamp::zero(), FRAME_COUNT, SAMPLE_RATE);
+            }
+
+            self.processor.process_audio(&mut ProcessContext {
+                input_buffer: input,
+                output_buffer: &mut output,
+                start_time: &Timestamp::zero(),
+                parameters: &self.parameters,
+                midi_events: &[],
+            });
+
+            output
+        }
+    }
+
+    fn ramp(start: usize) -> OwnedAudioBuffer {
+        let mut buffer = OwnedAudioBuffer::new(FRAME_COUNT, 1, SAMPLE_RATE);
+
+        for frame in 0..FRAME_COUNT {
+            buffer.set_sample(
+                SampleLocation::frame(frame),
+                (start + frame) as f32 / 1_000.0,
+            );
+        }
+
+        buffer
+    }
+
+    #[test]
+    fn is_delayed_by_latency_at_unity_ratio() {
+        let mut fixture = Fixture::new(1.0, SAMPLE_RATE);
+        let latency = fixture.processor.latency();
+
+        let mut output = fixture.process(&ramp(0));
+        output = output.extended_with_buffer(&fixture.process(&ramp(FRAME_COUNT)));
+
+        for frame in latency..2 * FRAME_COUNT {
+            assert_relative_eq!(
+                output.get_sample(SampleLocation::frame(frame)),
+                (frame - latency) as f32 / 1_000.0,
+                epsilon = 1e-4
+            );
+        }
+    }
+
+    #[test]
+    fn plays_at_half_speed() {
+        let mut fixture = Fixture::new(0.5, SAMPLE_RATE);
+        let latency = fixture.processor.latency();
+
+        let output = fixture.process(&ramp(0));
+
+        for frame in 2 * latency..FRAME_COUNT {
+            assert_relative_eq!(
+                output.get_sample(SampleLocation::frame(frame)),
+                (frame as f32 * 0.5 - latency as f32) / 1_000.0,
+                epsilon = 1e-4
+            );
+        }
+    }
+
+    #[test]
+    fn runs_out_when_playing_faster_than_input() {
+        let mut fixture = Fixture::new(2.0, SAMPLE_RATE);
+
+        let output = fixture.process(&ramp(0));
+        let last_frame = FRAME_COUNT - 1;
+
+        assert_ne!(output.get_sample(SampleLocation::frame(100)), 0.0);
+        assert_eq!(output.get_sample(SampleLocation::frame(last_frame)), 0.0);
+    }
+
+    #[test]
+    fn drops_oldest_audio_when_backlog_is_full() {
+        let maximum_buffer_frames = 600;
+        let mut fixture = Fixture::new(0.125, maximum_buffer_frames);
+
+        for block in 0..4 {
+            fixture.process(&ramp(block * FRAME_COUNT));
+        }
+
+        assert_eq!(
+            fixture.processor.buffered_frame_count,
+            maximum_buffer_frames
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{CommandQueue, Timestamp};
+use crate::{CommandQueue, RejectedConnection, Timestamp};
 
 #[derive(PartialEq)]
 pub enum NotifierStatus {
@@ -30,6 +30,13 @@ pub trait Context {
     /// The maximum number of frames that will be processed per block
     fn maximum_frame_count(&self) -> usize;
 
+    /// The maximum number of channels between any two nodes
+    ///
+    /// A context without a limit returns `usize::MAX`
+    fn maximum_channel_count(&self) -> usize {
+        usize::MAX
+    }
+
     /// Get the command queue to send commands to the context
     fn get_command_queue(&self) -> Box<dyn CommandQueue>;
 
@@ -39,4 +46,12 @@ pub trait Context {
 
     /// Generate all notifications
     fn process_notifications(&mut self);
+
+    /// Take the connections that have been rejected by the audio process since
+    /// this was last called
+    ///
+    /// Connections that close a feedback cycle are processed with a delay of
+    /// one block. There is a limit to how many of these connections the audio
+    /// process can hold, and any further feedback connections will be rejected.
+    fn take_rejected_connections(&mut self) -> Vec<RejectedConnection>;
 }
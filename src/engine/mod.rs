@@ -1,10 +1,12 @@
 mod audio_process;
 mod command_queue;
 mod context;
+mod offline_context;
 mod root;
 
 pub use audio_process::AudioProcess;
 pub use command_queue::CommandQueue;
 pub use context::Context;
 pub use context::NotifierStatus;
+pub use offline_context::{OfflineContext, OfflineRenderStatus};
 pub use root::{create_engine, create_engine_with_options, EngineOptions};
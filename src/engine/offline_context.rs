@@ -0,0 +1,206 @@
+use super::{context::NotifierStatus, CommandQueue};
+use crate::{prelude::*, RejectedConnection};
+use std::time::Duration;
+
+/// The result of rendering an [OfflineContext]
+#[derive(Debug, PartialEq, Eq)]
+pub enum OfflineRenderStatus {
+    /// Rendering reached a time that was passed to [OfflineContext::suspend_at]
+    ///
+    /// The graph can be changed before calling [OfflineContext::render] again
+    Suspended(Timestamp),
+
+    /// The whole duration has been rendered
+    Complete,
+}
+
+/// A context that renders a fixed duration of audio as fast as possible
+///
+/// This is similar to an `OfflineAudioContext` in the Web Audio API. Nodes are
+/// created with it in the same way as any other [Context], and then the audio
+/// is rendered into an [OwnedAudioBuffer] on the calling thread.
+///
+/// Commands are processed before every block, and notifications are processed
+/// after every block, so rendering is deterministic.
+///
+/// # Example
+///
+/// ```rust
+/// use rawdio::{prelude::*, OfflineContext, OfflineRenderStatus, Oscillator};
+/// use std::time::Duration;
+///
+/// let channel_count = 2;
+/// let mut context =
+///     OfflineContext::new(EngineOptions::default(), channel_count, Duration::from_secs(2));
+///
+/// let mut oscillator = Oscillator::sine(&context, 440.0, channel_count);
+/// connect_nodes!(oscillator => "output");
+///
+/// context.suspend_at(Timestamp::from_seconds(1.0));
+///
+/// while let OfflineRenderStatus::Suspended(_) = context.render() {
+///     oscillator.frequency().set_value_now(880.0);
+/// }
+///
+/// let rendered = context.into_output_buffer();
+/// ```
+pub struct OfflineContext {
+    context: Box<dyn Context>,
+    process: Box<dyn AudioProcess + Send>,
+    input_buffer: OwnedAudioBuffer,
+    output_buffer: OwnedAudioBuffer,
+    position: usize,
+    suspensions: Vec<usize>,
+}
+
+impl OfflineContext {
+    /// Create an offline context that will render `duration` of audio with
+    /// `channel_count` output channels
+    pub fn new(options: EngineOptions, channel_count: usize, duration: Duration) -> Self {
+        let (context, process) = create_engine_with_options(options);
+
+        let sample_rate = context.get_sample_rate();
+        let frame_count = Timestamp::from_duration(duration)
+            .as_samples(sample_rate)
+            .ceil() as usize;
+
+        Self {
+            input_buffer: OwnedAudioBuffer::new(
+                context.maximum_frame_count(),
+                channel_count,
+                sample_rate,
+            ),
+            output_buffer: OwnedAudioBuffer::new(frame_count, channel_count, sample_rate),
+            context,
+            process,
+            position: 0,
+            suspensions: Vec::new(),
+        }
+    }
+
+    /// The total number of frames that will be rendered
+    pub fn frame_count(&self) -> usize {
+        self.output_buffer.frame_count()
+    }
+
+    /// Suspend rendering when it reaches `time`
+    ///
+    /// The suspension is sample accurate. Times that have already been
+    /// rendered, or that are beyond the end of the render, are ignored.
+    pub fn suspend_at(&mut self, time: Timestamp) {
+        let frame = time.as_samples(self.get_sample_rate()).round() as usize;
+
+        if frame < self.position || frame >= self.frame_count() {
+            return;
+        }
+
+        if let Err(index) = self.suspensions.binary_search(&frame) {
+            self.suspensions.insert(index, frame);
+        }
+    }
+
+    /// Render until the next suspension, or until the end
+    ///
+    /// This will start the context if it hasn't been started
+    pub fn render(&mut self) -> OfflineRenderStatus {
+        self.context.start();
+
+        loop {
+            if self.suspensions.first() == Some(&self.position) {
+                self.suspensions.remove(0);
+
+                return OfflineRenderStatus::Suspended(Timestamp::from_samples(
+                    self.position as f64,
+                    self.get_sample_rate(),
+                ));
+            }
+
+            if self.position >= self.frame_count() {
+                return OfflineRenderStatus::Complete;
+            }
+
+            let end = self
+                .suspensions
+                .first()
+                .copied()
+                .unwrap_or(usize::MAX)
+                .min(self.frame_count())
+                .min(self.position + self.maximum_frame_count());
+
+            self.render_block(end - self.position);
+        }
+    }
+
+    /// Render the whole duration, ignoring any suspensions
+    pub fn render_to_end(&mut self) {
+        self.suspensions.clear();
+        self.render();
+    }
+
+    /// The audio that has been rendered
+    pub fn output_buffer(&self) -> &OwnedAudioBuffer {
+        &self.output_buffer
+    }
+
+    /// Take the audio that has been rendered
+    pub fn into_output_buffer(self) -> OwnedAudioBuffer {
+        self.output_buffer
+    }
+
+    fn render_block(&mut self, frame_count: usize) {
+        let input_slice = BorrowedAudioBuffer::slice_frames(&self.input_buffer, 0, frame_count);
+
+        let mut output_slice = MutableBorrowedAudioBuffer::slice_frames(
+            &mut self.output_buffer,
+            self.position,
+            frame_count,
+        );
+
+        self.process.process(&input_slice, &mut output_slice);
+        self.context.process_notifications();
+
+        self.position += frame_count;
+    }
+}
+
+impl Context for OfflineContext {
+    fn start(&mut self) {
+        self.context.start();
+    }
+
+    fn stop(&mut self) {
+        self.context.stop();
+    }
+
+    fn current_time(&self) -> Timestamp {
+        self.context.current_time()
+    }
+
+    fn get_sample_rate(&self) -> usize {
+        self.context.get_sample_rate()
+    }
+
+    fn maximum_frame_count(&self) -> usize {
+        self.context.maximum_frame_count()
+    }
+
+    fn maximum_channel_count(&self) -> usize {
+        self.context.maximum_channel_count()
+    }
+
+    fn get_command_queue(&self) -> Box<dyn CommandQueue> {
+        self.context.get_command_queue()
+    }
+
+    fn add_notifier(&mut self, notifier: Box<dyn Fn() -> NotifierStatus>) {
+        self.context.add_notifier(notifier);
+    }
+
+    fn process_notifications(&mut self) {
+        self.context.process_notifications();
+    }
+
+    fn take_rejected_connections(&mut self) -> Vec<RejectedConnection> {
+        self.context.take_rejected_connections()
+    }
+}
@@ -1,5 +1,5 @@
 use super::{context::NotifierStatus, CommandQueue};
-use crate::{commands::Command, prelude::*, realtime::Processor};
+use crate::{commands::Command, prelude::*, realtime::Processor, RejectedConnection};
 use std::sync::{atomic::AtomicI64, Arc};
 
 pub struct Root {
@@ -7,9 +7,13 @@ pub struct Root {
     timestamp: Arc<AtomicI64>,
     command_transmitter: CommandTransmitter,
     notifiers: Vec<Box<dyn Fn() -> NotifierStatus>>,
+    rejected_connection_rx: crossbeam::channel::Receiver<RejectedConnection>,
     maximum_frame_count: usize,
+    maximum_channel_count: usize,
 }
 
+const REJECTED_CONNECTION_CHANNEL_CAPACITY: usize = 64;
+
 impl Context for Root {
     fn start(&mut self) {
         self.command_transmitter.send(Command::Start);
@@ -43,6 +47,14 @@ impl Context for Root {
     fn maximum_frame_count(&self) -> usize {
         self.maximum_frame_count
     }
+
+    fn maximum_channel_count(&self) -> usize {
+        self.maximum_channel_count
+    }
+
+    fn take_rejected_connections(&mut self) -> Vec<RejectedConnection> {
+        self.rejected_connection_rx.try_iter().collect()
+    }
 }
 
 /// Options to control the engine
@@ -50,6 +62,7 @@ pub struct EngineOptions {
     sample_rate: usize,
     maximum_channel_count: usize,
     maximum_frame_count: usize,
+    worker_thread_count: usize,
 }
 
 impl Default for EngineOptions {
@@ -58,6 +71,7 @@ impl Default for EngineOptions {
             sample_rate: 44_100,
             maximum_channel_count: 2,
             maximum_frame_count: 512,
+            worker_thread_count: 0,
         }
     }
 }
@@ -88,6 +102,18 @@ impl EngineOptions {
         self.maximum_frame_count = maximum_frame_count;
         self
     }
+
+    /// Specify the number of worker threads used to process the graph
+    ///
+    /// Independent branches of the graph will be processed concurrently by
+    /// the workers and the audio thread. Each worker busy-waits while a block
+    /// is being processed, so this is best suited to large graphs.
+    ///
+    /// The default of zero processes the whole graph on the audio thread.
+    pub fn with_worker_thread_count(mut self, worker_thread_count: usize) -> Self {
+        self.worker_thread_count = worker_thread_count;
+        self
+    }
 }
 
 /// Create an engine with the default options
@@ -121,11 +147,16 @@ pub fn create_engine_with_options(
 
     let timestamp = Arc::new(AtomicI64::new(0));
 
+    let (rejected_connection_tx, rejected_connection_rx) =
+        crossbeam::channel::bounded(REJECTED_CONNECTION_CHANNEL_CAPACITY);
+
     let processor = Box::new(Processor::new(
         options.sample_rate,
         options.maximum_channel_count,
         options.maximum_frame_count,
+        options.worker_thread_count,
         command_receiver,
+        rejected_connection_tx,
         Arc::clone(&timestamp),
     ));
 
@@ -134,7 +165,9 @@ pub fn create_engine_with_options(
         timestamp,
         command_transmitter,
         notifiers: Vec::new(),
+        rejected_connection_rx,
         maximum_frame_count: options.maximum_frame_count,
+        maximum_channel_count: options.maximum_channel_count,
     });
 
     (engine, processor)
@@ -24,6 +24,10 @@ where
         self.assigned_buffers.insert(*id, buffer);
     }
 
+    pub fn get(&self, id: &Identifier) -> Option<&OwnedAudioBuffer> {
+        self.assigned_buffers.get(id)
+    }
+
     pub fn is_empty(&self) -> bool {
         self.assigned_buffers.is_empty()
     }
@@ -32,11 +36,22 @@ where
         self.assigned_buffers.contains_key(id)
     }
 
+    pub fn remove_where<Predicate>(
+        &mut self,
+        predicate: Predicate,
+    ) -> Option<(Identifier, OwnedAudioBuffer)>
+    where
+        Predicate: Fn(&Identifier) -> bool,
+    {
+        let id = *self.assigned_buffers.keys().find(|id| predicate(id))?;
+
+        let buffer = self.remove(&id).expect("Buffer not found");
+
+        Some((id, buffer))
+    }
+
     pub fn remove_next(&mut self) -> Option<(Identifier, OwnedAudioBuffer)> {
-        let id = match self.assigned_buffers.keys().next() {
-            Some(id) => *id,
-            None => return None,
-        };
+        let id = *self.assigned_buffers.keys().next()?;
 
         let buffer = self.remove(&id).expect("Buffer not found");
 
@@ -1,4 +1,4 @@
-use crate::commands::Id;
+use crate::{commands::Id, parameter::ParameterId};
 
 use super::endpoint::{Endpoint, EndpointType};
 
@@ -8,6 +8,7 @@ pub struct Connection {
     pub source_output_channel: usize,
     pub destination: Endpoint,
     pub destination_input_channel: usize,
+    pub destination_parameter: Option<ParameterId>,
     pub channel_count: usize,
 }
 
@@ -18,6 +19,7 @@ impl Connection {
             destination: Endpoint::new(destination_id, EndpointType::Input),
             source_output_channel: 0,
             destination_input_channel: 0,
+            destination_parameter: None,
             channel_count,
         }
     }
@@ -31,4 +33,13 @@ impl Connection {
         self.destination_input_channel = channel;
         self
     }
+
+    pub fn with_destination_parameter(mut self, parameter_id: ParameterId) -> Self {
+        self.destination_parameter = Some(parameter_id);
+        self
+    }
+
+    pub fn is_to_parameter(&self) -> bool {
+        self.destination_parameter.is_some()
+    }
 }
@@ -1,8 +1,14 @@
 use crate::{
     commands::{CancelChangeRequest, Command, Id, ParameterChangeRequest},
     engine::CommandQueue,
+    midi::{MidiBlockEvent, MidiEvent, MidiQueue},
+    parameter::ParameterId,
     prelude::*,
 };
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
 
 use super::DspParameters;
 
@@ -12,6 +18,8 @@ pub struct Dsp {
     output_count: usize,
     processor: Box<dyn DspProcessor + Send + Sync>,
     parameters: DspParameters,
+    midi_queue: MidiQueue,
+    dropped_midi_event_count: Arc<AtomicUsize>,
 }
 
 /// The context that is passed to every [DspProcessor]
@@ -27,9 +35,22 @@ pub struct ProcessContext<'a> {
 
     /// The audio-rate parameters
     pub parameters: &'a DspParameters,
+
+    /// The MIDI messages that happen during the block, in order
+    pub midi_events: &'a [MidiBlockEvent],
 }
 
+/// The audio thread side of a node in the graph
+///
+/// Implement this trait to create your own node, and add it to the graph
+/// using [crate::GraphNodeBuilder].
+///
+/// `process_audio` is called on the audio thread, so it shouldn't allocate,
+/// lock, or block.
 pub trait DspProcessor {
+    /// Process a single block of audio
+    ///
+    /// The output buffer should be completely filled by the processor
     fn process_audio(&mut self, context: &mut ProcessContext);
 }
 
@@ -47,6 +68,8 @@ impl Dsp {
             output_count,
             processor,
             parameters,
+            midi_queue: MidiQueue::default(),
+            dropped_midi_event_count: Arc::new(AtomicUsize::new(0)),
         }
     }
 
@@ -70,6 +93,26 @@ impl Dsp {
         self.id
     }
 
+    pub fn process_parameters(
+        &mut self,
+        start_time: &Timestamp,
+        frame_count: usize,
+        sample_rate: usize,
+    ) {
+        for (_, parameter) in self.parameters.iter_mut() {
+            parameter.process(start_time, frame_count, sample_rate);
+        }
+
+        self.midi_queue
+            .prepare_block(start_time, frame_count, sample_rate);
+    }
+
+    pub fn modulate_parameter(&mut self, parameter_id: ParameterId, modulation: &[f32]) {
+        self.parameters
+            .get_parameter_mut(parameter_id)
+            .add_modulation(modulation);
+    }
+
     pub fn process_audio(
         &mut self,
         input_buffer: &dyn AudioBuffer,
@@ -80,11 +123,7 @@ impl Dsp {
         assert_eq!(output_buffer.channel_count(), self.output_count);
 
         for (_, parameter) in self.parameters.iter_mut() {
-            parameter.process(
-                start_time,
-                output_buffer.frame_count(),
-                output_buffer.sample_rate(),
-            );
+            parameter.clamp_modulation();
         }
 
         self.processor.process_audio(&mut ProcessContext {
@@ -92,9 +131,27 @@ impl Dsp {
             output_buffer,
             start_time,
             parameters: &self.parameters,
+            midi_events: self.midi_queue.block_events(),
         });
     }
 
+    pub fn schedule_midi_event(&mut self, event: MidiEvent) {
+        if !self.midi_queue.schedule(event) {
+            self.dropped_midi_event_count
+                .fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// The number of MIDI events that were dropped because the queue was
+    /// full, shared with the node on the control thread
+    pub fn dropped_midi_event_count(&self) -> Arc<AtomicUsize> {
+        Arc::clone(&self.dropped_midi_event_count)
+    }
+
+    pub fn cancel_midi_events(&mut self) {
+        self.midi_queue.cancel_all();
+    }
+
     pub fn request_parameter_change(&mut self, parameter_change: ParameterChangeRequest) {
         let parameter = self
             .parameters
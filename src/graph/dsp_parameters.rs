@@ -1,12 +1,14 @@
 use crate::parameter::*;
 use std::collections::HashMap;
 
+/// The audio-rate values of a node's parameters, as seen by the [crate::DspProcessor]
 pub struct DspParameters {
     parameters: HashMap<ParameterId, RealtimeAudioParameter>,
 }
 
 impl DspParameters {
-    pub fn new<I>(parameters: I) -> Self
+    #[cfg(test)]
+    pub(crate) fn new<I>(parameters: I) -> Self
     where
         I: IntoIterator<Item = RealtimeAudioParameter>,
     {
@@ -18,30 +20,33 @@ impl DspParameters {
         }
     }
 
-    pub fn with_parameter(mut self, parameter: RealtimeAudioParameter) -> Self {
+    pub(crate) fn with_parameter(mut self, parameter: RealtimeAudioParameter) -> Self {
         self.parameters.insert(parameter.get_id(), parameter);
         self
     }
 
-    pub fn empty() -> Self {
+    pub(crate) fn empty() -> Self {
         Self {
             parameters: HashMap::new(),
         }
     }
 
-    pub fn get_parameter(&self, id: ParameterId) -> &RealtimeAudioParameter {
+    pub(crate) fn get_parameter(&self, id: ParameterId) -> &RealtimeAudioParameter {
         self.parameters.get(&id).expect("Missing parameter")
     }
 
-    pub fn get_parameter_mut(&mut self, id: ParameterId) -> &mut RealtimeAudioParameter {
+    pub(crate) fn get_parameter_mut(&mut self, id: ParameterId) -> &mut RealtimeAudioParameter {
         self.parameters.get_mut(&id).expect("Missing parameter")
     }
 
+    /// Get the values of a parameter for each frame of the current block
+    ///
+    /// Panics if the node doesn't have a parameter with that name
     pub fn get_parameter_values(&self, id: ParameterId, frame_count: usize) -> &[f32] {
         self.get_parameter(id).get_values(frame_count)
     }
 
-    pub fn iter_mut(
+    pub(crate) fn iter_mut(
         &mut self,
     ) -> impl Iterator<Item = (&ParameterId, &mut RealtimeAudioParameter)> {
         self.parameters.iter_mut()
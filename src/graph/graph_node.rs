@@ -1,16 +1,26 @@
-use super::{Connection, Dsp, DspParameters, DspProcessor, Endpoint, EndpointType};
-use crate::{commands::*, engine::CommandQueue, prelude::*};
+use super::{Connection, Dsp, DspParameters, DspProcessor};
+use crate::{
+    commands::*,
+    engine::CommandQueue,
+    midi::{MidiEvent, MidiMessage},
+    prelude::*,
+};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
 
 /// A node the connects into the audio graph
 pub struct GraphNode {
     id: Id,
     command_queue: Box<dyn CommandQueue>,
+    input_count: usize,
     output_count: usize,
+    dropped_midi_event_count: Arc<AtomicUsize>,
 }
 
 impl GraphNode {
-    /// Create a new audio graph
-    pub fn new(
+    pub(crate) fn new(
         id: Id,
         context: &dyn Context,
         input_count: usize,
@@ -19,6 +29,7 @@ impl GraphNode {
         parameters: DspParameters,
     ) -> Self {
         let dsp = Dsp::new(id, input_count, output_count, processor, parameters);
+        let dropped_midi_event_count = dsp.dropped_midi_event_count();
 
         let command_queue = context.get_command_queue();
 
@@ -27,45 +38,76 @@ impl GraphNode {
         Self {
             id,
             command_queue,
+            input_count,
             output_count,
+            dropped_midi_event_count,
         }
     }
 
-    fn get_id(&self) -> Id {
+    /// Get the unique identifier of the node
+    pub fn get_id(&self) -> Id {
         self.id
     }
 
     /// Connect this node to the system input
     ///
-    /// Only one node can be connected to the system input.
-    /// If this is called on more than one node, the second connection will
-    /// replace the first.
+    /// Any number of nodes can be connected to the system input
     pub fn connect_to_input(&self) {
         self.command_queue
-            .send(Command::ConnectToInput(Endpoint::new(
+            .send(Command::AddConnection(Connection::new(
+                Id::system_input(),
                 self.get_id(),
-                EndpointType::Input,
+                self.input_count,
             )));
     }
 
+    /// Connect a subset of the system input channels to this node
+    pub fn connect_channels_to_input(
+        &self,
+        system_input_channel: usize,
+        destination_input_channel: usize,
+        channel_count: usize,
+    ) {
+        self.command_queue.send(Command::AddConnection(
+            Connection::new(Id::system_input(), self.get_id(), channel_count)
+                .with_source_output_channel(system_input_channel)
+                .with_destination_input_channel(destination_input_channel),
+        ));
+    }
+
     /// Connect this node to the system output
     ///
-    /// Only one node can be connected to the system output.
-    /// If this is called on more than one node, the second connection will
-    /// replace the first.
-    ///
-    /// If you need to connect more than one node to the output, you can
-    /// connect all nodes to another node (e.g. gain), and connect that node to
-    /// the output.
+    /// Any number of nodes can be connected to the system output, and the
+    /// outputs of all connected nodes will be summed together
     pub fn connect_to_output(&self) {
         self.command_queue
-            .send(Command::ConnectToOutput(Endpoint::new(
+            .send(Command::AddConnection(Connection::new(
                 self.get_id(),
-                EndpointType::Output,
+                Id::system_output(),
+                self.output_count,
             )));
     }
 
+    /// Connect a subset of channels from this node to the system output
+    pub fn connect_channels_to_output(
+        &self,
+        source_output_channel: usize,
+        system_output_channel: usize,
+        channel_count: usize,
+    ) {
+        self.command_queue.send(Command::AddConnection(
+            Connection::new(self.get_id(), Id::system_output(), channel_count)
+                .with_source_output_channel(source_output_channel)
+                .with_destination_input_channel(system_output_channel),
+        ));
+    }
+
     /// Connect the output of this node to the input of another node
+    ///
+    /// Connections can form cycles. A connection that closes a cycle will
+    /// deliver the output of this node from the previous block, and so adds a
+    /// delay of one block. See [Context::take_rejected_connections] for when
+    /// this isn't possible.
     pub fn connect_to(&self, node: &GraphNode) {
         self.command_queue
             .send(Command::AddConnection(Connection::new(
@@ -103,6 +145,86 @@ impl GraphNode {
     pub fn disconnect_from_node(&self, node: &GraphNode) {
         self.disconnect_from_id(node.get_id());
     }
+
+    /// Connect the output of this node to a parameter of another node
+    ///
+    /// The first output channel of this node will be added to the value of
+    /// the parameter for every sample. The result is clamped to the range of
+    /// the parameter.
+    pub fn connect_to_parameter(&self, parameter: &AudioParameter) {
+        self.connect_channel_to_parameter(parameter, 0);
+    }
+
+    /// Connect a single output channel of this node to a parameter of another
+    /// node
+    pub fn connect_channel_to_parameter(
+        &self,
+        parameter: &AudioParameter,
+        source_output_channel: usize,
+    ) {
+        self.command_queue.send(Command::AddConnection(
+            Connection::new(self.get_id(), parameter.get_dsp_id(), 1)
+                .with_source_output_channel(source_output_channel)
+                .with_destination_parameter(parameter.get_id()),
+        ));
+    }
+
+    /// Disconnect the output of this node from a parameter
+    pub fn disconnect_from_parameter(&self, parameter: &AudioParameter) {
+        self.command_queue.send(Command::RemoveConnection(
+            Connection::new(self.get_id(), parameter.get_dsp_id(), 1)
+                .with_destination_parameter(parameter.get_id()),
+        ));
+    }
+
+    /// Disconnect this node from the system output
+    pub fn disconnect_from_output(&self) {
+        self.disconnect_from_id(Id::system_output());
+    }
+
+    /// Schedule a MIDI message to be sent to this node
+    ///
+    /// The message will be delivered to the node's processor in
+    /// [crate::ProcessContext::midi_events] during the block that contains
+    /// `time`. Messages that are scheduled in the past will be delivered at
+    /// the start of the next block.
+    pub fn send_midi_at_time(&self, time: Timestamp, message: MidiMessage) {
+        self.send_midi_event(MidiEvent::new(time, message));
+    }
+
+    /// Schedule a MIDI event to be sent to this node
+    pub fn send_midi_event(&self, event: MidiEvent) {
+        self.command_queue
+            .send(Command::ScheduleMidiEvent(MidiEventRequest {
+                dsp_id: self.id,
+                event,
+            }));
+    }
+
+    /// Take the number of MIDI events that have been dropped since this was
+    /// last called
+    ///
+    /// A node can hold up to [crate::midi::MAXIMUM_PENDING_MIDI_EVENTS] events
+    /// that haven't been delivered yet. Any events sent while it is full are
+    /// dropped, so long sequences should be sent a little at a time.
+    pub fn take_dropped_midi_event_count(&self) -> usize {
+        self.dropped_midi_event_count.swap(0, Ordering::AcqRel)
+    }
+
+    /// Cancel all of the MIDI events for this node that haven't been delivered
+    pub fn cancel_midi_events(&self) {
+        self.command_queue.send(Command::CancelMidiEvents(self.id));
+    }
+
+    /// Disconnect this node from the system input
+    pub fn disconnect_from_input(&self) {
+        self.command_queue
+            .send(Command::RemoveConnection(Connection::new(
+                Id::system_input(),
+                self.get_id(),
+                self.input_count,
+            )));
+    }
 }
 
 impl Drop for GraphNode {
@@ -0,0 +1,99 @@
+use super::{DspProcessor, GraphNode};
+use crate::{
+    commands::Id,
+    parameter::{ParameterRange, Parameters},
+    utility::create_parameters,
+    Context,
+};
+
+/// Builder for creating a [GraphNode] with a user-defined [DspProcessor]
+///
+/// This can be used to create your own nodes that participate in the graph,
+/// scheduling, and parameter automation in the same way as the built-in nodes.
+///
+/// # Example
+///
+/// ```rust
+/// use rawdio::{prelude::*, DspProcessor, GraphNodeBuilder, ParameterRange, ProcessContext};
+///
+/// struct Silence;
+///
+/// impl DspProcessor for Silence {
+///     fn process_audio(&mut self, context: &mut ProcessContext) {
+///         context.output_buffer.clear();
+///     }
+/// }
+///
+/// let (context, _process) = create_engine();
+///
+/// let (node, parameters) = GraphNodeBuilder::new(context.as_ref())
+///     .with_output_count(2)
+///     .with_parameter("gain", ParameterRange::new(1.0, 0.0, 2.0))
+///     .build(Box::new(Silence));
+/// ```
+pub struct GraphNodeBuilder<'a> {
+    context: &'a dyn Context,
+    input_count: usize,
+    output_count: usize,
+    parameters: Vec<(&'static str, ParameterRange)>,
+}
+
+impl<'a> GraphNodeBuilder<'a> {
+    /// Create a new builder for a node with no inputs, outputs or parameters
+    pub fn new(context: &'a dyn Context) -> Self {
+        Self {
+            context,
+            input_count: 0,
+            output_count: 0,
+            parameters: Vec::new(),
+        }
+    }
+
+    /// Set the number of input channels
+    pub fn with_input_count(mut self, input_count: usize) -> Self {
+        self.input_count = input_count;
+        self
+    }
+
+    /// Set the number of output channels
+    pub fn with_output_count(mut self, output_count: usize) -> Self {
+        self.output_count = output_count;
+        self
+    }
+
+    /// Add an automatable parameter to the node
+    ///
+    /// The values can be read in the processor using
+    /// [crate::DspParameters::get_parameter_values] with the same name
+    pub fn with_parameter(mut self, name: &'static str, range: ParameterRange) -> Self {
+        assert!(
+            self.parameters.iter().all(|(id, _)| *id != name),
+            "Duplicate parameter: {name}"
+        );
+
+        self.parameters.push((name, range));
+        self
+    }
+
+    /// Create the node, adding the processor to the audio process
+    ///
+    /// Returns the node, along with the parameters that can be used to control
+    /// it from the main thread
+    pub fn build(self, processor: Box<dyn DspProcessor + Send + Sync>) -> (GraphNode, Parameters) {
+        let id = Id::generate();
+
+        let (parameters, realtime_parameters) =
+            create_parameters(id, self.context, self.parameters);
+
+        let node = GraphNode::new(
+            id,
+            self.context,
+            self.input_count,
+            self.output_count,
+            processor,
+            realtime_parameters,
+        );
+
+        (node, parameters)
+    }
+}
@@ -6,6 +6,8 @@ mod dsp_node;
 mod dsp_parameters;
 mod endpoint;
 mod graph_node;
+mod graph_node_builder;
+mod rejected_connection;
 
 pub use assigned_buffer_pool::AssignedBufferPool;
 pub use connection::Connection;
@@ -17,3 +19,5 @@ pub use dsp_parameters::DspParameters;
 pub use endpoint::Endpoint;
 pub use endpoint::EndpointType;
 pub use graph_node::GraphNode;
+pub use graph_node_builder::GraphNodeBuilder;
+pub use rejected_connection::RejectedConnection;
@@ -0,0 +1,15 @@
+use crate::commands::Id;
+
+/// A connection that was rejected by the audio process
+///
+/// Connections that close a feedback cycle are given a one-block delay. If the
+/// maximum number of these connections has been reached, any further
+/// connections that close a cycle will be rejected and removed from the graph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RejectedConnection {
+    /// The node at the source of the connection
+    pub source: Id,
+
+    /// The node at the destination of the connection
+    pub destination: Id,
+}
@@ -0,0 +1,45 @@
+//! Reading and writing audio files
+//!
+//! WAV files are decoded directly into, and encoded directly from, audio
+//! buffers. This supports:
+//!
+//! - 8, 16, 24, and 32-bit integer PCM
+//! - 32 and 64-bit IEEE floating point
+//! - WAVE_FORMAT_EXTENSIBLE
+//! - Cue points (`cue `) and loops (`smpl`)
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use rawdio::{
+//!     io::{WavReader, WavSampleFormat, WavSpec, WavWriter},
+//!     AudioBuffer,
+//! };
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let buffer = WavReader::open("input.wav")?.read_to_buffer()?;
+//!
+//! let spec = WavSpec {
+//!     channel_count: buffer.channel_count(),
+//!     sample_rate: buffer.sample_rate(),
+//!     sample_format: WavSampleFormat::Float32,
+//! };
+//!
+//! let mut writer = WavWriter::create("output.wav", spec)?;
+//! writer.write(&buffer)?;
+//! writer.finalize()?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod wav_error;
+mod wav_format;
+mod wav_metadata;
+mod wav_reader;
+mod wav_writer;
+
+pub use wav_error::WavError;
+pub use wav_format::{WavSampleFormat, WavSpec};
+pub use wav_metadata::{CuePoint, SampleLoop, SampleLoopType, SamplerInfo, WavMetadata};
+pub use wav_reader::WavReader;
+pub use wav_writer::WavWriter;
@@ -0,0 +1,55 @@
+use std::fmt;
+
+/// An error when reading or writing a WAV file
+#[derive(Debug)]
+pub enum WavError {
+    /// The underlying reader or writer failed
+    Io(std::io::Error),
+
+    /// The file isn't a valid RIFF/WAVE file
+    InvalidFile(&'static str),
+
+    /// The file contains a sample format that isn't supported
+    UnsupportedFormat {
+        /// The format tag from the format chunk
+        format_tag: u16,
+
+        /// The number of bits per sample from the format chunk
+        bits_per_sample: u16,
+    },
+
+    /// The audio data is too large to be stored in a WAV file
+    FileTooLarge,
+}
+
+impl fmt::Display for WavError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WavError::Io(error) => write!(f, "IO error: {error}"),
+            WavError::InvalidFile(reason) => write!(f, "Invalid WAV file: {reason}"),
+            WavError::UnsupportedFormat {
+                format_tag,
+                bits_per_sample,
+            } => write!(
+                f,
+                "Unsupported WAV format: format tag {format_tag:#06x}, {bits_per_sample} bits per sample"
+            ),
+            WavError::FileTooLarge => write!(f, "The audio data is too large for a WAV file"),
+        }
+    }
+}
+
+impl std::error::Error for WavError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WavError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for WavError {
+    fn from(error: std::io::Error) -> Self {
+        WavError::Io(error)
+    }
+}
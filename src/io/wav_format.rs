@@ -0,0 +1,179 @@
+pub(super) const WAVE_FORMAT_PCM: u16 = 0x0001;
+pub(super) const WAVE_FORMAT_IEEE_FLOAT: u16 = 0x0003;
+pub(super) const WAVE_FORMAT_EXTENSIBLE: u16 = 0xfffe;
+
+/// The tail of the sub-format GUID used by WAVE_FORMAT_EXTENSIBLE
+///
+/// The first two bytes of the GUID are the format tag
+pub(super) const SUB_FORMAT_GUID_TAIL: [u8; 14] = [
+    0x00, 0x00, 0x00, 0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0xaa, 0x00, 0x38, 0x9b, 0x71,
+];
+
+/// The format of the samples in a WAV file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WavSampleFormat {
+    /// Unsigned 8-bit integer
+    Pcm8,
+
+    /// Signed 16-bit integer
+    Pcm16,
+
+    /// Signed 24-bit integer
+    Pcm24,
+
+    /// Signed 32-bit integer
+    Pcm32,
+
+    /// 32-bit IEEE floating point
+    Float32,
+
+    /// 64-bit IEEE floating point
+    Float64,
+}
+
+impl WavSampleFormat {
+    pub(super) fn from_format_tag(format_tag: u16, bits_per_sample: u16) -> Option<Self> {
+        match (format_tag, bits_per_sample) {
+            (WAVE_FORMAT_PCM, 8) => Some(Self::Pcm8),
+            (WAVE_FORMAT_PCM, 16) => Some(Self::Pcm16),
+            (WAVE_FORMAT_PCM, 24) => Some(Self::Pcm24),
+            (WAVE_FORMAT_PCM, 32) => Some(Self::Pcm32),
+            (WAVE_FORMAT_IEEE_FLOAT, 32) => Some(Self::Float32),
+            (WAVE_FORMAT_IEEE_FLOAT, 64) => Some(Self::Float64),
+            _ => None,
+        }
+    }
+
+    pub(super) fn format_tag(&self) -> u16 {
+        match self {
+            Self::Float32 | Self::Float64 => WAVE_FORMAT_IEEE_FLOAT,
+            _ => WAVE_FORMAT_PCM,
+        }
+    }
+
+    /// The number of bits used to store each sample
+    pub fn bits_per_sample(&self) -> usize {
+        8 * self.bytes_per_sample()
+    }
+
+    /// The number of bytes used to store each sample
+    pub fn bytes_per_sample(&self) -> usize {
+        match self {
+            Self::Pcm8 => 1,
+            Self::Pcm16 => 2,
+            Self::Pcm24 => 3,
+            Self::Pcm32 | Self::Float32 => 4,
+            Self::Float64 => 8,
+        }
+    }
+
+    /// Whether the samples are stored as floating point
+    pub fn is_float(&self) -> bool {
+        self.format_tag() == WAVE_FORMAT_IEEE_FLOAT
+    }
+
+    pub(super) fn decode(&self, bytes: &[u8]) -> f32 {
+        match self {
+            Self::Pcm8 => (bytes[0] as f32 - 128.0) / 128.0,
+            Self::Pcm16 => i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32_768.0,
+            Self::Pcm24 => {
+                let value = i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8;
+                value as f32 / 8_388_608.0
+            }
+            Self::Pcm32 => {
+                let value = i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                (value as f64 / 2_147_483_648.0) as f32
+            }
+            Self::Float32 => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            Self::Float64 => f64::from_le_bytes([
+                bytes[0], bytes[1], bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7],
+            ]) as f32,
+        }
+    }
+
+    pub(super) fn encode(&self, sample: f32, bytes: &mut [u8]) {
+        let quantise = |scale: f64, minimum: f64, maximum: f64| {
+            (sample as f64 * scale).round().clamp(minimum, maximum)
+        };
+
+        match self {
+            Self::Pcm8 => bytes[0] = (quantise(128.0, -128.0, 127.0) as i16 + 128) as u8,
+            Self::Pcm16 => bytes[..2].copy_from_slice(
+                &(quantise(32_768.0, i16::MIN as f64, i16::MAX as f64) as i16).to_le_bytes(),
+            ),
+            Self::Pcm24 => {
+                let value = quantise(8_388_608.0, -8_388_608.0, 8_388_607.0) as i32;
+                bytes[..3].copy_from_slice(&value.to_le_bytes()[..3]);
+            }
+            Self::Pcm32 => bytes[..4].copy_from_slice(
+                &(quantise(2_147_483_648.0, i32::MIN as f64, i32::MAX as f64) as i32).to_le_bytes(),
+            ),
+            Self::Float32 => bytes[..4].copy_from_slice(&sample.to_le_bytes()),
+            Self::Float64 => bytes[..8].copy_from_slice(&(sample as f64).to_le_bytes()),
+        }
+    }
+}
+
+/// A description of the audio stored in a WAV file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WavSpec {
+    /// The number of interleaved channels
+    pub channel_count: usize,
+
+    /// The sample rate in Hz
+    pub sample_rate: usize,
+
+    /// The format used to store each sample
+    pub sample_format: WavSampleFormat,
+}
+
+impl WavSpec {
+    pub(super) fn block_align(&self) -> usize {
+        self.channel_count * self.sample_format.bytes_per_sample()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL_FORMATS: [WavSampleFormat; 6] = [
+        WavSampleFormat::Pcm8,
+        WavSampleFormat::Pcm16,
+        WavSampleFormat::Pcm24,
+        WavSampleFormat::Pcm32,
+        WavSampleFormat::Float32,
+        WavSampleFormat::Float64,
+    ];
+
+    #[test]
+    fn round_trips_samples() {
+        for format in ALL_FORMATS {
+            let epsilon = match format {
+                WavSampleFormat::Pcm8 => 1.0 / 128.0,
+                WavSampleFormat::Pcm16 => 1.0 / 32_768.0,
+                _ => 1e-6,
+            };
+
+            for sample in [-1.0, -0.5, -0.123, 0.0, 0.25, 0.75] {
+                let mut bytes = [0_u8; 8];
+                format.encode(sample, &mut bytes);
+                assert_relative_eq!(format.decode(&bytes), sample, epsilon = epsilon);
+            }
+        }
+    }
+
+    #[test]
+    fn clips_integer_samples() {
+        let mut bytes = [0_u8; 8];
+
+        WavSampleFormat::Pcm16.encode(2.0, &mut bytes);
+        assert_eq!(i16::from_le_bytes([bytes[0], bytes[1]]), i16::MAX);
+
+        WavSampleFormat::Pcm24.encode(-2.0, &mut bytes);
+        assert_relative_eq!(WavSampleFormat::Pcm24.decode(&bytes), -1.0);
+
+        WavSampleFormat::Pcm8.encode(1.0, &mut bytes);
+        assert_eq!(bytes[0], 255);
+    }
+}
@@ -0,0 +1,196 @@
+/// A marker in the audio, stored in a `cue ` chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CuePoint {
+    /// A unique identifier, that can be referenced by a [SampleLoop]
+    pub id: u32,
+
+    /// The position of the marker, in frames
+    pub position: usize,
+}
+
+/// How a [SampleLoop] should be played
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleLoopType {
+    /// Loop from the start to the end
+    Forward,
+
+    /// Alternate between playing forwards and backwards
+    PingPong,
+
+    /// Loop from the end to the start
+    Backward,
+
+    /// A manufacturer specific loop type
+    Other(u32),
+}
+
+impl SampleLoopType {
+    fn from_u32(value: u32) -> Self {
+        match value {
+            0 => Self::Forward,
+            1 => Self::PingPong,
+            2 => Self::Backward,
+            value => Self::Other(value),
+        }
+    }
+
+    fn as_u32(&self) -> u32 {
+        match self {
+            Self::Forward => 0,
+            Self::PingPong => 1,
+            Self::Backward => 2,
+            Self::Other(value) => *value,
+        }
+    }
+}
+
+/// A loop, stored in a `smpl` chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SampleLoop {
+    /// The [CuePoint] that this loop is associated with
+    pub cue_point_id: u32,
+
+    /// How the loop should be played
+    pub loop_type: SampleLoopType,
+
+    /// The first frame of the loop
+    pub start: usize,
+
+    /// The last frame of the loop (inclusive)
+    pub end: usize,
+
+    /// The number of times to play the loop, where 0 is infinite
+    pub play_count: u32,
+}
+
+/// Information for samplers, stored in a `smpl` chunk
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SamplerInfo {
+    /// The MIDI note that will play the sample at its original pitch
+    pub midi_unity_note: u32,
+
+    /// The fraction of a semitone above the unity note, where 0x80000000 is
+    /// half a semitone
+    pub midi_pitch_fraction: u32,
+
+    /// The loops in the sample
+    pub loops: Vec<SampleLoop>,
+}
+
+/// The metadata from the chunks of a WAV file
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct WavMetadata {
+    /// The markers in the `cue ` chunk
+    pub cue_points: Vec<CuePoint>,
+
+    /// The contents of the `smpl` chunk, if there is one
+    pub sampler_info: Option<SamplerInfo>,
+}
+
+const CUE_POINT_SIZE: usize = 24;
+const SAMPLE_LOOP_SIZE: usize = 24;
+const SAMPLER_HEADER_SIZE: usize = 36;
+
+pub(super) fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+pub(super) fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+pub(super) fn parse_cue_chunk(data: &[u8]) -> Vec<CuePoint> {
+    let count = read_u32(data, 0).unwrap_or(0) as usize;
+
+    (0..count)
+        .map_while(|index| {
+            let offset = 4 + index * CUE_POINT_SIZE;
+
+            Some(CuePoint {
+                id: read_u32(data, offset)?,
+                position: read_u32(data, offset + 20)? as usize,
+            })
+        })
+        .collect()
+}
+
+pub(super) fn create_cue_chunk(cue_points: &[CuePoint]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(4 + cue_points.len() * CUE_POINT_SIZE);
+
+    data.extend_from_slice(&(cue_points.len() as u32).to_le_bytes());
+
+    for cue_point in cue_points {
+        data.extend_from_slice(&cue_point.id.to_le_bytes());
+        data.extend_from_slice(&(cue_point.position as u32).to_le_bytes());
+        data.extend_from_slice(b"data");
+        data.extend_from_slice(&0_u32.to_le_bytes());
+        data.extend_from_slice(&0_u32.to_le_bytes());
+        data.extend_from_slice(&(cue_point.position as u32).to_le_bytes());
+    }
+
+    data
+}
+
+pub(super) fn parse_sampler_chunk(data: &[u8]) -> Option<SamplerInfo> {
+    let midi_unity_note = read_u32(data, 12)?;
+    let midi_pitch_fraction = read_u32(data, 16)?;
+    let loop_count = read_u32(data, 28)? as usize;
+
+    let loops = (0..loop_count)
+        .map_while(|index| {
+            let offset = SAMPLER_HEADER_SIZE + index * SAMPLE_LOOP_SIZE;
+
+            Some(SampleLoop {
+                cue_point_id: read_u32(data, offset)?,
+                loop_type: SampleLoopType::from_u32(read_u32(data, offset + 4)?),
+                start: read_u32(data, offset + 8)? as usize,
+                end: read_u32(data, offset + 12)? as usize,
+                play_count: read_u32(data, offset + 20)?,
+            })
+        })
+        .collect();
+
+    Some(SamplerInfo {
+        midi_unity_note,
+        midi_pitch_fraction,
+        loops,
+    })
+}
+
+pub(super) fn create_sampler_chunk(sampler_info: &SamplerInfo, sample_rate: usize) -> Vec<u8> {
+    let mut data =
+        Vec::with_capacity(SAMPLER_HEADER_SIZE + sampler_info.loops.len() * SAMPLE_LOOP_SIZE);
+
+    let sample_period = (1_000_000_000.0 / sample_rate as f64).round() as u32;
+
+    for value in [
+        0,
+        0,
+        sample_period,
+        sampler_info.midi_unity_note,
+        sampler_info.midi_pitch_fraction,
+        0,
+        0,
+        sampler_info.loops.len() as u32,
+        0,
+    ] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+
+    for sample_loop in &sampler_info.loops {
+        for value in [
+            sample_loop.cue_point_id,
+            sample_loop.loop_type.as_u32(),
+            sample_loop.start as u32,
+            sample_loop.end as u32,
+            0,
+            sample_loop.play_count,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+
+    data
+}
@@ -0,0 +1,383 @@
+use super::{
+    wav_error::WavError,
+    wav_format::{WavSampleFormat, WavSpec, WAVE_FORMAT_EXTENSIBLE},
+    wav_metadata::{parse_cue_chunk, parse_sampler_chunk, read_u16, read_u32, WavMetadata},
+};
+use crate::prelude::*;
+use std::{
+    fs::File,
+    io::{BufReader, ErrorKind, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+const READ_BLOCK_FRAME_COUNT: usize = 4096;
+
+/// Reads audio from a RIFF/WAVE stream
+///
+/// The header and metadata chunks are parsed when the reader is created. The
+/// audio can then be read in blocks, so long files don't need to be held in
+/// memory.
+pub struct WavReader<R> {
+    reader: R,
+    spec: WavSpec,
+    metadata: WavMetadata,
+    data_start: u64,
+    frame_count: usize,
+    position: usize,
+    scratch: Vec<u8>,
+}
+
+impl WavReader<BufReader<File>> {
+    /// Open a WAV file for reading
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, WavError> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read + Seek> WavReader<R> {
+    /// Create a reader, parsing the header from the stream
+    pub fn new(mut reader: R) -> Result<Self, WavError> {
+        let mut header = [0_u8; 12];
+        reader.read_exact(&mut header)?;
+
+        if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+            return Err(WavError::InvalidFile("Missing RIFF/WAVE header"));
+        }
+
+        let stream_end = reader.seek(SeekFrom::End(0))?;
+        reader.seek(SeekFrom::Start(header.len() as u64))?;
+
+        let mut spec = None;
+        let mut data = None;
+        let mut metadata = WavMetadata::default();
+
+        while let Some((chunk_id, chunk_size)) = read_chunk_header(&mut reader)? {
+            let chunk_start = reader.stream_position()?;
+
+            match &chunk_id {
+                b"fmt " => spec = Some(parse_format_chunk(&read_chunk(&mut reader, chunk_size)?)?),
+                b"data" => data = Some((chunk_start, chunk_size.min(stream_end - chunk_start))),
+                b"cue " => {
+                    metadata.cue_points = parse_cue_chunk(&read_chunk(&mut reader, chunk_size)?)
+                }
+                b"smpl" => {
+                    metadata.sampler_info =
+                        parse_sampler_chunk(&read_chunk(&mut reader, chunk_size)?)
+                }
+                _ => (),
+            }
+
+            let padding = chunk_size % 2;
+            let next_chunk = chunk_start + chunk_size + padding;
+
+            if next_chunk >= stream_end {
+                break;
+            }
+
+            reader.seek(SeekFrom::Start(next_chunk))?;
+        }
+
+        let spec = spec.ok_or(WavError::InvalidFile("Missing format chunk"))?;
+        let (data_start, data_size) = data.ok_or(WavError::InvalidFile("Missing data chunk"))?;
+
+        reader.seek(SeekFrom::Start(data_start))?;
+
+        Ok(Self {
+            reader,
+            spec,
+            metadata,
+            data_start,
+            frame_count: data_size as usize / spec.block_align(),
+            position: 0,
+            scratch: Vec::new(),
+        })
+    }
+
+    /// The format of the audio in the stream
+    pub fn spec(&self) -> WavSpec {
+        self.spec
+    }
+
+    /// The cue points and loops from the stream
+    pub fn metadata(&self) -> &WavMetadata {
+        &self.metadata
+    }
+
+    /// The total number of frames in the stream
+    pub fn frame_count(&self) -> usize {
+        self.frame_count
+    }
+
+    /// The next frame that will be read
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Move to a frame in the stream
+    pub fn seek(&mut self, frame: usize) -> Result<(), WavError> {
+        self.position = frame.min(self.frame_count);
+
+        let offset = self.data_start + (self.position * self.spec.block_align()) as u64;
+        self.reader.seek(SeekFrom::Start(offset))?;
+
+        Ok(())
+    }
+
+    /// Read frames into `buffer`, starting at the current position
+    ///
+    /// This will read up to the frame count of the buffer, and returns the
+    /// number of frames that were read, which will be zero at the end of the
+    /// stream. Channels in the buffer beyond the channel count of the stream
+    /// are left untouched.
+    pub fn read(&mut self, buffer: &mut dyn AudioBuffer) -> Result<usize, WavError> {
+        let frame_count = buffer.frame_count().min(self.frame_count - self.position);
+
+        let mut offset = 0;
+
+        while offset < frame_count {
+            let block_frame_count = (frame_count - offset).min(READ_BLOCK_FRAME_COUNT);
+            self.read_block(buffer, offset, block_frame_count)?;
+            offset += block_frame_count;
+        }
+
+        Ok(frame_count)
+    }
+
+    /// Read the rest of the stream into a new buffer
+    pub fn read_to_buffer(mut self) -> Result<OwnedAudioBuffer, WavError> {
+        let mut buffer = OwnedAudioBuffer::new(
+            self.frame_count - self.position,
+            self.spec.channel_count,
+            self.spec.sample_rate,
+        );
+
+        self.read(&mut buffer)?;
+
+        Ok(buffer)
+    }
+
+    fn read_block(
+        &mut self,
+        buffer: &mut dyn AudioBuffer,
+        frame_offset: usize,
+        frame_count: usize,
+    ) -> Result<(), WavError> {
+        let block_align = self.spec.block_align();
+        let bytes_per_sample = self.spec.sample_format.bytes_per_sample();
+
+        self.scratch.resize(frame_count * block_align, 0);
+        self.reader.read_exact(&mut self.scratch)?;
+
+        let channel_count = self.spec.channel_count.min(buffer.channel_count());
+
+        for channel in 0..channel_count {
+            let location = SampleLocation::new(channel, frame_offset);
+            let channel_data = &mut buffer.get_channel_data_mut(location)[..frame_count];
+
+            for (sample, frame) in channel_data
+                .iter_mut()
+                .zip(self.scratch.chunks_exact(block_align))
+            {
+                let start = channel * bytes_per_sample;
+                *sample = self
+                    .spec
+                    .sample_format
+                    .decode(&frame[start..start + bytes_per_sample]);
+            }
+        }
+
+        self.position += frame_count;
+
+        Ok(())
+    }
+}
+
+fn read_chunk_header<R: Read>(reader: &mut R) -> Result<Option<([u8; 4], u64)>, WavError> {
+    let mut header = [0_u8; 8];
+
+    match reader.read_exact(&mut header) {
+        Ok(()) => (),
+        Err(error) if error.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        Err(error) => return Err(error.into()),
+    }
+
+    let chunk_id = [header[0], header[1], header[2], header[3]];
+    let chunk_size = read_u32(&header, 4).unwrap_or(0) as u64;
+
+    Ok(Some((chunk_id, chunk_size)))
+}
+
+fn read_chunk<R: Read>(reader: &mut R, chunk_size: u64) -> Result<Vec<u8>, WavError> {
+    let mut data = Vec::new();
+    reader.take(chunk_size).read_to_end(&mut data)?;
+    Ok(data)
+}
+
+fn parse_format_chunk(data: &[u8]) -> Result<WavSpec, WavError> {
+    let invalid = WavError::InvalidFile("Format chunk is too short");
+
+    let mut format_tag = read_u16(data, 0).ok_or(invalid)?;
+    let channel_count = read_u16(data, 2).unwrap_or(0) as usize;
+    let sample_rate = read_u32(data, 4).unwrap_or(0) as usize;
+    let bits_per_sample = read_u16(data, 14).unwrap_or(0);
+
+    if format_tag == WAVE_FORMAT_EXTENSIBLE {
+        format_tag = read_u16(data, 24).ok_or(WavError::InvalidFile(
+            "Extensible format chunk is too short",
+        ))?;
+    }
+
+    if channel_count == 0 {
+        return Err(WavError::InvalidFile("No channels"));
+    }
+
+    let sample_format = WavSampleFormat::from_format_tag(format_tag, bits_per_sample).ok_or(
+        WavError::UnsupportedFormat {
+            format_tag,
+            bits_per_sample,
+        },
+    )?;
+
+    Ok(WavSpec {
+        channel_count,
+        sample_rate,
+        sample_format,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn create_file(chunks: &[(&[u8; 4], Vec<u8>)]) -> Vec<u8> {
+        let mut body = b"WAVE".to_vec();
+
+        for (chunk_id, data) in chunks {
+            body.extend_from_slice(*chunk_id);
+            body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            body.extend_from_slice(data);
+
+            if data.len() % 2 == 1 {
+                body.push(0);
+            }
+        }
+
+        let mut file = b"RIFF".to_vec();
+        file.extend_from_slice(&(body.len() as u32).to_le_bytes());
+        file.extend(body);
+        file
+    }
+
+    fn format_chunk(format_tag: u16, channel_count: u16, bits_per_sample: u16) -> Vec<u8> {
+        let sample_rate = 44_100_u32;
+        let block_align = channel_count * bits_per_sample / 8;
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&format_tag.to_le_bytes());
+        data.extend_from_slice(&channel_count.to_le_bytes());
+        data.extend_from_slice(&sample_rate.to_le_bytes());
+        data.extend_from_slice(&(sample_rate * block_align as u32).to_le_bytes());
+        data.extend_from_slice(&block_align.to_le_bytes());
+        data.extend_from_slice(&bits_per_sample.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn reads_8_bit_samples() {
+        let file = create_file(&[
+            (b"fmt ", format_chunk(1, 2, 8)),
+            (b"data", vec![128, 255, 0, 192, 64]),
+        ]);
+
+        let reader = WavReader::new(Cursor::new(file)).unwrap();
+
+        assert_eq!(reader.frame_count(), 2);
+        assert_eq!(reader.spec().sample_format, WavSampleFormat::Pcm8);
+
+        let buffer = reader.read_to_buffer().unwrap();
+
+        assert_eq!(
+            buffer.get_channel_data(SampleLocation::channel(0)),
+            &[0.0, -1.0]
+        );
+        assert_eq!(
+            buffer.get_channel_data(SampleLocation::channel(1)),
+            &[127.0 / 128.0, 0.5]
+        );
+    }
+
+    #[test]
+    fn skips_unknown_chunks() {
+        let file = create_file(&[
+            (b"JUNK", vec![1, 2, 3]),
+            (b"fmt ", format_chunk(1, 1, 16)),
+            (b"LIST", vec![0; 10]),
+            (b"data", 16_384_i16.to_le_bytes().to_vec()),
+        ]);
+
+        let buffer = WavReader::new(Cursor::new(file))
+            .unwrap()
+            .read_to_buffer()
+            .unwrap();
+
+        assert_eq!(buffer.frame_count(), 1);
+        assert_relative_eq!(buffer.get_sample(SampleLocation::origin()), 0.5);
+    }
+
+    #[test]
+    fn reads_metadata_after_data() {
+        let mut cue_chunk = Vec::new();
+        for value in [1_u32, 7, 0] {
+            cue_chunk.extend_from_slice(&value.to_le_bytes());
+        }
+        cue_chunk.extend_from_slice(b"data");
+        for value in [0_u32, 0, 3] {
+            cue_chunk.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let file = create_file(&[
+            (b"fmt ", format_chunk(1, 1, 16)),
+            (b"data", vec![0; 8]),
+            (b"cue ", cue_chunk),
+        ]);
+
+        let reader = WavReader::new(Cursor::new(file)).unwrap();
+
+        assert_eq!(reader.frame_count(), 4);
+        assert_eq!(reader.metadata().cue_points.len(), 1);
+        assert_eq!(reader.metadata().cue_points[0].id, 7);
+        assert_eq!(reader.metadata().cue_points[0].position, 3);
+    }
+
+    #[test]
+    fn rejects_unsupported_formats() {
+        let file = create_file(&[(b"fmt ", format_chunk(2, 1, 4)), (b"data", vec![0; 8])]);
+
+        assert!(matches!(
+            WavReader::new(Cursor::new(file)),
+            Err(WavError::UnsupportedFormat {
+                format_tag: 2,
+                bits_per_sample: 4
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_files_that_arent_wav() {
+        assert!(matches!(
+            WavReader::new(Cursor::new(b"RIFF\0\0\0\0AVI LIST".to_vec())),
+            Err(WavError::InvalidFile(_))
+        ));
+    }
+
+    #[test]
+    fn clamps_truncated_data() {
+        let mut file = create_file(&[(b"fmt ", format_chunk(1, 1, 16)), (b"data", vec![0; 8])]);
+
+        file.truncate(file.len() - 4);
+
+        let reader = WavReader::new(Cursor::new(file)).unwrap();
+        assert_eq!(reader.frame_count(), 2);
+    }
+}
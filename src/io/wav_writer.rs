@@ -0,0 +1,423 @@
+use super::{
+    wav_error::WavError,
+    wav_format::{WavSpec, SUB_FORMAT_GUID_TAIL, WAVE_FORMAT_EXTENSIBLE},
+    wav_metadata::{create_cue_chunk, create_sampler_chunk, WavMetadata},
+};
+use crate::prelude::*;
+use std::{
+    fs::File,
+    io::{BufWriter, Seek, SeekFrom, Write},
+    path::Path,
+};
+
+const WRITE_BLOCK_FRAME_COUNT: usize = 4096;
+const RIFF_SIZE_OFFSET: u64 = 4;
+
+/// Writes audio to a RIFF/WAVE stream
+///
+/// Audio can be written in blocks, so long files don't need to be held in
+/// memory. [WavWriter::finalize] must be called once all of the audio has been
+/// written, to write the metadata and the chunk sizes.
+///
+/// WAVE_FORMAT_EXTENSIBLE is used for files with more than two channels.
+pub struct WavWriter<W: Write + Seek> {
+    writer: W,
+    spec: WavSpec,
+    metadata: WavMetadata,
+    frame_count: usize,
+    data_size_offset: u64,
+    fact_offset: Option<u64>,
+    scratch: Vec<u8>,
+}
+
+impl WavWriter<BufWriter<File>> {
+    /// Create a WAV file for writing
+    pub fn create<P: AsRef<Path>>(path: P, spec: WavSpec) -> Result<Self, WavError> {
+        Self::new(BufWriter::new(File::create(path)?), spec)
+    }
+}
+
+impl<W: Write + Seek> WavWriter<W> {
+    /// Create a writer, writing the header to the stream
+    pub fn new(mut writer: W, spec: WavSpec) -> Result<Self, WavError> {
+        let is_extensible = spec.channel_count > 2;
+        let sample_format = spec.sample_format;
+
+        let mut format_chunk = Vec::with_capacity(40);
+
+        let format_tag = match is_extensible {
+            true => WAVE_FORMAT_EXTENSIBLE,
+            false => sample_format.format_tag(),
+        };
+
+        format_chunk.extend_from_slice(&format_tag.to_le_bytes());
+        format_chunk.extend_from_slice(&(spec.channel_count as u16).to_le_bytes());
+        format_chunk.extend_from_slice(&(spec.sample_rate as u32).to_le_bytes());
+        format_chunk
+            .extend_from_slice(&((spec.sample_rate * spec.block_align()) as u32).to_le_bytes());
+        format_chunk.extend_from_slice(&(spec.block_align() as u16).to_le_bytes());
+        format_chunk.extend_from_slice(&(sample_format.bits_per_sample() as u16).to_le_bytes());
+
+        if is_extensible {
+            format_chunk.extend_from_slice(&22_u16.to_le_bytes());
+            format_chunk.extend_from_slice(&(sample_format.bits_per_sample() as u16).to_le_bytes());
+            format_chunk.extend_from_slice(&channel_mask(spec.channel_count).to_le_bytes());
+            format_chunk.extend_from_slice(&sample_format.format_tag().to_le_bytes());
+            format_chunk.extend_from_slice(&SUB_FORMAT_GUID_TAIL);
+        } else if sample_format.is_float() {
+            format_chunk.extend_from_slice(&0_u16.to_le_bytes());
+        }
+
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&0_u32.to_le_bytes())?;
+        writer.write_all(b"WAVE")?;
+
+        write_chunk(&mut writer, b"fmt ", &format_chunk)?;
+
+        let fact_offset = match sample_format.is_float() {
+            true => {
+                let offset = writer.stream_position()? + 8;
+                write_chunk(&mut writer, b"fact", &0_u32.to_le_bytes())?;
+                Some(offset)
+            }
+            false => None,
+        };
+
+        writer.write_all(b"data")?;
+        let data_size_offset = writer.stream_position()?;
+        writer.write_all(&0_u32.to_le_bytes())?;
+
+        Ok(Self {
+            writer,
+            spec,
+            metadata: WavMetadata::default(),
+            frame_count: 0,
+            data_size_offset,
+            fact_offset,
+            scratch: Vec::new(),
+        })
+    }
+
+    /// Set the cue points and loops that will be written when the writer is
+    /// finalized
+    pub fn set_metadata(&mut self, metadata: WavMetadata) {
+        self.metadata = metadata;
+    }
+
+    /// The number of frames that have been written
+    pub fn frame_count(&self) -> usize {
+        self.frame_count
+    }
+
+    /// Write all of the frames in `buffer`
+    ///
+    /// Integer formats will be clipped to the range [-1.0, 1.0]. If the buffer
+    /// has fewer channels than the stream, the remaining channels are silent.
+    pub fn write(&mut self, buffer: &dyn AudioBuffer) -> Result<(), WavError> {
+        let data_size = (self.frame_count + buffer.frame_count()) * self.spec.block_align();
+
+        if data_size > u32::MAX as usize {
+            return Err(WavError::FileTooLarge);
+        }
+
+        let mut offset = 0;
+
+        while offset < buffer.frame_count() {
+            let frame_count = (buffer.frame_count() - offset).min(WRITE_BLOCK_FRAME_COUNT);
+            self.write_block(buffer, offset, frame_count)?;
+            offset += frame_count;
+        }
+
+        Ok(())
+    }
+
+    /// Write the metadata and chunk sizes, returning the underlying stream
+    pub fn finalize(mut self) -> Result<W, WavError> {
+        let data_size = (self.frame_count * self.spec.block_align()) as u32;
+
+        if data_size % 2 == 1 {
+            self.writer.write_all(&[0])?;
+        }
+
+        if !self.metadata.cue_points.is_empty() {
+            let cue_chunk = create_cue_chunk(&self.metadata.cue_points);
+            write_chunk(&mut self.writer, b"cue ", &cue_chunk)?;
+        }
+
+        if let Some(sampler_info) = &self.metadata.sampler_info {
+            let sampler_chunk = create_sampler_chunk(sampler_info, self.spec.sample_rate);
+            write_chunk(&mut self.writer, b"smpl", &sampler_chunk)?;
+        }
+
+        let end = self.writer.stream_position()?;
+
+        self.writer.seek(SeekFrom::Start(RIFF_SIZE_OFFSET))?;
+        self.writer
+            .write_all(&((end - RIFF_SIZE_OFFSET - 4) as u32).to_le_bytes())?;
+
+        self.writer.seek(SeekFrom::Start(self.data_size_offset))?;
+        self.writer.write_all(&data_size.to_le_bytes())?;
+
+        if let Some(fact_offset) = self.fact_offset {
+            self.writer.seek(SeekFrom::Start(fact_offset))?;
+            self.writer
+                .write_all(&(self.frame_count as u32).to_le_bytes())?;
+        }
+
+        self.writer.seek(SeekFrom::Start(end))?;
+        self.writer.flush()?;
+
+        Ok(self.writer)
+    }
+
+    fn write_block(
+        &mut self,
+        buffer: &dyn AudioBuffer,
+        frame_offset: usize,
+        frame_count: usize,
+    ) -> Result<(), WavError> {
+        let block_align = self.spec.block_align();
+        let bytes_per_sample = self.spec.sample_format.bytes_per_sample();
+
+        self.scratch.clear();
+        self.scratch.resize(frame_count * block_align, 0);
+
+        for channel in 0..self.spec.channel_count {
+            let channel_data = match channel < buffer.channel_count() {
+                true => {
+                    let location = SampleLocation::new(channel, frame_offset);
+                    &buffer.get_channel_data(location)[..frame_count]
+                }
+                false => &[],
+            };
+
+            for (frame, sample) in self
+                .scratch
+                .chunks_exact_mut(block_align)
+                .zip(channel_data.iter().chain(std::iter::repeat(&0.0)))
+            {
+                let start = channel * bytes_per_sample;
+                self.spec
+                    .sample_format
+                    .encode(*sample, &mut frame[start..start + bytes_per_sample]);
+            }
+        }
+
+        self.writer.write_all(&self.scratch)?;
+        self.frame_count += frame_count;
+
+        Ok(())
+    }
+}
+
+fn write_chunk<W: Write>(writer: &mut W, chunk_id: &[u8; 4], data: &[u8]) -> Result<(), WavError> {
+    writer.write_all(chunk_id)?;
+    writer.write_all(&(data.len() as u32).to_le_bytes())?;
+    writer.write_all(data)?;
+
+    if data.len() % 2 == 1 {
+        writer.write_all(&[0])?;
+    }
+
+    Ok(())
+}
+
+fn channel_mask(channel_count: usize) -> u32 {
+    match channel_count {
+        0..=31 => (1 << channel_count) - 1,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::io::{
+        CuePoint, SampleLoop, SampleLoopType, SamplerInfo, WavReader, WavSampleFormat,
+    };
+    use std::io::Cursor;
+
+    fn write_and_read(
+        buffer: &OwnedAudioBuffer,
+        sample_format: WavSampleFormat,
+    ) -> WavReader<Cursor<Vec<u8>>> {
+        let spec = WavSpec {
+            channel_count: buffer.channel_count(),
+            sample_rate: buffer.sample_rate(),
+            sample_format,
+        };
+
+        let mut writer = WavWriter::new(Cursor::new(Vec::new()), spec).unwrap();
+        writer.write(buffer).unwrap();
+
+        let mut stream = writer.finalize().unwrap();
+        stream.set_position(0);
+
+        WavReader::new(stream).unwrap()
+    }
+
+    #[test]
+    fn round_trips_all_formats() {
+        let sample_rate = 48_000;
+        let buffer = OwnedAudioBuffer::sine(1_000, 2, sample_rate, 440.0, 0.5);
+
+        for (sample_format, epsilon) in [
+            (WavSampleFormat::Pcm8, 1.0 / 128.0),
+            (WavSampleFormat::Pcm16, 1.0 / 32_768.0),
+            (WavSampleFormat::Pcm24, 1e-6),
+            (WavSampleFormat::Pcm32, 1e-6),
+            (WavSampleFormat::Float32, 0.0),
+            (WavSampleFormat::Float64, 0.0),
+        ] {
+            let reader = write_and_read(&buffer, sample_format);
+
+            assert_eq!(reader.spec().sample_format, sample_format);
+            assert_eq!(reader.spec().sample_rate, sample_rate);
+
+            let decoded = reader.read_to_buffer().unwrap();
+
+            assert_eq!(decoded.frame_count(), buffer.frame_count());
+            assert_eq!(decoded.channel_count(), buffer.channel_count());
+
+            for channel in 0..buffer.channel_count() {
+                let location = SampleLocation::channel(channel);
+
+                for (decoded, original) in decoded
+                    .get_channel_data(location)
+                    .iter()
+                    .zip(buffer.get_channel_data(location))
+                {
+                    assert_relative_eq!(*decoded, *original, epsilon = epsilon);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn writes_extensible_format_for_multichannel_audio() {
+        let channel_count = 6;
+        let mut buffer = OwnedAudioBuffer::new(16, channel_count, 44_100);
+
+        for channel in 0..channel_count {
+            buffer.fill_channel_with_value(channel, channel as f32 / 10.0);
+        }
+
+        let reader = write_and_read(&buffer, WavSampleFormat::Pcm24);
+        let decoded = reader.read_to_buffer().unwrap();
+
+        for channel in 0..channel_count {
+            assert_relative_eq!(
+                decoded.get_sample(SampleLocation::new(channel, 15)),
+                channel as f32 / 10.0,
+                epsilon = 1e-6
+            );
+        }
+    }
+
+    #[test]
+    fn round_trips_metadata() {
+        let spec = WavSpec {
+            channel_count: 1,
+            sample_rate: 44_100,
+            sample_format: WavSampleFormat::Pcm16,
+        };
+
+        let metadata = WavMetadata {
+            cue_points: vec![CuePoint {
+                id: 1,
+                position: 10,
+            }],
+            sampler_info: Some(SamplerInfo {
+                midi_unity_note: 60,
+                midi_pitch_fraction: 0,
+                loops: vec![SampleLoop {
+                    cue_point_id: 1,
+                    loop_type: SampleLoopType::PingPong,
+                    start: 10,
+                    end: 90,
+                    play_count: 0,
+                }],
+            }),
+        };
+
+        let mut writer = WavWriter::new(Cursor::new(Vec::new()), spec).unwrap();
+        writer.set_metadata(metadata.clone());
+        writer
+            .write(&OwnedAudioBuffer::new(101, 1, 44_100))
+            .unwrap();
+
+        let mut stream = writer.finalize().unwrap();
+        stream.set_position(0);
+
+        let reader = WavReader::new(stream).unwrap();
+
+        assert_eq!(reader.frame_count(), 101);
+        assert_eq!(reader.metadata(), &metadata);
+    }
+
+    #[test]
+    fn streams_in_blocks() {
+        let sample_rate = 44_100;
+        let channel_count = 2;
+        let block_size = 100;
+
+        let buffer = OwnedAudioBuffer::white_noise(1_050, channel_count, sample_rate);
+
+        let spec = WavSpec {
+            channel_count,
+            sample_rate,
+            sample_format: WavSampleFormat::Float32,
+        };
+
+        let mut writer = WavWriter::new(Cursor::new(Vec::new()), spec).unwrap();
+
+        for offset in (0..buffer.frame_count()).step_by(block_size) {
+            let frame_count = block_size.min(buffer.frame_count() - offset);
+            writer
+                .write(&BorrowedAudioBuffer::slice_frames(
+                    &buffer,
+                    offset,
+                    frame_count,
+                ))
+                .unwrap();
+        }
+
+        let mut stream = writer.finalize().unwrap();
+        stream.set_position(0);
+
+        let mut reader = WavReader::new(stream).unwrap();
+        let mut block = OwnedAudioBuffer::new(block_size, channel_count, sample_rate);
+        let mut decoded = OwnedAudioBuffer::new(0, channel_count, sample_rate);
+
+        loop {
+            let frame_count = reader.read(&mut block).unwrap();
+
+            if frame_count == 0 {
+                break;
+            }
+
+            decoded = decoded.extended_with_buffer(&BorrowedAudioBuffer::slice_frames(
+                &block,
+                0,
+                frame_count,
+            ));
+        }
+
+        assert_eq!(decoded.frame_count(), buffer.frame_count());
+
+        for channel in 0..channel_count {
+            let location = SampleLocation::channel(channel);
+            assert_eq!(
+                decoded.get_channel_data(location),
+                buffer.get_channel_data(location)
+            );
+        }
+
+        reader.seek(1_000).unwrap();
+        assert_eq!(reader.read(&mut block).unwrap(), 50);
+        assert_eq!(
+            block.get_sample(SampleLocation::frame(0)),
+            buffer.get_sample(SampleLocation::frame(1_000))
+        );
+    }
+}
@@ -38,8 +38,11 @@ mod dsp;
 mod effects;
 mod engine;
 mod graph;
+pub mod io;
+pub mod midi;
 mod parameter;
 mod realtime;
+pub mod resample;
 mod utility;
 
 pub use buffer::AudioBuffer;
@@ -48,18 +51,47 @@ pub use buffer::MutableBorrowedAudioBuffer;
 pub use buffer::OwnedAudioBuffer;
 pub use buffer::SampleLocation;
 
+pub use commands::Id;
+
 pub use effects::Adsr;
 pub use effects::Biquad;
 pub use effects::BiquadFilterType;
 pub use effects::Compressor;
+pub use effects::CompressorDetection;
+pub use effects::CompressorOptions;
 pub use effects::Convolution;
+pub use effects::Delay;
+pub use effects::DelayInterpolation;
 pub use effects::Envelope;
+pub use effects::EqBand;
+pub use effects::EqBandType;
+pub use effects::Filter;
+pub use effects::FilterDesign;
 pub use effects::Gain;
+pub use effects::LadderFilter;
+pub use effects::Limiter;
+pub use effects::LimiterOptions;
 pub use effects::Mixer;
+pub use effects::Noise;
+pub use effects::NoiseColour;
+pub use effects::NoiseOptions;
 pub use effects::Oscillator;
+pub use effects::OscillatorWaveform;
 pub use effects::Pan;
+pub use effects::ParametricEq;
 pub use effects::Recorder;
+pub use effects::Reverb;
+pub use effects::SampleZone;
 pub use effects::Sampler;
+pub use effects::SamplerInstrument;
+pub use effects::StateVariableFilter;
+pub use effects::StateVariableFilterOutput;
+pub use effects::StreamingSampler;
+pub use effects::Synth;
+pub use effects::SynthOscillator;
+pub use effects::SynthVoiceTemplate;
+pub use effects::Varispeed;
+pub use effects::VoiceStealing;
 pub use effects::Waveshaper;
 
 pub use engine::create_engine;
@@ -68,13 +100,20 @@ pub use engine::AudioProcess;
 pub(crate) use engine::CommandQueue;
 pub use engine::Context;
 pub use engine::EngineOptions;
+pub use engine::OfflineContext;
+pub use engine::OfflineRenderStatus;
 
-pub(crate) use graph::DspNode;
+pub use graph::DspNode;
+pub use graph::DspParameters;
+pub use graph::DspProcessor;
 pub use graph::GraphNode;
+pub use graph::GraphNodeBuilder;
 pub use graph::ProcessContext;
+pub use graph::RejectedConnection;
 
 pub use parameter::AudioParameter;
-pub(crate) use parameter::Parameters;
+pub use parameter::ParameterRange;
+pub use parameter::Parameters;
 
 pub use utility::Level;
 pub use utility::Timestamp;
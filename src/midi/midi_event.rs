@@ -0,0 +1,40 @@
+use super::MidiMessage;
+use crate::Timestamp;
+
+/// A MIDI message that is scheduled to happen at a time
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MidiEvent {
+    /// The time of the message
+    pub time: Timestamp,
+
+    /// The message
+    pub message: MidiMessage,
+}
+
+impl MidiEvent {
+    /// Create a new event
+    pub fn new(time: Timestamp, message: MidiMessage) -> Self {
+        Self { time, message }
+    }
+
+    /// Get a copy of the event that happens `offset` later
+    pub fn delayed_by(&self, offset: Timestamp) -> Self {
+        Self {
+            time: self.time + offset,
+            message: self.message,
+        }
+    }
+}
+
+/// A MIDI message that happens during the block being processed
+///
+/// These are passed to a processor in [crate::ProcessContext], in the order
+/// that they occur
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MidiBlockEvent {
+    /// The frame within the block that the message happens on
+    pub frame: usize,
+
+    /// The message
+    pub message: MidiMessage,
+}
@@ -0,0 +1,521 @@
+use super::{midi_file_error::MidiFileError, MidiEvent, MidiMessage};
+use crate::Timestamp;
+use std::{
+    fs::File,
+    io::{BufReader, Read},
+    path::Path,
+};
+
+/// How the ticks in a MIDI file relate to time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MidiTimeDivision {
+    /// The number of ticks in a quarter note, which is scaled by the tempo
+    TicksPerQuarterNote(u16),
+
+    /// A fixed number of ticks per SMPTE frame
+    Smpte {
+        /// The number of frames per second, where 29 means 29.97 (drop-frame)
+        frames_per_second: u8,
+        /// The number of ticks in each frame
+        ticks_per_frame: u8,
+    },
+}
+
+/// A message in a MIDI file track, at a position in ticks from the start
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MidiTrackEvent {
+    /// The position of the message, in ticks from the start of the file
+    pub tick: u64,
+
+    /// The message
+    pub message: MidiMessage,
+}
+
+#[derive(Clone, Copy)]
+struct TempoChange {
+    tick: u64,
+    microseconds_per_quarter_note: u32,
+}
+
+/// A Standard MIDI File (SMF)
+///
+/// The channel messages and tempo changes are read from each track. Other
+/// meta events and system exclusive messages are skipped.
+pub struct MidiFile {
+    format: u16,
+    time_division: MidiTimeDivision,
+    tracks: Vec<Vec<MidiTrackEvent>>,
+    tempo_changes: Vec<TempoChange>,
+}
+
+const DEFAULT_MICROSECONDS_PER_QUARTER_NOTE: u32 = 500_000;
+
+impl MidiFile {
+    /// Open and parse a MIDI file
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, MidiFileError> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    /// Read and parse a MIDI file from a stream
+    pub fn read<R: Read>(mut reader: R) -> Result<Self, MidiFileError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Parse a MIDI file that is held in memory
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, MidiFileError> {
+        let mut cursor = ByteCursor::new(bytes);
+
+        let (chunk_id, header) = cursor.read_chunk()?;
+
+        if chunk_id != b"MThd" || header.len() < 6 {
+            return Err(MidiFileError::InvalidFile("Missing MThd header"));
+        }
+
+        let format = u16::from_be_bytes([header[0], header[1]]);
+        let track_count = u16::from_be_bytes([header[2], header[3]]) as usize;
+        let time_division = parse_time_division(u16::from_be_bytes([header[4], header[5]]))?;
+
+        if format > 2 {
+            return Err(MidiFileError::UnsupportedFormat(format));
+        }
+
+        let mut tracks = Vec::with_capacity(track_count);
+        let mut tempo_changes = Vec::new();
+
+        while tracks.len() < track_count && !cursor.is_empty() {
+            let (chunk_id, chunk) = cursor.read_chunk()?;
+
+            if chunk_id == b"MTrk" {
+                tracks.push(parse_track(chunk, &mut tempo_changes)?);
+            }
+        }
+
+        if tracks.len() < track_count {
+            return Err(MidiFileError::InvalidFile("Missing tracks"));
+        }
+
+        tempo_changes.sort_by_key(|change| change.tick);
+
+        Ok(Self {
+            format,
+            time_division,
+            tracks,
+            tempo_changes,
+        })
+    }
+
+    /// The format of the file
+    ///
+    /// - 0: a single track
+    /// - 1: multiple tracks that are played together
+    /// - 2: multiple independent sequences
+    pub fn format(&self) -> u16 {
+        self.format
+    }
+
+    /// How the ticks in the file relate to time
+    pub fn time_division(&self) -> MidiTimeDivision {
+        self.time_division
+    }
+
+    /// The number of tracks in the file
+    pub fn track_count(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// The messages in a track, in the order that they occur
+    pub fn track_events(&self, track: usize) -> &[MidiTrackEvent] {
+        &self.tracks[track]
+    }
+
+    /// Convert a position in ticks into a time from the start of the file,
+    /// using the tempo changes in the file
+    pub fn tick_to_time(&self, tick: u64) -> Timestamp {
+        let ticks_per_quarter_note = match self.time_division {
+            MidiTimeDivision::TicksPerQuarterNote(ticks) => ticks as f64,
+            MidiTimeDivision::Smpte {
+                frames_per_second,
+                ticks_per_frame,
+            } => {
+                let ticks_per_second = smpte_frame_rate(frames_per_second) * ticks_per_frame as f64;
+                return Timestamp::from_seconds(tick as f64 / ticks_per_second);
+            }
+        };
+
+        let mut seconds = 0.0;
+        let mut position = 0;
+        let mut tempo = DEFAULT_MICROSECONDS_PER_QUARTER_NOTE;
+
+        for change in self
+            .tempo_changes
+            .iter()
+            .take_while(|change| change.tick < tick)
+        {
+            seconds += ticks_to_seconds(change.tick - position, tempo, ticks_per_quarter_note);
+            position = change.tick;
+            tempo = change.microseconds_per_quarter_note;
+        }
+
+        seconds += ticks_to_seconds(tick - position, tempo, ticks_per_quarter_note);
+
+        Timestamp::from_seconds(seconds)
+    }
+
+    /// All of the messages in the file, merged into a single sequence and
+    /// timed from the start of the file
+    pub fn events(&self) -> Vec<MidiEvent> {
+        let mut events: Vec<MidiTrackEvent> = self.tracks.iter().flatten().copied().collect();
+        events.sort_by_key(|event| event.tick);
+
+        events
+            .iter()
+            .map(|event| MidiEvent::new(self.tick_to_time(event.tick), event.message))
+            .collect()
+    }
+
+    /// The time of the last message in the file
+    pub fn duration(&self) -> Timestamp {
+        let last_tick = self
+            .tracks
+            .iter()
+            .filter_map(|track| track.last())
+            .map(|event| event.tick)
+            .max()
+            .unwrap_or(0);
+
+        self.tick_to_time(last_tick)
+    }
+}
+
+fn ticks_to_seconds(
+    ticks: u64,
+    microseconds_per_quarter_note: u32,
+    ticks_per_quarter_note: f64,
+) -> f64 {
+    ticks as f64 * microseconds_per_quarter_note as f64 / (1_000_000.0 * ticks_per_quarter_note)
+}
+
+fn smpte_frame_rate(frames_per_second: u8) -> f64 {
+    match frames_per_second {
+        29 => 30_000.0 / 1_001.0,
+        frames_per_second => frames_per_second as f64,
+    }
+}
+
+fn parse_time_division(division: u16) -> Result<MidiTimeDivision, MidiFileError> {
+    if division & 0x8000 == 0 {
+        if division == 0 {
+            return Err(MidiFileError::InvalidFile("Zero ticks per quarter note"));
+        }
+
+        return Ok(MidiTimeDivision::TicksPerQuarterNote(division));
+    }
+
+    let frames_per_second = ((division >> 8) as u8 as i8).wrapping_neg() as u8;
+    let ticks_per_frame = (division & 0xff) as u8;
+
+    if frames_per_second == 0 || ticks_per_frame == 0 {
+        return Err(MidiFileError::InvalidFile("Invalid SMPTE time division"));
+    }
+
+    Ok(MidiTimeDivision::Smpte {
+        frames_per_second,
+        ticks_per_frame,
+    })
+}
+
+fn parse_track(
+    chunk: &[u8],
+    tempo_changes: &mut Vec<TempoChange>,
+) -> Result<Vec<MidiTrackEvent>, MidiFileError> {
+    let mut cursor = ByteCursor::new(chunk);
+    let mut events = Vec::new();
+    let mut tick = 0_u64;
+    let mut running_status = None;
+
+    while !cursor.is_empty() {
+        tick += cursor.read_variable_length()? as u64;
+
+        let mut status = cursor.peek()?;
+
+        if status < 0x80 {
+            status = running_status.ok_or(MidiFileError::InvalidFile("Missing status byte"))?;
+        } else {
+            cursor.skip(1)?;
+        }
+
+        match status {
+            0xff => {
+                let meta_type = cursor.read_byte()?;
+                let length = cursor.read_variable_length()? as usize;
+                let data = cursor.read_bytes(length)?;
+
+                match meta_type {
+                    0x2f => break,
+                    0x51 if data.len() >= 3 => tempo_changes.push(TempoChange {
+                        tick,
+                        microseconds_per_quarter_note: u32::from_be_bytes([
+                            0, data[0], data[1], data[2],
+                        ]),
+                    }),
+                    _ => (),
+                }
+            }
+            0xf0 | 0xf7 => {
+                let length = cursor.read_variable_length()? as usize;
+                cursor.skip(length)?;
+                running_status = None;
+            }
+            _ => {
+                let length = MidiMessage::data_length(status)
+                    .ok_or(MidiFileError::InvalidFile("Unexpected system message"))?;
+                let data = cursor.read_bytes(length)?;
+
+                if let Some(message) = MidiMessage::from_status_and_data(status, data) {
+                    events.push(MidiTrackEvent { tick, message });
+                }
+
+                running_status = Some(status);
+            }
+        }
+    }
+
+    Ok(events)
+}
+
+struct ByteCursor<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteCursor<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.position >= self.bytes.len()
+    }
+
+    fn peek(&self) -> Result<u8, MidiFileError> {
+        self.bytes
+            .get(self.position)
+            .copied()
+            .ok_or(MidiFileError::InvalidFile("Unexpected end of data"))
+    }
+
+    fn read_byte(&mut self) -> Result<u8, MidiFileError> {
+        let byte = self.peek()?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn read_bytes(&mut self, length: usize) -> Result<&'a [u8], MidiFileError> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(MidiFileError::InvalidFile("Unexpected end of data"))?;
+
+        let bytes = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, length: usize) -> Result<(), MidiFileError> {
+        self.read_bytes(length).map(|_| ())
+    }
+
+    /// Read a variable-length quantity, which is at most four bytes
+    fn read_variable_length(&mut self) -> Result<u32, MidiFileError> {
+        let mut value = 0_u32;
+
+        for _ in 0..4 {
+            let byte = self.read_byte()?;
+            value = (value << 7) | (byte & 0x7f) as u32;
+
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+
+        Err(MidiFileError::InvalidFile(
+            "Variable-length value is too long",
+        ))
+    }
+
+    fn read_chunk(&mut self) -> Result<(&'a [u8], &'a [u8]), MidiFileError> {
+        let chunk_id = self.read_bytes(4)?;
+        let length = self.read_bytes(4)?;
+        let length = u32::from_be_bytes([length[0], length[1], length[2], length[3]]);
+        let chunk = self.read_bytes(length as usize)?;
+        Ok((chunk_id, chunk))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use approx::assert_relative_eq;
+
+    fn chunk(id: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut bytes = id.to_vec();
+        bytes.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(data);
+        bytes
+    }
+
+    fn header(format: u16, track_count: u16, division: u16) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&format.to_be_bytes());
+        data.extend_from_slice(&track_count.to_be_bytes());
+        data.extend_from_slice(&division.to_be_bytes());
+        chunk(b"MThd", &data)
+    }
+
+    fn note_on(note: u8, velocity: u8) -> MidiMessage {
+        MidiMessage::NoteOn {
+            channel: 0,
+            note,
+            velocity,
+        }
+    }
+
+    #[test]
+    fn parses_a_single_track_with_running_status() {
+        let track = [
+            0x00, 0x90, 60, 100, // note on
+            0x60, 62, 100, // running status note on, 96 ticks later
+            0x81, 0x40, 60, 0, // running status, 192 ticks later
+            0x00, 0xff, 0x2f, 0x00, // end of track
+        ];
+
+        let mut bytes = header(0, 1, 96);
+        bytes.extend(chunk(b"MTrk", &track));
+
+        let file = MidiFile::from_bytes(&bytes).unwrap();
+
+        assert_eq!(file.format(), 0);
+        assert_eq!(file.track_count(), 1);
+        assert_eq!(
+            file.time_division(),
+            MidiTimeDivision::TicksPerQuarterNote(96)
+        );
+
+        assert_eq!(
+            file.track_events(0),
+            &[
+                MidiTrackEvent {
+                    tick: 0,
+                    message: note_on(60, 100)
+                },
+                MidiTrackEvent {
+                    tick: 96,
+                    message: note_on(62, 100)
+                },
+                MidiTrackEvent {
+                    tick: 288,
+                    message: note_on(60, 0)
+                },
+            ]
+        );
+
+        let events = file.events();
+        assert_relative_eq!(events[1].time.as_seconds(), 0.5, epsilon = 1e-6);
+        assert_relative_eq!(events[2].time.as_seconds(), 1.5, epsilon = 1e-6);
+        assert_relative_eq!(file.duration().as_seconds(), 1.5, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn applies_tempo_changes_from_all_tracks() {
+        let tempo_track = [
+            0x00, 0xff, 0x51, 0x03, 0x07, 0xa1, 0x20, // 120 bpm
+            0x60, 0xff, 0x51, 0x03, 0x0f, 0x42, 0x40, // 60 bpm after a beat
+            0x00, 0xff, 0x2f, 0x00,
+        ];
+
+        let note_track = [
+            0x00, 0xc0, 5, // program change
+            0x81, 0x40, 0x90, 60, 100, // two beats in
+            0x00, 0xf0, 0x02, 0x01, 0xf7, // sysex is skipped
+            0x00, 0xe0, 0x00, 0x60, // pitch bend
+            0x00, 0xff, 0x2f, 0x00,
+        ];
+
+        let mut bytes = header(1, 2, 96);
+        bytes.extend(chunk(b"MTrk", &tempo_track));
+        bytes.extend(chunk(b"XTRA", &[1, 2, 3]));
+        bytes.extend(chunk(b"MTrk", &note_track));
+
+        let file = MidiFile::from_bytes(&bytes).unwrap();
+        let events = file.events();
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[0].message,
+            MidiMessage::ProgramChange {
+                channel: 0,
+                program: 5
+            }
+        );
+        assert_eq!(events[1].message, note_on(60, 100));
+        assert_eq!(
+            events[2].message,
+            MidiMessage::PitchBend {
+                channel: 0,
+                value: 4096
+            }
+        );
+
+        assert_relative_eq!(events[1].time.as_seconds(), 1.5, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn uses_smpte_time_division() {
+        let track = [0x81, 0x20, 0x90, 60, 100, 0x00, 0xff, 0x2f, 0x00];
+
+        let division = ((-25_i8 as u8 as u16) << 8) | 40;
+        let mut bytes = header(0, 1, division);
+        bytes.extend(chunk(b"MTrk", &track));
+
+        let file = MidiFile::from_bytes(&bytes).unwrap();
+
+        assert_eq!(
+            file.time_division(),
+            MidiTimeDivision::Smpte {
+                frames_per_second: 25,
+                ticks_per_frame: 40
+            }
+        );
+
+        assert_relative_eq!(file.events()[0].time.as_seconds(), 0.16, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        assert!(matches!(
+            MidiFile::from_bytes(b"RIFF\0\0\0\0"),
+            Err(MidiFileError::InvalidFile(_))
+        ));
+
+        assert!(matches!(
+            MidiFile::from_bytes(&header(3, 0, 96)),
+            Err(MidiFileError::UnsupportedFormat(3))
+        ));
+
+        let mut truncated = header(0, 1, 96);
+        truncated.extend(chunk(b"MTrk", &[0x00, 0x90, 60]));
+
+        assert!(matches!(
+            MidiFile::from_bytes(&truncated),
+            Err(MidiFileError::InvalidFile(_))
+        ));
+
+        assert!(matches!(
+            MidiFile::from_bytes(&header(0, 1, 96)),
+            Err(MidiFileError::InvalidFile(_))
+        ));
+    }
+}
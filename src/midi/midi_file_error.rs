@@ -0,0 +1,41 @@
+use std::fmt;
+
+/// An error when reading a Standard MIDI File
+#[derive(Debug)]
+pub enum MidiFileError {
+    /// The underlying reader failed
+    Io(std::io::Error),
+
+    /// The file isn't a valid Standard MIDI File
+    InvalidFile(&'static str),
+
+    /// The file has a format other than 0, 1, or 2
+    UnsupportedFormat(u16),
+}
+
+impl fmt::Display for MidiFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MidiFileError::Io(error) => write!(f, "IO error: {error}"),
+            MidiFileError::InvalidFile(reason) => write!(f, "Invalid MIDI file: {reason}"),
+            MidiFileError::UnsupportedFormat(format) => {
+                write!(f, "Unsupported MIDI file format: {format}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MidiFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MidiFileError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for MidiFileError {
+    fn from(error: std::io::Error) -> Self {
+        MidiFileError::Io(error)
+    }
+}
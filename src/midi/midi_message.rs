@@ -0,0 +1,281 @@
+/// A MIDI channel voice message
+///
+/// Channels are numbered from 0 to 15, and all other values are 7-bit, apart
+/// from the pitch bend, which is a signed 14-bit value centred on zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MidiMessage {
+    /// Release a note
+    NoteOff {
+        /// The channel of the message
+        channel: u8,
+        /// The note number, where 60 is middle C
+        note: u8,
+        /// The release velocity
+        velocity: u8,
+    },
+
+    /// Play a note
+    ///
+    /// A velocity of zero is usually treated as a note off
+    NoteOn {
+        /// The channel of the message
+        channel: u8,
+        /// The note number, where 60 is middle C
+        note: u8,
+        /// The velocity of the note
+        velocity: u8,
+    },
+
+    /// Change the pressure of a single note that is being held
+    PolyphonicAftertouch {
+        /// The channel of the message
+        channel: u8,
+        /// The note number
+        note: u8,
+        /// The pressure applied to the note
+        pressure: u8,
+    },
+
+    /// Change the value of a controller
+    ControlChange {
+        /// The channel of the message
+        channel: u8,
+        /// The controller number
+        controller: u8,
+        /// The new value of the controller
+        value: u8,
+    },
+
+    /// Select a different program (patch)
+    ProgramChange {
+        /// The channel of the message
+        channel: u8,
+        /// The program number
+        program: u8,
+    },
+
+    /// Change the pressure of all of the notes on a channel
+    ChannelAftertouch {
+        /// The channel of the message
+        channel: u8,
+        /// The pressure applied to the channel
+        pressure: u8,
+    },
+
+    /// Bend the pitch of a channel
+    PitchBend {
+        /// The channel of the message
+        channel: u8,
+        /// The amount of bend, from -8192 to 8191
+        value: i16,
+    },
+}
+
+impl MidiMessage {
+    /// Parse a message from its bytes, including the status byte
+    ///
+    /// Returns `None` for system messages, or if there aren't enough bytes
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let status = *bytes.first()?;
+        Self::from_status_and_data(status, &bytes[1..])
+    }
+
+    /// Parse a message from a status byte and its data bytes
+    pub(crate) fn from_status_and_data(status: u8, data: &[u8]) -> Option<Self> {
+        let channel = status & 0x0f;
+
+        if data.len() < Self::data_length(status)? {
+            return None;
+        }
+
+        let message = match status & 0xf0 {
+            0x80 => Self::NoteOff {
+                channel,
+                note: data[0] & 0x7f,
+                velocity: data[1] & 0x7f,
+            },
+            0x90 => Self::NoteOn {
+                channel,
+                note: data[0] & 0x7f,
+                velocity: data[1] & 0x7f,
+            },
+            0xa0 => Self::PolyphonicAftertouch {
+                channel,
+                note: data[0] & 0x7f,
+                pressure: data[1] & 0x7f,
+            },
+            0xb0 => Self::ControlChange {
+                channel,
+                controller: data[0] & 0x7f,
+                value: data[1] & 0x7f,
+            },
+            0xc0 => Self::ProgramChange {
+                channel,
+                program: data[0] & 0x7f,
+            },
+            0xd0 => Self::ChannelAftertouch {
+                channel,
+                pressure: data[0] & 0x7f,
+            },
+            0xe0 => {
+                let value = ((data[1] as i16 & 0x7f) << 7) | (data[0] as i16 & 0x7f);
+
+                Self::PitchBend {
+                    channel,
+                    value: value - 8192,
+                }
+            }
+            _ => return None,
+        };
+
+        Some(message)
+    }
+
+    /// The number of data bytes that follow a channel status byte
+    pub(crate) fn data_length(status: u8) -> Option<usize> {
+        match status & 0xf0 {
+            0x80 | 0x90 | 0xa0 | 0xb0 | 0xe0 => Some(2),
+            0xc0 | 0xd0 => Some(1),
+            _ => None,
+        }
+    }
+
+    /// Get the bytes of the message
+    ///
+    /// Returns the bytes, along with how many of them are used
+    pub fn to_bytes(&self) -> ([u8; 3], usize) {
+        match *self {
+            Self::NoteOff {
+                channel,
+                note,
+                velocity,
+            } => ([0x80 | channel, note, velocity], 3),
+            Self::NoteOn {
+                channel,
+                note,
+                velocity,
+            } => ([0x90 | channel, note, velocity], 3),
+            Self::PolyphonicAftertouch {
+                channel,
+                note,
+                pressure,
+            } => ([0xa0 | channel, note, pressure], 3),
+            Self::ControlChange {
+                channel,
+                controller,
+                value,
+            } => ([0xb0 | channel, controller, value], 3),
+            Self::ProgramChange { channel, program } => ([0xc0 | channel, program, 0], 2),
+            Self::ChannelAftertouch { channel, pressure } => ([0xd0 | channel, pressure, 0], 2),
+            Self::PitchBend { channel, value } => {
+                let value = (value.clamp(-8192, 8191) + 8192) as u16;
+                (
+                    [0xe0 | channel, (value & 0x7f) as u8, (value >> 7) as u8],
+                    3,
+                )
+            }
+        }
+    }
+
+    /// Get the channel of the message
+    pub fn channel(&self) -> u8 {
+        match *self {
+            Self::NoteOff { channel, .. }
+            | Self::NoteOn { channel, .. }
+            | Self::PolyphonicAftertouch { channel, .. }
+            | Self::ControlChange { channel, .. }
+            | Self::ProgramChange { channel, .. }
+            | Self::ChannelAftertouch { channel, .. }
+            | Self::PitchBend { channel, .. } => channel,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_note_on() {
+        assert_eq!(
+            MidiMessage::from_bytes(&[0x93, 60, 100]),
+            Some(MidiMessage::NoteOn {
+                channel: 3,
+                note: 60,
+                velocity: 100
+            })
+        );
+    }
+
+    #[test]
+    fn parses_pitch_bend() {
+        assert_eq!(
+            MidiMessage::from_bytes(&[0xe0, 0x00, 0x40]),
+            Some(MidiMessage::PitchBend {
+                channel: 0,
+                value: 0
+            })
+        );
+
+        assert_eq!(
+            MidiMessage::from_bytes(&[0xe0, 0x00, 0x00]),
+            Some(MidiMessage::PitchBend {
+                channel: 0,
+                value: -8192
+            })
+        );
+
+        assert_eq!(
+            MidiMessage::from_bytes(&[0xe0, 0x7f, 0x7f]),
+            Some(MidiMessage::PitchBend {
+                channel: 0,
+                value: 8191
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_short_and_system_messages() {
+        assert_eq!(MidiMessage::from_bytes(&[0x90, 60]), None);
+        assert_eq!(MidiMessage::from_bytes(&[0xf8]), None);
+        assert_eq!(MidiMessage::from_bytes(&[]), None);
+    }
+
+    #[test]
+    fn round_trips_through_bytes() {
+        let messages = [
+            MidiMessage::NoteOff {
+                channel: 1,
+                note: 64,
+                velocity: 0,
+            },
+            MidiMessage::PolyphonicAftertouch {
+                channel: 2,
+                note: 10,
+                pressure: 20,
+            },
+            MidiMessage::ControlChange {
+                channel: 15,
+                controller: 7,
+                value: 127,
+            },
+            MidiMessage::ProgramChange {
+                channel: 9,
+                program: 42,
+            },
+            MidiMessage::ChannelAftertouch {
+                channel: 0,
+                pressure: 3,
+            },
+            MidiMessage::PitchBend {
+                channel: 4,
+                value: -1234,
+            },
+        ];
+
+        for message in messages {
+            let (bytes, length) = message.to_bytes();
+            assert_eq!(MidiMessage::from_bytes(&bytes[..length]), Some(message));
+        }
+    }
+}
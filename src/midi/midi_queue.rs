@@ -0,0 +1,180 @@
+use super::{MidiBlockEvent, MidiEvent};
+use crate::Timestamp;
+
+/// The events that have been scheduled for a node on the audio thread
+///
+/// Before each block is processed, the events that happen during the block
+/// are moved into a list with their position in the block. Events that are
+/// scheduled in the past are delivered at the start of the next block.
+///
+/// The queue never grows beyond its capacity, so that scheduling doesn't
+/// allocate on the audio thread. Events that don't fit are dropped.
+pub struct MidiQueue {
+    pending_events: Vec<MidiEvent>,
+    block_events: Vec<MidiBlockEvent>,
+}
+
+/// The most events that can be waiting to be delivered to a node
+pub const MAXIMUM_PENDING_MIDI_EVENTS: usize = 1_024;
+const SUB_FRAME_RESOLUTION: f64 = 10_000.0;
+
+impl Default for MidiQueue {
+    fn default() -> Self {
+        Self::with_capacity(MAXIMUM_PENDING_MIDI_EVENTS)
+    }
+}
+
+impl MidiQueue {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            pending_events: Vec::with_capacity(capacity),
+            block_events: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Add an event, after any other events with the same time
+    ///
+    /// Returns false if the queue is full, in which case the event is dropped
+    pub fn schedule(&mut self, event: MidiEvent) -> bool {
+        if self.pending_events.len() == self.pending_events.capacity() {
+            return false;
+        }
+
+        let index = self
+            .pending_events
+            .partition_point(|pending| pending.time <= event.time);
+
+        self.pending_events.insert(index, event);
+        true
+    }
+
+    pub fn cancel_all(&mut self) {
+        self.pending_events.clear();
+    }
+
+    pub fn prepare_block(
+        &mut self,
+        start_time: &Timestamp,
+        frame_count: usize,
+        sample_rate: usize,
+    ) {
+        self.block_events.clear();
+
+        let end_time = start_time.incremented_by_samples(frame_count, sample_rate);
+        let event_count = self
+            .pending_events
+            .partition_point(|event| event.time < end_time);
+
+        for event in self.pending_events.drain(..event_count) {
+            let event_time = std::cmp::max(event.time, *start_time);
+            let position = (event_time - *start_time).as_samples(sample_rate);
+
+            // Timestamps are fixed-point, so snap to a sub-frame resolution
+            // before rounding down to avoid landing on the previous frame
+            let frame =
+                ((position * SUB_FRAME_RESOLUTION).round() / SUB_FRAME_RESOLUTION).floor() as usize;
+
+            self.block_events.push(MidiBlockEvent {
+                frame: frame.min(frame_count.saturating_sub(1)),
+                message: event.message,
+            });
+        }
+    }
+
+    pub fn block_events(&self) -> &[MidiBlockEvent] {
+        &self.block_events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::midi::MidiMessage;
+
+    fn note_on(note: u8) -> MidiMessage {
+        MidiMessage::NoteOn {
+            channel: 0,
+            note,
+            velocity: 100,
+        }
+    }
+
+    fn frames(queue: &MidiQueue) -> Vec<(usize, MidiMessage)> {
+        queue
+            .block_events()
+            .iter()
+            .map(|event| (event.frame, event.message))
+            .collect()
+    }
+
+    #[test]
+    fn delivers_events_in_their_block() {
+        let sample_rate = 1_000;
+        let mut queue = MidiQueue::default();
+
+        queue.schedule(MidiEvent::new(Timestamp::from_seconds(0.15), note_on(2)));
+        queue.schedule(MidiEvent::new(Timestamp::from_seconds(0.01), note_on(1)));
+
+        queue.prepare_block(&Timestamp::zero(), 100, sample_rate);
+        assert_eq!(frames(&queue), vec![(10, note_on(1))]);
+
+        queue.prepare_block(&Timestamp::from_seconds(0.1), 100, sample_rate);
+        assert_eq!(frames(&queue), vec![(50, note_on(2))]);
+
+        queue.prepare_block(&Timestamp::from_seconds(0.2), 100, sample_rate);
+        assert!(frames(&queue).is_empty());
+    }
+
+    #[test]
+    fn keeps_the_order_of_simultaneous_events() {
+        let mut queue = MidiQueue::default();
+
+        for note in 0..4 {
+            queue.schedule(MidiEvent::new(Timestamp::zero(), note_on(note)));
+        }
+
+        queue.prepare_block(&Timestamp::zero(), 64, 48_000);
+
+        let notes: Vec<_> = frames(&queue)
+            .into_iter()
+            .map(|(_, message)| message)
+            .collect();
+
+        assert_eq!(notes, (0..4).map(note_on).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn delivers_late_events_at_the_start_of_the_block() {
+        let mut queue = MidiQueue::default();
+
+        queue.schedule(MidiEvent::new(Timestamp::from_seconds(0.5), note_on(1)));
+        queue.prepare_block(&Timestamp::from_seconds(1.0), 64, 48_000);
+
+        assert_eq!(frames(&queue), vec![(0, note_on(1))]);
+    }
+
+    #[test]
+    fn cancels_pending_events() {
+        let mut queue = MidiQueue::default();
+
+        queue.schedule(MidiEvent::new(Timestamp::from_seconds(0.5), note_on(1)));
+        queue.cancel_all();
+        queue.prepare_block(&Timestamp::zero(), 48_000, 48_000);
+
+        assert!(frames(&queue).is_empty());
+    }
+
+    #[test]
+    fn drops_events_when_full() {
+        let mut queue = MidiQueue::with_capacity(2);
+
+        assert!(queue.schedule(MidiEvent::new(Timestamp::zero(), note_on(1))));
+        assert!(queue.schedule(MidiEvent::new(Timestamp::zero(), note_on(2))));
+        assert!(!queue.schedule(MidiEvent::new(Timestamp::zero(), note_on(3))));
+
+        queue.prepare_block(&Timestamp::zero(), 64, 48_000);
+        assert_eq!(frames(&queue), vec![(0, note_on(1)), (0, note_on(2))]);
+
+        assert!(queue.schedule(MidiEvent::new(Timestamp::zero(), note_on(3))));
+    }
+}
@@ -0,0 +1,50 @@
+//! Sending MIDI messages to nodes
+//!
+//! MIDI events can be scheduled on any [crate::GraphNode]. The events are
+//! delivered to the node's [crate::DspProcessor] in
+//! [crate::ProcessContext::midi_events], along with the frame in the block
+//! that they happen on.
+//!
+//! Standard MIDI Files can be read with [MidiFile], so that recorded
+//! sequences can be played through the graph.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use rawdio::{
+//!     midi::{MidiFile, MAXIMUM_PENDING_MIDI_EVENTS},
+//!     prelude::*,
+//!     SampleZone, SamplerInstrument,
+//! };
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let (mut context, mut process) = create_engine();
+//! # let sample = OwnedAudioBuffer::new(48_000, 2, 48_000);
+//!
+//! let zones = vec![SampleZone::new(sample, 60)];
+//! let instrument = SamplerInstrument::new(context.as_ref(), 2, zones);
+//!
+//! let file = MidiFile::open("sequence.mid")?;
+//! let start_time = context.current_time();
+//!
+//! // Each node holds a limited number of pending events, so longer sequences
+//! // should be sent a little at a time as playback reaches them
+//! for event in file.events().into_iter().take(MAXIMUM_PENDING_MIDI_EVENTS) {
+//!     instrument.node.send_midi_event(event.delayed_by(start_time));
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+mod midi_event;
+mod midi_file;
+mod midi_file_error;
+mod midi_message;
+mod midi_queue;
+
+pub use midi_event::{MidiBlockEvent, MidiEvent};
+pub use midi_file::{MidiFile, MidiTimeDivision, MidiTrackEvent};
+pub use midi_file_error::MidiFileError;
+pub use midi_message::MidiMessage;
+pub(crate) use midi_queue::MidiQueue;
+pub use midi_queue::MAXIMUM_PENDING_MIDI_EVENTS;
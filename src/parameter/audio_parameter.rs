@@ -27,7 +27,8 @@ impl AudioParameter {
     ) -> (Self, RealtimeAudioParameter) {
         let value = ParameterValue::new(AtomicF64::new(range.default()));
         let realtime_audio_param =
-            RealtimeAudioParameter::new(parameter_id, value.clone(), context.maximum_frame_count());
+            RealtimeAudioParameter::new(parameter_id, value.clone(), context.maximum_frame_count())
+                .with_range(&range);
 
         (
             Self {
@@ -46,6 +47,10 @@ impl AudioParameter {
         self.parameter_id
     }
 
+    pub(crate) fn get_dsp_id(&self) -> Id {
+        self.dsp_id
+    }
+
     /// Get the last known value of this parameter
     pub fn get_value(&self) -> ParameterValue {
         self.value.clone()
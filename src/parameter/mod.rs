@@ -8,6 +8,7 @@ mod realtime_parameter;
 
 pub use audio_parameter::AudioParameter;
 pub use parameter_change::ParameterChange;
+pub(crate) use parameter_id::intern_parameter_id;
 pub use parameter_id::ParameterId;
 pub use parameter_range::ParameterRange;
 pub use parameters::Parameters;
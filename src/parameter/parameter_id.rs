@@ -1 +1,42 @@
+use std::{
+    collections::HashSet,
+    sync::{Mutex, OnceLock},
+};
+
 pub type ParameterId = &'static str;
+
+/// Get a [ParameterId] for a name that is only known at runtime, such as the
+/// parameters of each band in a node with a configurable number of bands
+///
+/// Each distinct name is only allocated once, so this can be called every
+/// time a node is created
+pub(crate) fn intern_parameter_id(name: &str) -> ParameterId {
+    static NAMES: OnceLock<Mutex<HashSet<&'static str>>> = OnceLock::new();
+
+    let mut names = NAMES
+        .get_or_init(|| Mutex::new(HashSet::new()))
+        .lock()
+        .expect("Parameter name lock was poisoned");
+
+    if let Some(id) = names.get(name) {
+        return id;
+    }
+
+    let id: &'static str = Box::leak(name.to_owned().into_boxed_str());
+    names.insert(id);
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interns_each_name_once() {
+        let first = intern_parameter_id("band-0-frequency");
+        let second = intern_parameter_id(&format!("band-{}-frequency", 0));
+
+        assert_eq!(first, "band-0-frequency");
+        assert!(std::ptr::eq(first, second));
+    }
+}
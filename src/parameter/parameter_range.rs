@@ -1,3 +1,5 @@
+/// The range of values that a parameter can take
+#[derive(Clone, Copy, Debug)]
 pub struct ParameterRange {
     default: f64,
     minimum: f64,
@@ -5,6 +7,9 @@ pub struct ParameterRange {
 }
 
 impl ParameterRange {
+    /// Create a new range
+    ///
+    /// Panics if the default value is outside of the minimum and maximum
     pub fn new(default: f64, minimum: f64, maximum: f64) -> Self {
         let range = Self {
             default,
@@ -17,10 +22,22 @@ impl ParameterRange {
         range
     }
 
+    /// The value of the parameter before any changes are made
     pub fn default(&self) -> f64 {
         self.default
     }
 
+    /// The lowest value of the parameter
+    pub fn minimum(&self) -> f64 {
+        self.minimum
+    }
+
+    /// The highest value of the parameter
+    pub fn maximum(&self) -> f64 {
+        self.maximum
+    }
+
+    /// Whether the range is valid
     pub fn is_valid(&self) -> bool {
         if self.maximum < self.minimum {
             return false;
@@ -33,6 +50,7 @@ impl ParameterRange {
         true
     }
 
+    /// Clamp a value to the range
     pub fn clamp(&self, value: f64) -> f64 {
         value.clamp(self.minimum, self.maximum)
     }
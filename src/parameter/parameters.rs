@@ -1,34 +1,47 @@
 #![allow(dead_code)]
 
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::atomic::Ordering};
 
 use crate::AudioParameter;
 
+/// The parameters that belong to a node, referenced by name
 pub struct Parameters {
     params: HashMap<&'static str, AudioParameter>,
 }
 
 impl Parameters {
-    pub fn empty() -> Self {
+    pub(crate) fn empty() -> Self {
         Self {
             params: HashMap::new(),
         }
     }
 
-    pub fn with_parameter(mut self, name: &'static str, param: AudioParameter) -> Self {
+    pub(crate) fn with_parameter(mut self, name: &'static str, param: AudioParameter) -> Self {
         self.add(name, param);
         self
     }
 
-    pub fn add(&mut self, name: &'static str, param: AudioParameter) {
+    pub(crate) fn add(&mut self, name: &'static str, param: AudioParameter) {
         self.params.insert(name, param);
     }
 
+    /// Get a parameter by name
     pub fn get(&self, name: &str) -> Option<&AudioParameter> {
         self.params.get(name)
     }
 
+    /// Get a mutable parameter by name
     pub fn get_mut(&mut self, name: &str) -> Option<&mut AudioParameter> {
         self.params.get_mut(name)
     }
+
+    /// Get the last value of a parameter that was used by the audio thread
+    ///
+    /// Panics if there isn't a parameter with that name
+    pub(crate) fn current_value(&self, name: &str) -> f64 {
+        self.get(name)
+            .unwrap_or_else(|| panic!("Parameter not found: {name}"))
+            .get_value()
+            .load(Ordering::Acquire)
+    }
 }
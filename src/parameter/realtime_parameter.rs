@@ -2,7 +2,7 @@ use crate::Timestamp;
 
 use super::{
     parameter_change::ValueChangeMethod, parameter_value::ParameterValue, ParameterChange,
-    ParameterId,
+    ParameterId, ParameterRange,
 };
 
 use std::sync::atomic::Ordering;
@@ -46,6 +46,9 @@ pub struct RealtimeAudioParameter {
     increment: f64,
     coefficient: f64,
     current_change: ParameterChange,
+    minimum: f32,
+    maximum: f32,
+    is_modulated: bool,
 }
 
 impl RealtimeAudioParameter {
@@ -64,9 +67,18 @@ impl RealtimeAudioParameter {
                 end_time: Timestamp::zero(),
                 method: ValueChangeMethod::Immediate,
             },
+            minimum: f32::MIN,
+            maximum: f32::MAX,
+            is_modulated: false,
         }
     }
 
+    pub fn with_range(mut self, range: &ParameterRange) -> Self {
+        self.minimum = range.minimum() as f32;
+        self.maximum = range.maximum() as f32;
+        self
+    }
+
     pub fn get_id(&self) -> ParameterId {
         self.id
     }
@@ -93,6 +105,7 @@ impl RealtimeAudioParameter {
 
     pub fn process(&mut self, time: &Timestamp, frame_count: usize, sample_rate: usize) {
         self.parameter_buffer.reset();
+        self.is_modulated = false;
 
         let mut value = self.get_value();
 
@@ -160,6 +173,29 @@ impl RealtimeAudioParameter {
         self.parameter_buffer.get_values(frame_count)
     }
 
+    /// Add audio-rate values to the values calculated in `process()`
+    ///
+    /// `clamp_modulation()` should be called once all modulation has been added
+    pub fn add_modulation(&mut self, modulation: &[f32]) {
+        for (value, modulation) in self.parameter_buffer.values.iter_mut().zip(modulation) {
+            *value += *modulation;
+        }
+
+        self.is_modulated = true;
+    }
+
+    pub fn clamp_modulation(&mut self) {
+        if !self.is_modulated {
+            return;
+        }
+
+        for value in self.parameter_buffer.values.iter_mut() {
+            *value = value.clamp(self.minimum, self.maximum);
+        }
+
+        self.is_modulated = false;
+    }
+
     pub fn set_value(&mut self, value: f64) {
         self.value.store(value, Ordering::Release)
     }
@@ -1,28 +1,44 @@
-use super::{garbage_collector::*, graph::*, topological_sort::TopologicalSort};
+use super::{
+    garbage_collector::*, graph::*, passthrough_processor::PassthroughProcessor,
+    topological_sort::TopologicalSort, worker_pool::WorkerPool,
+};
 use crate::{buffer::BufferPool, commands::*, graph::*, prelude::*};
 
 struct BufferPools {
     free: BufferPool,
     assigned: AssignedBufferPool<Endpoint>,
+    feedback: AssignedBufferPool<Id>,
 }
 
 pub struct DspGraph {
     graph: Graph<Box<Dsp>, Connection>,
     topological_sort: TopologicalSort,
-    input_endpoint: Option<Endpoint>,
-    output_endpoint: Option<Endpoint>,
     garbage_collection_tx: GarbaseCollectionSender,
     graph_needs_sort: bool,
     buffer_pools: BufferPools,
+    rejected_connections: Vec<Connection>,
     maximum_channel_count: usize,
     maximum_frame_count: usize,
+    sample_rate: usize,
+    worker_pool: Option<WorkerPool>,
 }
 
 static MAXIMUM_BUFFER_COUNT: usize = 1024;
 static MAXIMUM_GRAPH_NODE_COUNT: usize = 512;
 static MAXIMUM_GRAPH_EDGE_COUNT: usize = 512;
+static MAXIMUM_FEEDBACK_EDGE_COUNT: usize = 64;
 static GARBAGE_COLLECTION_CHANNEL_CAPACITY: usize = 512;
 
+fn create_system_dsp(id: Id, channel_count: usize) -> Box<Dsp> {
+    Box::new(Dsp::new(
+        id,
+        channel_count,
+        channel_count,
+        Box::new(PassthroughProcessor),
+        DspParameters::empty(),
+    ))
+}
+
 impl DspGraph {
     pub fn new(
         maximum_frame_count: usize,
@@ -33,12 +49,10 @@ impl DspGraph {
             crossbeam::channel::bounded(GARBAGE_COLLECTION_CHANNEL_CAPACITY);
         run_garbage_collector(garbage_collection_rx);
 
-        Self {
+        let mut graph = Self {
             graph: Graph::with_capacity(MAXIMUM_GRAPH_NODE_COUNT, MAXIMUM_GRAPH_EDGE_COUNT),
             topological_sort: TopologicalSort::with_capacity(MAXIMUM_GRAPH_NODE_COUNT),
             graph_needs_sort: false,
-            input_endpoint: None,
-            output_endpoint: None,
             garbage_collection_tx,
             buffer_pools: BufferPools {
                 free: BufferPool::new(
@@ -48,10 +62,41 @@ impl DspGraph {
                     sample_rate,
                 ),
                 assigned: AssignedBufferPool::with_capacity(MAXIMUM_BUFFER_COUNT),
+                feedback: AssignedBufferPool::with_capacity(MAXIMUM_FEEDBACK_EDGE_COUNT),
             },
+            rejected_connections: Vec::with_capacity(MAXIMUM_GRAPH_EDGE_COUNT),
             maximum_channel_count,
             maximum_frame_count,
-        }
+            sample_rate,
+            worker_pool: None,
+        };
+
+        graph.add_dsp(create_system_dsp(Id::system_input(), maximum_channel_count));
+        graph.add_dsp(create_system_dsp(
+            Id::system_output(),
+            maximum_channel_count,
+        ));
+
+        graph
+    }
+
+    /// Process independent branches of the graph concurrently, using this
+    /// many worker threads alongside the audio thread
+    pub fn with_worker_thread_count(mut self, worker_thread_count: usize) -> Self {
+        self.worker_pool = match worker_thread_count {
+            0 => None,
+            _ => Some(WorkerPool::new(
+                worker_thread_count,
+                MAXIMUM_GRAPH_NODE_COUNT,
+                MAXIMUM_GRAPH_EDGE_COUNT,
+                self.maximum_frame_count,
+                self.maximum_channel_count,
+                self.sample_rate,
+            )),
+        };
+
+        self.mark_graph_needs_sort();
+        self
     }
 
     pub fn process(
@@ -68,38 +113,55 @@ impl DspGraph {
 
         self.sort_graph();
 
-        if let Some(input_endpoint) = self.input_endpoint {
-            if let Some(mut buffer) = self.buffer_pools.free.remove() {
-                buffer.copy_from(
-                    input_buffer,
-                    SampleLocation::origin(),
-                    SampleLocation::origin(),
-                    input_channel_count,
-                    frame_count,
-                );
+        if self
+            .worker_pool
+            .as_ref()
+            .is_some_and(WorkerPool::is_scheduled)
+        {
+            self.process_in_parallel(
+                input_buffer,
+                output_buffer,
+                input_channel_count,
+                output_channel_count,
+                frame_count,
+                start_time,
+            );
 
-                self.buffer_pools.assigned.add(buffer, &input_endpoint);
-            }
+            return;
         }
 
-        self.process_dsps(frame_count, start_time);
+        let input_endpoint = Endpoint::new(Id::system_input(), EndpointType::Input);
 
-        if let Some(output_endpoint) = self.output_endpoint {
-            let source_channel = 0;
-            let destination_channel = 0;
-
-            mix_endpoint(
-                &mut self.buffer_pools.assigned,
-                &output_endpoint,
-                output_buffer,
-                source_channel,
-                destination_channel,
-                output_channel_count,
+        if let Some(mut buffer) = self.buffer_pools.free.remove() {
+            buffer.copy_from(
+                input_buffer,
+                SampleLocation::origin(),
+                SampleLocation::origin(),
+                input_channel_count,
                 frame_count,
-                MixBehaviour::Overwrite,
             );
+
+            self.buffer_pools.assigned.add(buffer, &input_endpoint);
         }
 
+        self.process_dsps(frame_count, start_time);
+        self.store_feedback(frame_count);
+
+        let output_endpoint = Endpoint::new(Id::system_output(), EndpointType::Output);
+        let source_channel = 0;
+        let destination_channel = 0;
+
+        mix_endpoint(
+            &mut self.buffer_pools.assigned,
+            &output_endpoint,
+            output_buffer,
+            source_channel,
+            destination_channel,
+            output_channel_count,
+            frame_count,
+            MixBehaviour::Overwrite,
+        );
+
         while let Some((_, buffer)) = self.buffer_pools.assigned.remove_next() {
             self.buffer_pools.free.add(buffer);
         }
@@ -107,6 +169,66 @@ impl DspGraph {
         debug_assert!(self.buffer_pools.assigned.is_empty());
     }
 
+    fn process_in_parallel(
+        &mut self,
+        input_buffer: &dyn AudioBuffer,
+        output_buffer: &mut dyn AudioBuffer,
+        input_channel_count: usize,
+        output_channel_count: usize,
+        frame_count: usize,
+        start_time: &Timestamp,
+    ) {
+        let worker_pool = match self.worker_pool.as_mut() {
+            Some(worker_pool) => worker_pool,
+            None => return,
+        };
+
+        worker_pool.prepare(&mut self.graph, frame_count, start_time, self.sample_rate);
+
+        worker_pool.write_input(
+            Id::system_input(),
+            input_buffer,
+            input_channel_count,
+            frame_count,
+        );
+
+        for edge_id in self.topological_sort.get_feedback_edges() {
+            if let (Some(edge), Some(buffer)) = (
+                self.graph.get_edge(*edge_id),
+                self.buffer_pools.feedback.get(edge_id),
+            ) {
+                worker_pool.write_feedback(edge, buffer, frame_count);
+            }
+        }
+
+        worker_pool.process();
+
+        for edge_id in self.topological_sort.get_feedback_edges() {
+            let edge = match self.graph.get_edge(*edge_id) {
+                Some(edge) => edge,
+                None => continue,
+            };
+
+            if let Some(mut buffer) = self.buffer_pools.feedback.remove(edge_id) {
+                let channel_count = buffer.channel_count();
+
+                buffer.clear();
+                worker_pool.read_output(edge.from_node_id, &mut buffer, channel_count, frame_count);
+
+                self.buffer_pools.feedback.add(buffer, edge_id);
+            }
+        }
+
+        worker_pool.read_output(
+            Id::system_output(),
+            output_buffer,
+            output_channel_count,
+            frame_count,
+        );
+
+        worker_pool.finish(&mut self.graph);
+    }
+
     pub fn add_dsp(&mut self, dsp: Box<Dsp>) {
         let id = dsp.get_id();
         self.graph.add_node_with_id(id, dsp);
@@ -118,10 +240,94 @@ impl DspGraph {
     }
 
     fn sort_graph(&mut self) {
-        if self.graph_needs_sort {
+        if !self.graph_needs_sort {
+            return;
+        }
+
+        self.topological_sort.sort(&self.graph);
+
+        while self.topological_sort.get_feedback_edges().len() > MAXIMUM_FEEDBACK_EDGE_COUNT {
+            self.reject_last_feedback_edge();
             self.topological_sort.sort(&self.graph);
-            self.graph_needs_sort = false;
         }
+
+        self.assign_feedback_buffers();
+
+        if let Some(worker_pool) = self.worker_pool.as_mut() {
+            worker_pool.schedule(
+                &self.graph,
+                self.topological_sort.get_sorted_graph(),
+                self.topological_sort.get_feedback_edges(),
+            );
+        }
+
+        self.graph_needs_sort = false;
+    }
+
+    fn reject_last_feedback_edge(&mut self) {
+        if let Some(edge_id) = self.topological_sort.get_feedback_edges().last() {
+            if let Some(connection) = self.graph.remove_edge_with_id(*edge_id) {
+                self.rejected_connections.push(connection);
+            }
+        }
+    }
+
+    fn assign_feedback_buffers(&mut self) {
+        let feedback_edges = self.topological_sort.get_feedback_edges();
+
+        while let Some((_, buffer)) = self
+            .buffer_pools
+            .feedback
+            .remove_where(|edge_id| !feedback_edges.contains(edge_id))
+        {
+            self.buffer_pools.free.add(buffer);
+        }
+
+        for edge_id in feedback_edges {
+            if self.buffer_pools.feedback.has(edge_id) {
+                continue;
+            }
+
+            if let Some(buffer) = self.buffer_pools.free.remove() {
+                self.buffer_pools.feedback.add(buffer, edge_id);
+            }
+        }
+    }
+
+    fn store_feedback(&mut self, frame_count: usize) {
+        for edge_id in self.topological_sort.get_feedback_edges() {
+            let edge = match self.graph.get_edge(*edge_id) {
+                Some(edge) => edge,
+                None => continue,
+            };
+
+            let mut buffer = match self.buffer_pools.feedback.remove(edge_id) {
+                Some(buffer) => buffer,
+                None => continue,
+            };
+
+            let source_endpoint = Endpoint::new(edge.from_node_id, EndpointType::Output);
+            let channel_count = buffer.channel_count();
+
+            buffer.clear();
+
+            mix_endpoint(
+                &mut self.buffer_pools.assigned,
+                &source_endpoint,
+                &mut buffer,
+                0,
+                0,
+                channel_count,
+                frame_count,
+                MixBehaviour::Overwrite,
+            );
+
+            self.buffer_pools.feedback.add(buffer, edge_id);
+        }
+    }
+
+    pub fn drain_rejected_connections(&mut self) -> impl Iterator<Item = Connection> + '_ {
+        self.rejected_connections.drain(..)
     }
 
     pub fn remove_dsp(&mut self, id: Id) {
@@ -146,6 +352,18 @@ impl DspGraph {
         }
     }
 
+    pub fn schedule_midi_event(&mut self, midi_request: MidiEventRequest) {
+        if let Some(dsp) = self.graph.get_node_mut(midi_request.dsp_id) {
+            dsp.schedule_midi_event(midi_request.event);
+        }
+    }
+
+    pub fn cancel_midi_events(&mut self, id: Id) {
+        if let Some(dsp) = self.graph.get_node_mut(id) {
+            dsp.cancel_midi_events();
+        }
+    }
+
     pub fn add_connection(&mut self, connection: Connection) {
         self.graph.add_edge(
             connection.source.dsp_id,
@@ -157,28 +375,28 @@ impl DspGraph {
     }
 
     pub fn remove_connection(&mut self, connection: Connection) {
-        self.graph
-            .remove_edge(connection.source.dsp_id, connection.destination.dsp_id);
+        while let Some(edge_id) = self.find_edge(&connection) {
+            self.graph.remove_edge_with_id(edge_id);
+        }
 
         self.mark_graph_needs_sort();
     }
 
-    pub fn connect_to_output(&mut self, output_endpoint: Endpoint) {
-        self.output_endpoint = Some(output_endpoint);
-    }
-
-    pub fn connect_to_input(&mut self, input_endpoint: Endpoint) {
-        self.input_endpoint = Some(input_endpoint);
+    fn find_edge(&self, connection: &Connection) -> Option<Id> {
+        self.graph
+            .edge_iterator(connection.source.dsp_id, Direction::Outgoing)
+            .find(|edge_id| {
+                self.graph.get_edge(*edge_id).is_some_and(|edge| {
+                    edge.to_node_id == connection.destination.dsp_id
+                        && edge.edge_data.destination_parameter == connection.destination_parameter
+                })
+            })
     }
 
     fn process_dsps(&mut self, frame_count: usize, start_time: &Timestamp) {
         let sorted_graph = self.topological_sort.get_sorted_graph();
         for dsp_id in sorted_graph {
-            debug_assert!(can_process_dsp(
-                dsp_id,
-                &self.graph,
-                &self.buffer_pools.assigned
-            ));
+            debug_assert!(can_process_dsp(dsp_id, &self.graph, &self.buffer_pools));
 
             process_dsp(
                 &mut self.buffer_pools,
@@ -202,6 +420,14 @@ fn process_dsp(
 
     let mut output_buffer = get_buffer_with_endpoint(&output_endpoint, buffer_pools);
 
+    if let Some(dsp) = graph.get_node_mut(dsp_id) {
+        dsp.process_parameters(start_time, frame_count, output_buffer.sample_rate());
+    }
+
+    // Modulate before preparing the input, as a single input can take the
+    // buffer of a source that is also modulating one of the parameters
+    modulate_parameters(buffer_pools, graph, dsp_id, frame_count);
+
     let (input_buffer, input_endpoint) = prepare_input(buffer_pools, graph, dsp_id, frame_count);
 
     if let Some(dsp) = graph.get_node_mut(dsp_id) {
@@ -229,15 +455,59 @@ fn process_dsp(
     }
 }
 
+fn modulate_parameters(
+    buffer_pools: &BufferPools,
+    graph: &mut Graph<Box<Dsp>, Connection>,
+    dsp_id: Id,
+    frame_count: usize,
+) {
+    let mut edge_id = None;
+
+    while let Some(next_edge_id) = graph.next_edge(dsp_id, edge_id, Direction::Incoming) {
+        edge_id = Some(next_edge_id);
+
+        let edge = graph.get_edge(next_edge_id).expect("Edge not found");
+
+        let parameter_id = match edge.edge_data.destination_parameter {
+            Some(parameter_id) => parameter_id,
+            None => continue,
+        };
+
+        let source_location = SampleLocation::channel(edge.edge_data.source_output_channel);
+
+        let source_buffer = if buffer_pools.feedback.has(&next_edge_id) {
+            buffer_pools.feedback.get(&next_edge_id)
+        } else {
+            let source_endpoint = Endpoint::new(edge.from_node_id, EndpointType::Output);
+            buffer_pools.assigned.get(&source_endpoint)
+        };
+
+        if let (Some(source_buffer), Some(dsp)) = (source_buffer, graph.get_node_mut(dsp_id)) {
+            let modulation = &source_buffer.get_channel_data(source_location)[..frame_count];
+            dsp.modulate_parameter(parameter_id, modulation);
+        }
+    }
+}
+
+fn is_audio_edge(graph: &Graph<Box<Dsp>, Connection>, edge_id: Id) -> bool {
+    graph
+        .get_edge(edge_id)
+        .is_some_and(|edge| !edge.edge_data.is_to_parameter())
+}
+
 fn can_process_dsp(
     id: &Id,
     graph: &Graph<Box<Dsp>, Connection>,
-    assigned_buffer_pool: &AssignedBufferPool<Endpoint>,
+    buffer_pools: &BufferPools,
 ) -> bool {
     graph
-        .node_iter(*id, Direction::Incoming)
-        .all(|incoming_node_id| {
-            assigned_buffer_pool.has(&Endpoint::new(incoming_node_id, EndpointType::Output))
+        .edge_iterator(*id, Direction::Incoming)
+        .filter(|edge_id| !buffer_pools.feedback.has(edge_id))
+        .all(|edge_id| {
+            let edge = graph.get_edge(edge_id).expect("Edge not found");
+            buffer_pools
+                .assigned
+                .has(&Endpoint::new(edge.from_node_id, EndpointType::Output))
         })
 }
 
@@ -247,16 +517,18 @@ enum MixBehaviour {
 }
 
 #[allow(clippy::too_many_arguments)]
-fn mix_endpoint(
-    assigned_buffer_pool: &mut AssignedBufferPool<Endpoint>,
-    endpoint: &Endpoint,
+fn mix_endpoint<Identifier>(
+    assigned_buffer_pool: &mut AssignedBufferPool<Identifier>,
+    endpoint: &Identifier,
     output_buffer: &mut dyn AudioBuffer,
     source_channel: usize,
     destination_channel: usize,
     channel_count: usize,
     frame_count: usize,
     mix_behaviour: MixBehaviour,
-) {
+) where
+    Identifier: std::cmp::Eq + std::hash::Hash + Copy,
+{
     if let Some(buffer) = assigned_buffer_pool.remove(endpoint) {
         let source_location = SampleLocation::channel(source_channel);
         let destination_location = SampleLocation::channel(destination_channel);
@@ -283,7 +555,7 @@ fn mix_endpoint(
 }
 
 fn copy_output_from_dependencies(
-    assigned_buffer_pool: &mut AssignedBufferPool<Endpoint>,
+    buffer_pools: &mut BufferPools,
     graph: &Graph<Box<Dsp>, Connection>,
     dsp_id: Id,
     destination_buffer: &mut dyn AudioBuffer,
@@ -294,18 +566,35 @@ fn copy_output_from_dependencies(
     for edge_id in graph.edge_iterator(dsp_id, Direction::Incoming) {
         let edge = graph.get_edge(edge_id).expect("Edge not found");
 
-        let endpoint = Endpoint::new(edge.from_node_id, EndpointType::Output);
+        if edge.edge_data.is_to_parameter() {
+            continue;
+        }
 
-        mix_endpoint(
-            assigned_buffer_pool,
-            &endpoint,
-            destination_buffer,
-            edge.edge_data.source_output_channel,
-            edge.edge_data.destination_input_channel,
-            edge.edge_data.channel_count,
-            frame_count,
-            mix_behaviour,
-        );
+        if buffer_pools.feedback.has(&edge_id) {
+            mix_endpoint(
+                &mut buffer_pools.feedback,
+                &edge_id,
+                destination_buffer,
+                edge.edge_data.source_output_channel,
+                edge.edge_data.destination_input_channel,
+                edge.edge_data.channel_count,
+                frame_count,
+                mix_behaviour,
+            );
+        } else {
+            let endpoint = Endpoint::new(edge.from_node_id, EndpointType::Output);
+
+            mix_endpoint(
+                &mut buffer_pools.assigned,
+                &endpoint,
+                destination_buffer,
+                edge.edge_data.source_output_channel,
+                edge.edge_data.destination_input_channel,
+                edge.edge_data.channel_count,
+                frame_count,
+                mix_behaviour,
+            );
+        }
 
         mix_behaviour = MixBehaviour::Mix;
     }
@@ -335,7 +624,7 @@ fn prepare_n_input_node(
     let mut node_input_buffer = get_buffer_with_endpoint(&input_endpoint, buffer_pools);
 
     copy_output_from_dependencies(
-        &mut buffer_pools.assigned,
+        buffer_pools,
         graph,
         dsp_id,
         &mut node_input_buffer,
@@ -365,12 +654,15 @@ fn prepare_single_input_node(
 ) -> (OwnedAudioBuffer, Endpoint) {
     let edge_id = graph
         .edge_iterator(dsp_id, Direction::Incoming)
-        .next()
+        .find(|edge_id| is_audio_edge(graph, *edge_id))
         .unwrap();
 
     let edge = graph.get_edge(edge_id).unwrap();
 
-    if edge.edge_data.destination_input_channel == 0 && edge.edge_data.source_output_channel == 0 {
+    if edge.edge_data.destination_input_channel == 0
+        && edge.edge_data.source_output_channel == 0
+        && !buffer_pools.feedback.has(&edge_id)
+    {
         let input_endpoint = Endpoint::new(edge.from_node_id, EndpointType::Output);
 
         return (
@@ -388,7 +680,12 @@ fn prepare_input(
     dsp_id: Id,
     frame_count: usize,
 ) -> (OwnedAudioBuffer, Endpoint) {
-    match graph.connection_count(dsp_id, Direction::Incoming) {
+    let audio_edge_count = graph
+        .edge_iterator(dsp_id, Direction::Incoming)
+        .filter(|edge_id| is_audio_edge(graph, *edge_id))
+        .count();
+
+    match audio_edge_count {
         0 => prepare_zero_input_node(buffer_pools, dsp_id),
         1 => prepare_single_input_node(buffer_pools, graph, dsp_id, frame_count),
         _ => prepare_n_input_node(buffer_pools, graph, dsp_id, frame_count),
@@ -476,7 +773,7 @@ mod tests {
 
         assert_relative_ne!(output_buffer.get_sample(location), value);
 
-        graph.connect_to_output(Endpoint::new(dsp_id, EndpointType::Output));
+        graph.add_connection(Connection::new(dsp_id, Id::system_output(), channel_count));
 
         graph.process(&input_buffer, &mut output_buffer, &Timestamp::default());
 
@@ -508,7 +805,11 @@ mod tests {
 
         let frame_count = 128;
 
-        graph.connect_to_output(Endpoint::new(dsp_id_2, EndpointType::Output));
+        graph.add_connection(Connection::new(
+            dsp_id_2,
+            Id::system_output(),
+            channel_count,
+        ));
 
         graph.add_connection(Connection::new(dsp_id_1, dsp_id_2, channel_count));
 
@@ -534,7 +835,7 @@ mod tests {
 
         let frame_count = 128;
 
-        graph.connect_to_output(Endpoint::new(dsp_id, EndpointType::Output));
+        graph.add_connection(Connection::new(dsp_id, Id::system_output(), channel_count));
 
         let input_buffer = OwnedAudioBuffer::new(frame_count, channel_count * 2, sample_rate);
         let mut output_buffer = OwnedAudioBuffer::new(frame_count, channel_count * 2, sample_rate);
@@ -555,7 +856,7 @@ mod tests {
 
         graph.add_dsp(dsp);
 
-        graph.connect_to_output(Endpoint::new(dsp_id, EndpointType::Output));
+        graph.add_connection(Connection::new(dsp_id, Id::system_output(), channel_count));
 
         let input_buffer =
             OwnedAudioBuffer::new(maximum_number_of_frames * 2, channel_count, sample_rate);
@@ -611,7 +912,11 @@ mod tests {
                 .with_source_output_channel(source_output_channel),
         );
 
-        graph.connect_to_output(Endpoint::new(dsp_2_id, EndpointType::Output));
+        graph.add_connection(Connection::new(
+            dsp_2_id,
+            Id::system_output(),
+            graph_channel_count,
+        ));
 
         let input_buffer =
             OwnedAudioBuffer::new(maximum_frame_count, graph_channel_count, sample_rate);
@@ -626,4 +931,404 @@ mod tests {
         );
         assert_relative_eq!(output_buffer.get_sample(value_2_location), value_2);
     }
+
+    #[test]
+    fn sums_multiple_connections_to_output() {
+        let frame_count = 128;
+        let channel_count = 2;
+        let sample_rate = 44100;
+
+        let value_1 = 0.25;
+        let value_2 = 0.5;
+        let location = SampleLocation::frame(12);
+
+        let dsp_1 = make_dsp(value_1, location, channel_count, channel_count);
+        let dsp_2 = make_dsp(value_2, location, channel_count, channel_count);
+        let dsp_3 = make_dsp(value_2, location, 1, 1);
+
+        let dsp_1_id = dsp_1.get_id();
+        let dsp_2_id = dsp_2.get_id();
+        let dsp_3_id = dsp_3.get_id();
+
+        let mut graph = DspGraph::new(frame_count, channel_count, sample_rate);
+
+        graph.add_dsp(dsp_1);
+        graph.add_dsp(dsp_2);
+        graph.add_dsp(dsp_3);
+
+        graph.add_connection(Connection::new(
+            dsp_1_id,
+            Id::system_output(),
+            channel_count,
+        ));
+        graph.add_connection(Connection::new(
+            dsp_2_id,
+            Id::system_output(),
+            channel_count,
+        ));
+        graph.add_connection(
+            Connection::new(dsp_3_id, Id::system_output(), 1).with_destination_input_channel(1),
+        );
+
+        let input_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+        let mut output_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+
+        graph.process(&input_buffer, &mut output_buffer, &Timestamp::zero());
+
+        assert_relative_eq!(output_buffer.get_sample(location), value_1 + value_2);
+        assert_relative_eq!(output_buffer.get_sample(location.with_channel(1)), value_2);
+    }
+
+    #[test]
+    fn disconnects_from_output() {
+        let frame_count = 128;
+        let channel_count = 2;
+        let sample_rate = 44100;
+
+        let value = 0.5;
+        let location = SampleLocation::frame(12);
+
+        let dsp = make_dsp(value, location, channel_count, channel_count);
+        let dsp_id = dsp.get_id();
+
+        let mut graph = DspGraph::new(frame_count, channel_count, sample_rate);
+        graph.add_dsp(dsp);
+
+        let connection = Connection::new(dsp_id, Id::system_output(), channel_count);
+        graph.add_connection(connection.clone());
+
+        let input_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+        let mut output_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+
+        graph.process(&input_buffer, &mut output_buffer, &Timestamp::zero());
+        assert_relative_eq!(output_buffer.get_sample(location), value);
+
+        graph.remove_connection(connection);
+
+        graph.process(&input_buffer, &mut output_buffer, &Timestamp::zero());
+        assert_relative_eq!(output_buffer.get_sample(location), 0.0);
+    }
+
+    #[test]
+    fn routes_input_to_multiple_nodes() {
+        let frame_count = 128;
+        let channel_count = 2;
+        let sample_rate = 44100;
+
+        let value = 0.5;
+        let location = SampleLocation::frame(12);
+
+        let dsp_1 = make_dsp(0.0, SampleLocation::origin(), channel_count, channel_count);
+        let dsp_2 = make_dsp(0.0, SampleLocation::origin(), channel_count, channel_count);
+
+        let dsp_1_id = dsp_1.get_id();
+        let dsp_2_id = dsp_2.get_id();
+
+        let mut graph = DspGraph::new(frame_count, channel_count, sample_rate);
+
+        graph.add_dsp(dsp_1);
+        graph.add_dsp(dsp_2);
+
+        for dsp_id in [dsp_1_id, dsp_2_id] {
+            graph.add_connection(Connection::new(Id::system_input(), dsp_id, channel_count));
+            graph.add_connection(Connection::new(dsp_id, Id::system_output(), channel_count));
+        }
+
+        let mut input_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+        input_buffer.set_sample(location, value);
+
+        let mut output_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+
+        graph.process(&input_buffer, &mut output_buffer, &Timestamp::zero());
+        assert_relative_eq!(output_buffer.get_sample(location), 2.0 * value);
+    }
+
+    #[test]
+    fn removing_a_dsp_removes_its_connections() {
+        let frame_count = 128;
+        let channel_count = 2;
+        let sample_rate = 44100;
+
+        let dsp = make_dsp(0.5, SampleLocation::origin(), channel_count, channel_count);
+        let dsp_id = dsp.get_id();
+
+        let mut graph = DspGraph::new(frame_count, channel_count, sample_rate);
+        graph.add_dsp(dsp);
+
+        graph.add_connection(Connection::new(Id::system_input(), dsp_id, channel_count));
+        graph.add_connection(Connection::new(dsp_id, Id::system_output(), channel_count));
+
+        graph.remove_dsp(dsp_id);
+
+        let input_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+        let mut output_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+
+        graph.process(&input_buffer, &mut output_buffer, &Timestamp::zero());
+        assert!(output_buffer.channel_is_silent(0));
+    }
+
+    #[test]
+    fn feedback_is_delayed_by_one_block() {
+        let frame_count = 128;
+        let channel_count = 1;
+        let sample_rate = 44100;
+
+        let dsp = make_dsp(
+            0.0,
+            SampleLocation::frame(100),
+            channel_count,
+            channel_count,
+        );
+        let dsp_id = dsp.get_id();
+
+        let mut graph = DspGraph::new(frame_count, channel_count, sample_rate);
+        graph.add_dsp(dsp);
+
+        graph.add_connection(Connection::new(Id::system_input(), dsp_id, channel_count));
+        graph.add_connection(Connection::new(dsp_id, dsp_id, channel_count));
+        graph.add_connection(Connection::new(dsp_id, Id::system_output(), channel_count));
+
+        let impulse_location = SampleLocation::frame(5);
+
+        let mut input_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+        input_buffer.set_sample(impulse_location, 1.0);
+
+        let mut output_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+
+        graph.process(&input_buffer, &mut output_buffer, &Timestamp::zero());
+        assert_relative_eq!(output_buffer.get_sample(impulse_location), 1.0);
+
+        input_buffer.clear();
+
+        for _ in 0..3 {
+            graph.process(&input_buffer, &mut output_buffer, &Timestamp::zero());
+            assert_relative_eq!(output_buffer.get_sample(impulse_location), 1.0);
+        }
+
+        graph.remove_connection(Connection::new(dsp_id, dsp_id, channel_count));
+
+        graph.process(&input_buffer, &mut output_buffer, &Timestamp::zero());
+        assert!(output_buffer.channel_is_silent(0));
+    }
+
+    #[test]
+    fn processes_cycle_between_nodes() {
+        let frame_count = 128;
+        let channel_count = 1;
+        let sample_rate = 44100;
+
+        let value_1 = 0.25;
+        let value_2 = 0.5;
+
+        let location_1 = SampleLocation::frame(10);
+        let location_2 = SampleLocation::frame(20);
+
+        let dsp_1 = make_dsp(value_1, location_1, channel_count, channel_count);
+        let dsp_2 = make_dsp(value_2, location_2, channel_count, channel_count);
+
+        let dsp_1_id = dsp_1.get_id();
+        let dsp_2_id = dsp_2.get_id();
+
+        let mut graph = DspGraph::new(frame_count, channel_count, sample_rate);
+
+        graph.add_dsp(dsp_1);
+        graph.add_dsp(dsp_2);
+
+        graph.add_connection(Connection::new(dsp_1_id, dsp_2_id, channel_count));
+        graph.add_connection(Connection::new(dsp_2_id, dsp_1_id, channel_count));
+        graph.add_connection(Connection::new(
+            dsp_2_id,
+            Id::system_output(),
+            channel_count,
+        ));
+
+        let input_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+        let mut output_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+
+        for _ in 0..2 {
+            graph.process(&input_buffer, &mut output_buffer, &Timestamp::zero());
+        }
+
+        assert_relative_eq!(output_buffer.get_sample(location_1), value_1);
+        assert_relative_eq!(output_buffer.get_sample(location_2), value_2);
+        assert_eq!(graph.drain_rejected_connections().count(), 0);
+    }
+
+    fn render_branching_graph(worker_thread_count: usize) -> Vec<f32> {
+        // input -> A -> B -> D -> output
+        //          |         ^ |
+        //          +--> C ---+ |
+        //                    ^ |
+        //                    +-+
+
+        let frame_count = 64;
+        let channel_count = 2;
+        let sample_rate = 44100;
+
+        let dsps: Vec<Box<Dsp>> = (0..4)
+            .map(|index| {
+                make_dsp(
+                    0.1 * (index + 1) as f32,
+                    SampleLocation::new(index % channel_count, 10 * index),
+                    channel_count,
+                    channel_count,
+                )
+            })
+            .collect();
+
+        let ids: Vec<Id> = dsps.iter().map(|dsp| dsp.get_id()).collect();
+
+        let mut graph = DspGraph::new(frame_count, channel_count, sample_rate)
+            .with_worker_thread_count(worker_thread_count);
+
+        for dsp in dsps {
+            graph.add_dsp(dsp);
+        }
+
+        for (source, destination) in [
+            (Id::system_input(), ids[0]),
+            (ids[0], ids[1]),
+            (ids[0], ids[2]),
+            (ids[1], ids[3]),
+            (ids[2], ids[3]),
+            (ids[3], ids[3]),
+            (ids[3], Id::system_output()),
+        ] {
+            graph.add_connection(Connection::new(source, destination, channel_count));
+        }
+
+        let mut input_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+        input_buffer.set_sample(SampleLocation::new(1, 5), 1.0);
+
+        let mut output_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+
+        let mut rendered = Vec::new();
+
+        for _ in 0..4 {
+            graph.process(&input_buffer, &mut output_buffer, &Timestamp::zero());
+
+            for channel in 0..channel_count {
+                rendered.extend_from_slice(
+                    output_buffer.get_channel_data(SampleLocation::channel(channel)),
+                );
+            }
+        }
+
+        rendered
+    }
+
+    #[test]
+    fn parallel_processing_matches_single_threaded() {
+        let expected = render_branching_graph(0);
+
+        assert!(expected.iter().any(|sample| *sample != 0.0));
+
+        for worker_thread_count in [1, 3] {
+            let rendered = render_branching_graph(worker_thread_count);
+
+            for (sample, expected) in rendered.iter().zip(expected.iter()) {
+                assert_relative_eq!(*sample, *expected, epsilon = 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_processing_sums_independent_branches() {
+        let frame_count = 128;
+        let channel_count = 1;
+        let sample_rate = 44100;
+        let branch_count = 32;
+
+        let value = 0.125;
+        let location = SampleLocation::frame(42);
+
+        let mut graph =
+            DspGraph::new(frame_count, channel_count, sample_rate).with_worker_thread_count(2);
+
+        for _ in 0..branch_count {
+            let dsp = make_dsp(value, location, channel_count, channel_count);
+            let dsp_id = dsp.get_id();
+
+            graph.add_dsp(dsp);
+            graph.add_connection(Connection::new(dsp_id, Id::system_output(), channel_count));
+        }
+
+        let input_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+        let mut output_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+
+        for _ in 0..8 {
+            graph.process(&input_buffer, &mut output_buffer, &Timestamp::zero());
+
+            assert_relative_eq!(
+                output_buffer.get_sample(location),
+                value * branch_count as f32,
+                epsilon = 1e-6
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_feedback_connections_over_the_limit() {
+        let frame_count = 128;
+        let channel_count = 1;
+        let sample_rate = 44100;
+
+        let mut graph = DspGraph::new(frame_count, channel_count, sample_rate);
+
+        for _ in 0..MAXIMUM_FEEDBACK_EDGE_COUNT + 1 {
+            let dsp = make_dsp(0.0, SampleLocation::origin(), channel_count, channel_count);
+            let dsp_id = dsp.get_id();
+
+            graph.add_dsp(dsp);
+            graph.add_connection(Connection::new(dsp_id, dsp_id, channel_count));
+        }
+
+        let input_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+        let mut output_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+
+        graph.process(&input_buffer, &mut output_buffer, &Timestamp::zero());
+
+        let rejected_connections: Vec<Connection> = graph.drain_rejected_connections().collect();
+        assert_eq!(rejected_connections.len(), 1);
+        assert!(
+            rejected_connections[0].source.dsp_id == rejected_connections[0].destination.dsp_id
+        );
+    }
+
+    #[test]
+    fn falls_back_to_single_threaded_when_the_pool_is_full() {
+        let frame_count = 128;
+        let channel_count = 1;
+        let sample_rate = 44100;
+
+        let value = 0.5;
+        let location = SampleLocation::frame(7);
+
+        let mut graph =
+            DspGraph::new(frame_count, channel_count, sample_rate).with_worker_thread_count(2);
+
+        let mut previous_id = Id::system_input();
+
+        for _ in 0..MAXIMUM_GRAPH_NODE_COUNT + 1 {
+            let dsp = make_dsp(value, location, channel_count, channel_count);
+            let dsp_id = dsp.get_id();
+
+            graph.add_dsp(dsp);
+            graph.add_connection(Connection::new(previous_id, dsp_id, channel_count));
+            previous_id = dsp_id;
+        }
+
+        graph.add_connection(Connection::new(
+            previous_id,
+            Id::system_output(),
+            channel_count,
+        ));
+
+        let input_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+        let mut output_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+
+        graph.process(&input_buffer, &mut output_buffer, &Timestamp::zero());
+
+        assert_relative_eq!(output_buffer.get_sample(location), value);
+    }
 }
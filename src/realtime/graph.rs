@@ -67,6 +67,7 @@ impl<NodeData, EdgeData> Graph<NodeData, EdgeData> {
         edge_id
     }
 
+    #[cfg(test)]
     fn find_edge_between_nodes(&self, from_node_id: Id, to_node_id: Id) -> Option<Id> {
         self.edges
             .iter()
@@ -74,22 +75,34 @@ impl<NodeData, EdgeData> Graph<NodeData, EdgeData> {
             .map(|(id, _)| *id)
     }
 
+    #[cfg(test)]
     pub fn remove_edge(&mut self, from_node_id: Id, to_node_id: Id) -> Option<EdgeData> {
-        let id = self.find_edge_between_nodes(from_node_id, to_node_id);
+        let id = self.find_edge_between_nodes(from_node_id, to_node_id)?;
+        self.remove_edge_with_id(id)
+    }
+
+    pub fn remove_edge_with_id(&mut self, id: Id) -> Option<EdgeData> {
+        let (_, edge) = self.edges.remove_entry(&id)?;
 
-        if let Some(id) = id {
-            if let Some((_, edge)) = self.edges.remove_entry(&id) {
-                replace_connections(self, Direction::Outgoing, id, edge.next_out);
-                replace_connections(self, Direction::Incoming, id, edge.next_in);
+        replace_connections(self, Direction::Outgoing, id, edge.next_out);
+        replace_connections(self, Direction::Incoming, id, edge.next_in);
+
+        Some(edge.edge_data)
+    }
 
-                return Some(edge.edge_data);
+    fn remove_node_edges(&mut self, id: Id) {
+        for direction in [Direction::Outgoing, Direction::Incoming] {
+            while let Some(edge_id) =
+                EdgeIterator::new(id, None, direction, &self.nodes, &self.edges).next()
+            {
+                self.remove_edge_with_id(edge_id);
             }
         }
-
-        None
     }
 
     pub fn remove_node(&mut self, id: Id) -> Option<NodeData> {
+        self.remove_node_edges(id);
+
         if let Some((_, node)) = self.nodes.remove_entry(&id) {
             assert!(node.incoming.is_none());
             assert!(node.outgoing.is_none());
@@ -108,12 +121,11 @@ impl<NodeData, EdgeData> Graph<NodeData, EdgeData> {
         self.nodes.insert(id, Node::new(node_data));
     }
 
-    pub fn is_connected_to(&self, from_node_id: Id, to_node_id: Id) -> bool {
-        NodeIterator::new(from_node_id, Direction::Outgoing, &self.nodes, &self.edges)
-            .any(|id| id == to_node_id)
-    }
-
-    pub fn node_iter(&self, node_id: Id, direction: Direction) -> NodeIterator<NodeData, EdgeData> {
+    pub fn node_iter(
+        &self,
+        node_id: Id,
+        direction: Direction,
+    ) -> NodeIterator<'_, NodeData, EdgeData> {
         NodeIterator::new(node_id, direction, &self.nodes, &self.edges)
     }
 
@@ -121,19 +133,19 @@ impl<NodeData, EdgeData> Graph<NodeData, EdgeData> {
         &self,
         node_id: Id,
         direction: Direction,
-    ) -> EdgeIterator<NodeData, EdgeData> {
+    ) -> EdgeIterator<'_, NodeData, EdgeData> {
         EdgeIterator::new(node_id, None, direction, &self.nodes, &self.edges)
     }
 
-    pub fn get_edge(&self, edge_id: Id) -> Option<&Edge<EdgeData>> {
-        self.edges.get(&edge_id)
+    pub fn next_edge(&self, node_id: Id, edge_id: Option<Id>, direction: Direction) -> Option<Id> {
+        EdgeIterator::new(node_id, edge_id, direction, &self.nodes, &self.edges).next()
     }
 
-    pub fn connection_count(&self, node_id: Id, direction: Direction) -> usize {
-        self.node_iter(node_id, direction).count()
+    pub fn get_edge(&self, edge_id: Id) -> Option<&Edge<EdgeData>> {
+        self.edges.get(&edge_id)
     }
 
-    pub fn all_node_ids(&self) -> Keys<Id, Node<NodeData>> {
+    pub fn all_node_ids(&self) -> Keys<'_, Id, Node<NodeData>> {
         self.nodes.keys()
     }
 
@@ -148,7 +160,7 @@ fn replace_edge_connections<N, E>(
     find_edge_id: Id,
     replace_edge_id: Option<Id>,
 ) {
-    for (_, edge) in graph.edges.iter_mut() {
+    for edge in graph.edges.values_mut() {
         match direction {
             Direction::Outgoing => {
                 if let Some(next_out) = edge.next_out {
@@ -174,7 +186,7 @@ fn replace_node_connections<N, E>(
     find_edge_id: Id,
     replace_edge_id: Option<Id>,
 ) {
-    for (_, node) in graph.nodes.iter_mut() {
+    for node in graph.nodes.values_mut() {
         match direction {
             Direction::Outgoing => {
                 if let Some(outgoing) = node.outgoing {
@@ -3,7 +3,10 @@ mod edge;
 mod garbage_collector;
 mod graph;
 mod node;
+mod passthrough_processor;
 mod processor;
+mod strongly_connected_components;
 mod topological_sort;
+mod worker_pool;
 
 pub(crate) type Processor = processor::Processor;
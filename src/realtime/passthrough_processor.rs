@@ -0,0 +1,15 @@
+use crate::{graph::DspProcessor, prelude::*, ProcessContext};
+
+pub struct PassthroughProcessor;
+
+impl DspProcessor for PassthroughProcessor {
+    fn process_audio(&mut self, context: &mut ProcessContext) {
+        context.output_buffer.copy_from(
+            context.input_buffer,
+            SampleLocation::origin(),
+            SampleLocation::origin(),
+            context.output_buffer.channel_count(),
+            context.output_buffer.frame_count(),
+        );
+    }
+}
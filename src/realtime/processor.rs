@@ -1,13 +1,15 @@
 use super::dsp_graph::DspGraph;
-use crate::{commands::Command, prelude::*};
+use crate::{commands::Command, prelude::*, RejectedConnection};
 use std::sync::{atomic::AtomicI64, atomic::Ordering, Arc};
 
 type CommandReceiver = crossbeam::channel::Receiver<Command>;
+type RejectedConnectionSender = crossbeam::channel::Sender<RejectedConnection>;
 
 pub struct Processor {
     started: bool,
     sample_rate: usize,
     command_rx: CommandReceiver,
+    rejected_connection_tx: RejectedConnectionSender,
 
     frame_position: usize,
     current_time: Arc<AtomicI64>,
@@ -21,16 +23,20 @@ impl Processor {
         sample_rate: usize,
         maximum_channel_count: usize,
         maximum_frame_count: usize,
+        worker_thread_count: usize,
         command_rx: CommandReceiver,
+        rejected_connection_tx: RejectedConnectionSender,
         current_time: Arc<AtomicI64>,
     ) -> Self {
         Self {
             started: false,
             sample_rate,
             command_rx,
+            rejected_connection_tx,
             frame_position: 0,
             current_time,
-            graph: DspGraph::new(maximum_frame_count, maximum_channel_count, sample_rate),
+            graph: DspGraph::new(maximum_frame_count, maximum_channel_count, sample_rate)
+                .with_worker_thread_count(worker_thread_count),
             maximum_frame_count,
         }
     }
@@ -80,6 +86,7 @@ impl AudioProcess for Processor {
         let frame_count = output_buffer.frame_count();
         self.process_graph(input_buffer, output_buffer);
         self.update_position(frame_count);
+        self.report_rejected_connections();
     }
 }
 
@@ -100,18 +107,26 @@ impl Processor {
                     self.graph.cancel_parameter_changes(change_request)
                 }
 
+                Command::ScheduleMidiEvent(midi_request) => {
+                    self.graph.schedule_midi_event(midi_request)
+                }
+                Command::CancelMidiEvents(id) => self.graph.cancel_midi_events(id),
+
                 Command::AddConnection(connection) => self.graph.add_connection(connection),
                 Command::RemoveConnection(connection) => self.graph.remove_connection(connection),
-                Command::ConnectToOutput(output_endpoint) => {
-                    self.graph.connect_to_output(output_endpoint)
-                }
-                Command::ConnectToInput(input_endpoint) => {
-                    self.graph.connect_to_input(input_endpoint)
-                }
             }
         }
     }
 
+    fn report_rejected_connections(&mut self) {
+        for connection in self.graph.drain_rejected_connections() {
+            let _ = self.rejected_connection_tx.try_send(RejectedConnection {
+                source: connection.source.dsp_id,
+                destination: connection.destination.dsp_id,
+            });
+        }
+    }
+
     fn update_position(&mut self, frame_count: usize) {
         self.frame_position += frame_count;
 
@@ -0,0 +1,192 @@
+use super::graph::{Direction, Graph};
+use crate::commands::Id;
+use std::collections::{HashMap, HashSet};
+
+pub struct StronglyConnectedComponents {
+    index: HashMap<Id, usize>,
+    low_link: HashMap<Id, usize>,
+    on_stack: HashSet<Id>,
+    stack: Vec<Id>,
+    call_stack: Vec<(Id, Option<Id>)>,
+    next_index: usize,
+    nodes: Vec<Id>,
+    component_ends: Vec<usize>,
+}
+
+impl StronglyConnectedComponents {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            index: HashMap::with_capacity(capacity),
+            low_link: HashMap::with_capacity(capacity),
+            on_stack: HashSet::with_capacity(capacity),
+            stack: Vec::with_capacity(capacity),
+            call_stack: Vec::with_capacity(capacity),
+            next_index: 0,
+            nodes: Vec::with_capacity(capacity),
+            component_ends: Vec::with_capacity(capacity),
+        }
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.component_ends.len()
+    }
+
+    /// Get a component by index
+    ///
+    /// Components are stored in reverse topological order, so no component
+    /// has an edge to a component with a higher index
+    pub fn component(&self, index: usize) -> &[Id] {
+        let start = match index {
+            0 => 0,
+            _ => self.component_ends[index - 1],
+        };
+
+        &self.nodes[start..self.component_ends[index]]
+    }
+
+    /// Find the strongly connected components using Tarjan's algorithm
+    pub fn find<NodeData, EdgeData>(&mut self, graph: &Graph<NodeData, EdgeData>) {
+        self.index.clear();
+        self.low_link.clear();
+        self.on_stack.clear();
+        self.stack.clear();
+        self.call_stack.clear();
+        self.next_index = 0;
+        self.nodes.clear();
+        self.component_ends.clear();
+
+        for node_id in graph.all_node_ids() {
+            if !self.index.contains_key(node_id) {
+                self.connect(graph, *node_id);
+            }
+        }
+    }
+
+    fn visit(&mut self, node_id: Id) {
+        self.index.insert(node_id, self.next_index);
+        self.low_link.insert(node_id, self.next_index);
+        self.next_index += 1;
+
+        self.stack.push(node_id);
+        self.on_stack.insert(node_id);
+        self.call_stack.push((node_id, None));
+    }
+
+    fn update_low_link(&mut self, node_id: Id, value: usize) {
+        if let Some(low_link) = self.low_link.get_mut(&node_id) {
+            *low_link = (*low_link).min(value);
+        }
+    }
+
+    fn connect<NodeData, EdgeData>(&mut self, graph: &Graph<NodeData, EdgeData>, start: Id) {
+        self.visit(start);
+
+        while let Some((node_id, last_edge_id)) = self.call_stack.last().copied() {
+            match graph.next_edge(node_id, last_edge_id, Direction::Outgoing) {
+                Some(edge_id) => {
+                    if let Some(frame) = self.call_stack.last_mut() {
+                        frame.1 = Some(edge_id);
+                    }
+
+                    let to_node_id = graph.get_edge(edge_id).expect("Edge not found").to_node_id;
+
+                    if !self.index.contains_key(&to_node_id) {
+                        self.visit(to_node_id);
+                    } else if self.on_stack.contains(&to_node_id) {
+                        self.update_low_link(node_id, self.index[&to_node_id]);
+                    }
+                }
+                None => {
+                    self.call_stack.pop();
+
+                    let low_link = self.low_link[&node_id];
+
+                    if let Some((parent_id, _)) = self.call_stack.last().copied() {
+                        self.update_low_link(parent_id, low_link);
+                    }
+
+                    if low_link == self.index[&node_id] {
+                        self.pop_component(node_id);
+                    }
+                }
+            }
+        }
+    }
+
+    fn pop_component(&mut self, root_id: Id) {
+        while let Some(node_id) = self.stack.pop() {
+            self.on_stack.remove(&node_id);
+            self.nodes.push(node_id);
+
+            if node_id == root_id {
+                break;
+            }
+        }
+
+        self.component_ends.push(self.nodes.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add_node(graph: &mut Graph<(), ()>) -> Id {
+        let id = Id::generate();
+        graph.add_node_with_id(id, ());
+        id
+    }
+
+    fn components(graph: &Graph<(), ()>) -> Vec<Vec<Id>> {
+        let mut scc = StronglyConnectedComponents::with_capacity(8);
+        scc.find(graph);
+
+        (0..scc.component_count())
+            .map(|index| scc.component(index).to_vec())
+            .collect()
+    }
+
+    #[test]
+    fn acyclic_graph_has_one_component_per_node() {
+        let mut graph = Graph::with_capacity(3, 3);
+
+        let a_id = add_node(&mut graph);
+        let b_id = add_node(&mut graph);
+        let c_id = add_node(&mut graph);
+
+        graph.add_edge(a_id, b_id, ());
+        graph.add_edge(b_id, c_id, ());
+
+        let components = components(&graph);
+
+        assert_eq!(components, vec![vec![c_id], vec![b_id], vec![a_id]]);
+    }
+
+    #[test]
+    fn finds_cycle() {
+        // A -> B -> C -> D
+        //      ^    |
+        //      +----+
+
+        let mut graph = Graph::with_capacity(4, 4);
+
+        let a_id = add_node(&mut graph);
+        let b_id = add_node(&mut graph);
+        let c_id = add_node(&mut graph);
+        let d_id = add_node(&mut graph);
+
+        graph.add_edge(a_id, b_id, ());
+        graph.add_edge(b_id, c_id, ());
+        graph.add_edge(c_id, b_id, ());
+        graph.add_edge(c_id, d_id, ());
+
+        let components = components(&graph);
+
+        assert_eq!(components.len(), 3);
+        assert_eq!(components[0], vec![d_id]);
+        assert_eq!(components[1].len(), 2);
+        assert!(components[1].contains(&b_id));
+        assert!(components[1].contains(&c_id));
+        assert_eq!(components[2], vec![a_id]);
+    }
+}
@@ -1,19 +1,23 @@
-use super::graph::Graph;
+use super::{graph::Graph, strongly_connected_components::StronglyConnectedComponents};
 use crate::{commands::Id, realtime::graph::Direction};
 use std::collections::HashMap;
 
 pub struct TopologicalSort {
+    components: StronglyConnectedComponents,
     dependency_count: HashMap<Id, usize>,
+    position: HashMap<Id, usize>,
     order: Vec<Id>,
-    ready_to_process: Vec<Id>,
+    feedback_edges: Vec<Id>,
 }
 
 impl TopologicalSort {
     pub fn with_capacity(capacity: usize) -> Self {
         Self {
+            components: StronglyConnectedComponents::with_capacity(capacity),
             dependency_count: HashMap::with_capacity(capacity),
+            position: HashMap::with_capacity(capacity),
             order: Vec::with_capacity(capacity),
-            ready_to_process: Vec::with_capacity(capacity),
+            feedback_edges: Vec::with_capacity(capacity),
         }
     }
 
@@ -21,39 +25,90 @@ impl TopologicalSort {
         &self.order
     }
 
+    /// The edges that point backwards in the sorted order
+    ///
+    /// These edges close a cycle, so the destination must read the output of
+    /// the source from the previous block
+    pub fn get_feedback_edges(&self) -> &[Id] {
+        &self.feedback_edges
+    }
+
     pub fn sort<NodeData, EdgeData>(&mut self, graph: &Graph<NodeData, EdgeData>) -> &[Id] {
-        self.dependency_count.clear();
         self.order.clear();
-        self.ready_to_process.clear();
+        self.feedback_edges.clear();
+
+        self.components.find(graph);
+
+        for index in (0..self.components.component_count()).rev() {
+            self.sort_component(graph, index);
+        }
+
+        assert_eq!(self.order.len(), graph.node_count());
+
+        self.find_feedback_edges(graph);
+
+        &self.order
+    }
+
+    fn sort_component<NodeData, EdgeData>(
+        &mut self,
+        graph: &Graph<NodeData, EdgeData>,
+        component_index: usize,
+    ) {
+        let component = self.components.component(component_index);
+
+        if let [node_id] = component {
+            self.order.push(*node_id);
+            return;
+        }
+
+        self.dependency_count.clear();
+
+        for node_id in component {
+            self.dependency_count.insert(*node_id, 0);
+        }
 
-        for node_id in graph.all_node_ids() {
-            let incoming_node_count = graph.connection_count(*node_id, Direction::Incoming);
-            self.dependency_count.insert(*node_id, incoming_node_count);
+        for node_id in component {
+            for next_node_id in graph.node_iter(*node_id, Direction::Outgoing) {
+                if let Some(count) = self.dependency_count.get_mut(&next_node_id) {
+                    *count += 1;
+                }
+            }
         }
 
-        while let Some(next_node_id) = self.node_without_dependencies() {
+        while let Some(next_node_id) = component
+            .iter()
+            .filter_map(|id| self.dependency_count.get(id).map(|count| (*id, *count)))
+            .min_by_key(|(_, count)| *count)
+            .map(|(id, _)| id)
+        {
             self.order.push(next_node_id);
             self.dependency_count.remove(&next_node_id);
 
-            for node_id in graph.all_node_ids() {
-                if graph.is_connected_to(next_node_id, *node_id) {
-                    let previous_value = self.dependency_count.get_mut(node_id).unwrap();
-                    assert!(*previous_value > 0);
-                    *previous_value -= 1;
+            for node_id in graph.node_iter(next_node_id, Direction::Outgoing) {
+                if let Some(count) = self.dependency_count.get_mut(&node_id) {
+                    *count = count.saturating_sub(1);
                 }
             }
         }
+    }
 
-        assert_eq!(self.order.len(), graph.node_count()); // cycle detected
+    fn find_feedback_edges<NodeData, EdgeData>(&mut self, graph: &Graph<NodeData, EdgeData>) {
+        self.position.clear();
 
-        &self.order
-    }
+        for (position, node_id) in self.order.iter().enumerate() {
+            self.position.insert(*node_id, position);
+        }
 
-    fn node_without_dependencies(&self) -> Option<Id> {
-        self.dependency_count
-            .iter()
-            .find(|(_, count)| **count == 0)
-            .map(|(id, _)| *id)
+        for (position, node_id) in self.order.iter().enumerate() {
+            for edge_id in graph.edge_iterator(*node_id, Direction::Outgoing) {
+                let edge = graph.get_edge(edge_id).expect("Edge not found");
+
+                if self.position[&edge.to_node_id] <= position {
+                    self.feedback_edges.push(edge_id);
+                }
+            }
+        }
     }
 }
 
@@ -103,4 +158,71 @@ mod tests {
         assert!(sorted[3] == c_id || sorted[3] == d_id);
         assert_eq!(sorted[4], e_id);
     }
+
+    #[test]
+    fn sorts_graph_with_cycle() {
+        // A -> B -> C -> D
+        //      ^    |
+        //      +----+
+
+        let mut graph = Graph::with_capacity(4, 4);
+
+        let a_id = add_node(&mut graph, ());
+        let b_id = add_node(&mut graph, ());
+        let c_id = add_node(&mut graph, ());
+        let d_id = add_node(&mut graph, ());
+
+        graph.add_edge(a_id, b_id, ());
+        let b_to_c_id = graph.add_edge(b_id, c_id, ());
+        let c_to_b_id = graph.add_edge(c_id, b_id, ());
+        graph.add_edge(c_id, d_id, ());
+
+        let mut topo_sort = TopologicalSort::with_capacity(4);
+        let sorted = topo_sort.sort(&graph).to_vec();
+
+        assert_eq!(sorted.len(), 4);
+        assert_eq!(sorted[0], a_id);
+        assert_eq!(sorted[3], d_id);
+
+        let feedback_edges = topo_sort.get_feedback_edges();
+        assert_eq!(feedback_edges.len(), 1);
+
+        if sorted[1] == b_id {
+            assert_eq!(feedback_edges[0], c_to_b_id);
+        } else {
+            assert_eq!(feedback_edges[0], b_to_c_id);
+        }
+    }
+
+    #[test]
+    fn self_connection_is_feedback() {
+        let mut graph = Graph::with_capacity(1, 1);
+
+        let a_id = add_node(&mut graph, ());
+        let a_to_a_id = graph.add_edge(a_id, a_id, ());
+
+        let mut topo_sort = TopologicalSort::with_capacity(1);
+        let sorted = topo_sort.sort(&graph);
+
+        assert_eq!(sorted, &[a_id]);
+        assert_eq!(topo_sort.get_feedback_edges(), &[a_to_a_id]);
+    }
+
+    #[test]
+    fn acyclic_graph_has_no_feedback() {
+        let mut graph = Graph::with_capacity(3, 3);
+
+        let a_id = add_node(&mut graph, ());
+        let b_id = add_node(&mut graph, ());
+        let c_id = add_node(&mut graph, ());
+
+        graph.add_edge(a_id, b_id, ());
+        graph.add_edge(a_id, c_id, ());
+        graph.add_edge(b_id, c_id, ());
+
+        let mut topo_sort = TopologicalSort::with_capacity(3);
+        topo_sort.sort(&graph);
+
+        assert!(topo_sort.get_feedback_edges().is_empty());
+    }
 }
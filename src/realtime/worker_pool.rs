@@ -0,0 +1,473 @@
+use super::{edge::Edge, graph::*, passthrough_processor::PassthroughProcessor};
+use crate::{commands::Id, graph::*, parameter::ParameterId, prelude::*};
+use crossbeam::queue::ArrayQueue;
+use std::{
+    collections::HashMap,
+    ops::Range,
+    sync::{
+        atomic::{AtomicBool, AtomicI64, AtomicUsize, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    thread::{self, Thread},
+    time::Duration,
+};
+
+static WORKER_PARK_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// An incoming, non-feedback connection to a scheduled node
+struct ScheduledConnection {
+    source: usize,
+    source_output_channel: usize,
+    destination_input_channel: usize,
+    channel_count: usize,
+    destination_parameter: Option<ParameterId>,
+}
+
+struct ScheduledNode {
+    connections: Range<usize>,
+    dependents: Range<usize>,
+    dependency_count: usize,
+}
+
+/// The flattened graph, indexed by slot
+///
+/// This is only written while the workers are idle, so reading it during a
+/// block never contends.
+struct Schedule {
+    nodes: Vec<ScheduledNode>,
+    connections: Vec<ScheduledConnection>,
+    dependents: Vec<usize>,
+}
+
+/// Storage for a single node while a block is being processed
+///
+/// The locks are only ever acquired with `try_lock`, the schedule guarantees
+/// that there is never more than one writer to a slot at a time.
+struct Slot {
+    dsp: Mutex<Box<Dsp>>,
+    input: Mutex<OwnedAudioBuffer>,
+    output: RwLock<OwnedAudioBuffer>,
+    pending_dependencies: AtomicUsize,
+}
+
+struct Shared {
+    slots: Vec<Slot>,
+    schedule: RwLock<Schedule>,
+    ready: ArrayQueue<usize>,
+    remaining: AtomicUsize,
+    frame_count: AtomicUsize,
+    start_time: AtomicI64,
+    is_running: AtomicBool,
+}
+
+/// Processes independent branches of the graph on a pool of worker threads
+///
+/// Before each block the nodes are moved into preallocated slots, the nodes
+/// without any dependencies are pushed to a lock-free queue, and the audio
+/// thread and the workers pop nodes from it until the block is complete. When
+/// a node finishes, it decrements the dependency count of the nodes it feeds,
+/// queueing any that become ready.
+pub struct WorkerPool {
+    shared: Arc<Shared>,
+    workers: Vec<Thread>,
+    slot_indices: HashMap<Id, usize>,
+    slot_ids: Vec<Id>,
+    is_scheduled: bool,
+}
+
+fn create_placeholder_dsp() -> Box<Dsp> {
+    Box::new(Dsp::new(
+        Id::generate(),
+        0,
+        0,
+        Box::new(PassthroughProcessor),
+        DspParameters::empty(),
+    ))
+}
+
+impl WorkerPool {
+    pub fn new(
+        worker_count: usize,
+        slot_count: usize,
+        maximum_edge_count: usize,
+        maximum_frame_count: usize,
+        maximum_channel_count: usize,
+        sample_rate: usize,
+    ) -> Self {
+        let create_buffer =
+            || OwnedAudioBuffer::new(maximum_frame_count, maximum_channel_count, sample_rate);
+
+        let shared = Arc::new(Shared {
+            slots: (0..slot_count)
+                .map(|_| Slot {
+                    dsp: Mutex::new(create_placeholder_dsp()),
+                    input: Mutex::new(create_buffer()),
+                    output: RwLock::new(create_buffer()),
+                    pending_dependencies: AtomicUsize::new(0),
+                })
+                .collect(),
+            schedule: RwLock::new(Schedule {
+                nodes: Vec::with_capacity(slot_count),
+                connections: Vec::with_capacity(maximum_edge_count),
+                dependents: Vec::with_capacity(maximum_edge_count),
+            }),
+            ready: ArrayQueue::new(slot_count),
+            remaining: AtomicUsize::new(0),
+            frame_count: AtomicUsize::new(0),
+            start_time: AtomicI64::new(0),
+            is_running: AtomicBool::new(true),
+        });
+
+        let workers = (0..worker_count)
+            .map(|_| {
+                let shared = Arc::clone(&shared);
+                thread::spawn(move || run_worker(&shared)).thread().clone()
+            })
+            .collect();
+
+        Self {
+            shared,
+            workers,
+            slot_indices: HashMap::with_capacity(slot_count),
+            slot_ids: Vec::with_capacity(slot_count),
+            is_scheduled: false,
+        }
+    }
+
+    /// Flatten the sorted graph into the schedule used by the workers
+    ///
+    /// Feedback edges are excluded, as they don't create a dependency within
+    /// a block.
+    ///
+    /// If the graph has more nodes than there are slots, nothing is scheduled
+    /// and [WorkerPool::is_scheduled] returns false, so that the graph can be
+    /// processed on the calling thread instead.
+    pub fn schedule(
+        &mut self,
+        graph: &Graph<Box<Dsp>, Connection>,
+        sorted_graph: &[Id],
+        feedback_edges: &[Id],
+    ) {
+        self.is_scheduled = sorted_graph.len() <= self.shared.slots.len();
+
+        let mut schedule = self
+            .shared
+            .schedule
+            .try_write()
+            .expect("Schedule is in use");
+
+        schedule.nodes.clear();
+        schedule.connections.clear();
+        schedule.dependents.clear();
+
+        self.slot_indices.clear();
+        self.slot_ids.clear();
+
+        if !self.is_scheduled {
+            return;
+        }
+
+        for (slot_index, dsp_id) in sorted_graph.iter().enumerate() {
+            self.slot_indices.insert(*dsp_id, slot_index);
+            self.slot_ids.push(*dsp_id);
+        }
+
+        for dsp_id in sorted_graph {
+            let connections_start = schedule.connections.len();
+
+            for edge_id in graph.edge_iterator(*dsp_id, Direction::Incoming) {
+                if feedback_edges.contains(&edge_id) {
+                    continue;
+                }
+
+                let edge = graph.get_edge(edge_id).expect("Edge not found");
+
+                schedule.connections.push(ScheduledConnection {
+                    source: self.slot_indices[&edge.from_node_id],
+                    source_output_channel: edge.edge_data.source_output_channel,
+                    destination_input_channel: edge.edge_data.destination_input_channel,
+                    channel_count: edge.edge_data.channel_count,
+                    destination_parameter: edge.edge_data.destination_parameter,
+                });
+            }
+
+            let dependents_start = schedule.dependents.len();
+
+            for edge_id in graph.edge_iterator(*dsp_id, Direction::Outgoing) {
+                if feedback_edges.contains(&edge_id) {
+                    continue;
+                }
+
+                let edge = graph.get_edge(edge_id).expect("Edge not found");
+                schedule
+                    .dependents
+                    .push(self.slot_indices[&edge.to_node_id]);
+            }
+
+            let connections = connections_start..schedule.connections.len();
+            let dependents = dependents_start..schedule.dependents.len();
+
+            schedule.nodes.push(ScheduledNode {
+                dependency_count: connections.len(),
+                connections,
+                dependents,
+            });
+        }
+    }
+
+    /// Whether the current graph fits in the slots, and can be processed by
+    /// the pool
+    pub fn is_scheduled(&self) -> bool {
+        self.is_scheduled
+    }
+
+    /// Move the nodes into their slots and process their parameters
+    pub fn prepare(
+        &mut self,
+        graph: &mut Graph<Box<Dsp>, Connection>,
+        frame_count: usize,
+        start_time: &Timestamp,
+        sample_rate: usize,
+    ) {
+        let schedule = self.shared.schedule.try_read().expect("Schedule is in use");
+
+        for (slot_index, dsp_id) in self.slot_ids.iter().enumerate() {
+            let slot = &self.shared.slots[slot_index];
+
+            let mut slot_dsp = slot.dsp.try_lock().expect("Slot is in use");
+
+            if let Some(dsp) = graph.get_node_mut(*dsp_id) {
+                std::mem::swap(dsp, &mut *slot_dsp);
+            }
+
+            slot_dsp.process_parameters(start_time, frame_count, sample_rate);
+
+            slot.input.try_lock().expect("Slot is in use").clear();
+
+            slot.pending_dependencies.store(
+                schedule.nodes[slot_index].dependency_count,
+                Ordering::Release,
+            );
+        }
+
+        self.shared
+            .frame_count
+            .store(frame_count, Ordering::Release);
+        self.shared
+            .start_time
+            .store(start_time.as_raw_i64(), Ordering::Release);
+    }
+
+    /// Copy the system input into the input of a node
+    pub fn write_input(
+        &self,
+        dsp_id: Id,
+        input_buffer: &dyn AudioBuffer,
+        channel_count: usize,
+        frame_count: usize,
+    ) {
+        if let Some(slot) = self.get_slot(dsp_id) {
+            slot.input.try_lock().expect("Slot is in use").copy_from(
+                input_buffer,
+                SampleLocation::origin(),
+                SampleLocation::origin(),
+                channel_count,
+                frame_count,
+            );
+        }
+    }
+
+    /// Mix the buffer stored for a feedback edge into its destination
+    pub fn write_feedback(
+        &self,
+        edge: &Edge<Connection>,
+        feedback_buffer: &dyn AudioBuffer,
+        frame_count: usize,
+    ) {
+        let slot = match self.get_slot(edge.to_node_id) {
+            Some(slot) => slot,
+            None => return,
+        };
+
+        let connection = &edge.edge_data;
+
+        match connection.destination_parameter {
+            Some(parameter_id) => {
+                let location = SampleLocation::channel(connection.source_output_channel);
+                let modulation = &feedback_buffer.get_channel_data(location)[..frame_count];
+
+                slot.dsp
+                    .try_lock()
+                    .expect("Slot is in use")
+                    .modulate_parameter(parameter_id, modulation);
+            }
+            None => slot.input.try_lock().expect("Slot is in use").add_from(
+                feedback_buffer,
+                SampleLocation::channel(connection.source_output_channel),
+                SampleLocation::channel(connection.destination_input_channel),
+                connection.channel_count,
+                frame_count,
+            ),
+        }
+    }
+
+    /// Process all of the nodes, returning once the block is complete
+    ///
+    /// The calling thread takes part in the processing
+    pub fn process(&self) {
+        let schedule = self.shared.schedule.try_read().expect("Schedule is in use");
+
+        self.shared
+            .remaining
+            .store(schedule.nodes.len(), Ordering::Release);
+
+        for (slot_index, node) in schedule.nodes.iter().enumerate() {
+            if node.dependency_count == 0 {
+                let _ = self.shared.ready.push(slot_index);
+            }
+        }
+
+        drop(schedule);
+
+        for worker in &self.workers {
+            worker.unpark();
+        }
+
+        process_ready_slots(&self.shared);
+    }
+
+    /// Copy the output of a node into a buffer
+    pub fn read_output(
+        &self,
+        dsp_id: Id,
+        output_buffer: &mut dyn AudioBuffer,
+        channel_count: usize,
+        frame_count: usize,
+    ) {
+        if let Some(slot) = self.get_slot(dsp_id) {
+            output_buffer.copy_from(
+                &*slot.output.try_read().expect("Slot is in use"),
+                SampleLocation::origin(),
+                SampleLocation::origin(),
+                channel_count,
+                frame_count,
+            );
+        }
+    }
+
+    /// Move the nodes back into the graph
+    pub fn finish(&mut self, graph: &mut Graph<Box<Dsp>, Connection>) {
+        for (slot_index, dsp_id) in self.slot_ids.iter().enumerate() {
+            let slot = &self.shared.slots[slot_index];
+
+            if let Some(dsp) = graph.get_node_mut(*dsp_id) {
+                std::mem::swap(dsp, &mut *slot.dsp.try_lock().expect("Slot is in use"));
+            }
+        }
+    }
+
+    fn get_slot(&self, dsp_id: Id) -> Option<&Slot> {
+        self.slot_indices
+            .get(&dsp_id)
+            .map(|slot_index| &self.shared.slots[*slot_index])
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        self.shared.is_running.store(false, Ordering::Release);
+
+        for worker in &self.workers {
+            worker.unpark();
+        }
+    }
+}
+
+fn run_worker(shared: &Shared) {
+    while shared.is_running.load(Ordering::Acquire) {
+        if shared.remaining.load(Ordering::Acquire) == 0 {
+            thread::park_timeout(WORKER_PARK_TIMEOUT);
+            continue;
+        }
+
+        process_ready_slots(shared);
+    }
+}
+
+fn process_ready_slots(shared: &Shared) {
+    while shared.remaining.load(Ordering::Acquire) > 0 {
+        match shared.ready.pop() {
+            Some(slot_index) => process_slot(shared, slot_index),
+            None => std::hint::spin_loop(),
+        }
+    }
+}
+
+fn process_slot(shared: &Shared, slot_index: usize) {
+    let schedule = shared.schedule.try_read().expect("Schedule is in use");
+    let node = &schedule.nodes[slot_index];
+    let slot = &shared.slots[slot_index];
+
+    let frame_count = shared.frame_count.load(Ordering::Acquire);
+    let start_time = Timestamp::from_raw_i64(shared.start_time.load(Ordering::Acquire));
+
+    {
+        let mut dsp = slot.dsp.try_lock().expect("Slot is in use");
+        let mut input_buffer = slot.input.try_lock().expect("Slot is in use");
+        let mut output_buffer = slot.output.try_write().expect("Slot is in use");
+
+        for connection in &schedule.connections[node.connections.clone()] {
+            let source_buffer = shared.slots[connection.source]
+                .output
+                .try_read()
+                .expect("Source slot is in use");
+
+            let source_location = SampleLocation::channel(connection.source_output_channel);
+
+            match connection.destination_parameter {
+                Some(parameter_id) => {
+                    let modulation =
+                        &source_buffer.get_channel_data(source_location)[..frame_count];
+                    dsp.modulate_parameter(parameter_id, modulation);
+                }
+                None => input_buffer.add_from(
+                    &*source_buffer,
+                    source_location,
+                    SampleLocation::channel(connection.destination_input_channel),
+                    connection.channel_count,
+                    frame_count,
+                ),
+            }
+        }
+
+        output_buffer.clear();
+
+        let mut output_slice = MutableBorrowedAudioBuffer::slice_channels_and_frames(
+            &mut *output_buffer,
+            frame_count,
+            dsp.output_count(),
+        );
+
+        let input_slice = BorrowedAudioBuffer::slice_channels_and_frames(
+            &*input_buffer,
+            frame_count,
+            dsp.input_count(),
+        );
+
+        dsp.process_audio(&input_slice, &mut output_slice, &start_time);
+    }
+
+    for dependent in &schedule.dependents[node.dependents.clone()] {
+        if shared.slots[*dependent]
+            .pending_dependencies
+            .fetch_sub(1, Ordering::AcqRel)
+            == 1
+        {
+            let _ = shared.ready.push(*dependent);
+        }
+    }
+
+    drop(schedule);
+
+    shared.remaining.fetch_sub(1, Ordering::AcqRel);
+}
@@ -0,0 +1,33 @@
+//! Sample rate conversion
+//!
+//! The [Resampler] converts a stream of audio between sample rates, a block at
+//! a time, and the ratio can change over time. To convert a whole buffer, use
+//! [resample_buffer].
+//!
+//! Two qualities are available:
+//!
+//! - [ResamplerQuality::Sinc] uses a windowed-sinc polyphase filter, and
+//!   filters out frequencies above the new Nyquist when downsampling
+//! - [ResamplerQuality::Cubic] uses cubic interpolation, which is much cheaper
+//!   but will alias
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use rawdio::{io::WavReader, resample::{resample_buffer, ResamplerQuality}};
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let buffer = WavReader::open("input_96k.wav")?.read_to_buffer()?;
+//! let buffer = resample_buffer(&buffer, 48_000, ResamplerQuality::Sinc);
+//! # Ok(())
+//! # }
+//! ```
+
+mod resample_buffer;
+mod resampler;
+mod resampler_quality;
+mod sinc_table;
+
+pub use resample_buffer::resample_buffer;
+pub use resampler::{ResampleResult, Resampler};
+pub use resampler_quality::ResamplerQuality;
@@ -0,0 +1,84 @@
+use super::{Resampler, ResamplerQuality};
+use crate::{AudioBuffer, BorrowedAudioBuffer, MutableBorrowedAudioBuffer, OwnedAudioBuffer};
+
+/// Convert a buffer to a different sample rate
+///
+/// The output is aligned with the input, so the first frame of the output is
+/// at the same time as the first frame of the input, and it has the same
+/// duration (rounded up to a whole frame).
+pub fn resample_buffer(
+    buffer: &dyn AudioBuffer,
+    sample_rate: usize,
+    quality: ResamplerQuality,
+) -> OwnedAudioBuffer {
+    let ratio = buffer.sample_rate() as f64 / sample_rate as f64;
+    let channel_count = buffer.channel_count();
+    let output_frame_count = (buffer.frame_count() as f64 / ratio).ceil() as usize;
+
+    let mut output = OwnedAudioBuffer::new(output_frame_count, channel_count, sample_rate);
+    let mut resampler = Resampler::new(quality, channel_count, ratio);
+
+    let padding = OwnedAudioBuffer::new(resampler.latency() + 1, channel_count, sample_rate);
+
+    let mut input_position = 0;
+    let mut output_position = 0;
+
+    while output_position < output_frame_count {
+        let input_remaining = buffer.frame_count() - input_position;
+
+        let input = if input_remaining > 0 {
+            BorrowedAudioBuffer::slice_frames(buffer, input_position, input_remaining)
+        } else {
+            BorrowedAudioBuffer::slice_frames(&padding, 0, padding.frame_count())
+        };
+
+        let mut output = MutableBorrowedAudioBuffer::slice_frames(
+            &mut output,
+            output_position,
+            output_frame_count - output_position,
+        );
+
+        let result = resampler.process(&input, &mut output);
+
+        input_position = (input_position + result.input_frame_count).min(buffer.frame_count());
+        output_position += result.output_frame_count;
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SampleLocation;
+
+    #[test]
+    fn converts_duration() {
+        let input = OwnedAudioBuffer::white_noise(96_000, 2, 96_000);
+
+        for quality in [ResamplerQuality::Cubic, ResamplerQuality::Sinc] {
+            let output = resample_buffer(&input, 48_000, quality);
+
+            assert_eq!(output.frame_count(), 48_000);
+            assert_eq!(output.channel_count(), 2);
+            assert_eq!(output.sample_rate(), 48_000);
+        }
+    }
+
+    #[test]
+    fn is_aligned_with_the_input() {
+        let input = OwnedAudioBuffer::sine(44_100, 1, 44_100, 100.0, 1.0);
+        let output = resample_buffer(&input, 48_000, ResamplerQuality::Sinc);
+        let expected = OwnedAudioBuffer::sine(48_000, 1, 48_000, 100.0, 1.0);
+
+        for (expected, actual) in expected
+            .get_channel_data(SampleLocation::origin())
+            .iter()
+            .zip(output.get_channel_data(SampleLocation::origin()))
+            .skip(100)
+            .take(47_800)
+        {
+            assert_relative_eq!(expected, actual, epsilon = 1e-3);
+        }
+    }
+}
@@ -0,0 +1,405 @@
+use super::{
+    sinc_table::{SincTable, SINC_HALF_TAP_COUNT},
+    ResamplerQuality,
+};
+use crate::{dsp::read_cubic, AudioBuffer, SampleLocation};
+
+/// The largest downsampling ratio that the anti-aliasing filter will adapt to
+///
+/// Higher ratios can still be used, but will alias
+const MAXIMUM_FILTER_RATIO: f64 = 8.0;
+
+/// The number of input frames that are buffered at a time
+const INPUT_BLOCK_FRAME_COUNT: usize = 1_024;
+
+const CUBIC_HALF_WIDTH: usize = 2;
+
+/// The number of frames used and produced by [Resampler::process]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct ResampleResult {
+    /// The number of frames that were read from the input
+    pub input_frame_count: usize,
+
+    /// The number of frames that were written to the output
+    pub output_frame_count: usize,
+}
+
+/// A streaming sample rate converter
+///
+/// The ratio is the number of input frames for each output frame, e.g. to
+/// convert from 96 kHz to 48 kHz the ratio is 2. It can be changed between,
+/// or during, calls to `process`.
+///
+/// The resampler doesn't allocate after it has been created, so can be used
+/// on the audio thread.
+pub struct Resampler {
+    quality: ResamplerQuality,
+    ratio: f64,
+    history: Vec<Vec<f32>>,
+    buffered_frame_count: usize,
+    position: f64,
+    half_width: usize,
+}
+
+impl Resampler {
+    /// Create a resampler
+    pub fn new(quality: ResamplerQuality, channel_count: usize, ratio: f64) -> Self {
+        let half_width = match quality {
+            ResamplerQuality::Cubic => CUBIC_HALF_WIDTH,
+            ResamplerQuality::Sinc => {
+                (SINC_HALF_TAP_COUNT as f64 * MAXIMUM_FILTER_RATIO).ceil() as usize
+            }
+        };
+
+        if quality == ResamplerQuality::Sinc {
+            SincTable::shared();
+        }
+
+        let capacity = 2 * half_width + INPUT_BLOCK_FRAME_COUNT;
+
+        let mut resampler = Self {
+            quality,
+            ratio: 1.0,
+            history: (0..channel_count).map(|_| vec![0.0; capacity]).collect(),
+            buffered_frame_count: 0,
+            position: 0.0,
+            half_width,
+        };
+
+        resampler.set_ratio(ratio);
+        resampler.reset();
+        resampler
+    }
+
+    /// The number of channels that the resampler was created with
+    pub fn channel_count(&self) -> usize {
+        self.history.len()
+    }
+
+    /// The current ratio of input frames to output frames
+    pub fn ratio(&self) -> f64 {
+        self.ratio
+    }
+
+    /// Set the ratio of input frames to output frames
+    pub fn set_ratio(&mut self, ratio: f64) {
+        debug_assert!(ratio > 0.0);
+        self.ratio = ratio;
+    }
+
+    /// The number of input frames that have to be provided before the first
+    /// output frame can be produced
+    pub fn latency(&self) -> usize {
+        self.half_width
+    }
+
+    /// Clear the audio in the resampler
+    pub fn reset(&mut self) {
+        for channel in self.history.iter_mut() {
+            channel.fill(0.0);
+        }
+
+        self.buffered_frame_count = self.half_width;
+        self.position = self.half_width as f64;
+    }
+
+    /// Resample audio from `input` into `output` with the current ratio
+    ///
+    /// This will stop when either all of the input has been used or the output
+    /// is full, so it may need to be called repeatedly
+    pub fn process(
+        &mut self,
+        input: &dyn AudioBuffer,
+        output: &mut dyn AudioBuffer,
+    ) -> ResampleResult {
+        let ratio = self.ratio;
+        self.process_with_ratio(input, output, |_| ratio)
+    }
+
+    /// Resample audio from `input` into `output` with a ratio for each output frame
+    ///
+    /// `ratios` should have at least as many values as `output` has frames.
+    /// The ratio is left at the last value that was used.
+    pub fn process_with_ratios(
+        &mut self,
+        input: &dyn AudioBuffer,
+        output: &mut dyn AudioBuffer,
+        ratios: &[f32],
+    ) -> ResampleResult {
+        debug_assert!(ratios.len() >= output.frame_count());
+        self.process_with_ratio(input, output, |frame| ratios[frame] as f64)
+    }
+
+    fn process_with_ratio(
+        &mut self,
+        input: &dyn AudioBuffer,
+        output: &mut dyn AudioBuffer,
+        ratio: impl Fn(usize) -> f64,
+    ) -> ResampleResult {
+        let channel_count = self
+            .channel_count()
+            .min(input.channel_count())
+            .min(output.channel_count());
+
+        let mut result = ResampleResult::default();
+
+        loop {
+            while result.output_frame_count < output.frame_count() {
+                let index = self.position.floor() as usize;
+
+                if index + self.half_width >= self.buffered_frame_count {
+                    break;
+                }
+
+                self.ratio = ratio(result.output_frame_count).max(f64::EPSILON);
+
+                for channel in 0..channel_count {
+                    let value = self.interpolate(channel);
+                    output.set_sample(
+                        SampleLocation::new(channel, result.output_frame_count),
+                        value,
+                    );
+                }
+
+                self.position += self.ratio;
+                result.output_frame_count += 1;
+            }
+
+            if result.output_frame_count == output.frame_count()
+                || result.input_frame_count == input.frame_count()
+            {
+                break;
+            }
+
+            self.discard_used_frames();
+
+            let frame_count = self.buffer_input(input, result.input_frame_count);
+
+            if frame_count == 0 {
+                break;
+            }
+
+            result.input_frame_count += frame_count;
+        }
+
+        result
+    }
+
+    fn discard_used_frames(&mut self) {
+        let discard_count = (self.position.floor() as usize)
+            .saturating_sub(self.half_width)
+            .min(self.buffered_frame_count);
+
+        if discard_count == 0 {
+            return;
+        }
+
+        for channel in self.history.iter_mut() {
+            channel.copy_within(discard_count..self.buffered_frame_count, 0);
+        }
+
+        self.buffered_frame_count -= discard_count;
+        self.position -= discard_count as f64;
+    }
+
+    fn buffer_input(&mut self, input: &dyn AudioBuffer, input_offset: usize) -> usize {
+        let capacity = self.history.first().map_or(0, |channel| channel.len());
+
+        let frame_count =
+            (capacity - self.buffered_frame_count).min(input.frame_count() - input_offset);
+
+        let start = self.buffered_frame_count;
+
+        for (channel, history) in self.history.iter_mut().enumerate() {
+            let history = &mut history[start..start + frame_count];
+
+            if channel < input.channel_count() {
+                let input = input.get_channel_data(SampleLocation::new(channel, input_offset));
+                history.copy_from_slice(&input[..frame_count]);
+            } else {
+                history.fill(0.0);
+            }
+        }
+
+        self.buffered_frame_count += frame_count;
+
+        frame_count
+    }
+
+    fn interpolate(&self, channel: usize) -> f32 {
+        let index = self.position.floor() as usize;
+        let fraction = (self.position - index as f64) as f32;
+        let history = &self.history[channel];
+
+        match self.quality {
+            ResamplerQuality::Cubic => read_cubic(history, index as f64 + fraction as f64),
+            ResamplerQuality::Sinc => {
+                let scale = (1.0 / self.ratio).clamp(1.0 / MAXIMUM_FILTER_RATIO, 1.0) as f32;
+                interpolate_sinc(history, index, fraction, scale)
+            }
+        }
+    }
+}
+
+/// Interpolate with a sinc filter, stretched by `1 / scale` to lower the cutoff
+fn interpolate_sinc(history: &[f32], index: usize, fraction: f32, scale: f32) -> f32 {
+    let table = SincTable::shared();
+    let half_width = (SINC_HALF_TAP_COUNT as f32 / scale).ceil() as usize;
+
+    let first = index + 1 - half_width;
+    let last = index + half_width;
+
+    let sum: f32 = history[first..=last]
+        .iter()
+        .enumerate()
+        .map(|(offset, sample)| {
+            let time = (offset as f32 - (half_width - 1) as f32 - fraction) * scale;
+            sample * table.value(time)
+        })
+        .sum();
+
+    sum * scale
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MutableBorrowedAudioBuffer, OwnedAudioBuffer};
+
+    const SAMPLE_RATE: usize = 48_000;
+
+    fn resample_sine(
+        quality: ResamplerQuality,
+        frequency: f64,
+        ratio: f64,
+        output_frame_count: usize,
+    ) -> OwnedAudioBuffer {
+        let input_frame_count = (output_frame_count as f64 * ratio) as usize + 1_024;
+        let input = OwnedAudioBuffer::sine(input_frame_count, 1, SAMPLE_RATE, frequency, 1.0);
+        let mut output = OwnedAudioBuffer::new(output_frame_count, 1, SAMPLE_RATE);
+
+        let mut resampler = Resampler::new(quality, 1, ratio);
+        let result = resampler.process(&input, &mut output);
+
+        assert_eq!(result.output_frame_count, output_frame_count);
+
+        output
+    }
+
+    fn rms(data: &[f32]) -> f32 {
+        (data.iter().map(|sample| sample * sample).sum::<f32>() / data.len() as f32).sqrt()
+    }
+
+    fn expect_sine(buffer: &dyn AudioBuffer, frequency: f64) {
+        let expected = OwnedAudioBuffer::sine(buffer.frame_count(), 1, SAMPLE_RATE, frequency, 1.0);
+
+        // Skip the start, where the filter rings from the step at the start of the sine
+        for (expected, actual) in expected
+            .get_channel_data(SampleLocation::origin())
+            .iter()
+            .zip(buffer.get_channel_data(SampleLocation::origin()))
+            .skip(64)
+        {
+            assert_relative_eq!(expected, actual, epsilon = 0.01);
+        }
+    }
+
+    #[test]
+    fn passes_audio_through_at_unity_ratio() {
+        for quality in [ResamplerQuality::Cubic, ResamplerQuality::Sinc] {
+            let output = resample_sine(quality, 1_000.0, 1.0, 4_096);
+            expect_sine(&output, 1_000.0);
+        }
+    }
+
+    #[test]
+    fn keeps_frequency_when_upsampling() {
+        for quality in [ResamplerQuality::Cubic, ResamplerQuality::Sinc] {
+            // At half the ratio, each input cycle is stretched over twice as many frames
+            let output = resample_sine(quality, 1_000.0, 0.5, 4_096);
+            expect_sine(&output, 500.0);
+        }
+    }
+
+    #[test]
+    fn removes_frequencies_above_nyquist_when_downsampling() {
+        let frame_count = 4_096;
+
+        // 18 kHz at 48 kHz is above the Nyquist frequency once downsampled by 2
+        let sinc = resample_sine(ResamplerQuality::Sinc, 18_000.0, 2.0, frame_count);
+        let cubic = resample_sine(ResamplerQuality::Cubic, 18_000.0, 2.0, frame_count);
+
+        let sinc_level = rms(&sinc.get_channel_data(SampleLocation::origin())[256..]);
+        let cubic_level = rms(&cubic.get_channel_data(SampleLocation::origin())[256..]);
+
+        assert!(sinc_level < 0.01, "{sinc_level}");
+        assert!(cubic_level > 0.1, "{cubic_level}");
+    }
+
+    #[test]
+    fn keeps_frequencies_below_nyquist_when_downsampling() {
+        let output = resample_sine(ResamplerQuality::Sinc, 5_000.0, 2.0, 4_096);
+        let level = rms(&output.get_channel_data(SampleLocation::origin())[256..]);
+
+        assert_relative_eq!(level, std::f32::consts::FRAC_1_SQRT_2, epsilon = 0.01);
+    }
+
+    #[test]
+    fn produces_the_same_output_in_small_blocks() {
+        let input = OwnedAudioBuffer::white_noise(8_192, 1, SAMPLE_RATE);
+        let ratio = 1.37;
+
+        let mut expected = OwnedAudioBuffer::new(4_096, 1, SAMPLE_RATE);
+        Resampler::new(ResamplerQuality::Sinc, 1, ratio).process(&input, &mut expected);
+
+        let mut actual = OwnedAudioBuffer::new(4_096, 1, SAMPLE_RATE);
+        let mut resampler = Resampler::new(ResamplerQuality::Sinc, 1, ratio);
+
+        let mut input_position = 0;
+        let mut output_position = 0;
+
+        while output_position < actual.frame_count() {
+            let input_block = crate::BorrowedAudioBuffer::slice_frames(
+                &input,
+                input_position,
+                100.min(input.frame_count() - input_position),
+            );
+
+            let output_frame_count = 37.min(actual.frame_count() - output_position);
+            let mut output_block = MutableBorrowedAudioBuffer::slice_frames(
+                &mut actual,
+                output_position,
+                output_frame_count,
+            );
+
+            let result = resampler.process(&input_block, &mut output_block);
+
+            input_position += result.input_frame_count;
+            output_position += result.output_frame_count;
+        }
+
+        assert_eq!(
+            expected.get_channel_data(SampleLocation::origin()),
+            actual.get_channel_data(SampleLocation::origin())
+        );
+    }
+
+    #[test]
+    fn follows_a_changing_ratio() {
+        let input = OwnedAudioBuffer::white_noise(8_192, 1, SAMPLE_RATE);
+        let mut output = OwnedAudioBuffer::new(2_048, 1, SAMPLE_RATE);
+
+        let ratios: Vec<f32> = (0..output.frame_count())
+            .map(|frame| 1.0 + frame as f32 / output.frame_count() as f32)
+            .collect();
+
+        let mut resampler = Resampler::new(ResamplerQuality::Cubic, 1, 1.0);
+        let result = resampler.process_with_ratios(&input, &mut output, &ratios);
+
+        let expected_input_frames: f32 = ratios.iter().sum();
+
+        assert_eq!(result.output_frame_count, output.frame_count());
+        assert!(result.input_frame_count as f32 >= expected_input_frames);
+        assert_relative_eq!(resampler.ratio(), *ratios.last().unwrap() as f64);
+    }
+}
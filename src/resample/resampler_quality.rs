@@ -0,0 +1,15 @@
+/// The algorithm used by a [super::Resampler]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResamplerQuality {
+    /// Cubic (Hermite) interpolation using the four nearest samples
+    ///
+    /// This is cheap, but there is no anti-aliasing filter, so it is best
+    /// suited to ratios close to 1
+    Cubic,
+
+    /// Windowed-sinc interpolation
+    ///
+    /// This uses a Kaiser-windowed sinc filter with 32 taps, which is widened
+    /// when downsampling so that frequencies above the new Nyquist are removed
+    Sinc,
+}
@@ -0,0 +1,129 @@
+use std::{f64::consts::PI, sync::OnceLock};
+
+/// The number of zero crossings on each side of the filter at a ratio of 1
+pub const SINC_HALF_TAP_COUNT: usize = 16;
+
+/// The number of table entries between each zero crossing
+const PHASE_COUNT: usize = 512;
+
+/// The fraction of the Nyquist frequency to pass, leaving room for the
+/// transition band
+const CUTOFF: f64 = 0.94;
+
+const KAISER_BETA: f64 = 8.6;
+
+/// One side of a symmetric windowed-sinc filter, sampled finely enough that
+/// any fractional position can be linearly interpolated from it
+pub struct SincTable {
+    values: Vec<f32>,
+}
+
+impl SincTable {
+    pub fn shared() -> &'static Self {
+        static TABLE: OnceLock<SincTable> = OnceLock::new();
+        TABLE.get_or_init(Self::new)
+    }
+
+    fn new() -> Self {
+        let length = SINC_HALF_TAP_COUNT * PHASE_COUNT + 2;
+
+        let values = (0..length)
+            .map(|index| {
+                let time = index as f64 / PHASE_COUNT as f64;
+
+                if time >= SINC_HALF_TAP_COUNT as f64 {
+                    return 0.0;
+                }
+
+                let window = kaiser_window(time / SINC_HALF_TAP_COUNT as f64, KAISER_BETA);
+
+                (CUTOFF * sinc(CUTOFF * time) * window) as f32
+            })
+            .collect();
+
+        Self { values }
+    }
+
+    /// The value of the filter at `time` samples from its centre
+    pub fn value(&self, time: f32) -> f32 {
+        let position = time.abs() * PHASE_COUNT as f32;
+        let index = position as usize;
+
+        if index + 1 >= self.values.len() {
+            return 0.0;
+        }
+
+        let fraction = position - index as f32;
+        let before = self.values[index];
+        let after = self.values[index + 1];
+
+        before + fraction * (after - before)
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        (PI * x).sin() / (PI * x)
+    }
+}
+
+/// A Kaiser window, where `x` is the position from the centre in the range 0 to 1
+fn kaiser_window(x: f64, beta: f64) -> f64 {
+    bessel_i0(beta * (1.0 - x * x).max(0.0).sqrt()) / bessel_i0(beta)
+}
+
+/// The zeroth order modified Bessel function of the first kind
+fn bessel_i0(x: f64) -> f64 {
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let half_x = x / 2.0;
+
+    for k in 1..50 {
+        term *= (half_x / k as f64).powi(2);
+        sum += term;
+
+        if term < 1e-12 * sum {
+            break;
+        }
+    }
+
+    sum
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_one_at_centre_and_zero_at_edges() {
+        let table = SincTable::shared();
+
+        assert_relative_eq!(table.value(0.0), CUTOFF as f32, epsilon = 1e-6);
+        assert_eq!(table.value(SINC_HALF_TAP_COUNT as f32), 0.0);
+        assert_eq!(table.value(100.0), 0.0);
+    }
+
+    #[test]
+    fn is_symmetric() {
+        let table = SincTable::shared();
+
+        for time in [0.1, 0.5, 1.25, 7.75] {
+            assert_eq!(table.value(time), table.value(-time));
+        }
+    }
+
+    #[test]
+    fn has_unity_gain_at_dc() {
+        let table = SincTable::shared();
+
+        for fraction in [0.0, 0.25, 0.5, 0.9] {
+            let sum: f32 = (-(SINC_HALF_TAP_COUNT as i32)..=SINC_HALF_TAP_COUNT as i32)
+                .map(|tap| table.value(tap as f32 - fraction))
+                .sum();
+
+            assert_relative_eq!(sum, 1.0, epsilon = 1e-3);
+        }
+    }
+}
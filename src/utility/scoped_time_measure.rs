@@ -1,3 +1,5 @@
+#![allow(dead_code)]
+
 pub struct ScopedTimeMeasure {
     start: std::time::Instant,
 }
@@ -0,0 +1,64 @@
+use rawdio::{prelude::*, Compressor, CompressorOptions, OfflineContext, Oscillator};
+use std::time::Duration;
+
+fn render_peak(connect_sidechain: bool) -> f32 {
+    let mut context = OfflineContext::new(
+        EngineOptions::default().with_sample_rate(48_000),
+        1,
+        Duration::from_millis(500),
+    );
+
+    let music = Oscillator::sine(&context, 440.0, 1);
+    let voice = Oscillator::sine(&context, 1_000.0, 1);
+
+    let mut compressor =
+        Compressor::new_with_options(&context, 1, CompressorOptions::default().with_sidechain(1));
+    compressor.threshold().set_value_now(-20.0);
+    compressor.ratio().set_value_now(20.0);
+
+    music.node.connect_to(&compressor.node);
+    if connect_sidechain {
+        voice.node.connect_channels_to(
+            &compressor.node,
+            0,
+            compressor.first_sidechain_channel(),
+            1,
+        );
+    }
+    compressor.node.connect_to_output();
+
+    context.render();
+    let rendered = context.into_output_buffer();
+
+    rendered.get_channel_data(SampleLocation::channel(0))[rendered.frame_count() / 2..]
+        .iter()
+        .fold(0.0, |peak, sample| sample.abs().max(peak))
+}
+
+#[test]
+fn sidechain_ducks_the_input() {
+    assert!(render_peak(false) > 0.95);
+    assert!(render_peak(true) < 0.2);
+}
+
+fn create_stereo_compressor(maximum_channel_count: usize) -> Compressor {
+    let context = OfflineContext::new(
+        EngineOptions::default().with_maximum_channel_count(maximum_channel_count),
+        2,
+        Duration::from_millis(10),
+    );
+
+    Compressor::new_with_options(&context, 2, CompressorOptions::default().with_sidechain(2))
+}
+
+#[test]
+fn stereo_sidechain_fits_a_larger_engine() {
+    let compressor = create_stereo_compressor(4);
+    assert_eq!(compressor.first_sidechain_channel(), 2);
+}
+
+#[test]
+#[should_panic(expected = "maximum channel count")]
+fn stereo_sidechain_is_rejected_by_the_default_engine() {
+    create_stereo_compressor(2);
+}
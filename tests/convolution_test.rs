@@ -0,0 +1,35 @@
+use rawdio::{prelude::*, Convolution, OfflineContext, Oscillator};
+use std::time::Duration;
+
+#[test]
+fn matrix_convolution_spreads_mono_to_stereo() {
+    let sample_rate = 48_000;
+    let mut context = OfflineContext::new(
+        EngineOptions::default().with_sample_rate(sample_rate),
+        2,
+        Duration::from_millis(100),
+    );
+
+    let oscillator = Oscillator::sine(&context, 440.0, 1);
+
+    let mut impulse = OwnedAudioBuffer::new(1, 2, sample_rate);
+    impulse.set_sample(SampleLocation::new(0, 0), 1.0);
+    impulse.set_sample(SampleLocation::new(1, 0), -0.5);
+
+    let convolution = Convolution::new_matrix(&context, 1, 2, impulse);
+
+    oscillator.node.connect_to(&convolution.node);
+    convolution.node.connect_to_output();
+
+    context.render();
+    let rendered = context.into_output_buffer();
+
+    let left = rendered.get_channel_data(SampleLocation::channel(0));
+    let right = rendered.get_channel_data(SampleLocation::channel(1));
+
+    assert!(left.iter().any(|sample| sample.abs() > 0.5));
+
+    for (left, right) in left.iter().zip(right.iter()) {
+        assert!((right + 0.5 * left).abs() < 1e-5);
+    }
+}
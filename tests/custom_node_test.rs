@@ -0,0 +1,93 @@
+use approx::assert_relative_eq;
+use rawdio::{
+    prelude::*, DspNode, DspProcessor, GraphNodeBuilder, ParameterRange, Parameters, ProcessContext,
+};
+
+struct OffsetProcessor;
+
+impl DspProcessor for OffsetProcessor {
+    fn process_audio(&mut self, context: &mut ProcessContext) {
+        let frame_count = context.output_buffer.frame_count();
+        let offset = context
+            .parameters
+            .get_parameter_values("offset", frame_count);
+
+        for channel in 0..context.output_buffer.channel_count() {
+            let location = SampleLocation::channel(channel);
+            let input = context.input_buffer.get_channel_data(location);
+            let output = context.output_buffer.get_channel_data_mut(location);
+
+            for ((output, input), offset) in output.iter_mut().zip(input).zip(offset) {
+                *output = *input + *offset;
+            }
+        }
+    }
+}
+
+struct Offset {
+    node: GraphNode,
+    params: Parameters,
+}
+
+impl DspNode for Offset {
+    fn get_parameters_mut(&mut self) -> &mut Parameters {
+        &mut self.params
+    }
+}
+
+impl Offset {
+    fn new(context: &dyn Context, channel_count: usize) -> Self {
+        let (node, params) = GraphNodeBuilder::new(context)
+            .with_input_count(channel_count)
+            .with_output_count(channel_count)
+            .with_parameter("offset", ParameterRange::new(0.0, -1.0, 1.0))
+            .build(Box::new(OffsetProcessor));
+
+        Self { node, params }
+    }
+
+    fn offset(&mut self) -> &mut AudioParameter {
+        self.get_parameter_mut("offset")
+    }
+}
+
+#[test]
+fn test_custom_node_with_automation() {
+    let sample_rate = 48_000;
+    let channel_count = 2;
+
+    let (mut context, mut process) =
+        create_engine_with_options(EngineOptions::default().with_sample_rate(sample_rate));
+
+    let mut offset = Offset::new(context.as_ref(), channel_count);
+
+    offset.offset().set_value_at_time(0.25, Timestamp::zero());
+    offset
+        .offset()
+        .set_value_at_time(-0.5, Timestamp::from_seconds(0.5));
+
+    connect_nodes!("input" => offset => "output");
+
+    context.start();
+
+    let frame_count = sample_rate;
+    let input_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+    let mut output_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+
+    process.process(&input_buffer, &mut output_buffer);
+
+    context.stop();
+
+    for channel in 0..channel_count {
+        let samples = output_buffer.get_channel_data(SampleLocation::channel(channel));
+        let (first_half, second_half) = samples.split_at(frame_count / 2);
+
+        first_half
+            .iter()
+            .for_each(|sample| assert_relative_eq!(*sample, 0.25));
+
+        second_half
+            .iter()
+            .for_each(|sample| assert_relative_eq!(*sample, -0.5));
+    }
+}
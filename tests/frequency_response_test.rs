@@ -0,0 +1,176 @@
+use approx::assert_relative_eq;
+use rawdio::{
+    prelude::*, Biquad, BiquadFilterType, Filter, FilterDesign, LadderFilter, OfflineContext,
+    OfflineRenderStatus, StateVariableFilter, StateVariableFilterOutput,
+};
+use std::{
+    f64::consts::{FRAC_PI_2, PI},
+    time::Duration,
+};
+
+fn create_context() -> OfflineContext {
+    let mut context = OfflineContext::new(
+        EngineOptions::default().with_sample_rate(48_000),
+        1,
+        Duration::from_secs(1),
+    );
+
+    context.suspend_at(Timestamp::from_seconds(0.5));
+    context
+}
+
+fn magnitude_db(magnitude: f64) -> f64 {
+    Level::from_linear(magnitude).as_db()
+}
+
+#[test]
+fn biquad_response_follows_the_parameters() {
+    let mut context = create_context();
+
+    let mut biquad = Biquad::new(&context, 1, BiquadFilterType::Peaking);
+    biquad.node.connect_to_output();
+
+    let frequencies = [100.0, 1_000.0, 2_000.0];
+    let mut magnitudes = [0.0; 3];
+    let mut phases = [0.0; 3];
+
+    while let OfflineRenderStatus::Suspended(_) = context.render() {
+        biquad.get_frequency_response(&frequencies, &mut magnitudes, &mut phases);
+        assert!(magnitudes
+            .iter()
+            .all(|magnitude| (magnitude - 1.0).abs() < 1e-9));
+
+        biquad.frequency().set_value_now(2_000.0);
+        biquad
+            .shelf_gain()
+            .set_value_now(Level::from_db(12.0).as_linear());
+    }
+
+    biquad.get_frequency_response(&frequencies, &mut magnitudes, &mut phases);
+
+    assert_relative_eq!(magnitude_db(magnitudes[2]), 12.0, epsilon = 1e-3);
+    assert_relative_eq!(phases[2], 0.0, epsilon = 1e-9);
+    assert!(magnitude_db(magnitudes[0]) < 0.1);
+}
+
+#[test]
+fn filter_response_includes_every_section() {
+    let mut context = create_context();
+
+    let mut filter = Filter::low_pass(&context, 1, FilterDesign::Butterworth, 4);
+    filter.node.connect_to_output();
+
+    let frequencies = [10.0, 500.0, 2_000.0];
+    let mut magnitudes = [0.0; 3];
+    let mut phases = [0.0; 3];
+
+    while let OfflineRenderStatus::Suspended(_) = context.render() {
+        filter.frequency().set_value_now(500.0);
+        filter.gain().set_value_now(0.5);
+    }
+
+    filter.get_frequency_response(&frequencies, &mut magnitudes, &mut phases);
+
+    assert_relative_eq!(magnitudes[0], 0.5, epsilon = 1e-3);
+    assert_relative_eq!(magnitude_db(magnitudes[1]), -3.01 - 6.02, epsilon = 1e-2);
+    assert!(magnitude_db(magnitudes[2]) < -48.0);
+
+    // Each of the two sections has a phase of -π/2 at the cutoff
+    assert_relative_eq!(phases[1].abs(), 2.0 * FRAC_PI_2, epsilon = 1e-6);
+}
+
+#[test]
+fn high_order_bessel_attenuates_above_the_cutoff() {
+    let mut context = create_context();
+
+    let mut filter = Filter::low_pass(&context, 1, FilterDesign::Bessel, 30);
+    filter.node.connect_to_output();
+
+    let frequencies = [10.0, 1_000.0, 8_000.0];
+    let mut magnitudes = [0.0; 3];
+    let mut phases = [0.0; 3];
+
+    while let OfflineRenderStatus::Suspended(_) = context.render() {
+        filter.frequency().set_value_now(1_000.0);
+    }
+
+    filter.get_frequency_response(&frequencies, &mut magnitudes, &mut phases);
+
+    assert_relative_eq!(magnitudes[0], 1.0, epsilon = 1e-3);
+    assert_relative_eq!(magnitude_db(magnitudes[1]), -3.01, epsilon = 1e-2);
+    assert!(magnitude_db(magnitudes[2]) < -60.0);
+}
+
+#[test]
+fn state_variable_filter_response_for_each_output() {
+    let mut context = create_context();
+
+    // The response is available for every output, even the ones that aren't
+    // produced
+    let mut filter =
+        StateVariableFilter::with_outputs(&context, 1, &[StateVariableFilterOutput::LowPass]);
+    filter.node.connect_to_output();
+
+    while let OfflineRenderStatus::Suspended(_) = context.render() {
+        filter.frequency().set_value_now(2_000.0);
+    }
+
+    let frequencies = [10.0, 2_000.0, 24_000.0];
+    let mut magnitudes = [0.0; 3];
+    let mut phases = [0.0; 3];
+
+    let mut response = |output| {
+        filter.get_frequency_response(output, &frequencies, &mut magnitudes, &mut phases);
+        (magnitudes, phases)
+    };
+
+    let (magnitudes, phases) = response(StateVariableFilterOutput::LowPass);
+    assert_relative_eq!(magnitudes[0], 1.0, epsilon = 1e-3);
+    assert_relative_eq!(magnitude_db(magnitudes[1]), -3.01, epsilon = 1e-2);
+    assert_relative_eq!(phases[1], -FRAC_PI_2, epsilon = 1e-6);
+    assert!(magnitudes[2] < 1e-9);
+
+    let (magnitudes, _) = response(StateVariableFilterOutput::HighPass);
+    assert!(magnitudes[0] < 1e-3);
+    assert_relative_eq!(magnitude_db(magnitudes[1]), -3.01, epsilon = 1e-2);
+    assert_relative_eq!(magnitudes[2], 1.0, epsilon = 1e-9);
+
+    let (magnitudes, phases) = response(StateVariableFilterOutput::BandPass);
+    assert_relative_eq!(magnitudes[1], 1.0, epsilon = 1e-9);
+    assert_relative_eq!(phases[1], 0.0, epsilon = 1e-9);
+
+    let (magnitudes, _) = response(StateVariableFilterOutput::Notch);
+    assert!(magnitudes[1] < 1e-9);
+}
+
+#[test]
+fn ladder_filter_response_is_the_small_signal_response() {
+    let mut context = create_context();
+
+    let mut filter = LadderFilter::new(&context, 1);
+    filter.node.connect_to_output();
+
+    let frequencies = [10.0, 1_000.0, 8_000.0];
+    let mut magnitudes = [0.0; 3];
+    let mut phases = [0.0; 3];
+
+    while let OfflineRenderStatus::Suspended(_) = context.render() {
+        filter.get_frequency_response(&frequencies, &mut magnitudes, &mut phases);
+
+        // Each of the four stages is 3dB down at the cutoff
+        assert_relative_eq!(magnitudes[0], 1.0, epsilon = 1e-3);
+        assert_relative_eq!(magnitude_db(magnitudes[1]), -12.04, epsilon = 1e-2);
+        assert_relative_eq!(phases[1].abs(), PI, epsilon = 1e-6);
+        assert!(magnitude_db(magnitudes[2]) < -60.0);
+
+        filter.resonance().set_value_now(0.9);
+        filter.drive().set_value_now(2.0);
+    }
+
+    filter.get_frequency_response(&frequencies, &mut magnitudes, &mut phases);
+
+    // Resonance thins out the low frequencies and boosts the cutoff
+    let feedback = 4.2 * 0.9;
+    assert_relative_eq!(magnitudes[0], 2.0 / (1.0 + feedback), epsilon = 1e-3);
+    assert!(magnitudes[1] > magnitudes[0]);
+}
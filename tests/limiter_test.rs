@@ -0,0 +1,39 @@
+use rawdio::{prelude::*, Limiter, LimiterOptions, OfflineContext, Oscillator};
+use std::time::Duration;
+
+#[test]
+fn limits_to_the_ceiling() {
+    let sample_rate = 48_000;
+    let mut context = OfflineContext::new(
+        EngineOptions::default().with_sample_rate(sample_rate),
+        1,
+        Duration::from_millis(200),
+    );
+
+    let oscillator = Oscillator::sine(&context, 1_000.0, 1);
+    let mut limiter = Limiter::new(
+        &context,
+        1,
+        LimiterOptions::default()
+            .with_look_ahead(Duration::from_millis(2))
+            .with_true_peak(true),
+    );
+    limiter.set_ceiling(Level::from_db(-6.0));
+
+    assert!(limiter.latency() > 96);
+
+    oscillator.node.connect_to(&limiter.node);
+    limiter.node.connect_to_output();
+
+    context.render();
+    let rendered = context.into_output_buffer();
+
+    let peak = rendered
+        .get_channel_data(SampleLocation::channel(0))
+        .iter()
+        .fold(0.0_f32, |peak, sample| sample.abs().max(peak));
+
+    let ceiling = Level::from_db(-6.0).as_linear_f32();
+    assert!(peak <= ceiling + 1e-6);
+    assert!(peak > 0.9 * ceiling);
+}
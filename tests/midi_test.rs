@@ -0,0 +1,182 @@
+use rawdio::{
+    midi::{MidiFile, MidiMessage, MAXIMUM_PENDING_MIDI_EVENTS},
+    prelude::*,
+    DspProcessor, GraphNodeBuilder, OfflineContext, ProcessContext, SampleZone, SamplerInstrument,
+};
+use std::time::Duration;
+
+/// Writes the note number of each note on into the output, on the frame it arrives
+struct NoteRecorder;
+
+impl DspProcessor for NoteRecorder {
+    fn process_audio(&mut self, context: &mut ProcessContext) {
+        context.output_buffer.clear();
+
+        for event in context.midi_events {
+            if let MidiMessage::NoteOn { note, .. } = event.message {
+                context
+                    .output_buffer
+                    .set_sample(SampleLocation::new(0, event.frame), note as f32);
+            }
+        }
+    }
+}
+
+fn create_midi_file() -> Vec<u8> {
+    let track = [
+        0x00, 0x90, 60, 100, // note on at the start
+        0x30, 0x80, 60, 0, // note off at 0.25 seconds
+        0x30, 0x90, 64, 100, // note on at 0.5 seconds
+        0x30, 0x80, 64, 0, // note off at 0.75 seconds
+        0x00, 0xff, 0x2f, 0x00,
+    ];
+
+    let mut bytes = b"MThd".to_vec();
+    bytes.extend_from_slice(&6_u32.to_be_bytes());
+    bytes.extend_from_slice(&[0, 0, 0, 1, 0, 96]);
+    bytes.extend_from_slice(b"MTrk");
+    bytes.extend_from_slice(&(track.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(&track);
+    bytes
+}
+
+fn note_frames(buffer: &dyn AudioBuffer) -> Vec<(usize, f32)> {
+    buffer
+        .get_channel_data(SampleLocation::channel(0))
+        .iter()
+        .enumerate()
+        .filter(|(_, sample)| **sample != 0.0)
+        .map(|(frame, sample)| (frame, *sample))
+        .collect()
+}
+
+#[test]
+fn delivers_scheduled_midi_to_custom_nodes() {
+    let sample_rate = 48_000;
+
+    let mut context = OfflineContext::new(
+        EngineOptions::default().with_sample_rate(sample_rate),
+        1,
+        Duration::from_secs(1),
+    );
+
+    let (node, _) = GraphNodeBuilder::new(&context)
+        .with_output_count(1)
+        .build(Box::new(NoteRecorder));
+
+    node.connect_to_output();
+
+    let file = MidiFile::from_bytes(&create_midi_file()).unwrap();
+    let start_time = Timestamp::from_samples(100.0, sample_rate);
+
+    for event in file.events() {
+        node.send_midi_event(event.delayed_by(start_time));
+    }
+
+    context.render();
+    let output = context.into_output_buffer();
+
+    assert_eq!(
+        note_frames(&output),
+        vec![(100, 60.0), (100 + sample_rate / 2, 64.0)]
+    );
+}
+
+#[test]
+fn cancels_scheduled_midi() {
+    let sample_rate = 48_000;
+
+    let mut context = OfflineContext::new(
+        EngineOptions::default().with_sample_rate(sample_rate),
+        1,
+        Duration::from_secs(1),
+    );
+
+    let (node, _) = GraphNodeBuilder::new(&context)
+        .with_output_count(1)
+        .build(Box::new(NoteRecorder));
+
+    node.connect_to_output();
+
+    let note_on = MidiMessage::NoteOn {
+        channel: 0,
+        note: 60,
+        velocity: 100,
+    };
+
+    node.send_midi_at_time(Timestamp::from_seconds(0.5), note_on);
+    node.cancel_midi_events();
+    node.send_midi_at_time(Timestamp::from_seconds(0.25), note_on);
+
+    context.render();
+    let output = context.into_output_buffer();
+
+    assert_eq!(note_frames(&output), vec![(sample_rate / 4, 60.0)]);
+}
+
+#[test]
+fn plays_a_midi_file_through_a_sampler_instrument() {
+    let sample_rate = 48_000;
+
+    let mut context = OfflineContext::new(
+        EngineOptions::default().with_sample_rate(sample_rate),
+        1,
+        Duration::from_secs(1),
+    );
+
+    let mut sample = OwnedAudioBuffer::new(sample_rate, 1, sample_rate);
+    sample.fill_with_value(0.5);
+
+    let instrument = SamplerInstrument::new(&context, 1, vec![SampleZone::new(sample, 60)]);
+    instrument.node.connect_to_output();
+
+    let file = MidiFile::from_bytes(&create_midi_file()).unwrap();
+
+    for event in file.events() {
+        instrument.node.send_midi_event(event);
+    }
+
+    context.render();
+    let output = context.into_output_buffer();
+    let output = output.get_channel_data(SampleLocation::channel(0));
+
+    assert!(output[1_000] > 0.3);
+    assert!(output[sample_rate / 4 + 2_000].abs() < 1e-3);
+    assert!(output[sample_rate / 2 + 1_000] > 0.3);
+    assert!(output[sample_rate * 3 / 4 + 2_000].abs() < 1e-3);
+}
+
+#[test]
+fn reports_events_dropped_when_the_queue_is_full() {
+    let sample_rate = 48_000;
+
+    let mut context = OfflineContext::new(
+        EngineOptions::default().with_sample_rate(sample_rate),
+        1,
+        Duration::from_millis(10),
+    );
+
+    let (node, _) = GraphNodeBuilder::new(&context)
+        .with_output_count(1)
+        .build(Box::new(NoteRecorder));
+
+    node.connect_to_output();
+
+    let extra_event_count = 10;
+
+    for _ in 0..MAXIMUM_PENDING_MIDI_EVENTS + extra_event_count {
+        node.send_midi_at_time(
+            Timestamp::from_seconds(10.0),
+            MidiMessage::NoteOn {
+                channel: 0,
+                note: 60,
+                velocity: 100,
+            },
+        );
+    }
+
+    context.render();
+
+    assert_eq!(node.take_dropped_midi_event_count(), extra_event_count);
+    assert_eq!(node.take_dropped_midi_event_count(), 0);
+}
@@ -0,0 +1,138 @@
+use approx::assert_relative_eq;
+use rawdio::{prelude::*, DspProcessor, Gain, GraphNodeBuilder, Pan, ProcessContext};
+
+struct ConstantProcessor {
+    value: f32,
+}
+
+impl DspProcessor for ConstantProcessor {
+    fn process_audio(&mut self, context: &mut ProcessContext) {
+        context.output_buffer.fill_with_value(self.value);
+    }
+}
+
+fn create_constant(context: &dyn Context, value: f32) -> GraphNode {
+    let (node, _) = GraphNodeBuilder::new(context)
+        .with_output_count(1)
+        .build(Box::new(ConstantProcessor { value }));
+    node
+}
+
+fn process(
+    audio_process: &mut dyn AudioProcess,
+    input_buffer: &OwnedAudioBuffer,
+    channel_count: usize,
+) -> OwnedAudioBuffer {
+    let mut output_buffer = OwnedAudioBuffer::new(
+        input_buffer.frame_count(),
+        channel_count,
+        input_buffer.sample_rate(),
+    );
+
+    audio_process.process(input_buffer, &mut output_buffer);
+
+    output_buffer
+}
+
+#[test]
+fn modulation_is_added_to_parameter_value() {
+    let sample_rate = 48_000;
+    let frame_count = 1_024;
+
+    let (mut context, mut audio_process) =
+        create_engine_with_options(EngineOptions::default().with_sample_rate(sample_rate));
+
+    let mut gain = Gain::new(context.as_ref(), 1);
+    gain.gain().set_value_now(0.5);
+
+    let modulator = create_constant(context.as_ref(), 0.25);
+    modulator.connect_to_parameter(gain.gain());
+
+    connect_nodes!("input" => gain => "output");
+
+    context.start();
+
+    let mut input_buffer = OwnedAudioBuffer::new(frame_count, 1, sample_rate);
+    input_buffer.fill_with_value(1.0);
+
+    let output_buffer = process(audio_process.as_mut(), &input_buffer, 1);
+
+    output_buffer
+        .get_channel_data(SampleLocation::origin())
+        .iter()
+        .for_each(|sample| assert_relative_eq!(*sample, 0.75));
+
+    modulator.disconnect_from_parameter(gain.gain());
+
+    let output_buffer = process(audio_process.as_mut(), &input_buffer, 1);
+
+    output_buffer
+        .get_channel_data(SampleLocation::origin())
+        .iter()
+        .for_each(|sample| assert_relative_eq!(*sample, 0.5));
+
+    context.stop();
+}
+
+#[test]
+fn source_can_modulate_the_node_it_feeds() {
+    let sample_rate = 48_000;
+    let frame_count = 1_024;
+
+    let (mut context, mut audio_process) =
+        create_engine_with_options(EngineOptions::default().with_sample_rate(sample_rate));
+
+    let mut gain = Gain::new(context.as_ref(), 1);
+    gain.gain().set_value_now(1.0);
+
+    let source = create_constant(context.as_ref(), 1.0);
+    source.connect_to(&gain.node);
+    source.connect_to_parameter(gain.gain());
+
+    gain.node.connect_to_output();
+
+    context.start();
+
+    let input_buffer = OwnedAudioBuffer::new(frame_count, 1, sample_rate);
+    let output_buffer = process(audio_process.as_mut(), &input_buffer, 1);
+
+    output_buffer
+        .get_channel_data(SampleLocation::origin())
+        .iter()
+        .for_each(|sample| assert_relative_eq!(*sample, 2.0));
+
+    context.stop();
+}
+
+#[test]
+fn modulated_value_is_clamped_to_parameter_range() {
+    let sample_rate = 48_000;
+    let frame_count = 1_024;
+    let channel_count = 2;
+
+    let (mut context, mut audio_process) =
+        create_engine_with_options(EngineOptions::default().with_sample_rate(sample_rate));
+
+    let mut pan = Pan::new(context.as_ref(), channel_count);
+
+    let modulator = create_constant(context.as_ref(), 5.0);
+    modulator.connect_to_parameter(pan.pan());
+
+    connect_nodes!("input" => pan => "output");
+
+    context.start();
+
+    let mut input_buffer = OwnedAudioBuffer::new(frame_count, channel_count, sample_rate);
+    input_buffer.fill_with_value(1.0);
+
+    let output_buffer = process(audio_process.as_mut(), &input_buffer, channel_count);
+
+    assert!(output_buffer.channel_is_silent(0));
+
+    output_buffer
+        .get_channel_data(SampleLocation::channel(1))
+        .iter()
+        .for_each(|sample| assert_relative_eq!(*sample, 1.0));
+
+    context.stop();
+}
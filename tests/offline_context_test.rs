@@ -0,0 +1,115 @@
+use rawdio::{prelude::*, OfflineContext, OfflineRenderStatus, Oscillator};
+use std::time::Duration;
+
+fn is_silent(buffer: &dyn AudioBuffer, start_frame: usize, end_frame: usize) -> bool {
+    (0..buffer.channel_count()).all(|channel| {
+        buffer.get_channel_data(SampleLocation::channel(channel))[start_frame..end_frame]
+            .iter()
+            .all(|sample| *sample == 0.0)
+    })
+}
+
+#[test]
+fn renders_fixed_duration() {
+    let sample_rate = 48_000;
+    let channel_count = 2;
+
+    let mut context = OfflineContext::new(
+        EngineOptions::default().with_sample_rate(sample_rate),
+        channel_count,
+        Duration::from_millis(500),
+    );
+
+    let oscillator = Oscillator::sine(&context, 440.0, channel_count);
+    connect_nodes!(oscillator => "output");
+
+    assert_eq!(context.render(), OfflineRenderStatus::Complete);
+
+    let output = context.into_output_buffer();
+
+    assert_eq!(output.frame_count(), sample_rate / 2);
+    assert_eq!(output.channel_count(), channel_count);
+    assert!(!is_silent(&output, 0, output.frame_count()));
+}
+
+#[test]
+fn suspends_to_change_the_graph() {
+    let sample_rate = 48_000;
+    let channel_count = 1;
+
+    let mut context = OfflineContext::new(
+        EngineOptions::default().with_sample_rate(sample_rate),
+        channel_count,
+        Duration::from_secs(1),
+    );
+
+    let oscillator = Oscillator::sine(&context, 440.0, channel_count);
+
+    let connect_time = Timestamp::from_seconds(0.25);
+    let disconnect_time = Timestamp::from_seconds(0.75);
+
+    context.suspend_at(disconnect_time);
+    context.suspend_at(connect_time);
+
+    assert_eq!(
+        context.render(),
+        OfflineRenderStatus::Suspended(connect_time)
+    );
+    assert_eq!(context.current_time(), connect_time);
+
+    oscillator.node.connect_to_output();
+
+    assert_eq!(
+        context.render(),
+        OfflineRenderStatus::Suspended(disconnect_time)
+    );
+
+    oscillator.node.disconnect_from_output();
+
+    assert_eq!(context.render(), OfflineRenderStatus::Complete);
+
+    let output = context.output_buffer();
+
+    let connect_frame = connect_time.as_samples(sample_rate) as usize;
+    let disconnect_frame = disconnect_time.as_samples(sample_rate) as usize;
+
+    assert!(is_silent(output, 0, connect_frame));
+    assert!(!is_silent(output, connect_frame, disconnect_frame));
+    assert!(is_silent(output, disconnect_frame, output.frame_count()));
+}
+
+#[test]
+fn rendering_is_deterministic() {
+    let render = || {
+        let channel_count = 2;
+
+        let mut context = OfflineContext::new(
+            EngineOptions::default(),
+            channel_count,
+            Duration::from_millis(100),
+        );
+
+        let mut oscillator = Oscillator::sine(&context, 1_000.0, channel_count);
+        oscillator.frequency().linear_ramp_to_value(
+            2_000.0,
+            Timestamp::zero(),
+            Timestamp::from_seconds(0.1),
+        );
+
+        connect_nodes!(oscillator => "output");
+
+        context.render_to_end();
+        context.into_output_buffer()
+    };
+
+    let first = render();
+    let second = render();
+
+    for channel in 0..first.channel_count() {
+        let location = SampleLocation::channel(channel);
+        assert_eq!(
+            first.get_channel_data(location),
+            second.get_channel_data(location)
+        );
+    }
+}
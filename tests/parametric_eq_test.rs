@@ -0,0 +1,74 @@
+use approx::assert_relative_eq;
+use rawdio::{
+    prelude::*, EqBand, EqBandType, OfflineContext, OfflineRenderStatus, Oscillator, ParametricEq,
+};
+use std::time::Duration;
+
+fn peak(buffer: &dyn AudioBuffer, start_frame: usize, end_frame: usize) -> f32 {
+    buffer.get_channel_data(SampleLocation::channel(0))[start_frame..end_frame]
+        .iter()
+        .fold(0.0, |peak, sample| sample.abs().max(peak))
+}
+
+#[test]
+fn boosts_a_bell_band_and_reports_the_response() {
+    let sample_rate = 48_000;
+    let channel_count = 1;
+
+    let mut context = OfflineContext::new(
+        EngineOptions::default().with_sample_rate(sample_rate),
+        channel_count,
+        Duration::from_secs(1),
+    );
+
+    let oscillator = Oscillator::sine(&context, 1_000.0, channel_count);
+    let mut eq = ParametricEq::new(
+        &context,
+        channel_count,
+        vec![
+            EqBand::new(EqBandType::Bell, 1_000.0)
+                .with_gain(6.0)
+                .with_q(2.0),
+            EqBand::new(EqBandType::HighPass { order: 2 }, 40.0),
+        ],
+    );
+
+    connect_nodes!(oscillator => eq => "output");
+
+    let change_time = sample_rate / 2;
+    context.suspend_at(Timestamp::from_samples(change_time as f64, sample_rate));
+
+    let mut magnitudes = [0.0];
+    let mut phases = [0.0];
+
+    while let OfflineRenderStatus::Suspended(_) = context.render() {
+        eq.get_frequency_response(&[1_000.0], &mut magnitudes, &mut phases);
+        assert_relative_eq!(
+            magnitudes[0],
+            Level::from_db(6.0).as_linear(),
+            epsilon = 1e-2
+        );
+
+        eq.gain(0).set_value_now(-6.0);
+    }
+
+    eq.get_frequency_response(&[1_000.0], &mut magnitudes, &mut phases);
+    assert_relative_eq!(
+        magnitudes[0],
+        Level::from_db(-6.0).as_linear(),
+        epsilon = 1e-2
+    );
+
+    let output = context.into_output_buffer();
+
+    assert_relative_eq!(
+        peak(&output, change_time - 4_800, change_time),
+        Level::from_db(6.0).as_linear() as f32,
+        epsilon = 1e-2
+    );
+    assert_relative_eq!(
+        peak(&output, output.frame_count() - 4_800, output.frame_count()),
+        Level::from_db(-6.0).as_linear() as f32,
+        epsilon = 1e-2
+    );
+}
@@ -0,0 +1,113 @@
+use rawdio::{
+    prelude::*, OfflineContext, Oscillator, StateVariableFilter, StateVariableFilterOutput,
+};
+use std::time::Duration;
+
+fn render_output(output: StateVariableFilterOutput) -> f32 {
+    let channel_count = 2;
+
+    let mut context = OfflineContext::new(
+        EngineOptions::default()
+            .with_sample_rate(48_000)
+            .with_maximum_channel_count(4 * channel_count),
+        1,
+        Duration::from_millis(500),
+    );
+
+    let oscillator = Oscillator::sine(&context, 100.0, channel_count);
+    let mut filter = StateVariableFilter::new(&context, channel_count);
+    filter.frequency().set_value_now(5_000.0);
+
+    oscillator.node.connect_to(&filter.node);
+    filter
+        .node
+        .connect_channels_to_output(filter.first_output_channel(output) + 1, 0, 1);
+
+    context.render();
+    let rendered = context.into_output_buffer();
+
+    rendered.get_channel_data(SampleLocation::channel(0))[rendered.frame_count() / 2..]
+        .iter()
+        .fold(0.0, |peak, sample| sample.abs().max(peak))
+}
+
+#[test]
+fn connects_each_output_separately() {
+    assert!(render_output(StateVariableFilterOutput::LowPass) > 0.95);
+    assert!(render_output(StateVariableFilterOutput::Notch) > 0.95);
+    assert!(render_output(StateVariableFilterOutput::HighPass) < 0.01);
+    assert!(render_output(StateVariableFilterOutput::BandPass) < 0.05);
+}
+
+#[test]
+fn single_output_fits_the_default_channel_count() {
+    let channel_count = 2;
+
+    let mut context = OfflineContext::new(
+        EngineOptions::default().with_sample_rate(48_000),
+        channel_count,
+        Duration::from_millis(500),
+    );
+
+    let oscillator = Oscillator::sine(&context, 100.0, channel_count);
+    let mut filter = StateVariableFilter::with_outputs(
+        &context,
+        channel_count,
+        &[StateVariableFilterOutput::HighPass],
+    );
+    filter.frequency().set_value_now(5_000.0);
+
+    oscillator.node.connect_to(&filter.node);
+    filter.node.connect_to_output();
+
+    assert_eq!(
+        filter.first_output_channel(StateVariableFilterOutput::HighPass),
+        0
+    );
+
+    context.render();
+    let rendered = context.into_output_buffer();
+
+    for channel in 0..channel_count {
+        let peak = rendered.get_channel_data(SampleLocation::channel(channel))
+            [rendered.frame_count() / 2..]
+            .iter()
+            .fold(0.0, |peak, sample| sample.abs().max(peak));
+
+        assert!(peak < 0.01);
+    }
+}
+
+#[test]
+#[should_panic(expected = "maximum channel count")]
+fn rejects_outputs_beyond_the_maximum_channel_count() {
+    let context = OfflineContext::new(EngineOptions::default(), 1, Duration::from_millis(10));
+
+    StateVariableFilter::with_outputs(
+        &context,
+        1,
+        &[
+            StateVariableFilterOutput::LowPass,
+            StateVariableFilterOutput::HighPass,
+            StateVariableFilterOutput::BandPass,
+        ],
+    );
+}
+
+#[test]
+#[should_panic(expected = "maximum channel count")]
+fn rejects_all_outputs_in_the_default_engine() {
+    let context = OfflineContext::new(EngineOptions::default(), 1, Duration::from_millis(10));
+
+    StateVariableFilter::new(&context, 1);
+}
+
+#[test]
+#[should_panic(expected = "no LowPass output")]
+fn rejects_outputs_that_were_not_selected() {
+    let context = OfflineContext::new(EngineOptions::default(), 1, Duration::from_millis(10));
+
+    let filter =
+        StateVariableFilter::with_outputs(&context, 1, &[StateVariableFilterOutput::Notch]);
+    filter.first_output_channel(StateVariableFilterOutput::LowPass);
+}
@@ -0,0 +1,45 @@
+use rawdio::{
+    io::{WavReader, WavSampleFormat, WavSpec, WavWriter},
+    prelude::*,
+};
+
+#[test]
+fn round_trips_a_file() {
+    let sample_rate = 48_000;
+    let channel_count = 2;
+
+    let buffer = OwnedAudioBuffer::sine(sample_rate, channel_count, sample_rate, 440.0, 0.5);
+
+    let path = std::env::temp_dir().join(format!("rawdio-wav-test-{}.wav", std::process::id()));
+
+    let spec = WavSpec {
+        channel_count,
+        sample_rate,
+        sample_format: WavSampleFormat::Pcm24,
+    };
+
+    let mut writer = WavWriter::create(&path, spec).expect("Unable to create file");
+    writer.write(&buffer).expect("Unable to write file");
+    writer.finalize().expect("Unable to finalize file");
+
+    let reader = WavReader::open(&path).expect("Unable to open file");
+
+    assert_eq!(reader.spec(), spec);
+    assert_eq!(reader.frame_count(), buffer.frame_count());
+
+    let decoded = reader.read_to_buffer().expect("Unable to read file");
+
+    let _ = std::fs::remove_file(&path);
+
+    for channel in 0..channel_count {
+        let location = SampleLocation::channel(channel);
+
+        for (decoded, original) in decoded
+            .get_channel_data(location)
+            .iter()
+            .zip(buffer.get_channel_data(location))
+        {
+            assert!((decoded - original).abs() < 1e-6);
+        }
+    }
+}